@@ -0,0 +1,159 @@
+//! Reports about the text of a document that parsing alone discards.
+use super::container::Container;
+use super::dialect::Json;
+use super::parser::{self, Observer, Span};
+use super::pointer;
+use std::collections::HashMap;
+
+/// A reference token of [`Path`].
+enum Token {
+    Index(usize),
+    Key(String),
+}
+
+/// Follows the JSON Pointer of the value being read from the events of
+/// an [`Observer`].
+#[derive(Default)]
+pub(crate) struct Path {
+    /// One token per open container, innermost last
+    tokens: Vec<Token>,
+}
+
+impl Path {
+    /// Number of open containers.
+    pub(crate) fn depth(&self) -> usize {
+        self.tokens.len()
+    }
+
+    pub(crate) fn open(&mut self, object: bool) {
+        self.tokens.push(if object {
+            Token::Key(String::new())
+        } else {
+            Token::Index(0)
+        });
+    }
+
+    pub(crate) fn key(&mut self, key: &str) {
+        if let Some(Token::Key(current)) = self.tokens.last_mut() {
+            key.clone_into(current);
+        }
+    }
+
+    /// Moves past a value (scalar or just closed container).
+    pub(crate) fn value_done(&mut self) {
+        if let Some(Token::Index(idx)) = self.tokens.last_mut() {
+            *idx += 1;
+        }
+    }
+
+    pub(crate) fn close(&mut self) {
+        self.tokens.pop();
+        self.value_done();
+    }
+
+    /// Pointer of the value being read, i.e. of the current element of
+    /// the innermost container.
+    pub(crate) fn pointer(&self) -> String {
+        self.pointer_at(self.tokens.len())
+    }
+
+    /// Pointer of the innermost open container.
+    pub(crate) fn container_pointer(&self) -> String {
+        self.pointer_at(self.tokens.len().saturating_sub(1))
+    }
+
+    fn pointer_at(&self, depth: usize) -> String {
+        let mut path = String::new();
+        for token in &self.tokens[..depth] {
+            path.push('/');
+            match token {
+                Token::Index(idx) => path.push_str(&idx.to_string()),
+                Token::Key(key) => path.push_str(&pointer::escape(key)),
+            }
+        }
+        path
+    }
+}
+
+/// A key written more than once in the same object. Parsing keeps only
+/// the last value of such a key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateKey {
+    /// JSON Pointer of the object holding the key
+    pub path: String,
+    /// The key, unescaped
+    pub key: String,
+    /// Every occurrence of the key, in input order
+    pub spans: Vec<Span>,
+}
+
+/// Collects the keys of every open object.
+#[derive(Default)]
+pub(crate) struct DuplicateScan {
+    pub(crate) path: Path,
+    /// Key occurrences of each open container (`None` for arrays)
+    keys: Vec<Option<HashMap<String, Vec<Span>>>>,
+    pub(crate) duplicates: Vec<DuplicateKey>,
+}
+
+impl Observer for DuplicateScan {
+    fn open(&mut self, object: bool, _span: Span) {
+        self.path.open(object);
+        self.keys.push(object.then(HashMap::new));
+    }
+
+    fn close(&mut self, _span: Span) {
+        if let Some(Some(keys)) = self.keys.pop() {
+            let path = self.path.container_pointer();
+            self.duplicates.extend(
+                keys.into_iter().filter(|(_, spans)| spans.len() > 1).map(
+                    |(key, spans)| DuplicateKey {
+                        path: path.clone(),
+                        key,
+                        spans,
+                    },
+                ),
+            );
+        }
+        self.path.close();
+    }
+
+    fn key(&mut self, key: &str, span: Span) {
+        self.path.key(key);
+        if let Some(Some(keys)) = self.keys.last_mut() {
+            keys.entry(key.to_owned()).or_default().push(span);
+        }
+    }
+
+    fn scalar(&mut self, _value: &Container, _span: Span) {
+        self.path.value_done();
+    }
+}
+
+/// Finds the keys occurring more than once in an object of `input`,
+/// ordered by their first occurrence.
+///
+/// Values are dropped as soon as they are read, so memory stays bounded
+/// by the keys of the objects being read. Syntax errors still fail.
+///
+/// ```
+/// use json_parser::analyze::duplicate_keys;
+///
+/// let input = r#"{"a": 1, "b": [{"id": 1, "id": 2}], "a": 3}"#;
+/// let duplicates = duplicate_keys(input).unwrap();
+/// assert_eq!(duplicates.len(), 2);
+/// assert_eq!(duplicates[0].path, "");
+/// assert_eq!(duplicates[0].key, "a");
+/// assert_eq!(duplicates[1].path, "/b/0");
+/// assert_eq!(duplicates[1].spans[1].start, 25);
+/// ```
+pub fn duplicate_keys(
+    input: &str,
+) -> Result<Vec<DuplicateKey>, Box<dyn core::error::Error>> {
+    let mut scan = DuplicateScan::default();
+    parser::observe(input, &Json, &mut scan)?;
+
+    let mut duplicates = scan.duplicates;
+    duplicates.sort_unstable_by_key(|duplicate| duplicate.spans[0].start);
+    Ok(duplicates)
+}
@@ -0,0 +1,395 @@
+//! Documents whose arrays, objects and strings are shared, for handing
+//! parsed documents to other threads and keeping subtrees around.
+//!
+//! An [`ArcContainer`] is built once from a `Container`. Cloning it, or
+//! any of its subtrees, then only counts a reference, and clones can be
+//! sent and read across threads. Values are read only: a change is made
+//! on a `Container` and converted again, as [`SharedDocument`] does for a
+//! document served to many threads.
+//!
+//! `Container`, `ArcContainer` and `SharedDocument` are all `Send` and
+//! `Sync`, which is checked when the crate is built.
+use super::container::Container;
+use super::map::{Key, Map, SMALL_LEN};
+use super::pointer;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+
+const _: () = {
+    const fn shareable<T: Send + Sync>() {}
+    shareable::<Container>();
+    shareable::<ArcContainer>();
+    shareable::<SharedDocument>();
+};
+
+/// The members of an object of an [`ArcContainer`], in order.
+#[derive(Clone, Default)]
+pub struct ArcMap {
+    entries: Vec<(Key, ArcContainer)>,
+    /// Position of each key in `entries`, for objects of more than
+    /// `SMALL_LEN` members only
+    index: HashMap<Key, usize>,
+}
+
+impl ArcMap {
+    /// Members in order, with distinct keys.
+    fn new(entries: Vec<(Key, ArcContainer)>) -> Self {
+        let mut index = HashMap::new();
+        if entries.len() > SMALL_LEN {
+            index.extend(
+                entries
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, (key, _))| (key.clone(), idx)),
+            );
+        }
+        Self { entries, index }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&ArcContainer> {
+        let idx = match self.index.is_empty() {
+            true => self.entries.iter().position(|(other, _)| &**other == key),
+            false => self.index.get(key).copied(),
+        };
+        idx.map(|idx| &self.entries[idx].1)
+    }
+
+    pub fn iter(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = (&str, &ArcContainer)> + ExactSizeIterator
+    {
+        self.entries.iter().map(|(key, value)| (&**key, value))
+    }
+
+    pub fn keys(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = &str> + ExactSizeIterator {
+        self.entries.iter().map(|(key, _)| &**key)
+    }
+}
+
+impl PartialEq for ArcMap {
+    /// Maps are equal when they hold the same members, in any order.
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+impl core::fmt::Debug for ArcMap {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// A counterpart of `Container` whose arrays, objects and strings are
+/// behind an `Arc`, so that cloning takes constant time.
+///
+/// ```
+/// use json_parser::arc_container::ArcContainer;
+/// use json_parser::parser::parse_str;
+///
+/// let doc = parse_str(r#"{"users": [{"name": "ada"}, {"name": "bob"}]}"#)
+///     .unwrap();
+/// let shared = ArcContainer::from(doc);
+/// let users = shared.get("users").clone();
+/// let handle = std::thread::spawn(move || {
+///     users.get_index(1).get("name").get_str().map(str::to_owned)
+/// });
+/// assert_eq!(handle.join().unwrap().as_deref(), Some("bob"));
+/// assert_eq!(shared.pointer("/users/0/name").unwrap().get_str(), Some("ada"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum ArcContainer {
+    /// Representing an object of null type
+    #[default]
+    Null,
+    /// A signed integer
+    Number(i64),
+    /// An unsigned integer
+    Unsigned(u64),
+    /// A signed integer, for negative values beyond `i64`
+    Number128(i128),
+    /// An unsigned integer, for values beyond `u64`
+    Unsigned128(u128),
+    /// A real number
+    Decimal(f64),
+    /// A number exactly as written in the input
+    RawNumber(Arc<str>),
+    /// An integer beyond the range of `i128` and `u128`
+    BigNumber(Arc<str>),
+    /// A UUID
+    #[cfg(feature = "uuid")]
+    Uuid([u8; 16]),
+    /// boolean value
+    Boolean(bool),
+    /// Shared string
+    String(Arc<str>),
+    /// Shared list of values
+    Array(Arc<[ArcContainer]>),
+    /// Shared key value pairs, in insertion order
+    Object(Arc<ArcMap>),
+}
+
+impl ArcContainer {
+    /// Returns the value associated with `key`, or `Null` if self is not
+    /// an object or the key is missing.
+    pub fn get(&self, key: &str) -> &ArcContainer {
+        match self {
+            Self::Object(map) => map.get(key).unwrap_or(&Self::Null),
+            _ => &Self::Null,
+        }
+    }
+
+    /// Returns the element at `idx`, or `Null` if self is not an array or
+    /// the index is out of bounds.
+    pub fn get_index(&self, idx: usize) -> &ArcContainer {
+        match self {
+            Self::Array(values) => values.get(idx).unwrap_or(&Self::Null),
+            _ => &Self::Null,
+        }
+    }
+
+    /// The value at the JSON Pointer `pointer`.
+    pub fn pointer(&self, pointer: &str) -> Option<&ArcContainer> {
+        let mut node = self;
+        for token in pointer::split(pointer)? {
+            node = match node {
+                Self::Object(map) => map.get(&token)?,
+                Self::Array(values) => {
+                    values.get(pointer::array_index(&token)?)?
+                }
+                _ => return None,
+            };
+        }
+        Some(node)
+    }
+
+    pub fn get_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_uint(&self) -> Option<u64> {
+        match self {
+            Self::Unsigned(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn get_int(&self) -> Option<i64> {
+        match self {
+            Self::Number(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn get_real(&self) -> Option<f64> {
+        match self {
+            Self::Decimal(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn get_bool(&self) -> Option<bool> {
+        match self {
+            Self::Boolean(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+
+    /// Returns the length of an array, object or string, else 1.
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Array(values) => values.len(),
+            Self::Object(map) => map.len(),
+            Self::String(value) => value.len(),
+            _ => 1,
+        }
+    }
+
+    /// Returns `true` if an array, object or string holds no elements
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Self::Array(values) => values.is_empty(),
+            Self::Object(map) => map.is_empty(),
+            Self::String(value) => value.is_empty(),
+            _ => false,
+        }
+    }
+
+    /// Builds an owned `Container` out of self. Keys stay shared.
+    pub fn to_container(&self) -> Container {
+        match self {
+            Self::Null => Container::Null,
+            Self::Number(value) => Container::Number(*value),
+            Self::Unsigned(value) => Container::Unsigned(*value),
+            Self::Number128(value) => Container::Number128(*value),
+            Self::Unsigned128(value) => Container::Unsigned128(*value),
+            Self::Decimal(value) => Container::Decimal(*value),
+            Self::RawNumber(text) => Container::RawNumber(text.to_string()),
+            Self::BigNumber(text) => Container::BigNumber(text.to_string()),
+            #[cfg(feature = "uuid")]
+            Self::Uuid(bytes) => Container::Uuid(*bytes),
+            Self::Boolean(value) => Container::Boolean(*value),
+            Self::String(value) => Container::String((**value).into()),
+            Self::Array(values) => Container::Array(
+                values.iter().map(Self::to_container).collect(),
+            ),
+            Self::Object(map) => {
+                let mut object = Map::with_capacity(map.len());
+                for (key, value) in &map.entries {
+                    object.insert(key.clone(), value.to_container());
+                }
+                Container::Object(object)
+            }
+        }
+    }
+}
+
+impl From<Container> for ArcContainer {
+    /// Moves the keys of `value`, which are already shared.
+    fn from(value: Container) -> Self {
+        match value {
+            Container::Array(values) => {
+                Self::Array(values.into_iter().map(Self::from).collect())
+            }
+            Container::Object(map) => Self::Object(Arc::new(ArcMap::new(
+                map.into_iter()
+                    .map(|(key, value)| (key, Self::from(value)))
+                    .collect(),
+            ))),
+            scalar => Self::from(&scalar),
+        }
+    }
+}
+
+impl From<&Container> for ArcContainer {
+    fn from(value: &Container) -> Self {
+        match value {
+            Container::Null => Self::Null,
+            Container::Number(value) => Self::Number(*value),
+            Container::Unsigned(value) => Self::Unsigned(*value),
+            Container::Number128(value) => Self::Number128(*value),
+            Container::Unsigned128(value) => Self::Unsigned128(*value),
+            Container::Decimal(value) => Self::Decimal(*value),
+            Container::RawNumber(text) => Self::RawNumber(text.as_str().into()),
+            Container::BigNumber(text) => Self::BigNumber(text.as_str().into()),
+            #[cfg(feature = "uuid")]
+            Container::Uuid(bytes) => Self::Uuid(*bytes),
+            Container::Boolean(value) => Self::Boolean(*value),
+            Container::String(value) => Self::String(value.as_str().into()),
+            Container::Array(values) => {
+                Self::Array(values.iter().map(Self::from).collect())
+            }
+            Container::Object(map) => Self::Object(Arc::new(ArcMap::new(
+                map.entries()
+                    .iter()
+                    .map(|(key, value)| (key.clone(), Self::from(value)))
+                    .collect(),
+            ))),
+        }
+    }
+}
+
+impl From<&ArcContainer> for Container {
+    fn from(value: &ArcContainer) -> Self {
+        value.to_container()
+    }
+}
+
+/// A document read by many threads and changed now and then, such as the
+/// configuration of a server.
+///
+/// Readers take a [`snapshot`](SharedDocument::snapshot): the version of
+/// the moment, kept as long as they like, which changes made later do
+/// not reach. Changes are made one at a time on a copy, then replace the
+/// document at once, so readers never wait for a change to be made.
+///
+/// ```
+/// use json_parser::arc_container::SharedDocument;
+/// use json_parser::container::Container;
+/// use json_parser::parser::parse_str;
+/// use std::sync::Arc;
+///
+/// let config = parse_str(r#"{"workers": 4, "mode": "fast"}"#).unwrap();
+/// let config = Arc::new(SharedDocument::new(config));
+/// let before = config.snapshot();
+///
+/// let writer = config.clone();
+/// std::thread::spawn(move || {
+///     writer.update(|doc| doc["workers"] = Container::from(8u64))
+/// })
+/// .join()
+/// .unwrap();
+/// assert_eq!(config.snapshot().get("workers").get_uint(), Some(8));
+/// assert_eq!(before.get("workers").get_uint(), Some(4));
+/// ```
+#[derive(Debug, Default)]
+pub struct SharedDocument {
+    current: RwLock<ArcContainer>,
+    /// Taken by changes, so that none is lost to another
+    writer: Mutex<()>,
+}
+
+impl SharedDocument {
+    pub fn new(document: Container) -> Self {
+        Self {
+            current: RwLock::new(ArcContainer::from(document)),
+            writer: Mutex::new(()),
+        }
+    }
+
+    /// The document as it is now; taking it only counts a reference.
+    pub fn snapshot(&self) -> ArcContainer {
+        // A panic elsewhere cannot leave the value half replaced.
+        self.current
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Changes a copy of the document with `update`, then makes it the
+    /// document.
+    pub fn update<T>(&self, update: impl FnOnce(&mut Container) -> T) -> T {
+        let _writer =
+            self.writer.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut document = self.snapshot().to_container();
+        let result = update(&mut document);
+        self.publish(ArcContainer::from(document));
+        result
+    }
+
+    /// Replaces the document.
+    pub fn replace(&self, document: Container) {
+        let _writer =
+            self.writer.lock().unwrap_or_else(PoisonError::into_inner);
+        self.publish(ArcContainer::from(document));
+    }
+
+    fn publish(&self, document: ArcContainer) {
+        let old = core::mem::replace(
+            &mut *self.current.write().unwrap_or_else(PoisonError::into_inner),
+            document,
+        );
+        // The old version is dropped, if no reader holds it, once the
+        // lock is free.
+        drop(old);
+    }
+}
@@ -0,0 +1,426 @@
+//! Documents allocated in a bump arena, for short-lived parses of many
+//! documents.
+//!
+//! [`parse_in`] puts the arrays, objects and strings of a document in a
+//! [`Bump`] handed over by the caller, as an [`ArenaValue`] borrowing
+//! from it. Values are never dropped one by one: their memory goes back
+//! all at once when the arena is reset or dropped.
+use super::container::Container;
+use super::dialect::Json;
+use super::parser::parse_str;
+use super::tokens::{inner_token, unescape, Token, Tokens};
+use std::alloc::{self, Layout};
+use std::cell::{Cell, RefCell};
+use std::ptr::NonNull;
+
+/// Bytes of the first chunk of an arena; each next chunk is twice as
+/// large, up to [`MAX_CHUNK_LEN`].
+const CHUNK_LEN: usize = 4096;
+const MAX_CHUNK_LEN: usize = 1 << 20;
+/// Alignment of chunks: enough for every value put in an arena.
+const CHUNK_ALIGN: usize = 16;
+
+/// A block of memory owned by an arena.
+struct Chunk {
+    ptr: NonNull<u8>,
+    layout: Layout,
+}
+
+impl Chunk {
+    fn new(len: usize) -> Self {
+        let layout = Layout::from_size_align(len, CHUNK_ALIGN)
+            .expect("chunk sizes are bounded by the values put in them");
+        let ptr = unsafe { alloc::alloc(layout) };
+        let ptr = NonNull::new(ptr)
+            .unwrap_or_else(|| alloc::handle_alloc_error(layout));
+        Self { ptr, layout }
+    }
+}
+
+impl Drop for Chunk {
+    fn drop(&mut self) {
+        unsafe { alloc::dealloc(self.ptr.as_ptr(), self.layout) }
+    }
+}
+
+/// A bump arena: memory handed out by moving a pointer through chunks,
+/// and released all at once.
+///
+/// Only values without `Drop` are put in an arena, so nothing runs when
+/// the memory is released.
+///
+/// ```
+/// use json_parser::arena::Bump;
+///
+/// let mut bump = Bump::new();
+/// let text = bump.alloc_str("hello");
+/// let numbers = bump.alloc_slice_copy(&[1u32, 2, 3]);
+/// assert_eq!((text, &*numbers), ("hello", &[1, 2, 3][..]));
+/// assert!(bump.allocated() >= 4096);
+/// bump.reset();
+/// ```
+pub struct Bump {
+    /// Chunks allocated, the one being filled last
+    chunks: RefCell<Vec<Chunk>>,
+    /// Next free byte of the last chunk
+    next: Cell<NonNull<u8>>,
+    /// Free bytes from `next` to the end of the last chunk
+    free: Cell<usize>,
+}
+
+impl Default for Bump {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl core::fmt::Debug for Bump {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Bump")
+            .field("allocated", &self.allocated())
+            .field("free", &self.free.get())
+            .finish()
+    }
+}
+
+impl Bump {
+    /// An arena which allocates its first chunk when first used.
+    pub fn new() -> Self {
+        Self {
+            chunks: RefCell::new(Vec::new()),
+            next: Cell::new(NonNull::dangling()),
+            free: Cell::new(0),
+        }
+    }
+
+    /// An arena holding `bytes` from the start.
+    pub fn with_capacity(bytes: usize) -> Self {
+        let bump = Self::new();
+        if bytes > 0 {
+            bump.push_chunk(bytes);
+        }
+        bump
+    }
+
+    /// Bytes of memory held by the arena, used or not.
+    pub fn allocated(&self) -> usize {
+        self.chunks
+            .borrow()
+            .iter()
+            .map(|chunk| chunk.layout.size())
+            .sum()
+    }
+
+    /// Releases every value, keeping the largest chunk to be filled again.
+    pub fn reset(&mut self) {
+        let chunks = self.chunks.get_mut();
+        let largest = chunks
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, chunk)| chunk.layout.size())
+            .map(|(idx, _)| idx);
+        match largest {
+            Some(idx) => {
+                let chunk = chunks.swap_remove(idx);
+                chunks.clear();
+                self.next.set(chunk.ptr);
+                self.free.set(chunk.layout.size());
+                chunks.push(chunk);
+            }
+            None => self.free.set(0),
+        }
+    }
+
+    fn push_chunk(&self, len: usize) {
+        let chunk = Chunk::new(len);
+        self.next.set(chunk.ptr);
+        self.free.set(len);
+        self.chunks.borrow_mut().push(chunk);
+    }
+
+    /// Memory for a value of `layout`, of non-zero size.
+    fn alloc_layout(&self, layout: Layout) -> NonNull<u8> {
+        let next = self.next.get();
+        let padding = next.as_ptr().align_offset(layout.align());
+        if padding + layout.size() <= self.free.get() {
+            let ptr = unsafe { next.add(padding) };
+            self.next.set(unsafe { ptr.add(layout.size()) });
+            self.free.set(self.free.get() - padding - layout.size());
+            return ptr;
+        }
+        let last = self
+            .chunks
+            .borrow()
+            .last()
+            .map_or(CHUNK_LEN / 2, |chunk| chunk.layout.size());
+        let len = (last * 2)
+            .min(MAX_CHUNK_LEN)
+            .max(layout.size() + layout.align());
+        self.push_chunk(len);
+        self.alloc_layout(layout)
+    }
+
+    /// Copies `values` into the arena.
+    pub fn alloc_slice_copy<T: Copy>(&self, values: &[T]) -> &[T] {
+        let layout = Layout::for_value(values);
+        if layout.size() == 0 {
+            let dangling = NonNull::<T>::dangling().as_ptr();
+            return unsafe {
+                core::slice::from_raw_parts(dangling, values.len())
+            };
+        }
+        let ptr = self.alloc_layout(layout).cast::<T>().as_ptr();
+        unsafe {
+            ptr.copy_from_nonoverlapping(values.as_ptr(), values.len());
+            core::slice::from_raw_parts(ptr, values.len())
+        }
+    }
+
+    /// Copies `text` into the arena.
+    pub fn alloc_str(&self, text: &str) -> &str {
+        let bytes = self.alloc_slice_copy(text.as_bytes());
+        unsafe { core::str::from_utf8_unchecked(bytes) }
+    }
+}
+
+/// A value of a document parsed by [`parse_in`], whose arrays, objects
+/// and strings are held by an arena.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArenaValue<'a> {
+    /// Representing an object of null type
+    Null,
+    /// A signed integer
+    Number(i64),
+    /// An unsigned integer
+    Unsigned(u64),
+    /// A real number
+    Decimal(f64),
+    /// A number beyond `i64`, `u64` or `f64`, as written
+    RawNumber(&'a str),
+    /// boolean value
+    Boolean(bool),
+    /// String
+    String(&'a str),
+    /// List of values
+    Array(&'a [ArenaValue<'a>]),
+    /// Key value pairs, in input order
+    Object(&'a [(&'a str, ArenaValue<'a>)]),
+}
+
+impl<'a> ArenaValue<'a> {
+    /// Returns the value of the first member named `key`, or `Null` if
+    /// self is not an object or the key is missing.
+    pub fn get(&self, key: &str) -> &ArenaValue<'a> {
+        match self {
+            Self::Object(pairs) => pairs
+                .iter()
+                .find(|(name, _)| *name == key)
+                .map_or(&Self::Null, |(_, value)| value),
+            _ => &Self::Null,
+        }
+    }
+
+    /// Returns the element at `idx`, or `Null` if self is not an array or
+    /// the index is out of bounds.
+    pub fn get_index(&self, idx: usize) -> &ArenaValue<'a> {
+        match self {
+            Self::Array(values) => values.get(idx).unwrap_or(&Self::Null),
+            _ => &Self::Null,
+        }
+    }
+
+    pub fn get_str(&self) -> Option<&'a str> {
+        match self {
+            Self::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_uint(&self) -> Option<u64> {
+        match self {
+            Self::Unsigned(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn get_int(&self) -> Option<i64> {
+        match self {
+            Self::Number(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn get_real(&self) -> Option<f64> {
+        match self {
+            Self::Decimal(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn get_bool(&self) -> Option<bool> {
+        match self {
+            Self::Boolean(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+
+    /// Returns the length of an array, object or string, else 1.
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Array(values) => values.len(),
+            Self::Object(pairs) => pairs.len(),
+            Self::String(value) => value.len(),
+            _ => 1,
+        }
+    }
+
+    /// Returns `true` if an array, object or string holds no elements
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Self::Array(values) => values.is_empty(),
+            Self::Object(pairs) => pairs.is_empty(),
+            Self::String(value) => value.is_empty(),
+            _ => false,
+        }
+    }
+
+    /// Builds an owned `Container` out of self, as [`parse_str`] would
+    /// have: of two members with the same key, the last is kept.
+    pub fn to_container(&self) -> Container {
+        match self {
+            Self::Null => Container::Null,
+            Self::Number(value) => Container::Number(*value),
+            Self::Unsigned(value) => Container::Unsigned(*value),
+            Self::Decimal(value) => Container::Decimal(*value),
+            Self::RawNumber(text) => parse_str(text).unwrap_or(Container::Null),
+            Self::Boolean(value) => Container::Boolean(*value),
+            Self::String(value) => Container::String((*value).into()),
+            Self::Array(values) => Container::Array(
+                values.iter().map(Self::to_container).collect(),
+            ),
+            Self::Object(pairs) => Container::Object(
+                pairs
+                    .iter()
+                    .map(|(key, value)| {
+                        ((*key).to_owned(), value.to_container())
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// A container whose elements are still being read, by the position of
+/// its first element in the scratch buffers.
+enum Open {
+    Array(usize),
+    Object(usize),
+}
+
+/// Copies the string `raw`, with its quotes and escapes, into `bump`.
+fn alloc_string<'a>(
+    bump: &'a Bump,
+    raw: &str,
+    scratch: &mut String,
+) -> &'a str {
+    let raw = &raw[1..raw.len() - 1];
+    if !raw.contains('\\') {
+        return bump.alloc_str(raw);
+    }
+    scratch.clear();
+    scratch.extend(unescape(raw));
+    bump.alloc_str(scratch)
+}
+
+/// The number written as `text`.
+fn number<'a>(bump: &'a Bump, text: &str) -> ArenaValue<'a> {
+    match parse_str(text) {
+        Ok(Container::Number(value)) => ArenaValue::Number(value),
+        Ok(Container::Unsigned(value)) => ArenaValue::Unsigned(value),
+        Ok(Container::Decimal(value)) => ArenaValue::Decimal(value),
+        _ => ArenaValue::RawNumber(bump.alloc_str(text)),
+    }
+}
+
+/// Parse `input` into `bump`.
+///
+/// Arrays, objects and strings are copied into the arena once complete,
+/// so the only memory allocated outside it is a few scratch buffers for
+/// the whole parse. Members of objects are kept in input order, all of
+/// them when a key is written twice.
+///
+/// ```
+/// use json_parser::arena::{parse_in, Bump};
+///
+/// let mut bump = Bump::new();
+/// for id in 0..3 {
+///     let message = format!(r#"{{"id": {}, "tags": ["a\tb", "c"]}}"#, id);
+///     let doc = parse_in(&message, &bump).unwrap();
+///     assert_eq!(doc.get("id").get_uint(), Some(id));
+///     assert_eq!(doc.get("tags").get_index(0).get_str(), Some("a\tb"));
+///     // Every value of the document is released at once.
+///     bump.reset();
+/// }
+/// assert!(parse_in("[1, 2", &bump).is_err());
+/// ```
+pub fn parse_in<'a>(
+    input: &str,
+    bump: &'a Bump,
+) -> Result<ArenaValue<'a>, Box<dyn core::error::Error>> {
+    let mut tokens = Tokens::new(input, &Json);
+    let (mut open, mut keys) = (Vec::new(), Vec::new());
+    let (mut elements, mut members) = (Vec::new(), Vec::new());
+    let mut scratch = String::new();
+    loop {
+        let value = match inner_token(&mut tokens)? {
+            Token::BeginArray => {
+                open.push(Open::Array(elements.len()));
+                continue;
+            }
+            Token::BeginObject => {
+                open.push(Open::Object(members.len()));
+                continue;
+            }
+            Token::Key(raw) => {
+                keys.push(alloc_string(bump, raw, &mut scratch));
+                continue;
+            }
+            Token::EndArray | Token::EndObject => match open.pop() {
+                Some(Open::Array(start)) => {
+                    let array = bump.alloc_slice_copy(&elements[start..]);
+                    elements.truncate(start);
+                    ArenaValue::Array(array)
+                }
+                Some(Open::Object(start)) => {
+                    let object = bump.alloc_slice_copy(&members[start..]);
+                    members.truncate(start);
+                    ArenaValue::Object(object)
+                }
+                // The tokenizer only closes containers it opened.
+                None => ArenaValue::Null,
+            },
+            Token::String(raw) => {
+                ArenaValue::String(alloc_string(bump, raw, &mut scratch))
+            }
+            Token::Number(text) => number(bump, text),
+            Token::Literal("true") => ArenaValue::Boolean(true),
+            Token::Literal("false") => ArenaValue::Boolean(false),
+            Token::Literal(_) => ArenaValue::Null,
+        };
+        match open.last() {
+            Some(Open::Array(_)) => elements.push(value),
+            // Every member value follows its key.
+            Some(Open::Object(_)) => {
+                members.push((keys.pop().unwrap_or_default(), value))
+            }
+            None => {
+                while tokens.next_token()?.is_some() {}
+                return Ok(value);
+            }
+        }
+    }
+}
@@ -0,0 +1,257 @@
+//! Reading documents from asynchronous sources, so that services can take
+//! in large request bodies without blocking a thread while they arrive.
+//!
+//! Only the reading is asynchronous: bytes are gathered as they arrive,
+//! and each document is parsed synchronously, on the polling task, once
+//! it is complete: the whole input for [`parse_async`], each line for
+//! [`AsyncLines`]. Parsing a large document thus still takes its time on
+//! the runtime's thread.
+//!
+//! Sources implement [`PollRead`], a trait of this crate rather than the
+//! `AsyncRead` of a runtime. Its `poll_read` has the signature of the
+//! `futures-io` `AsyncRead`, so that a reader of any runtime is adapted
+//! by a wrapper forwarding to it.
+use super::container::Container;
+use super::parser::parse_bytes;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use std::collections::VecDeque;
+use std::io;
+
+/// Bytes asked of a reader at a time.
+const CHUNK_LEN: usize = 8192;
+
+/// A source of bytes read without blocking, with the `poll_read` of the
+/// `futures-io` `AsyncRead`.
+pub trait PollRead {
+    /// Reads into `buf`, returning the number of bytes read, 0 at the end
+    /// of the input, or `Poll::Pending` after arranging for the task of
+    /// `cx` to be woken once bytes are available.
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>>;
+}
+
+impl PollRead for &[u8] {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let len = buf.len().min(self.len());
+        let (read, rest) = self.split_at(len);
+        buf[..len].copy_from_slice(read);
+        *self = rest;
+        Poll::Ready(Ok(len))
+    }
+}
+
+impl<R: PollRead + Unpin + ?Sized> PollRead for &mut R {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut **self).poll_read(cx, buf)
+    }
+}
+
+impl<R: PollRead + Unpin + ?Sized> PollRead for Box<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut **self).poll_read(cx, buf)
+    }
+}
+
+/// Future of one read of a reader.
+struct Read<'a, R: ?Sized> {
+    reader: &'a mut R,
+    buf: &'a mut [u8],
+}
+
+impl<R: PollRead + Unpin + ?Sized> Future for Read<'_, R> {
+    type Output = io::Result<usize>;
+
+    fn poll(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Self::Output> {
+        let Self { reader, buf } = &mut *self;
+        Pin::new(&mut **reader).poll_read(cx, buf)
+    }
+}
+
+fn read<'a, R: PollRead + Unpin + ?Sized>(
+    reader: &'a mut R,
+    buf: &'a mut [u8],
+) -> Read<'a, R> {
+    Read { reader, buf }
+}
+
+/// Read the whole input of `reader`, then parse it synchronously, in any
+/// encoding [`parse_bytes`] reads.
+///
+/// ```
+/// use json_parser::asynchronous::parse_async;
+/// # fn block_on<F: std::future::Future>(future: F) -> F::Output {
+/// #     let mut future = std::pin::pin!(future);
+/// #     let mut cx = std::task::Context::from_waker(std::task::Waker::noop());
+/// #     loop {
+/// #         if let std::task::Poll::Ready(value) = future.as_mut().poll(&mut cx) {
+/// #             return value;
+/// #         }
+/// #     }
+/// # }
+///
+/// let body: &[u8] = br#"{"user": "ada", "ids": [1, 2]}"#;
+/// let doc = block_on(parse_async(body)).unwrap();
+/// assert_eq!(doc["ids"][1].get_uint(), Some(2));
+/// ```
+pub async fn parse_async<R: PollRead + Unpin>(
+    mut reader: R,
+) -> Result<Container, Box<dyn core::error::Error>> {
+    let mut bytes = Vec::new();
+    let mut chunk = vec![0; CHUNK_LEN];
+    loop {
+        match read(&mut reader, &mut chunk).await? {
+            0 => return parse_bytes(&bytes),
+            len => bytes.extend_from_slice(&chunk[..len]),
+        }
+    }
+}
+
+/// JSON Lines put together from chunks of bytes, as they arrive.
+///
+/// Blank lines are skipped. Lines are kept as bytes until taken, so that
+/// a feeder holds no error and can be sent across threads.
+///
+/// ```
+/// use json_parser::asynchronous::LineFeeder;
+///
+/// let mut feeder = LineFeeder::new();
+/// feeder.feed(b"{\"a\": 1}\n{\"a\"");
+/// assert_eq!(feeder.next_document().unwrap().unwrap()["a"].get_uint(), Some(1));
+/// assert!(feeder.next_document().is_none());
+/// feeder.feed(b": 2}\n\n[3]");
+/// feeder.finish();
+/// assert_eq!(feeder.next_document().unwrap().unwrap()["a"].get_uint(), Some(2));
+/// assert_eq!(feeder.next_document().unwrap().unwrap()[0].get_uint(), Some(3));
+/// assert!(feeder.next_document().is_none());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct LineFeeder {
+    /// The line being put together
+    partial: Vec<u8>,
+    lines: VecDeque<Vec<u8>>,
+}
+
+impl LineFeeder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the next bytes of the input.
+    pub fn feed(&mut self, mut bytes: &[u8]) {
+        while let Some(end) = bytes.iter().position(|byte| *byte == b'\n') {
+            self.partial.extend_from_slice(&bytes[..end]);
+            self.end_line();
+            bytes = &bytes[end + 1..];
+        }
+        self.partial.extend_from_slice(bytes);
+    }
+
+    /// Ends the input, completing its last line.
+    pub fn finish(&mut self) {
+        self.end_line();
+    }
+
+    fn end_line(&mut self) {
+        let line = core::mem::take(&mut self.partial);
+        if !line.iter().all(u8::is_ascii_whitespace) {
+            self.lines.push_back(line);
+        }
+    }
+
+    /// Parses the next complete line, if any.
+    pub fn next_document(
+        &mut self,
+    ) -> Option<Result<Container, Box<dyn core::error::Error>>> {
+        self.lines.pop_front().map(|line| parse_bytes(&line))
+    }
+}
+
+/// The documents of a JSON Lines reader, one per line, created by
+/// [`parse_lines_async`].
+///
+/// A line that fails to parse yields its error, and reading goes on with
+/// the next line. A failure of the reader ends the documents.
+pub struct AsyncLines<R> {
+    reader: R,
+    feeder: LineFeeder,
+    chunk: Vec<u8>,
+    done: bool,
+}
+
+impl<R: PollRead + Unpin> AsyncLines<R> {
+    /// The document of the next line, reading as much as it takes.
+    pub async fn next(
+        &mut self,
+    ) -> Option<Result<Container, Box<dyn core::error::Error>>> {
+        loop {
+            if let Some(document) = self.feeder.next_document() {
+                return Some(document);
+            }
+            if self.done {
+                return None;
+            }
+            match read(&mut self.reader, &mut self.chunk).await {
+                Ok(0) => {
+                    self.done = true;
+                    self.feeder.finish();
+                }
+                Ok(len) => self.feeder.feed(&self.chunk[..len]),
+                Err(error) => {
+                    self.done = true;
+                    return Some(Err(error.into()));
+                }
+            }
+        }
+    }
+}
+
+/// Read the JSON Lines (NDJSON) of `reader` as they arrive, parsing each
+/// line synchronously once it is complete.
+///
+/// ```
+/// use json_parser::asynchronous::parse_lines_async;
+/// # fn block_on<F: std::future::Future>(future: F) -> F::Output {
+/// #     let mut future = std::pin::pin!(future);
+/// #     let mut cx = std::task::Context::from_waker(std::task::Waker::noop());
+/// #     loop {
+/// #         if let std::task::Poll::Ready(value) = future.as_mut().poll(&mut cx) {
+/// #             return value;
+/// #         }
+/// #     }
+/// # }
+///
+/// let mut lines = parse_lines_async(&b"{\"n\": 1}\n{\"n\": 2}\n"[..]);
+/// let mut total = 0;
+/// while let Some(doc) = block_on(lines.next()) {
+///     total += doc.unwrap()["n"].get_uint().unwrap();
+/// }
+/// assert_eq!(total, 3);
+/// ```
+pub fn parse_lines_async<R: PollRead + Unpin>(reader: R) -> AsyncLines<R> {
+    AsyncLines {
+        reader,
+        feeder: LineFeeder::new(),
+        chunk: vec![0; CHUNK_LEN],
+        done: false,
+    }
+}
@@ -0,0 +1,225 @@
+//! Construction of containers from a stream of visitor events, so that
+//! parsers for other formats can produce `Container`s.
+use super::container::Container;
+use super::error::{BuildError, Error};
+use super::map::Map;
+use super::serializer::Serializer;
+
+/// A container under construction.
+enum Frame {
+    Array(Vec<Container>),
+    /// Members read so far, and the key awaiting its value.
+    Object(Map, Option<String>),
+}
+
+/// A [`Serializer`] sink that assembles the events it receives into a
+/// `Container`.
+///
+/// Front-ends for other formats drive it with the same events
+/// `Container::serialize` emits, then call [`ContainerBuilder::finish`].
+/// Nesting is tracked on an explicit stack, so deep documents do not
+/// consume native stack.
+///
+/// ```
+/// use json_parser::builder::ContainerBuilder;
+/// use json_parser::parser::parse_str;
+/// use json_parser::serializer::Serializer;
+///
+/// let mut builder = ContainerBuilder::new();
+/// builder.begin_object(1).unwrap();
+/// builder.visit_key("ids").unwrap();
+/// builder.begin_array(2).unwrap();
+/// builder.visit_unsigned(1).unwrap();
+/// builder.visit_unsigned(2).unwrap();
+/// builder.end_array().unwrap();
+/// builder.end_object().unwrap();
+///
+/// assert_eq!(builder.finish().unwrap(), parse_str(r#"{"ids": [1, 2]}"#).unwrap());
+/// ```
+#[derive(Default)]
+pub struct ContainerBuilder {
+    stack: Vec<Frame>,
+    root: Option<Container>,
+}
+
+fn unexpected(event: &'static str) -> Box<dyn core::error::Error> {
+    Error::Build(BuildError::UnexpectedEvent(event)).into()
+}
+
+impl ContainerBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the finished document, or an error if containers are still
+    /// open or no value was received.
+    pub fn finish(self) -> Result<Container, Box<dyn core::error::Error>> {
+        match (self.stack.is_empty(), self.root) {
+            (true, Some(root)) => Ok(root),
+            _ => Err(Error::Build(BuildError::Incomplete).into()),
+        }
+    }
+
+    /// Checks that a value may start at the current position.
+    fn expect_value(&self) -> Result<(), Box<dyn core::error::Error>> {
+        match self.stack.last() {
+            None if self.root.is_some() => {
+                Err(unexpected("value after the document root"))
+            }
+            Some(Frame::Object(_, None)) => {
+                Err(unexpected("object member value without a key"))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn push_value(
+        &mut self,
+        value: Container,
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        self.expect_value()?;
+        match self.stack.last_mut() {
+            None => self.root = Some(value),
+            Some(Frame::Array(values)) => values.push(value),
+            Some(Frame::Object(map, key)) => {
+                if let Some(key) = key.take() {
+                    map.insert(key, value);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Serializer for ContainerBuilder {
+    fn visit_null(&mut self) -> Result<(), Box<dyn core::error::Error>> {
+        self.push_value(Container::Null)
+    }
+
+    fn visit_bool(
+        &mut self,
+        value: bool,
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        self.push_value(Container::Boolean(value))
+    }
+
+    fn visit_number(
+        &mut self,
+        value: i64,
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        self.push_value(Container::Number(value))
+    }
+
+    fn visit_unsigned(
+        &mut self,
+        value: u64,
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        self.push_value(Container::Unsigned(value))
+    }
+
+    fn visit_decimal(
+        &mut self,
+        value: f64,
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        self.push_value(Container::Decimal(value))
+    }
+
+    fn visit_str(
+        &mut self,
+        value: &str,
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        self.push_value(Container::String(value.into()))
+    }
+
+    fn visit_raw_number(
+        &mut self,
+        text: &str,
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        self.push_value(Container::RawNumber(text.to_owned()))
+    }
+
+    fn visit_number128(
+        &mut self,
+        value: i128,
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        self.push_value(Container::Number128(value))
+    }
+
+    fn visit_unsigned128(
+        &mut self,
+        value: u128,
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        self.push_value(Container::Unsigned128(value))
+    }
+
+    fn visit_big_number(
+        &mut self,
+        digits: &str,
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        self.push_value(Container::BigNumber(digits.to_owned()))
+    }
+
+    #[cfg(feature = "uuid")]
+    fn visit_uuid(
+        &mut self,
+        bytes: &[u8; 16],
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        self.push_value(Container::Uuid(*bytes))
+    }
+
+    fn begin_array(
+        &mut self,
+        len: usize,
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        self.expect_value()?;
+        self.stack.push(Frame::Array(Vec::with_capacity(len)));
+        Ok(())
+    }
+
+    fn end_array(&mut self) -> Result<(), Box<dyn core::error::Error>> {
+        match self.stack.pop() {
+            Some(Frame::Array(values)) => {
+                self.push_value(Container::Array(values))
+            }
+            _ => Err(unexpected("end of array outside of an array")),
+        }
+    }
+
+    fn begin_object(
+        &mut self,
+        len: usize,
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        self.expect_value()?;
+        self.stack
+            .push(Frame::Object(Map::with_capacity(len), None));
+        Ok(())
+    }
+
+    fn visit_key(
+        &mut self,
+        key: &str,
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        match self.stack.last_mut() {
+            Some(Frame::Object(_, pending @ None)) => {
+                *pending = Some(key.to_owned());
+                Ok(())
+            }
+            Some(Frame::Object(_, Some(_))) => {
+                Err(unexpected("key while a member value is expected"))
+            }
+            _ => Err(unexpected("key outside of an object")),
+        }
+    }
+
+    fn end_object(&mut self) -> Result<(), Box<dyn core::error::Error>> {
+        match self.stack.pop() {
+            Some(Frame::Object(map, None)) => {
+                self.push_value(Container::Object(map))
+            }
+            Some(Frame::Object(_, Some(_))) => Err(unexpected(
+                "end of object while a member value is expected",
+            )),
+            _ => Err(unexpected("end of object outside of an object")),
+        }
+    }
+}
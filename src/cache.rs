@@ -0,0 +1,165 @@
+//! Memoized results of computations over subtrees, for servers working on
+//! many mostly identical documents.
+//!
+//! A [`SubtreeCache`] is addressed by content: equal subtrees share their
+//! entry wherever they appear, and a subtree that is modified no longer
+//! matches the entry computed before the change, so entries never need to
+//! be invalidated.
+use super::container::Container;
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// Entries remembered before a cache is emptied.
+const CACHE_LIMIT: usize = 4096;
+
+/// Feeds the structure of `value` to `state`, consistently with `==`:
+/// members of objects in any order, `0.0` and `-0.0`, and numbers of
+/// different variants, hash as they compare.
+fn hash_into(value: &Container, state: &mut impl Hasher) {
+    core::mem::discriminant(value).hash(state);
+    match value {
+        Container::Decimal(value) => {
+            // `0.0 == -0.0`, and `NaN` equals nothing.
+            let value = if *value == 0.0 { 0.0 } else { *value };
+            value.to_bits().hash(state)
+        }
+        Container::Array(array) => {
+            array.len().hash(state);
+            array.iter().for_each(|element| hash_into(element, state));
+        }
+        Container::Object(map) => {
+            // Combined so that the order of the members does not matter.
+            let members = map.iter().fold(0u64, |sum, (key, value)| {
+                let mut member = DefaultHasher::new();
+                key.hash(&mut member);
+                hash_into(value, &mut member);
+                sum.wrapping_add(member.finish())
+            });
+            map.len().hash(state);
+            members.hash(state);
+        }
+        scalar => scalar.hash(state),
+    }
+}
+
+impl Container {
+    /// A hash of the whole structure of the value: equal values have the
+    /// same hash, whatever the order of their object members.
+    ///
+    /// ```
+    /// use json_parser::parser::parse_str;
+    ///
+    /// let a = parse_str(r#"{"x": [1, 2], "y": null}"#).unwrap();
+    /// let b = parse_str(r#"{"y": null, "x": [1, 2]}"#).unwrap();
+    /// let c = parse_str(r#"{"x": [2, 1], "y": null}"#).unwrap();
+    /// assert_eq!(a.structural_hash(), b.structural_hash());
+    /// assert_ne!(a.structural_hash(), c.structural_hash());
+    /// ```
+    pub fn structural_hash(&self) -> u64 {
+        let mut state = DefaultHasher::new();
+        hash_into(self, &mut state);
+        state.finish()
+    }
+}
+
+/// Results of a computation, such as a validation or canonical bytes,
+/// memoized per subtree.
+///
+/// Entries are found by [`Container::structural_hash`] and compared with
+/// the subtree, so that colliding hashes never share a result. The cache
+/// keeps a copy of each subtree it holds a result for, and is emptied once
+/// it holds its limit of entries.
+///
+/// ```
+/// use json_parser::cache::SubtreeCache;
+/// use json_parser::parser::parse_str;
+///
+/// let mut sizes = SubtreeCache::new();
+/// let mut runs = 0;
+/// let doc = parse_str(r#"[{"id": 1}, {"id": 1}, {"id": 2}]"#).unwrap();
+/// for idx in 0..doc.len() {
+///     sizes.get_or_insert_with(&doc[idx], |value| {
+///         runs += 1;
+///         value.to_string().len()
+///     });
+/// }
+/// assert_eq!(runs, 2);
+/// assert_eq!(sizes.get(&doc[0]), Some(&8));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SubtreeCache<T> {
+    entries: HashMap<u64, Vec<(Container, T)>>,
+    len: usize,
+    limit: usize,
+}
+
+impl<T> Default for SubtreeCache<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> SubtreeCache<T> {
+    pub fn new() -> Self {
+        Self::with_limit(CACHE_LIMIT)
+    }
+
+    /// A cache emptied once it holds `limit` entries.
+    pub fn with_limit(limit: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            len: 0,
+            limit: limit.max(1),
+        }
+    }
+
+    /// The result computed for a subtree equal to `value`, if any.
+    pub fn get(&self, value: &Container) -> Option<&T> {
+        self.entries
+            .get(&value.structural_hash())?
+            .iter()
+            .find(|(subtree, _)| subtree == value)
+            .map(|(_, result)| result)
+    }
+
+    /// The result computed for a subtree equal to `value`, computing it
+    /// with `compute` if there is none.
+    pub fn get_or_insert_with(
+        &mut self,
+        value: &Container,
+        compute: impl FnOnce(&Container) -> T,
+    ) -> &T {
+        let hash = value.structural_hash();
+        let found = self.entries.get(&hash).and_then(|bucket| {
+            bucket.iter().position(|(subtree, _)| subtree == value)
+        });
+        let idx = match found {
+            Some(idx) => idx,
+            None => {
+                if self.len >= self.limit {
+                    self.clear();
+                }
+                let result = compute(value);
+                let bucket = self.entries.entry(hash).or_default();
+                bucket.push((value.clone(), result));
+                self.len += 1;
+                bucket.len() - 1
+            }
+        };
+        &self.entries[&hash][idx].1
+    }
+
+    /// Number of results held.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.len = 0;
+    }
+}
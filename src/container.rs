@@ -1,7 +1,14 @@
+use super::dump;
+use super::error::{DumpError, Error};
+use super::map::{Entry, Key, Map};
+use super::small_string::SmallString;
+#[cfg(feature = "uuid")]
+use super::uuid;
 use core::fmt;
 use core::hash::{Hash, Hasher};
 use core::ops::{Index, IndexMut};
 use std::collections::HashMap;
+use std::io::{self, IsTerminal};
 
 /// A Container that has ability to store different kind
 /// of data at a time. This includes basic data types like
@@ -16,7 +23,7 @@ use std::collections::HashMap;
 /// ```
 /// use json_parser::container::Container;
 /// let storage: Container = Container::Decimal(2e9+76.0);
-/// let mut str_container: Container = Container::String("here".to_string());
+/// let mut str_container: Container = Container::String("here".into());
 /// assert_eq!(storage.get_real(), Some(2e9+76.0));
 /// assert_eq!(storage.get_string(), None);
 /// assert_eq!(str_container.get_string(), Some("here".into()));
@@ -25,7 +32,8 @@ use std::collections::HashMap;
 ///
 /// And combination of such like:
 /// - Array (An expandable, randomly accessible list)
-/// - Object (A HashMap, that associates a string key with a value)
+/// - Object (A [`Map`], that associates a string key with a value,
+///   keeping the members in insertion order)
 ///
 /// ```
 /// use json_parser::container::Container;
@@ -35,7 +43,7 @@ use std::collections::HashMap;
 /// array_container.push(Container::Decimal(2.34));
 ///
 /// let mut object_container: Container = Container::new_object();
-/// object_container.insert_str("key1", Container::String("hello".to_string()));
+/// object_container.insert_str("key1", Container::String("hello".into()));
 ///
 /// array_container.push(object_container);
 ///
@@ -51,19 +59,35 @@ pub enum Container {
     Number(i64),
     /// A 16 byte unsigned integer
     Unsigned(u64),
+    /// A 16 byte signed integer, for negative values beyond `i64`
+    Number128(i128),
+    /// A 16 byte unsigned integer, for values beyond `u64`
+    Unsigned128(u128),
     /// An 8 byte real number
     Decimal(f64),
+    /// A number exactly as written in the input, kept by the parser with
+    /// `ParserOptions::raw_numbers`, or for real numbers beyond `f64`
+    RawNumber(String),
+    /// An integer beyond the range of `i128` and `u128`, as its decimal
+    /// digits with an optional leading `-`
+    BigNumber(String),
+    /// A UUID, recognized in strings by the parser with
+    /// `ParserOptions::uuids` and written back in canonical form
+    #[cfg(feature = "uuid")]
+    Uuid([u8; 16]),
     /// boolean value
     Boolean(bool),
-    /// String
-    String(String),
+    /// String, kept inline when short
+    String(SmallString),
     /// Dynamic allocated that can store
     /// these containers in consecutive fashion
     /// of their insertion.
     Array(Vec<Container>),
     /// Key value pair, where key is string
-    /// and value can be any of these types
-    Object(HashMap<String, Container>),
+    /// and value can be any of these types.
+    /// Members iterate in insertion order; see
+    /// [`ObjectOrder`](super::parser::ObjectOrder) for parsed documents.
+    Object(Map),
 }
 
 impl Clone for Container {
@@ -72,7 +96,13 @@ impl Clone for Container {
         match self {
             Self::Number(element) => Self::Number(*element),
             Self::Unsigned(element) => Self::Unsigned(*element),
+            Self::Number128(element) => Self::Number128(*element),
+            Self::Unsigned128(element) => Self::Unsigned128(*element),
             Self::Decimal(element) => Self::Decimal(*element),
+            Self::RawNumber(element) => Self::RawNumber(element.to_owned()),
+            Self::BigNumber(element) => Self::BigNumber(element.to_owned()),
+            #[cfg(feature = "uuid")]
+            Self::Uuid(element) => Self::Uuid(*element),
             Self::Boolean(element) => Self::Boolean(*element),
             Self::String(element) => Self::String(element.to_owned()),
             Self::Array(array) => Self::Array(array.clone()),
@@ -87,8 +117,14 @@ impl Hash for Container {
         match self {
             Self::Number(v) => v.hash(s),
             Self::Unsigned(v) => v.hash(s),
+            Self::Number128(v) => v.hash(s),
+            Self::Unsigned128(v) => v.hash(s),
             Self::Boolean(v) => v.hash(s),
             Self::String(v) => v.hash(s),
+            Self::RawNumber(v) => v.hash(s),
+            Self::BigNumber(v) => v.hash(s),
+            #[cfg(feature = "uuid")]
+            Self::Uuid(v) => v.hash(s),
             _ => (),
         }
     }
@@ -112,7 +148,15 @@ impl PartialEq for Container {
         match (self, other) {
             (Self::Number(this), Self::Number(other)) => this == other,
             (Self::Unsigned(this), Self::Unsigned(other)) => this == other,
+            (Self::Number128(this), Self::Number128(other)) => this == other,
+            (Self::Unsigned128(this), Self::Unsigned128(other)) => {
+                this == other
+            }
             (Self::Decimal(this), Self::Decimal(other)) => this == other,
+            (Self::RawNumber(this), Self::RawNumber(other)) => this == other,
+            (Self::BigNumber(this), Self::BigNumber(other)) => this == other,
+            #[cfg(feature = "uuid")]
+            (Self::Uuid(this), Self::Uuid(other)) => this == other,
             (Self::Boolean(this), Self::Boolean(other)) => this == other,
             (Self::String(this), Self::String(other)) => this == other,
             (Self::Array(arr), Self::Array(oarr)) => {
@@ -129,10 +173,183 @@ impl PartialEq for Container {
     }
 }
 
+/// How `NaN` and infinite `Decimal` values, which JSON cannot represent,
+/// are written when dumping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NonFinite {
+    /// Write `NaN`, `Infinity` and `-Infinity`, as Python's `json` module
+    /// does; the parser reads them back with
+    /// `ParserOptions::non_finite_numbers`.
+    #[default]
+    Literal,
+    /// Write `null`.
+    Null,
+    /// Fail with `DumpError::NonFiniteNumber`.
+    Error,
+}
+
+/// Line ending of pretty-printed output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Newline {
+    /// `\n`
+    #[default]
+    Lf,
+    /// `\r\n`
+    CrLf,
+}
+
+impl Newline {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Newline::Lf => "\n",
+            Newline::CrLf => "\r\n",
+        }
+    }
+}
+
+/// Configuration of [`Container::dump`] and
+/// [`Container::dump_to_writer`], built from the compact layout of
+/// [`DumpOptions::new`].
+///
+/// ```
+/// use json_parser::container::{DumpOptions, Newline};
+/// use json_parser::parser::parse_str;
+///
+/// let value = parse_str(r#"{"b": "é", "a": [true]}"#).unwrap();
+/// let compact = DumpOptions::new().sort_keys(true).ascii(true);
+/// assert_eq!(value.dump(&compact).unwrap(), r#"{"a":[true],"b":"\u00e9"}"#);
+///
+/// let tabs = DumpOptions::new()
+///     .indent(1)
+///     .indent_char('\t')
+///     .newline(Newline::CrLf);
+/// assert_eq!(
+///     value.dump(&tabs).unwrap(),
+///     "{\r\n\t\"b\": \"é\",\r\n\t\"a\": [\r\n\t\ttrue\r\n\t]\r\n}"
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct DumpOptions {
+    pub(crate) indent: Option<usize>,
+    pub(crate) indent_char: char,
+    pub(crate) sort_keys: bool,
+    pub(crate) ascii: bool,
+    pub(crate) newline: Newline,
+    pub(crate) non_finite: NonFinite,
+    pub(crate) color: bool,
+    /// Nesting level of the dumped value, only set by the deprecated
+    /// dumps.
+    pub(crate) depth: usize,
+}
+
+impl Default for DumpOptions {
+    fn default() -> Self {
+        Self {
+            indent: None,
+            indent_char: ' ',
+            sort_keys: false,
+            ascii: false,
+            newline: Newline::default(),
+            non_finite: NonFinite::default(),
+            color: false,
+            depth: 1,
+        }
+    }
+}
+
+impl DumpOptions {
+    /// Compact output on a single line, keys in the order of the objects.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pretty-printed output, indented by 4 spaces per level.
+    pub fn pretty() -> Self {
+        Self::new().indent(4)
+    }
+
+    /// Writes members one per line, indented by `size` indent characters
+    /// per level.
+    pub fn indent(mut self, size: usize) -> Self {
+        self.indent = Some(size);
+        self
+    }
+
+    /// Character repeated to indent lines, a space by default.
+    pub fn indent_char(mut self, indent_char: char) -> Self {
+        self.indent_char = indent_char;
+        self
+    }
+
+    /// Writes the members of objects sorted by key, comparing bytes.
+    pub fn sort_keys(mut self, sort_keys: bool) -> Self {
+        self.sort_keys = sort_keys;
+        self
+    }
+
+    /// Escapes every character outside ASCII as `\uXXXX`.
+    pub fn ascii(mut self, ascii: bool) -> Self {
+        self.ascii = ascii;
+        self
+    }
+
+    /// Line ending written between indented lines, `\n` by default.
+    /// Compact output has none.
+    pub fn newline(mut self, newline: Newline) -> Self {
+        self.newline = newline;
+        self
+    }
+
+    /// Handling of `NaN` and infinite `Decimal` values.
+    pub fn non_finite(mut self, non_finite: NonFinite) -> Self {
+        self.non_finite = non_finite;
+        self
+    }
+
+    /// Colors keys, strings, numbers and literals with ANSI escapes, for
+    /// display in a terminal.
+    pub fn color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+}
+
+/// Options matching the positional arguments of the deprecated dumps.
+fn legacy_options(
+    indent: bool,
+    indent_size: usize,
+    depth: usize,
+) -> DumpOptions {
+    let options = DumpOptions {
+        depth,
+        ..DumpOptions::new()
+    };
+    if indent {
+        options.indent(indent_size)
+    } else {
+        options
+    }
+}
+
+/// `{}` writes compact JSON on a single line, and `{:#}` writes it
+/// pretty-printed, indented by 4 spaces per level or by the width, as in
+/// `{:#2}`.
+///
+/// ```
+/// use json_parser::parser::parse_str;
+///
+/// let value = parse_str(r#"{"ids": [1, 2]}"#).unwrap();
+/// assert_eq!(format!("{}", value), r#"{"ids":[1,2]}"#);
+/// assert_eq!(format!("{:#2}", value), "{\n  \"ids\": [\n    1,\n    2\n  ]\n}");
+/// ```
 impl fmt::Display for Container {
     #[inline(always)]
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.write_str(&self.dump_object(true, 4, 1))
+        let mut options = DumpOptions::new();
+        if f.alternate() {
+            options = options.indent(f.width().unwrap_or(4));
+        }
+        dump::write(self, f, &options)
     }
 }
 
@@ -142,7 +359,7 @@ impl Container {
     /// Returned New Object
     #[inline(always)]
     pub fn new_object() -> Self {
-        Self::Object(HashMap::new())
+        Self::Object(Map::new())
     }
 
     /// Returns New Array Object
@@ -177,101 +394,139 @@ impl Container {
     }
 
     /// Dump value to a string.
+    ///
+    /// Nested values are written iteratively into a single buffer, so
+    /// arbitrarily deep documents can be dumped.
+    #[deprecated(note = "use `Container::dump` with `DumpOptions`")]
     pub fn dump_object(
         &self,
         indent: bool,
         indent_size: usize,
         depth: usize,
     ) -> String {
-        match self {
-            Self::Array(value) => {
-                if value.is_empty() {
-                    "[]".to_owned()
-                } else if !indent {
-                    "[".to_owned()
-                        + &value
-                            .iter()
-                            .map(|e| {
-                                e.dump_object(indent, indent_size, depth + 1)
-                            })
-                            .collect::<Vec<String>>()
-                            .join(",")
-                        + "]"
-                } else {
-                    let wspace = " ".repeat((depth - 1) * indent_size);
-                    let space = " ".repeat(depth * indent_size);
-
-                    "[\n".to_owned()
-                        + &value
-                            .iter()
-                            .map(|e| {
-                                space.to_owned()
-                                    + &e.dump_object(
-                                        indent,
-                                        indent_size,
-                                        depth + 1,
-                                    )
-                            })
-                            .collect::<Vec<String>>()
-                            .join(",\n")
-                        + "\n"
-                        + &wspace
-                        + "]"
-                }
-            }
-            Self::Object(map) => {
-                if map.is_empty() {
-                    "{}".to_owned()
-                } else if !indent {
-                    "{".to_owned()
-                        + &map
-                            .iter()
-                            .map(|(k, v)| {
-                                format!("{:?}", k)
-                                    + &v.dump_object(
-                                        indent,
-                                        indent_size,
-                                        depth + 1,
-                                    )
-                            })
-                            .collect::<Vec<String>>()
-                            .join(",")
-                        + "}"
-                } else {
-                    let wspace = " ".repeat((depth - 1) * indent_size);
-                    let space = " ".repeat(depth * indent_size);
-
-                    "{\n".to_owned()
-                        + &map
-                            .iter()
-                            .map(|(k, v)| {
-                                space.to_owned()
-                                    + &format!("{:?}: ", k)
-                                    + &v.dump_object(
-                                        indent,
-                                        indent_size,
-                                        depth + 1,
-                                    )
-                            })
-                            .collect::<Vec<String>>()
-                            .join(",\n")
-                        + "\n"
-                        + &wspace
-                        + "}"
-                }
+        self.dump(&legacy_options(indent, indent_size, depth))
+            .expect("non-finite numbers are written as literals")
+    }
+
+    /// Dump value to a string like [`Container::dump_object`], writing
+    /// `NaN` and infinite `Decimal` values as told by `non_finite`.
+    #[deprecated(note = "use `Container::dump` with `DumpOptions`")]
+    pub fn dump_object_with(
+        &self,
+        indent: bool,
+        indent_size: usize,
+        depth: usize,
+        non_finite: NonFinite,
+    ) -> Result<String, Box<dyn core::error::Error>> {
+        self.dump(
+            &legacy_options(indent, indent_size, depth).non_finite(non_finite),
+        )
+    }
+
+    /// Dump value to a string laid out as told by `options`.
+    ///
+    /// Nested values are written iteratively into a single buffer, so
+    /// arbitrarily deep documents can be dumped. Fails with
+    /// `DumpError::NonFiniteNumber` as told by `options`.
+    ///
+    /// ```
+    /// use json_parser::container::{Container, DumpOptions, NonFinite};
+    ///
+    /// let values = Container::Array(vec![1.5.into(), f64::NAN.into()]);
+    /// let dump = |policy| values.dump(&DumpOptions::new().non_finite(policy));
+    /// assert_eq!(dump(NonFinite::Literal).unwrap(), "[1.5,NaN]");
+    /// assert_eq!(dump(NonFinite::Null).unwrap(), "[1.5,null]");
+    /// assert!(dump(NonFinite::Error).is_err());
+    /// ```
+    pub fn dump(
+        &self,
+        options: &DumpOptions,
+    ) -> Result<String, Box<dyn core::error::Error>> {
+        let mut out = String::new();
+        match dump::write(self, &mut out, options) {
+            Ok(()) => Ok(out),
+            Err(_) => Err(Error::Dump(DumpError::NonFiniteNumber).into()),
+        }
+    }
+
+    /// Dump value pretty-printed for the terminal: colored when the
+    /// standard output is a terminal and the `NO_COLOR` environment
+    /// variable is not set, as with [`DumpOptions::color`], and plain
+    /// otherwise so that it can be piped.
+    pub fn dump_colored(&self) -> String {
+        let color = io::stdout().is_terminal()
+            && std::env::var_os("NO_COLOR").is_none();
+        let mut out = String::new();
+        dump::write(self, &mut out, &DumpOptions::pretty().color(color))
+            .expect("writing into a String cannot fail");
+        out
+    }
+
+    /// Dump value to compact JSON that [`parse_str`] always reads back.
+    ///
+    /// `NaN` and infinite `Decimal` values, which JSON cannot represent,
+    /// are written as `null`, and a `RawNumber` or `BigNumber` holding
+    /// text that is not a number is written as a string.
+    ///
+    /// [`parse_str`]: crate::parser::parse_str
+    ///
+    /// ```
+    /// use json_parser::container::Container;
+    /// use json_parser::parser::parse_str;
+    ///
+    /// let mut value = Container::new_object();
+    /// value.insert_str("quote \"\u{1}\"", Container::from(f64::INFINITY));
+    /// let text = value.to_json_string();
+    /// assert_eq!(text, r#"{"quote \"\u0001\"":null}"#);
+    /// assert!(parse_str(&text).is_ok());
+    /// ```
+    pub fn to_json_string(&self) -> String {
+        let options = DumpOptions::new().non_finite(NonFinite::Null);
+        let mut out = String::new();
+        dump::write(self, &mut out, &options)
+            .expect("writing into a String cannot fail");
+        out
+    }
+
+    /// Writes the value into `writer` as it is serialized, without
+    /// building the text in memory first. Writes are small, so a
+    /// `BufWriter` should wrap unbuffered writers such as files.
+    ///
+    /// Fails with the first error of `writer`, or with
+    /// `DumpError::NonFiniteNumber` as told by `options`.
+    ///
+    /// ```
+    /// use json_parser::container::{Container, DumpOptions, NonFinite};
+    /// use json_parser::parser::parse_str;
+    ///
+    /// let value = parse_str(r#"{"id": 7, "tags": ["a", "b"]}"#).unwrap();
+    /// let mut out = Vec::new();
+    /// value.dump_to_writer(&mut out, &DumpOptions::new()).unwrap();
+    /// assert_eq!(out, br#"{"id":7,"tags":["a","b"]}"#);
+    ///
+    /// let options = DumpOptions::new().non_finite(NonFinite::Error);
+    /// let nan = Container::from(f64::NAN);
+    /// assert!(nan.dump_to_writer(&mut Vec::new(), &options).is_err());
+    /// ```
+    pub fn dump_to_writer(
+        &self,
+        writer: &mut impl io::Write,
+        options: &DumpOptions,
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        let mut out = dump::IoWriter::new(writer);
+        let written = dump::write(self, &mut out, options);
+        match (written, out.error) {
+            (Ok(()), _) => Ok(()),
+            (Err(_), Some(error)) => Err(error.into()),
+            (Err(_), None) => {
+                Err(Error::Dump(DumpError::NonFiniteNumber).into())
             }
-            Self::Number(value) => value.to_string(),
-            Self::Unsigned(value) => value.to_string(),
-            Self::Boolean(value) => value.to_string(),
-            Self::Decimal(value) => value.to_string(),
-            Self::String(value) => format!("{:?}", value),
-            Self::Null => "null".to_owned(),
         }
     }
 
     pub fn get_string(&self) -> Option<String> {
         match self {
-            Self::String(value) => Some(value.to_owned()),
+            Self::String(value) => Some(value.to_string()),
             _ => None,
         }
     }
@@ -290,6 +545,20 @@ impl Container {
         }
     }
 
+    pub fn get_int128(&self) -> Option<i128> {
+        match self {
+            Self::Number128(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn get_uint128(&self) -> Option<u128> {
+        match self {
+            Self::Unsigned128(value) => Some(*value),
+            _ => None,
+        }
+    }
+
     pub fn get_real(&self) -> Option<f64> {
         match self {
             Self::Decimal(value) => Some(*value),
@@ -297,6 +566,40 @@ impl Container {
         }
     }
 
+    pub fn get_raw_number(&self) -> Option<String> {
+        match self {
+            Self::RawNumber(value) => Some(value.to_owned()),
+            _ => None,
+        }
+    }
+
+    pub fn get_big_number(&self) -> Option<String> {
+        match self {
+            Self::BigNumber(value) => Some(value.to_owned()),
+            _ => None,
+        }
+    }
+
+    /// Returns the bytes of a `Uuid`, or of a string holding a UUID in
+    /// canonical form, so that it works whether or not the parser
+    /// recognized it.
+    ///
+    /// ```
+    /// use json_parser::container::Container;
+    ///
+    /// let id = Container::from("67e55044-10b1-426f-9247-bb680e5fe0c8");
+    /// assert_eq!(id.get_uuid().map(|bytes| bytes[0]), Some(0x67));
+    /// assert_eq!(Container::from("67e55044").get_uuid(), None);
+    /// ```
+    #[cfg(feature = "uuid")]
+    pub fn get_uuid(&self) -> Option<[u8; 16]> {
+        match self {
+            Self::Uuid(value) => Some(*value),
+            Self::String(value) => uuid::parse(value),
+            _ => None,
+        }
+    }
+
     pub fn get_bool(&self) -> Option<bool> {
         match self {
             Self::Boolean(value) => Some(*value),
@@ -362,7 +665,7 @@ impl Container {
     #[inline]
     pub fn is_object_and<F>(&self, f: F) -> bool
     where
-        F: Fn(&HashMap<String, Self>) -> bool,
+        F: Fn(&Map) -> bool,
     {
         match self {
             Self::Object(val) => f(val),
@@ -385,8 +688,19 @@ impl Container {
 
     define_type_checks!(Unsigned, is_unsigned);
 
+    define_type_checks!(Number128, is_number128);
+
+    define_type_checks!(Unsigned128, is_unsigned128);
+
     define_type_checks!(Decimal, is_decimal);
 
+    define_type_checks!(RawNumber, is_raw_number);
+
+    define_type_checks!(BigNumber, is_big_number);
+
+    #[cfg(feature = "uuid")]
+    define_type_checks!(Uuid, is_uuid);
+
     define_type_checks!(Boolean, is_bool);
 
     define_type_checks!(String, is_str);
@@ -408,6 +722,181 @@ impl Container {
             _ => 1,
         }
     }
+
+    /// Returns `true` if an array, object or string holds no elements
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Self::Array(value) => value.is_empty(),
+            Self::Object(value) => value.is_empty(),
+            Self::String(value) => value.is_empty(),
+            _ => false,
+        }
+    }
+
+    /// Returns the value of the member `key`, or `None` if self is not an
+    /// object or has no such member. Indexing returns `Null` instead.
+    ///
+    /// ```
+    /// use json_parser::parser::parse_str;
+    ///
+    /// let doc = parse_str(r#"{"note": null, "tags": ["a"]}"#).unwrap();
+    /// assert!(doc.get("note").is_some_and(|note| note.is_null()));
+    /// assert!(doc.get("missing").is_none());
+    /// assert!(doc["note"].is_null() && doc["missing"].is_null());
+    /// assert_eq!(doc.get("tags").and_then(|tags| tags.get_index(0)), Some(&"a".into()));
+    /// assert!(doc["tags"].get_index(1).is_none());
+    /// ```
+    pub fn get(&self, key: &str) -> Option<&Container> {
+        match self {
+            Self::Object(map) => map.get(key),
+            _ => None,
+        }
+    }
+
+    /// Returns the element at `idx`, or `None` if self is not an array or
+    /// the index is out of bounds.
+    pub fn get_index(&self, idx: usize) -> Option<&Container> {
+        match self {
+            Self::Array(values) => values.get(idx),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of the member `key` to change it in place, or
+    /// `None` if self is not an object or has no such member. Unlike
+    /// `IndexMut`, the member is not created if missing.
+    ///
+    /// ```
+    /// use json_parser::parser::parse_str;
+    ///
+    /// let mut doc = parse_str(r#"{"hits": 1, "tags": []}"#).unwrap();
+    /// *doc.get_mut("hits").unwrap() = 2.into();
+    /// assert!(doc.get_mut("misses").is_none());
+    /// assert!(doc["tags"].get_index_mut(0).is_none());
+    /// assert_eq!(doc, parse_str(r#"{"hits": 2, "tags": []}"#).unwrap());
+    /// ```
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Container> {
+        match self {
+            Self::Object(map) => map.get_mut(key),
+            _ => None,
+        }
+    }
+
+    /// Returns the element at `idx` to change it in place, or `None` if
+    /// self is not an array or the index is out of bounds.
+    pub fn get_index_mut(&mut self, idx: usize) -> Option<&mut Container> {
+        match self {
+            Self::Array(values) => values.get_mut(idx),
+            _ => None,
+        }
+    }
+
+    /// The member `key`, to insert or update it with a single lookup, or
+    /// `None` if self is not an object.
+    ///
+    /// ```
+    /// use json_parser::container::Container;
+    /// use json_parser::map::Map;
+    ///
+    /// let mut doc = Container::Object(Map::new());
+    /// for (group, name) in [("a", "ada"), ("b", "bob"), ("a", "al")] {
+    ///     let members = doc
+    ///         .entry(group)
+    ///         .unwrap()
+    ///         .or_insert_with(|| Container::Array(Vec::new()));
+    ///     if let Container::Array(members) = members {
+    ///         members.push(name.into());
+    ///     }
+    /// }
+    /// assert_eq!(doc.to_string(), r#"{"a":["ada","al"],"b":["bob"]}"#);
+    /// assert!(Container::Null.entry("a").is_none());
+    /// ```
+    pub fn entry(&mut self, key: impl Into<Key>) -> Option<Entry<'_>> {
+        match self {
+            Self::Object(map) => Some(map.entry(key)),
+            _ => None,
+        }
+    }
+
+    /// Removes the member `key` and returns its value, keeping the order
+    /// of the others, or `None` if self is not an object or has no such
+    /// member.
+    ///
+    /// ```
+    /// use json_parser::container::Container;
+    /// use json_parser::parser::parse_str;
+    ///
+    /// let mut doc = parse_str(r#"{"a": 1, "b": [1, 2, 3]}"#).unwrap();
+    /// assert_eq!(doc.remove("a"), Some(1.into()));
+    /// assert_eq!(doc.remove("a"), None);
+    /// assert_eq!(doc["b"].remove_index(0), Some(1.into()));
+    /// assert_eq!(doc["b"].pop(), Some(3.into()));
+    /// assert_eq!(doc["b"].remove_index(1), None);
+    ///
+    /// let b = doc["b"].take();
+    /// assert_eq!(b, parse_str("[2]").unwrap());
+    /// assert_eq!(doc, parse_str(r#"{"b": null}"#).unwrap());
+    /// ```
+    pub fn remove(&mut self, key: &str) -> Option<Container> {
+        match self {
+            Self::Object(map) => map.remove(key),
+            _ => None,
+        }
+    }
+
+    /// Removes the element at `idx`, shifting the ones after it, or
+    /// returns `None` if self is not an array or the index is out of
+    /// bounds.
+    pub fn remove_index(&mut self, idx: usize) -> Option<Container> {
+        match self {
+            Self::Array(values) if idx < values.len() => {
+                Some(values.remove(idx))
+            }
+            _ => None,
+        }
+    }
+
+    /// Removes the last element of an array, or returns `None` if self is
+    /// not an array or is empty.
+    pub fn pop(&mut self) -> Option<Container> {
+        match self {
+            Self::Array(values) => values.pop(),
+            _ => None,
+        }
+    }
+
+    /// Returns the value, leaving `Null` in its place.
+    pub fn take(&mut self) -> Container {
+        core::mem::replace(self, Self::Null)
+    }
+
+    /// Drops the value from a work list of its nested arrays and objects
+    /// instead of recursively. Dropping a value as usual recurses once per
+    /// level, which overflows the stack for documents parsed with a large
+    /// `ParserOptions::max_depth`.
+    ///
+    /// ```
+    /// use json_parser::parser::{parse_with_options, ParserOptions};
+    ///
+    /// let options = ParserOptions {
+    ///     max_depth: u16::MAX,
+    ///     ..Default::default()
+    /// };
+    /// let input = "[".repeat(50_000) + &"]".repeat(50_000);
+    /// parse_with_options(&input, &options).unwrap().drop_deep();
+    /// ```
+    pub fn drop_deep(self) {
+        let mut stack = vec![self];
+        while let Some(value) = stack.pop() {
+            match value {
+                Self::Array(values) => stack.extend(values),
+                Self::Object(map) => {
+                    stack.extend(map.into_iter().map(|(_, value)| value))
+                }
+                _ => {}
+            }
+        }
+    }
 }
 
 impl Index<usize> for Container {
@@ -516,3 +1005,119 @@ impl IndexMut<&str> for Container {
         }
     }
 }
+
+macro_rules! impl_from_signed {
+    ($($int:ty),*) => {
+        $(
+            impl From<$int> for Container {
+                /// Non-negative values are stored as `Unsigned`, matching
+                /// what the parser produces for the same literal.
+                fn from(value: $int) -> Self {
+                    if value < 0 {
+                        Self::Number(value as i64)
+                    } else {
+                        Self::Unsigned(value as u64)
+                    }
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_from_unsigned {
+    ($($uint:ty),*) => {
+        $(
+            impl From<$uint> for Container {
+                fn from(value: $uint) -> Self {
+                    Self::Unsigned(value as u64)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_signed!(i8, i16, i32, i64, isize);
+
+impl_from_unsigned!(u8, u16, u32, u64, usize);
+
+impl From<i128> for Container {
+    /// Values are stored in the smallest variant holding them, like the
+    /// parser does.
+    fn from(value: i128) -> Self {
+        if let Ok(value) = u64::try_from(value) {
+            Self::Unsigned(value)
+        } else if let Ok(value) = i64::try_from(value) {
+            Self::Number(value)
+        } else if let Ok(value) = u128::try_from(value) {
+            Self::Unsigned128(value)
+        } else {
+            Self::Number128(value)
+        }
+    }
+}
+
+impl From<u128> for Container {
+    /// Values beyond `u64` are stored as `Unsigned128`.
+    fn from(value: u128) -> Self {
+        match u64::try_from(value) {
+            Ok(value) => Self::Unsigned(value),
+            Err(_) => Self::Unsigned128(value),
+        }
+    }
+}
+
+impl From<f32> for Container {
+    fn from(value: f32) -> Self {
+        Self::Decimal(value as f64)
+    }
+}
+
+impl From<f64> for Container {
+    fn from(value: f64) -> Self {
+        Self::Decimal(value)
+    }
+}
+
+impl From<bool> for Container {
+    fn from(value: bool) -> Self {
+        Self::Boolean(value)
+    }
+}
+
+impl From<&str> for Container {
+    fn from(value: &str) -> Self {
+        Self::String(value.into())
+    }
+}
+
+impl From<String> for Container {
+    fn from(value: String) -> Self {
+        Self::String(value.into())
+    }
+}
+
+impl From<Vec<Container>> for Container {
+    fn from(value: Vec<Container>) -> Self {
+        Self::Array(value)
+    }
+}
+
+impl From<Map> for Container {
+    fn from(value: Map) -> Self {
+        Self::Object(value)
+    }
+}
+
+impl From<HashMap<String, Container>> for Container {
+    /// Members are kept in the (unspecified) iteration order of `value`.
+    fn from(value: HashMap<String, Container>) -> Self {
+        Self::Object(value.into_iter().collect())
+    }
+}
+
+impl<T: Into<Container>> From<Option<T>> for Container {
+    /// `None` is stored as `Null`.
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Self::Null, Into::into)
+    }
+}
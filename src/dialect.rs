@@ -0,0 +1,180 @@
+//! Hooks for parsing JSON-like dialects with the core parser.
+//!
+//! A dialect customizes the tokenizer (string quotes, comments, bare
+//! object keys, trailing commas) and the table of literal keywords, while
+//! nesting limits, number parsing and error reporting stay those of the
+//! core parser.
+use super::static_container::StaticContainer;
+
+/// The literal keywords of standard JSON.
+pub const JSON_LITERALS: &[(&str, StaticContainer)] = &[
+    ("true", StaticContainer::Boolean(true)),
+    ("false", StaticContainer::Boolean(false)),
+    ("null", StaticContainer::Null),
+];
+
+/// The non-finite numbers written by Python's `json` module and many
+/// scientific tools, accepted by the parser with
+/// `ParserOptions::non_finite_numbers`.
+pub const NON_FINITE_LITERALS: &[(&str, StaticContainer)] = &[
+    ("NaN", StaticContainer::Decimal(f64::NAN)),
+    ("Infinity", StaticContainer::Decimal(f64::INFINITY)),
+    ("-Infinity", StaticContainer::Decimal(f64::NEG_INFINITY)),
+];
+
+/// Describes a JSON-like syntax understood by the parser.
+///
+/// Every method has a default matching standard JSON, so a dialect only
+/// overrides what it changes.
+///
+/// ```
+/// use json_parser::dialect::Dialect;
+/// use json_parser::parser::parse_with_dialect;
+///
+/// /// JSON with `#` line comments and trailing commas.
+/// struct Relaxed;
+///
+/// impl Dialect for Relaxed {
+///     fn comment_starts(&self) -> &[u8] {
+///         b"#"
+///     }
+///
+///     fn skip_comment(&self, rest: &[u8]) -> Option<usize> {
+///         Some(rest.iter().position(|b| *b == b'\n').unwrap_or(rest.len()))
+///     }
+///
+///     fn trailing_commas(&self) -> bool {
+///         true
+///     }
+/// }
+///
+/// let doc = parse_with_dialect("[1, 2, # two\n 3,]", &Relaxed).unwrap();
+/// assert_eq!(doc.len(), 3);
+/// ```
+pub trait Dialect {
+    /// Bytes that open (and close) a string.
+    fn quotes(&self) -> &[u8] {
+        b"\""
+    }
+
+    /// Literal keywords and the values they stand for. When several
+    /// keywords match, the longest one wins.
+    fn literals(&self) -> &[(&str, StaticContainer)] {
+        JSON_LITERALS
+    }
+
+    /// Bytes that may start a comment. [`Dialect::skip_comment`] is only
+    /// consulted when one of them is found between tokens.
+    fn comment_starts(&self) -> &[u8] {
+        b""
+    }
+
+    /// Given the remaining input (starting with a byte of
+    /// [`Dialect::comment_starts`]), returns the length of the comment to
+    /// skip, or `None` if no comment starts there.
+    fn skip_comment(&self, _rest: &[u8]) -> Option<usize> {
+        None
+    }
+
+    /// Whether a `,` may follow the last element of an array or object.
+    fn trailing_commas(&self) -> bool {
+        false
+    }
+
+    /// Whether object keys may be written as bare identifiers
+    /// (`[A-Za-z_$][A-Za-z0-9_$]*`) instead of strings.
+    fn bare_keys(&self) -> bool {
+        false
+    }
+}
+
+/// Standard JSON.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Json;
+
+impl Dialect for Json {}
+
+/// The literals of JSON with the non-finite numbers.
+const LENIENT_LITERALS: &[(&str, StaticContainer)] = &[
+    ("true", StaticContainer::Boolean(true)),
+    ("false", StaticContainer::Boolean(false)),
+    ("null", StaticContainer::Null),
+    ("NaN", StaticContainer::Decimal(f64::NAN)),
+    ("Infinity", StaticContainer::Decimal(f64::INFINITY)),
+    ("-Infinity", StaticContainer::Decimal(f64::NEG_INFINITY)),
+];
+
+/// Length of the `//` or `/* */` comment at the start of `rest`.
+fn skip_slash_comment(rest: &[u8]) -> Option<usize> {
+    match rest {
+        [b'/', b'/', ..] => {
+            Some(rest.iter().position(|b| *b == b'\n').unwrap_or(rest.len()))
+        }
+        [b'/', b'*', body @ ..] => {
+            body.windows(2).position(|w| w == b"*/").map(|end| end + 4)
+        }
+        _ => None,
+    }
+}
+
+/// JSON as commonly written by hand: `//` and `/* */` comments, trailing
+/// commas, strings in single quotes, and `NaN` and the infinities.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Lenient;
+
+impl Dialect for Lenient {
+    fn quotes(&self) -> &[u8] {
+        b"\"'"
+    }
+
+    fn literals(&self) -> &[(&str, StaticContainer)] {
+        LENIENT_LITERALS
+    }
+
+    fn comment_starts(&self) -> &[u8] {
+        b"/"
+    }
+
+    fn skip_comment(&self, rest: &[u8]) -> Option<usize> {
+        skip_slash_comment(rest)
+    }
+
+    fn trailing_commas(&self) -> bool {
+        true
+    }
+}
+
+/// The syntax of JSON5 that the core parser reads: that of [`Lenient`],
+/// and keys written as bare identifiers.
+///
+/// Hexadecimal numbers are read with `ParserOptions::extended_numbers`.
+/// Numbers with a leading `+` or starting or ending with `.`, and strings
+/// continued over lines, are still rejected.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Json5;
+
+impl Dialect for Json5 {
+    fn quotes(&self) -> &[u8] {
+        b"\"'"
+    }
+
+    fn literals(&self) -> &[(&str, StaticContainer)] {
+        LENIENT_LITERALS
+    }
+
+    fn comment_starts(&self) -> &[u8] {
+        b"/"
+    }
+
+    fn skip_comment(&self, rest: &[u8]) -> Option<usize> {
+        skip_slash_comment(rest)
+    }
+
+    fn trailing_commas(&self) -> bool {
+        true
+    }
+
+    fn bare_keys(&self) -> bool {
+        true
+    }
+}
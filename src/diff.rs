@@ -0,0 +1,566 @@
+//! Structural diff between two containers, expressed as a JSON Patch
+//! (RFC 6902) document that `Container::apply_patch` accepts.
+//!
+//! Arrays are aligned before their elements are compared: an element found
+//! on both sides, by content or by an id member, is kept or moved instead of
+//! being rewritten at every index it shifted from.
+//!
+//! [`DiffOptions`] can also leave paths out of the comparison, tolerate
+//! small numeric differences, and ignore the order of arrays, so that two
+//! responses of an API can be compared despite their timestamps and
+//! unordered lists.
+use super::container::Container;
+use super::pointer;
+use core::hash::{Hash, Hasher};
+use std::collections::HashMap;
+
+/// How [`diff_with`] compares two documents.
+///
+/// ```
+/// use json_parser::diff::{diff_with, DiffOptions};
+/// use json_parser::parser::parse_str;
+///
+/// let source = parse_str(r#"[{"id": 1, "n": "a"}, {"id": 2}]"#).unwrap();
+/// let target = parse_str(r#"[{"id": 2}, {"id": 1, "n": "b"}]"#).unwrap();
+/// let patch = diff_with(&source, &target, &DiffOptions::new().id_field("id"));
+/// let expected = parse_str(
+///     r#"[{"op": "move", "path": "/0", "from": "/1"},
+///         {"op": "replace", "path": "/1/n", "value": "b"}]"#,
+/// )
+/// .unwrap();
+/// assert_eq!(patch, expected);
+///
+/// let source = parse_str(r#"{"at": 1, "tags": ["a", "b"], "load": 0.5}"#)
+///     .unwrap();
+/// let target = parse_str(r#"{"at": 2, "tags": ["b", "a"], "load": 0.51}"#)
+///     .unwrap();
+/// let loose = DiffOptions::new()
+///     .ignore_path("/at")
+///     .tolerance(0.05)
+///     .ignore_array_order(true);
+/// assert!(diff_with(&source, &target, &loose).is_empty());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DiffOptions {
+    id_field: Option<Vec<String>>,
+    /// Escaped tokens of the paths left out
+    ignored: Vec<Vec<String>>,
+    tolerance: Option<f64>,
+    ignore_array_order: bool,
+}
+
+impl DiffOptions {
+    /// Elements matched by content.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Matches elements by the value of `field`, a member name or a JSON
+    /// Pointer when it starts with `/`, and diffs the matched elements
+    /// member by member. Elements without it are matched by content.
+    pub fn id_field(mut self, field: &str) -> Self {
+        self.id_field = if field.starts_with('/') {
+            pointer::split(field)
+        } else {
+            Some(vec![field.to_owned()])
+        };
+        self
+    }
+
+    /// Leaves out the values at `pattern`, a JSON Pointer where a `*`
+    /// token stands for any member or element. A pattern that is not a
+    /// JSON Pointer leaves out nothing.
+    pub fn ignore_path(mut self, pattern: &str) -> Self {
+        if let Some(tokens) = pointer::split(pattern) {
+            let tokens = tokens.iter().map(|token| pointer::escape(token));
+            self.ignored.push(tokens.collect());
+        }
+        self
+    }
+
+    /// Takes numbers at most `epsilon` apart as equal.
+    pub fn tolerance(mut self, epsilon: f64) -> Self {
+        self.tolerance = Some(epsilon.abs());
+        self
+    }
+
+    /// Takes arrays with the same elements in any order as equal. Elements
+    /// are then matched wherever they are, changed ones are patched in
+    /// place and new ones are appended.
+    pub fn ignore_array_order(mut self, ignore: bool) -> Self {
+        self.ignore_array_order = ignore;
+        self
+    }
+
+    /// Whether the value at the escaped JSON Pointer `path` is left out.
+    fn is_ignored(&self, path: &str) -> bool {
+        self.ignored.iter().any(|pattern| {
+            let mut tokens = path.split('/').skip(1);
+            pattern.iter().all(|wanted| {
+                tokens
+                    .next()
+                    .is_some_and(|token| wanted == "*" || wanted == token)
+            }) && tokens.next().is_none()
+        })
+    }
+
+    /// Whether `source` and `target`, at `path`, differ by nothing but
+    /// what is left out or tolerated.
+    fn equivalent(
+        &self,
+        source: &Container,
+        target: &Container,
+        mut path: String,
+    ) -> bool {
+        let mut operations = Vec::new();
+        diff_into(source, target, &mut path, self, &mut operations);
+        operations.is_empty()
+    }
+}
+
+fn number(value: &Container) -> Option<f64> {
+    match *value {
+        Container::Decimal(value) => Some(value),
+        Container::Number(value) => Some(value as f64),
+        Container::Unsigned(value) => Some(value as f64),
+        Container::Number128(value) => Some(value as f64),
+        Container::Unsigned128(value) => Some(value as f64),
+        _ => None,
+    }
+}
+
+/// Returns a JSON Patch array that turns `source` into `target`.
+///
+/// Object members are visited in sorted key order, so the same pair of
+/// documents always yields the same patch. Array elements are matched by
+/// content, so that an inserted element is one `add` and a moved one a
+/// `move`.
+///
+/// ```
+/// use json_parser::diff::diff;
+/// use json_parser::parser::parse_str;
+///
+/// let mut source = parse_str(r#"{"a": 1, "b": [1, 2]}"#).unwrap();
+/// let target = parse_str(r#"{"b": [1, 3, 4], "c": null}"#).unwrap();
+///
+/// let patch = diff(&source, &target);
+/// source.apply_patch(&patch).unwrap();
+/// assert_eq!(source, target);
+/// ```
+pub fn diff(source: &Container, target: &Container) -> Container {
+    diff_with(source, target, &DiffOptions::new())
+}
+
+/// Returns a JSON Patch array that turns `source` into `target`, compared
+/// as `options` tell.
+pub fn diff_with(
+    source: &Container,
+    target: &Container,
+    options: &DiffOptions,
+) -> Container {
+    let mut operations = Vec::new();
+    diff_into(source, target, &mut String::new(), options, &mut operations);
+    Container::Array(operations)
+}
+
+/// Builds a single patch operation object.
+pub(crate) fn operation(
+    op: &str,
+    path: &str,
+    value: Option<&Container>,
+) -> Container {
+    let mut operation = Container::new_object();
+    operation.insert_str("op", Container::from(op));
+    operation.insert_str("path", Container::from(path));
+    if let Some(value) = value {
+        operation.insert_str("value", value.clone());
+    }
+    operation
+}
+
+fn diff_into(
+    source: &Container,
+    target: &Container,
+    path: &mut String,
+    options: &DiffOptions,
+    operations: &mut Vec<Container>,
+) {
+    if source == target || options.is_ignored(path) {
+        return;
+    }
+    if let (Some(epsilon), Some(old), Some(new)) =
+        (options.tolerance, number(source), number(target))
+    {
+        if (old - new).abs() <= epsilon {
+            return;
+        }
+    }
+
+    match (source, target) {
+        (Container::Object(old), Container::Object(new)) => {
+            let mut keys: Vec<&str> = old.keys().chain(new.keys()).collect();
+            keys.sort_unstable();
+            keys.dedup();
+
+            for key in keys {
+                let len = path.len();
+                path.push('/');
+                path.push_str(&pointer::escape(key));
+
+                match (old.get(key), new.get(key)) {
+                    _ if options.is_ignored(path) => {}
+                    (Some(old_value), Some(new_value)) => diff_into(
+                        old_value, new_value, path, options, operations,
+                    ),
+                    (Some(_), None) => {
+                        operations.push(operation("remove", path, None))
+                    }
+                    (None, Some(new_value)) => {
+                        operations.push(operation("add", path, Some(new_value)))
+                    }
+                    (None, None) => {}
+                }
+                path.truncate(len);
+            }
+        }
+        (Container::Array(old), Container::Array(new)) => {
+            if options.ignore_array_order {
+                diff_unordered(old, new, path, options, operations)
+            } else {
+                diff_arrays(old, new, path, options, operations)
+            }
+        }
+        _ => operations.push(operation("replace", path, Some(target))),
+    }
+}
+
+/// What an array element is matched by: its id, or its whole content.
+struct Key<'a> {
+    value: &'a Container,
+    by_id: bool,
+    hash: u64,
+}
+
+impl<'a> Key<'a> {
+    fn new(element: &'a Container, options: &DiffOptions) -> Self {
+        let id = options
+            .id_field
+            .as_deref()
+            .and_then(|field| pointer::resolve(element, field));
+        let value = id.unwrap_or(element);
+        Self {
+            value,
+            by_id: id.is_some(),
+            hash: value.structural_hash(),
+        }
+    }
+}
+
+impl PartialEq for Key<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash
+            && self.by_id == other.by_id
+            && self.value == other.value
+    }
+}
+
+impl Eq for Key<'_> {}
+
+impl Hash for Key<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.hash.hash(state)
+    }
+}
+
+/// The longest run of `pairs`, sorted by their second member, whose first
+/// members increase as well.
+fn increasing(pairs: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    // Patience sorting: `tops[k]` ends the best run of `k + 1` pairs.
+    let mut tops: Vec<usize> = Vec::new();
+    let mut previous = vec![None; pairs.len()];
+    for (idx, &(first, _)) in pairs.iter().enumerate() {
+        let pile = tops.partition_point(|&top| pairs[top].0 < first);
+        previous[idx] = pile.checked_sub(1).map(|pile| tops[pile]);
+        if pile == tops.len() {
+            tops.push(idx);
+        } else {
+            tops[pile] = idx;
+        }
+    }
+    let mut run = Vec::new();
+    let mut next = tops.last().copied();
+    while let Some(idx) = next {
+        run.push(pairs[idx]);
+        next = previous[idx];
+    }
+    run.reverse();
+    run
+}
+
+/// Appends to `kept` the pairs of equal keys of `old[olo..ohi]` and
+/// `new[nlo..nhi]` that stay in order, patience diff style: the common ends
+/// first, then the longest ordered run of keys found once on each side,
+/// then the same between the keys of that run.
+fn align(
+    old: &[Key],
+    new: &[Key],
+    (mut olo, mut ohi): (usize, usize),
+    (mut nlo, mut nhi): (usize, usize),
+    kept: &mut Vec<(usize, usize)>,
+) {
+    while olo < ohi && nlo < nhi && old[olo] == new[nlo] {
+        kept.push((olo, nlo));
+        olo += 1;
+        nlo += 1;
+    }
+    let mut tail = 0;
+    while olo < ohi && nlo < nhi && old[ohi - 1] == new[nhi - 1] {
+        ohi -= 1;
+        nhi -= 1;
+        tail += 1;
+    }
+
+    // Occurrences in `old`, occurrences in `new`, and the index in `old`.
+    let mut counts: HashMap<&Key, (usize, usize, usize)> = HashMap::new();
+    for (idx, key) in old.iter().enumerate().take(ohi).skip(olo) {
+        let count = counts.entry(key).or_default();
+        count.0 += 1;
+        count.2 = idx;
+    }
+    for key in &new[nlo..nhi] {
+        if let Some(count) = counts.get_mut(key) {
+            count.1 += 1;
+        }
+    }
+    let unique: Vec<_> = (nlo..nhi)
+        .filter_map(|idx| match counts.get(&new[idx]) {
+            Some(&(1, 1, old_idx)) => Some((old_idx, idx)),
+            _ => None,
+        })
+        .collect();
+
+    let anchors = increasing(&unique);
+    if !anchors.is_empty() {
+        let (mut old_at, mut new_at) = (olo, nlo);
+        for (old_idx, new_idx) in anchors {
+            align(old, new, (old_at, old_idx), (new_at, new_idx), kept);
+            kept.push((old_idx, new_idx));
+            (old_at, new_at) = (old_idx + 1, new_idx + 1);
+        }
+        align(old, new, (old_at, ohi), (new_at, nhi), kept);
+    }
+    kept.extend((0..tail).map(|idx| (ohi + idx, nhi + idx)));
+}
+
+/// Diffs two arrays: old elements matched by none are removed, matched
+/// ones out of order are moved, new ones are added, and the matched pairs
+/// are then diffed in place.
+fn diff_arrays(
+    old: &[Container],
+    new: &[Container],
+    path: &mut String,
+    options: &DiffOptions,
+    operations: &mut Vec<Container>,
+) {
+    let old_keys: Vec<_> = old.iter().map(|e| Key::new(e, options)).collect();
+    let new_keys: Vec<_> = new.iter().map(|e| Key::new(e, options)).collect();
+    let mut kept = Vec::new();
+    align(
+        &old_keys,
+        &new_keys,
+        (0, old.len()),
+        (0, new.len()),
+        &mut kept,
+    );
+
+    // The old element each new one comes from.
+    let mut source = vec![None; new.len()];
+    let mut matched = vec![false; old.len()];
+    for &(old_idx, new_idx) in &kept {
+        source[new_idx] = Some(old_idx);
+        matched[old_idx] = true;
+    }
+
+    // Equal keys left on both sides are elements that moved.
+    let mut moved = vec![false; old.len()];
+    let mut left: HashMap<&Key, Vec<usize>> = HashMap::new();
+    for idx in (0..old.len()).rev().filter(|&idx| !matched[idx]) {
+        left.entry(&old_keys[idx]).or_default().push(idx);
+    }
+    for (new_idx, key) in new_keys.iter().enumerate() {
+        if source[new_idx].is_some() {
+            continue;
+        }
+        if let Some(old_idx) = left.get_mut(key).and_then(Vec::pop) {
+            source[new_idx] = Some(old_idx);
+            matched[old_idx] = true;
+            moved[old_idx] = true;
+        }
+    }
+
+    // Elements without an id still left between the same kept ones
+    // changed in place.
+    let (mut old_at, mut new_at) = (0, 0);
+    for (old_end, new_end) in
+        kept.iter().copied().chain([(old.len(), new.len())])
+    {
+        let olds: Vec<_> = (old_at..old_end)
+            .filter(|&idx| !matched[idx] && !old_keys[idx].by_id)
+            .collect();
+        let news: Vec<_> = (new_at..new_end)
+            .filter(|&idx| source[idx].is_none() && !new_keys[idx].by_id)
+            .collect();
+        for (old_idx, new_idx) in olds.into_iter().zip(news) {
+            source[new_idx] = Some(old_idx);
+            matched[old_idx] = true;
+        }
+        (old_at, new_at) = (old_end + 1, new_end + 1);
+    }
+
+    // Removals go back to front to keep indices valid.
+    for idx in (0..old.len()).rev().filter(|&idx| !matched[idx]) {
+        operations.push(operation(
+            "remove",
+            &format!("{}/{}", path, idx),
+            None,
+        ));
+    }
+
+    // The new array is built front to back: after the element last put in
+    // place come the kept elements not reached yet, in order, and the moved
+    // ones not reached yet, anywhere.
+    let mut current: Vec<_> = (0..old.len())
+        .filter(|&idx| matched[idx])
+        .map(Some)
+        .collect();
+    let mut done = 0;
+    for (new_idx, new_value) in new.iter().enumerate() {
+        match source[new_idx] {
+            Some(old_idx) if !moved[old_idx] => {
+                done += current[done..]
+                    .iter()
+                    .position(|&slot| slot == Some(old_idx))
+                    .unwrap_or(0)
+                    + 1;
+            }
+            Some(old_idx) => {
+                let from = current
+                    .iter()
+                    .position(|&slot| slot == Some(old_idx))
+                    .unwrap_or(done);
+                current.remove(from);
+                if from < done {
+                    done -= 1;
+                }
+                current.insert(done, Some(old_idx));
+                if from != done {
+                    let mut op =
+                        operation("move", &format!("{}/{}", path, done), None);
+                    let from = format!("{}/{}", path, from);
+                    op.insert_str("from", Container::from(from.as_str()));
+                    operations.push(op);
+                }
+                done += 1;
+            }
+            None => {
+                current.insert(done, None);
+                operations.push(operation(
+                    "add",
+                    &format!("{}/{}", path, done),
+                    Some(new_value),
+                ));
+                done += 1;
+            }
+        }
+    }
+
+    for (new_idx, old_idx) in source.into_iter().enumerate() {
+        if let Some(old_idx) = old_idx {
+            let len = path.len();
+            path.push_str(&format!("/{}", new_idx));
+            diff_into(&old[old_idx], &new[new_idx], path, options, operations);
+            path.truncate(len);
+        }
+    }
+}
+
+/// Diffs two arrays whose order does not matter: elements are matched by
+/// key, then by equivalence, then in order between the elements without
+/// an id. Matched pairs are diffed in place, the old elements left are
+/// removed and the new ones appended.
+fn diff_unordered(
+    old: &[Container],
+    new: &[Container],
+    path: &mut String,
+    options: &DiffOptions,
+    operations: &mut Vec<Container>,
+) {
+    let old_keys: Vec<_> = old.iter().map(|e| Key::new(e, options)).collect();
+    let new_keys: Vec<_> = new.iter().map(|e| Key::new(e, options)).collect();
+
+    let mut source = vec![None; new.len()];
+    let mut matched = vec![false; old.len()];
+    let mut left: HashMap<&Key, Vec<usize>> = HashMap::new();
+    for (idx, key) in old_keys.iter().enumerate().rev() {
+        left.entry(key).or_default().push(idx);
+    }
+    for (new_idx, key) in new_keys.iter().enumerate() {
+        if let Some(old_idx) = left.get_mut(key).and_then(Vec::pop) {
+            source[new_idx] = Some(old_idx);
+            matched[old_idx] = true;
+        }
+    }
+    for (new_idx, new_value) in new.iter().enumerate() {
+        if source[new_idx].is_some() {
+            continue;
+        }
+        let found = (0..old.len()).find(|&idx| {
+            !matched[idx] && {
+                let at = format!("{}/{}", path, idx);
+                options.equivalent(&old[idx], new_value, at)
+            }
+        });
+        if let Some(old_idx) = found {
+            source[new_idx] = Some(old_idx);
+            matched[old_idx] = true;
+        }
+    }
+    let olds: Vec<_> = (0..old.len())
+        .filter(|&idx| !matched[idx] && !old_keys[idx].by_id)
+        .collect();
+    let news: Vec<_> = (0..new.len())
+        .filter(|&idx| source[idx].is_none() && !new_keys[idx].by_id)
+        .collect();
+    for (old_idx, new_idx) in olds.into_iter().zip(news) {
+        source[new_idx] = Some(old_idx);
+        matched[old_idx] = true;
+    }
+
+    // Pairs are diffed at their old index, before the removals move it.
+    for (new_idx, old_idx) in source.iter().enumerate() {
+        if let Some(old_idx) = *old_idx {
+            let len = path.len();
+            path.push_str(&format!("/{}", old_idx));
+            diff_into(&old[old_idx], &new[new_idx], path, options, operations);
+            path.truncate(len);
+        }
+    }
+    for idx in (0..old.len()).rev().filter(|&idx| !matched[idx]) {
+        operations.push(operation(
+            "remove",
+            &format!("{}/{}", path, idx),
+            None,
+        ));
+    }
+    let mut len = matched.iter().filter(|matched| **matched).count();
+    for (new_idx, new_value) in new.iter().enumerate() {
+        if source[new_idx].is_none() {
+            operations.push(operation(
+                "add",
+                &format!("{}/{}", path, len),
+                Some(new_value),
+            ));
+            len += 1;
+        }
+    }
+}
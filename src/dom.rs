@@ -0,0 +1,797 @@
+//! An index-based document model parsed into reusable buffers, for
+//! systems that cannot afford allocator jitter while parsing.
+//!
+//! A [`Dom`] keeps every node in one `Vec`, in document order, and the
+//! text of strings and keys in one `String`. Parsing clears both without
+//! releasing their memory, so once the buffers have grown to fit the
+//! largest document (or were created large enough with
+//! [`Dom::with_capacity`]), parsing does not allocate. Only failures do,
+//! to report their error.
+//!
+//! [`Dom::parse_indexed`] parses in two stages instead, reading the nodes
+//! from a [`StructuralIndex`] of the text.
+use super::container::Container;
+use super::encoding::bom_len;
+use super::error::{Error, ParseError};
+use super::index::StructuralIndex;
+use super::parser::Span;
+
+/// Deepest nesting accepted, as for the parser.
+const NEST_LIMIT: usize = 500;
+
+/// A value of a [`Dom`]. Strings refer to a byte range of the text
+/// buffer, and containers to the index just past their last descendant,
+/// their elements (or key and value of each member) following them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Node {
+    Null,
+    Boolean(bool),
+    Number(i64),
+    Unsigned(u64),
+    Number128(i128),
+    Unsigned128(u128),
+    Decimal(f64),
+    /// Digits of an integer beyond `i128` and `u128`
+    BigNumber {
+        start: usize,
+        end: usize,
+    },
+    /// A real number beyond `f64`, as written
+    RawNumber {
+        start: usize,
+        end: usize,
+    },
+    String {
+        start: usize,
+        end: usize,
+    },
+    /// The key of an object member, followed by its value
+    Key {
+        start: usize,
+        end: usize,
+    },
+    Array {
+        len: usize,
+        end: usize,
+    },
+    Object {
+        len: usize,
+        end: usize,
+    },
+}
+
+/// A parsed document, and the buffers reused for the next one.
+///
+/// ```
+/// use json_parser::dom::Dom;
+///
+/// let mut dom = Dom::with_capacity(64, 256, 8);
+/// dom.parse(r#"{"id": 7, "tags": ["a", "b"]}"#).unwrap();
+/// let root = dom.root().unwrap();
+/// assert_eq!(root.get("id").unwrap().as_u64(), Some(7));
+/// assert_eq!(root.get("tags").unwrap().at(1).unwrap().as_str(), Some("b"));
+///
+/// // The next document reuses the memory of the previous one.
+/// dom.parse("[1, 2, 3]").unwrap();
+/// assert_eq!(dom.root().unwrap().len(), 3);
+/// ```
+#[derive(Debug, Default)]
+pub struct Dom {
+    nodes: Vec<Node>,
+    text: String,
+    /// Indexes of the open containers while parsing
+    stack: Vec<usize>,
+    /// Whether `nodes` holds a whole document
+    complete: bool,
+}
+
+/// A node of a [`Dom`], with access to its children.
+#[derive(Debug, Clone, Copy)]
+pub struct NodeRef<'a> {
+    dom: &'a Dom,
+    id: usize,
+}
+
+impl Dom {
+    /// Creates an empty document with room for `nodes` values, `text`
+    /// bytes of string and key text, and `depth` levels of nesting.
+    pub fn with_capacity(nodes: usize, text: usize, depth: usize) -> Self {
+        Self {
+            nodes: Vec::with_capacity(nodes),
+            text: String::with_capacity(text),
+            stack: Vec::with_capacity(depth),
+            complete: false,
+        }
+    }
+
+    /// The root of the last document parsed successfully.
+    pub fn root(&self) -> Option<NodeRef<'_>> {
+        self.complete.then_some(NodeRef { dom: self, id: 0 })
+    }
+
+    /// The nodes of the document, in document order.
+    pub fn nodes(&self) -> &[Node] {
+        if self.complete {
+            &self.nodes
+        } else {
+            &[]
+        }
+    }
+
+    /// Parses `input`, replacing the previous document.
+    pub fn parse(
+        &mut self,
+        input: &str,
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        self.parse_from(Reader::new(input.as_bytes(), bom_len(input)))
+    }
+
+    /// Parses `input` in two stages, replacing the previous document:
+    /// `index` is built with the offsets of its tokens, then the nodes are
+    /// read from token to token, without looking at whitespace or at the
+    /// text of strings on the way. The document is the one [`Dom::parse`]
+    /// builds; indented documents are read faster, compact ones about as
+    /// fast.
+    ///
+    /// ```
+    /// use json_parser::dom::Dom;
+    /// use json_parser::index::StructuralIndex;
+    ///
+    /// let (mut dom, mut index) = (Dom::default(), StructuralIndex::new());
+    /// dom.parse_indexed(r#"{"rows": [[1, "a"], [2, "b"]]}"#, &mut index)
+    ///     .unwrap();
+    /// let rows = dom.root().unwrap().get("rows").unwrap();
+    /// assert_eq!(rows.at(1).unwrap().at(1).unwrap().as_str(), Some("b"));
+    /// assert!(dom.parse_indexed("[1, 2", &mut index).is_err());
+    /// ```
+    pub fn parse_indexed(
+        &mut self,
+        input: &str,
+        index: &mut StructuralIndex,
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        index.build(input);
+        let mut reader = Reader::new(input.as_bytes(), bom_len(input));
+        reader.index = index.offsets();
+        self.parse_from(reader)
+    }
+
+    fn parse_from(
+        &mut self,
+        mut reader: Reader,
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        self.nodes.clear();
+        self.text.clear();
+        self.stack.clear();
+        self.complete = false;
+
+        let result = self.read(&mut reader);
+        self.stack.clear();
+        result?;
+        self.complete = true;
+        Ok(())
+    }
+
+    fn read(
+        &mut self,
+        reader: &mut Reader,
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        'value: loop {
+            match reader.next_token()? {
+                byte @ (b'[' | b'{') => {
+                    if self.stack.len() == NEST_LIMIT {
+                        return Err(Error::Parsing(
+                            ParseError::NestedDepthExceeded(
+                                NEST_LIMIT as u16 + 1,
+                                reader.span(reader.pos - 1, reader.pos),
+                            ),
+                        )
+                        .into());
+                    }
+                    let object = byte == b'{';
+                    self.stack.push(self.nodes.len());
+                    self.nodes.push(if object {
+                        Node::Object { len: 0, end: 0 }
+                    } else {
+                        Node::Array { len: 0, end: 0 }
+                    });
+
+                    let close = if object { b'}' } else { b']' };
+                    if reader.peek_token() == Some(close) {
+                        reader.pos += 1;
+                        self.close();
+                    } else {
+                        if object {
+                            self.read_key(reader)?;
+                        }
+                        continue 'value;
+                    }
+                }
+                b'"' => {
+                    let (start, end) = self.read_string(reader)?;
+                    self.nodes.push(Node::String { start, end });
+                }
+                _ => {
+                    reader.pos -= 1;
+                    let node = self.read_scalar(reader)?;
+                    self.nodes.push(node);
+                }
+            }
+
+            // A value is complete: read separators and closing brackets.
+            loop {
+                let Some(&top) = self.stack.last() else {
+                    return match reader.peek_token() {
+                        Some(byte) => {
+                            Err(reader.unexpected_at(byte, reader.pos))
+                        }
+                        None => Ok(()),
+                    };
+                };
+                let object = matches!(self.nodes[top], Node::Object { .. });
+                if let Node::Array { len, .. } | Node::Object { len, .. } =
+                    &mut self.nodes[top]
+                {
+                    *len += 1;
+                }
+
+                match reader.next_token()? {
+                    b',' => {
+                        if object {
+                            self.read_key(reader)?;
+                        }
+                        continue 'value;
+                    }
+                    b']' if !object => self.close(),
+                    b'}' if object => self.close(),
+                    byte @ (b']' | b'}') => {
+                        return Err(Error::Parsing(
+                            ParseError::ContainerParanthesisMismatch {
+                                opening_container: if object {
+                                    '{'
+                                } else {
+                                    '['
+                                },
+                                closing_container: byte as char,
+                                span: reader.span(reader.pos - 1, reader.pos),
+                            },
+                        )
+                        .into());
+                    }
+                    byte => return Err(reader.unexpected(byte)),
+                }
+            }
+        }
+    }
+
+    /// Marks the end of the innermost open container.
+    fn close(&mut self) {
+        if let Some(top) = self.stack.pop() {
+            let next = self.nodes.len();
+            if let Node::Array { end, .. } | Node::Object { end, .. } =
+                &mut self.nodes[top]
+            {
+                *end = next;
+            }
+        }
+    }
+
+    /// Reads a member key and the `:` after it.
+    fn read_key(
+        &mut self,
+        reader: &mut Reader,
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        match reader.next_token()? {
+            b'"' => {
+                let (start, end) = self.read_string(reader)?;
+                self.nodes.push(Node::Key { start, end });
+            }
+            byte => return Err(reader.unexpected(byte)),
+        }
+        match reader.next_token()? {
+            b':' => Ok(()),
+            byte => Err(reader.unexpected(byte)),
+        }
+    }
+
+    /// Unescapes a string whose opening quote was read into the text
+    /// buffer, returning its range.
+    fn read_string(
+        &mut self,
+        reader: &mut Reader,
+    ) -> Result<(usize, usize), Box<dyn core::error::Error>> {
+        let start = self.text.len();
+        loop {
+            let rest = &reader.bytes[reader.pos..];
+            let run = rest
+                .iter()
+                .position(|byte| matches!(byte, b'"' | b'\\'))
+                .ok_or_else(|| reader.end_of_buffer())?;
+            // Runs end before an ASCII byte, so they are whole characters.
+            self.text.push_str(unsafe {
+                core::str::from_utf8_unchecked(&rest[..run])
+            });
+            reader.pos += run + 1;
+            if rest[run] == b'"' {
+                return Ok((start, self.text.len()));
+            }
+
+            let escape_at = reader.pos;
+            let chr = match reader.next_byte()? {
+                b'"' => '"',
+                b'\\' => '\\',
+                b'/' => '/',
+                b'b' => '\u{8}',
+                b'f' => '\u{c}',
+                b'n' => '\n',
+                b'r' => '\r',
+                b't' => '\t',
+                b'u' => reader.read_unicode()?,
+                _ => {
+                    let chr = core::str::from_utf8(&reader.bytes[escape_at..])
+                        .ok()
+                        .and_then(|text| text.chars().next())
+                        .unwrap_or(char::REPLACEMENT_CHARACTER);
+                    return Err(Error::Parsing(ParseError::InvalidEscape(
+                        chr,
+                        reader.span(escape_at, escape_at + chr.len_utf8()),
+                    ))
+                    .into());
+                }
+            };
+            self.text.push(chr);
+        }
+    }
+
+    /// Reads a literal or a number.
+    fn read_scalar(
+        &mut self,
+        reader: &mut Reader,
+    ) -> Result<Node, Box<dyn core::error::Error>> {
+        let rest = &reader.bytes[reader.pos..];
+        for (word, node) in [
+            ("true", Node::Boolean(true)),
+            ("false", Node::Boolean(false)),
+            ("null", Node::Null),
+        ] {
+            if rest.starts_with(word.as_bytes()) {
+                reader.pos += word.len();
+                return match reader.bytes.get(reader.pos) {
+                    Some(byte) if !is_delimiter(*byte) => {
+                        Err(reader.unexpected_at(*byte, reader.pos))
+                    }
+                    _ => Ok(node),
+                };
+            }
+        }
+
+        let start = reader.pos;
+        let (integer, len) =
+            number_len(rest).ok_or_else(|| match rest.first() {
+                Some(b'-' | b'0'..=b'9') => {
+                    Error::Parsing(ParseError::InvalidNumberParse(
+                        rest.get(1).map_or('\0', |byte| *byte as char),
+                        reader.span(start, (start + 2).min(reader.bytes.len())),
+                    ))
+                    .into()
+                }
+                Some(byte) => reader.unexpected_at(*byte, start),
+                None => reader.end_of_buffer(),
+            })?;
+        reader.pos += len;
+        if let Some(byte) = reader.bytes.get(reader.pos) {
+            if !is_delimiter(*byte) {
+                return Err(Error::Parsing(ParseError::InvalidNumberParse(
+                    *byte as char,
+                    reader.span(reader.pos, reader.pos + 1),
+                ))
+                .into());
+            }
+        }
+
+        // The number is ASCII, hence a `str`.
+        let text = unsafe { core::str::from_utf8_unchecked(&rest[..len]) };
+        let node = if !integer {
+            match text.parse()? {
+                value if f64::is_finite(value) => Node::Decimal(value),
+                _ => {
+                    let start = self.text.len();
+                    self.text.push_str(text);
+                    Node::RawNumber {
+                        start,
+                        end: self.text.len(),
+                    }
+                }
+            }
+        } else if let Ok(value) = text.parse() {
+            Node::Unsigned(value)
+        } else if let Ok(value) = text.parse() {
+            Node::Number(value)
+        } else if let Ok(value) = text.parse() {
+            Node::Unsigned128(value)
+        } else if let Ok(value) = text.parse() {
+            Node::Number128(value)
+        } else {
+            let start = self.text.len();
+            self.text.push_str(text);
+            Node::BigNumber {
+                start,
+                end: self.text.len(),
+            }
+        };
+        Ok(node)
+    }
+
+    fn node_to_container(&self, id: usize) -> Container {
+        let node = NodeRef { dom: self, id };
+        match self.nodes[id] {
+            Node::Null => Container::Null,
+            Node::Boolean(value) => Container::Boolean(value),
+            Node::Number(value) => Container::Number(value),
+            Node::Unsigned(value) => Container::Unsigned(value),
+            Node::Number128(value) => Container::Number128(value),
+            Node::Unsigned128(value) => Container::Unsigned128(value),
+            Node::Decimal(value) => Container::Decimal(value),
+            Node::BigNumber { start, end } => {
+                Container::BigNumber(self.text[start..end].to_owned())
+            }
+            Node::RawNumber { start, end } => {
+                Container::RawNumber(self.text[start..end].to_owned())
+            }
+            Node::String { start, end } | Node::Key { start, end } => {
+                Container::String(self.text[start..end].into())
+            }
+            Node::Array { .. } => Container::Array(
+                node.elements()
+                    .map(|element| self.node_to_container(element.id))
+                    .collect(),
+            ),
+            Node::Object { .. } => Container::Object(
+                node.members()
+                    .map(|(key, value)| {
+                        (key.to_owned(), self.node_to_container(value.id))
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Builds the `Container` of the document, `Null` if there is none.
+    pub fn to_container(&self) -> Container {
+        match self.complete {
+            true => self.node_to_container(0),
+            false => Container::Null,
+        }
+    }
+}
+
+/// Whether `byte` may follow a scalar.
+fn is_delimiter(byte: u8) -> bool {
+    matches!(
+        byte,
+        b' ' | b'\t' | b'\n' | b'\r' | 0x0c | b',' | b']' | b'}'
+    )
+}
+
+/// Returns whether the JSON number at the start of `bytes` is an integer,
+/// and its length, or `None` if it is malformed.
+pub(crate) fn number_len(bytes: &[u8]) -> Option<(bool, usize)> {
+    let digits = |from: usize| {
+        bytes[from..]
+            .iter()
+            .take_while(|byte| byte.is_ascii_digit())
+            .count()
+    };
+
+    let mut len = usize::from(bytes.first() == Some(&b'-'));
+    match bytes.get(len)? {
+        b'0' => len += 1,
+        b'1'..=b'9' => len += digits(len),
+        _ => return None,
+    }
+    let integer_end = len;
+    if bytes.get(len) == Some(&b'.') {
+        let fraction = digits(len + 1);
+        if fraction == 0 {
+            return None;
+        }
+        len += 1 + fraction;
+    }
+    if matches!(bytes.get(len), Some(b'e' | b'E')) {
+        len += 1;
+        if matches!(bytes.get(len), Some(b'+' | b'-')) {
+            len += 1;
+        }
+        let exponent = digits(len);
+        if exponent == 0 {
+            return None;
+        }
+        len += exponent;
+    }
+    Some((len == integer_end, len))
+}
+
+/// Position in the input being parsed.
+pub(crate) struct Reader<'a> {
+    pub(crate) bytes: &'a [u8],
+    pub(crate) pos: usize,
+    /// Offsets of the tokens of `bytes`, or nothing to look for them
+    index: &'a [usize],
+    /// Entries of `index` read
+    cursor: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(bytes: &'a [u8], pos: usize) -> Self {
+        Self {
+            bytes,
+            pos,
+            index: &[],
+            cursor: 0,
+        }
+    }
+
+    /// Line and column of the byte at `pos`, counted only on failure.
+    pub(crate) fn position(&self, pos: usize) -> (usize, usize) {
+        let before = &self.bytes[..pos.min(self.bytes.len())];
+        let line_start = before
+            .iter()
+            .rposition(|byte| *byte == b'\n')
+            .map_or(0, |idx| idx + 1);
+        let line = 1 + before.iter().filter(|byte| **byte == b'\n').count();
+        (line, pos - line_start + 1)
+    }
+
+    /// Span of the bytes `start..end`.
+    pub(crate) fn span(&self, start: usize, end: usize) -> Span {
+        let (line, col) = self.position(start);
+        Span {
+            start,
+            end,
+            line,
+            col,
+            path: None,
+        }
+    }
+
+    pub(crate) fn unexpected_at(
+        &self,
+        byte: u8,
+        pos: usize,
+    ) -> Box<dyn core::error::Error> {
+        let span = self.span(pos, pos + 1);
+        Error::Parsing(ParseError::UnexpectedToken(byte as char, span)).into()
+    }
+
+    /// Error for the input ending where more of it is expected.
+    pub(crate) fn end_of_buffer(&self) -> Box<dyn core::error::Error> {
+        let end = self.bytes.len();
+        Error::Parsing(ParseError::EndOfBuffer(self.span(end, end))).into()
+    }
+
+    /// Error for the byte just read.
+    pub(crate) fn unexpected(&self, byte: u8) -> Box<dyn core::error::Error> {
+        self.unexpected_at(byte, self.pos - 1)
+    }
+
+    pub(crate) fn next_byte(
+        &mut self,
+    ) -> Result<u8, Box<dyn core::error::Error>> {
+        let byte = *self
+            .bytes
+            .get(self.pos)
+            .ok_or_else(|| self.end_of_buffer())?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    fn skip_whitespace(&mut self) {
+        if !self.index.is_empty() {
+            // Moves to the first token not read yet; the bytes before it
+            // are whitespace, or the rest of a scalar that was checked.
+            while self.index.get(self.cursor).is_some_and(|&at| at < self.pos) {
+                self.cursor += 1;
+            }
+            self.pos = self
+                .index
+                .get(self.cursor)
+                .map_or(self.bytes.len().max(self.pos), |&at| at);
+            return;
+        }
+        // Form feeds too, as for the parser outside strict mode.
+        while matches!(
+            self.bytes.get(self.pos),
+            Some(b' ' | b'\t' | b'\n' | b'\r' | 0x0c)
+        ) {
+            self.pos += 1;
+        }
+    }
+
+    /// Reads the first byte after whitespace.
+    fn next_token(&mut self) -> Result<u8, Box<dyn core::error::Error>> {
+        self.skip_whitespace();
+        self.next_byte()
+    }
+
+    /// Returns the first byte after whitespace, without reading it.
+    fn peek_token(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn read_hex_unit(&mut self) -> Result<u16, Box<dyn core::error::Error>> {
+        let digits = self.bytes.get(self.pos..self.pos + 4).and_then(|hex| {
+            core::str::from_utf8(hex)
+                .ok()
+                .filter(|hex| hex.bytes().all(|byte| byte.is_ascii_hexdigit()))
+        });
+        match digits {
+            Some(hex) => {
+                self.pos += 4;
+                Ok(u16::from_str_radix(hex, 16)?)
+            }
+            None => {
+                let span =
+                    self.span(self.pos, (self.pos + 1).min(self.bytes.len()));
+                Err(Error::Parsing(ParseError::InvalidUnicodeEscape(span))
+                    .into())
+            }
+        }
+    }
+
+    /// Decodes a `\u` escape whose `u` was just read, with the second
+    /// half of a surrogate pair.
+    pub(crate) fn read_unicode(
+        &mut self,
+    ) -> Result<char, Box<dyn core::error::Error>> {
+        let at = self.pos;
+        let lone = |reader: &Self, unit| -> Box<dyn core::error::Error> {
+            // The escape starts at the backslash before the `u`.
+            let span = reader.span(at - 2, at + 4);
+            Error::Parsing(ParseError::LoneSurrogate(unit, span)).into()
+        };
+        let unit = self.read_hex_unit()?;
+        let code = match unit {
+            0xD800..=0xDBFF => {
+                if !self.bytes[self.pos..].starts_with(b"\\u") {
+                    return Err(lone(self, unit));
+                }
+                self.pos += 2;
+                let low = self.read_hex_unit()?;
+                if !(0xDC00..=0xDFFF).contains(&low) {
+                    return Err(lone(self, unit));
+                }
+                0x10000 + ((unit as u32 - 0xD800) << 10) + (low as u32 - 0xDC00)
+            }
+            0xDC00..=0xDFFF => return Err(lone(self, unit)),
+            _ => unit as u32,
+        };
+        Ok(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER))
+    }
+}
+
+impl<'a> NodeRef<'a> {
+    pub fn node(&self) -> Node {
+        self.dom.nodes[self.id]
+    }
+
+    /// Index of the node in [`Dom::nodes`].
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Index of the node following self and its descendants.
+    fn next_id(&self) -> usize {
+        match self.node() {
+            Node::Array { end, .. } | Node::Object { end, .. } => end,
+            _ => self.id + 1,
+        }
+    }
+
+    fn text(&self, start: usize, end: usize) -> &'a str {
+        &self.dom.text[start..end]
+    }
+
+    pub fn as_str(&self) -> Option<&'a str> {
+        match self.node() {
+            Node::String { start, end } => Some(self.text(start, end)),
+            _ => None,
+        }
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match self.node() {
+            Node::Unsigned(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self.node() {
+            Node::Number(value) => Some(value),
+            Node::Unsigned(value) => i64::try_from(value).ok(),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self.node() {
+            Node::Decimal(value) => Some(value),
+            Node::Number(value) => Some(value as f64),
+            Node::Unsigned(value) => Some(value as f64),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.node() {
+            Node::Boolean(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        self.node() == Node::Null
+    }
+
+    /// Number of elements or members of a container, 0 otherwise.
+    pub fn len(&self) -> usize {
+        match self.node() {
+            Node::Array { len, .. } | Node::Object { len, .. } => len,
+            _ => 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The elements of an array; nothing for other nodes.
+    pub fn elements(&self) -> impl Iterator<Item = NodeRef<'a>> {
+        let (dom, end) = match self.node() {
+            Node::Array { end, .. } => (self.dom, end),
+            _ => (self.dom, self.id + 1),
+        };
+        let mut next = self.id + 1;
+        core::iter::from_fn(move || {
+            let element = (next < end).then_some(NodeRef { dom, id: next })?;
+            next = element.next_id();
+            Some(element)
+        })
+    }
+
+    /// The keys and values of the members of an object; nothing for other
+    /// nodes.
+    pub fn members(&self) -> impl Iterator<Item = (&'a str, NodeRef<'a>)> {
+        let (dom, end) = match self.node() {
+            Node::Object { end, .. } => (self.dom, end),
+            _ => (self.dom, self.id + 1),
+        };
+        let mut next = self.id + 1;
+        core::iter::from_fn(move || {
+            if next >= end {
+                return None;
+            }
+            let key = match dom.nodes[next] {
+                Node::Key { start, end } => &dom.text[start..end],
+                _ => return None,
+            };
+            let value = NodeRef { dom, id: next + 1 };
+            next = value.next_id();
+            Some((key, value))
+        })
+    }
+
+    /// The element at `index` of an array.
+    pub fn at(&self, index: usize) -> Option<NodeRef<'a>> {
+        self.elements().nth(index)
+    }
+
+    /// The value of the last member named `key` of an object, like the
+    /// `Container` the parser builds.
+    pub fn get(&self, key: &str) -> Option<NodeRef<'a>> {
+        self.members()
+            .filter(|(name, _)| *name == key)
+            .map(|(_, value)| value)
+            .last()
+    }
+}
@@ -0,0 +1,401 @@
+//! Serialization of a `Container` into text.
+//!
+//! The writer walks the document with an explicit stack of iterators
+//! instead of recursing, and writes everything straight into one output,
+//! so neither the native stack nor intermediate `String`s grow with the
+//! depth of the document.
+use super::container::{Container, DumpOptions, NonFinite};
+use super::dialect::Json;
+use super::dom;
+use super::map;
+use super::tokens::{Token, Tokens};
+#[cfg(feature = "uuid")]
+use super::uuid;
+use core::fmt::{self, Write};
+use std::io;
+
+/// ANSI escapes starting each kind of token in colored output.
+const KEY_COLOR: &str = "\x1b[1;34m";
+const STRING_COLOR: &str = "\x1b[32m";
+const NUMBER_COLOR: &str = "\x1b[33m";
+const LITERAL_COLOR: &str = "\x1b[35m";
+const RESET: &str = "\x1b[0m";
+
+/// Runs of spaces written at once while indenting.
+const SPACES: &str = "                                ";
+
+/// A container whose elements are being written.
+enum Frame<'a> {
+    Array(core::slice::Iter<'a, Container>),
+    Object(map::Iter<'a>),
+    /// Members of an object, sorted by key.
+    Sorted(std::vec::IntoIter<(&'a str, &'a Container)>),
+}
+
+/// Adapts an `io::Write` to the `fmt::Write` the writer uses, keeping
+/// the error that made a write fail.
+pub(crate) struct IoWriter<'a, W: io::Write> {
+    inner: &'a mut W,
+    pub(crate) error: Option<io::Error>,
+}
+
+impl<'a, W: io::Write> IoWriter<'a, W> {
+    pub(crate) fn new(inner: &'a mut W) -> Self {
+        Self { inner, error: None }
+    }
+}
+
+impl<W: io::Write> Write for IoWriter<'_, W> {
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        self.inner.write_all(text.as_bytes()).map_err(|error| {
+            self.error = Some(error);
+            fmt::Error
+        })
+    }
+}
+
+/// Layout of the output.
+struct Layout<'o> {
+    options: &'o DumpOptions,
+}
+
+impl Layout<'_> {
+    /// Starts a new line indented for the given depth.
+    fn new_line<W: Write>(&self, out: &mut W, depth: usize) -> fmt::Result {
+        let Some(indent_size) = self.options.indent else {
+            return Ok(());
+        };
+        out.write_str(self.options.newline.as_str())?;
+        let mut width = depth * indent_size;
+        if self.options.indent_char != ' ' {
+            return (0..width)
+                .try_for_each(|_| out.write_char(self.options.indent_char));
+        }
+        while width > 0 {
+            let run = width.min(SPACES.len());
+            out.write_str(&SPACES[..run])?;
+            width -= run;
+        }
+        Ok(())
+    }
+}
+
+/// Writes `value` into `out` as told by `options`.
+///
+/// Fails without a cause when a non-finite number is found with
+/// `NonFinite::Error`, or when `out` fails.
+pub(crate) fn write<W: Write>(
+    value: &Container,
+    out: &mut W,
+    options: &DumpOptions,
+) -> fmt::Result {
+    let layout = Layout { options };
+    // Depth of the container on top of the stack is `depth + len - 1`.
+    let depth = options.depth.max(1);
+    let mut stack: Vec<(Frame, bool)> = Vec::new();
+
+    let mut next = Some(value);
+    loop {
+        if let Some(value) = next.take() {
+            match value {
+                Container::Array(values) if !values.is_empty() => {
+                    out.write_char('[')?;
+                    stack.push((Frame::Array(values.iter()), true));
+                }
+                Container::Object(map) if !map.is_empty() => {
+                    out.write_char('{')?;
+                    let frame = if options.sort_keys {
+                        let mut members: Vec<_> = map.iter().collect();
+                        members.sort_unstable_by(|a, b| a.0.cmp(b.0));
+                        Frame::Sorted(members.into_iter())
+                    } else {
+                        Frame::Object(map.iter())
+                    };
+                    stack.push((frame, true));
+                }
+                _ => match scalar_color(value, options.non_finite) {
+                    Some(color) if options.color => {
+                        out.write_str(color)?;
+                        write_scalar(value, out, options)?;
+                        out.write_str(RESET)?;
+                    }
+                    _ => write_scalar(value, out, options)?,
+                },
+            }
+        }
+
+        let level = depth + stack.len();
+        let Some((frame, first)) = stack.last_mut() else {
+            return Ok(());
+        };
+
+        let (key, value, close) = match frame {
+            Frame::Array(values) => (None, values.next(), ']'),
+            Frame::Object(members) => match members.next() {
+                Some((key, value)) => (Some(key), Some(value), '}'),
+                None => (None, None, '}'),
+            },
+            Frame::Sorted(members) => match members.next() {
+                Some((key, value)) => (Some(key), Some(value), '}'),
+                None => (None, None, '}'),
+            },
+        };
+
+        match value {
+            Some(value) => {
+                if !*first {
+                    out.write_char(',')?;
+                }
+                *first = false;
+                layout.new_line(out, level - 1)?;
+                if let Some(key) = key {
+                    if options.color {
+                        out.write_str(KEY_COLOR)?;
+                    }
+                    write_string(key, out, options.ascii)?;
+                    if options.color {
+                        out.write_str(RESET)?;
+                    }
+                    let colon =
+                        if options.indent.is_some() { ": " } else { ":" };
+                    out.write_str(colon)?;
+                }
+                next = Some(value);
+            }
+            None => {
+                stack.pop();
+                layout.new_line(out, level - 2)?;
+                out.write_char(close)?;
+            }
+        }
+    }
+}
+
+/// Writes the document `input` token by token, laid out as told by
+/// `options`. Strings and numbers are copied as written, and members keep
+/// their order: `sort_keys` and `non_finite` do not apply.
+pub(crate) fn reformat<W: Write>(
+    input: &str,
+    out: &mut W,
+    options: &DumpOptions,
+) -> Result<(), Box<dyn core::error::Error>> {
+    let layout = Layout { options };
+    let mut tokens = Tokens::new(input, &Json);
+    let (mut depth, mut prev) = (0, None);
+    while let Some(token) = tokens.next_token()? {
+        let opened =
+            matches!(prev, Some(Token::BeginArray | Token::BeginObject));
+        match token {
+            Token::EndArray | Token::EndObject => {
+                if !opened {
+                    layout.new_line(out, depth - 1)?;
+                }
+                out.write_char(if token == Token::EndArray {
+                    ']'
+                } else {
+                    '}'
+                })?;
+                depth -= 1;
+            }
+            _ => {
+                if depth > 0 && !matches!(prev, Some(Token::Key(_))) {
+                    if !opened {
+                        out.write_char(',')?;
+                    }
+                    layout.new_line(out, depth)?;
+                }
+                let (text, color) = match token {
+                    Token::Key(text) => (text, Some(KEY_COLOR)),
+                    Token::String(text) => (text, Some(STRING_COLOR)),
+                    Token::Number(text) => (text, Some(NUMBER_COLOR)),
+                    Token::Literal(text) => (text, Some(LITERAL_COLOR)),
+                    // Only brackets opening a container are left.
+                    _ => {
+                        depth += 1;
+                        let open =
+                            if token == Token::BeginArray { "[" } else { "{" };
+                        (open, None)
+                    }
+                };
+                match color {
+                    Some(color) if options.color => {
+                        out.write_str(color)?;
+                        write_raw(text, out, options.ascii)?;
+                        out.write_str(RESET)?;
+                    }
+                    _ => write_raw(text, out, options.ascii)?,
+                }
+                if let Token::Key(_) = token {
+                    let colon =
+                        if options.indent.is_some() { ": " } else { ":" };
+                    out.write_str(colon)?;
+                }
+            }
+        }
+        prev = Some(token);
+    }
+    Ok(())
+}
+
+/// Writes JSON text as is, with `ascii` escaping every character outside
+/// ASCII, which only appear in strings.
+fn write_raw<W: Write>(text: &str, out: &mut W, ascii: bool) -> fmt::Result {
+    if !ascii || text.is_ascii() {
+        return out.write_str(text);
+    }
+    let mut start = 0;
+    for (idx, chr) in text.char_indices().filter(|(_, chr)| !chr.is_ascii()) {
+        out.write_str(&text[start..idx])?;
+        start = idx + chr.len_utf8();
+        let mut units = [0; 2];
+        for unit in chr.encode_utf16(&mut units) {
+            write!(out, "\\u{:04x}", unit)?;
+        }
+    }
+    out.write_str(&text[start..])
+}
+
+/// Writes `value` as a quoted JSON string, escaping what JSON requires
+/// and, with `ascii`, every character outside ASCII.
+fn write_string<W: Write>(
+    value: &str,
+    out: &mut W,
+    ascii: bool,
+) -> fmt::Result {
+    out.write_char('"')?;
+    // Runs of characters needing no escape are written at once.
+    let mut start = 0;
+    for (idx, chr) in value.char_indices() {
+        let escape = match chr {
+            '"' => "\\\"",
+            '\\' => "\\\\",
+            '\n' => "\\n",
+            '\r' => "\\r",
+            '\t' => "\\t",
+            '\u{8}' => "\\b",
+            '\u{c}' => "\\f",
+            '\0'..='\u{1f}' => "",
+            _ if ascii && !chr.is_ascii() => "",
+            _ => continue,
+        };
+        out.write_str(&value[start..idx])?;
+        start = idx + chr.len_utf8();
+        if !escape.is_empty() {
+            out.write_str(escape)?;
+            continue;
+        }
+        let mut units = [0; 2];
+        for unit in chr.encode_utf16(&mut units) {
+            write!(out, "\\u{:04x}", unit)?;
+        }
+    }
+    out.write_str(&value[start..])?;
+    out.write_char('"')
+}
+
+/// The shortest text parsing back to the finite `value`, as a real number:
+/// `0.1`, `1.0`, `1e21`, `-2.5e-7`.
+///
+/// The standard formatting already picks the fewest significant digits
+/// that round-trip; this only picks the shorter of the positional and the
+/// exponent notation, and keeps a fraction on integral values so that
+/// they are not read back as integers.
+pub(crate) fn format_decimal(value: f64) -> String {
+    let scientific = format!("{:e}", value);
+    let (mantissa, exponent) =
+        scientific.split_once('e').unwrap_or((&scientific, "0"));
+    let exponent: i32 = exponent.parse().unwrap_or(0);
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(mantissa) => ("-", mantissa),
+        None => ("", mantissa),
+    };
+    let digits = mantissa.replace('.', "");
+
+    // Position of the decimal point relative to the first digit.
+    let point = exponent + 1;
+    let positional = if point <= 0 {
+        format!("{}0.{}{}", sign, "0".repeat(-point as usize), digits)
+    } else if point as usize >= digits.len() {
+        let zeros = "0".repeat(point as usize - digits.len());
+        format!("{}{}{}.0", sign, digits, zeros)
+    } else {
+        let (integer, fraction) = digits.split_at(point as usize);
+        format!("{}{}.{}", sign, integer, fraction)
+    };
+
+    if scientific.len() < positional.len() {
+        scientific
+    } else {
+        positional
+    }
+}
+
+/// Whether `text` is exactly one JSON number.
+pub(crate) fn is_number(text: &str) -> bool {
+    dom::number_len(text.as_bytes()).is_some_and(|(_, len)| len == text.len())
+}
+
+/// Color of a value written by [`write_scalar`], if it has one.
+fn scalar_color(
+    value: &Container,
+    non_finite: NonFinite,
+) -> Option<&'static str> {
+    Some(match value {
+        Container::Array(_) | Container::Object(_) => return None,
+        Container::Null | Container::Boolean(_) => LITERAL_COLOR,
+        Container::Decimal(value)
+            if !value.is_finite() && non_finite == NonFinite::Null =>
+        {
+            LITERAL_COLOR
+        }
+        Container::RawNumber(value) | Container::BigNumber(value)
+            if !is_number(value) =>
+        {
+            STRING_COLOR
+        }
+        Container::String(_) => STRING_COLOR,
+        #[cfg(feature = "uuid")]
+        Container::Uuid(_) => STRING_COLOR,
+        _ => NUMBER_COLOR,
+    })
+}
+
+/// Writes values without members, including empty arrays and objects.
+fn write_scalar<W: Write>(
+    value: &Container,
+    out: &mut W,
+    options: &DumpOptions,
+) -> fmt::Result {
+    match value {
+        Container::Array(_) => out.write_str("[]"),
+        Container::Object(_) => out.write_str("{}"),
+        Container::Number(value) => write!(out, "{}", value),
+        Container::Unsigned(value) => write!(out, "{}", value),
+        Container::Number128(value) => write!(out, "{}", value),
+        Container::Unsigned128(value) => write!(out, "{}", value),
+        Container::Boolean(value) => write!(out, "{}", value),
+        Container::Decimal(value) if value.is_finite() => {
+            out.write_str(&format_decimal(*value))
+        }
+        Container::Decimal(value) => match options.non_finite {
+            NonFinite::Literal if value.is_nan() => out.write_str("NaN"),
+            NonFinite::Literal if *value > 0.0 => out.write_str("Infinity"),
+            NonFinite::Literal => out.write_str("-Infinity"),
+            NonFinite::Null => out.write_str("null"),
+            NonFinite::Error => Err(fmt::Error),
+        },
+        Container::RawNumber(value) | Container::BigNumber(value)
+            if is_number(value) =>
+        {
+            out.write_str(value)
+        }
+        // Text that is not a number would make the output invalid.
+        Container::RawNumber(value) | Container::BigNumber(value) => {
+            write_string(value, out, options.ascii)
+        }
+        Container::String(value) => write_string(value, out, options.ascii),
+        #[cfg(feature = "uuid")]
+        Container::Uuid(value) => write!(out, "\"{}\"", uuid::format(value)),
+        Container::Null => out.write_str("null"),
+    }
+}
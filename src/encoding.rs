@@ -0,0 +1,149 @@
+//! Detection and decoding of the Unicode encoding of raw input.
+//!
+//! A byte order mark is honoured (and skipped) when present. Otherwise the
+//! encoding is guessed from the pattern of zero bytes at the start of the
+//! input, as described in RFC 4627: the first two characters of a JSON
+//! text are always ASCII.
+use super::error::{Error, ParseError};
+use std::borrow::Cow;
+
+/// Encodings understood by [`decode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Utf32Le,
+    Utf32Be,
+}
+
+/// Returns the encoding of `bytes` and the length of its byte order mark
+/// (0 if there is none).
+pub fn detect(bytes: &[u8]) -> (Encoding, usize) {
+    match bytes {
+        [0xEF, 0xBB, 0xBF, ..] => (Encoding::Utf8, 3),
+        [0xFF, 0xFE, 0, 0, ..] => (Encoding::Utf32Le, 4),
+        [0, 0, 0xFE, 0xFF, ..] => (Encoding::Utf32Be, 4),
+        [0xFF, 0xFE, ..] => (Encoding::Utf16Le, 2),
+        [0xFE, 0xFF, ..] => (Encoding::Utf16Be, 2),
+        [0, 0, 0, _, ..] => (Encoding::Utf32Be, 0),
+        [_, 0, 0, 0, ..] => (Encoding::Utf32Le, 0),
+        [0, _, ..] => (Encoding::Utf16Be, 0),
+        [_, 0, ..] => (Encoding::Utf16Le, 0),
+        _ => (Encoding::Utf8, 0),
+    }
+}
+
+/// Length of the byte order mark starting `text`, which is not part of
+/// the document.
+pub(crate) fn bom_len(text: &str) -> usize {
+    match text.starts_with('\u{feff}') {
+        true => '\u{feff}'.len_utf8(),
+        false => 0,
+    }
+}
+
+fn invalid(encoding: &'static str) -> Box<dyn core::error::Error> {
+    Error::Parsing(ParseError::InvalidEncoding(encoding)).into()
+}
+
+/// Decodes `bytes` into text, skipping a byte order mark. UTF-8 input
+/// without a mark is borrowed as is.
+///
+/// ```
+/// use json_parser::encoding::decode;
+///
+/// let utf16: Vec<u8> = "\u{feff}[1]"
+///     .encode_utf16()
+///     .flat_map(u16::to_le_bytes)
+///     .collect();
+/// assert_eq!(decode(&utf16).unwrap(), "[1]");
+/// assert_eq!(decode(b"\xEF\xBB\xBF{}").unwrap(), "{}");
+/// ```
+pub fn decode(
+    bytes: &[u8],
+) -> Result<Cow<'_, str>, Box<dyn core::error::Error>> {
+    let (encoding, bom) = detect(bytes);
+    let bytes = &bytes[bom..];
+
+    match encoding {
+        Encoding::Utf8 => core::str::from_utf8(bytes)
+            .map(Cow::Borrowed)
+            .map_err(|_| invalid("UTF-8")),
+        Encoding::Utf16Le | Encoding::Utf16Be => {
+            let name = if encoding == Encoding::Utf16Le {
+                "UTF-16LE"
+            } else {
+                "UTF-16BE"
+            };
+            if !bytes.len().is_multiple_of(2) {
+                return Err(invalid(name));
+            }
+            let units = bytes.chunks_exact(2).map(|pair| {
+                let pair = [pair[0], pair[1]];
+                if encoding == Encoding::Utf16Le {
+                    u16::from_le_bytes(pair)
+                } else {
+                    u16::from_be_bytes(pair)
+                }
+            });
+            char::decode_utf16(units)
+                .collect::<Result<String, _>>()
+                .map(Cow::Owned)
+                .map_err(|_| invalid(name))
+        }
+        Encoding::Utf32Le | Encoding::Utf32Be => {
+            let name = if encoding == Encoding::Utf32Le {
+                "UTF-32LE"
+            } else {
+                "UTF-32BE"
+            };
+            if !bytes.len().is_multiple_of(4) {
+                return Err(invalid(name));
+            }
+            bytes
+                .chunks_exact(4)
+                .map(|quad| {
+                    let quad = [quad[0], quad[1], quad[2], quad[3]];
+                    let code = if encoding == Encoding::Utf32Le {
+                        u32::from_le_bytes(quad)
+                    } else {
+                        u32::from_be_bytes(quad)
+                    };
+                    char::from_u32(code).ok_or_else(|| invalid(name))
+                })
+                .collect::<Result<String, _>>()
+                .map(Cow::Owned)
+        }
+    }
+}
+
+/// Byte offset of a U+FFFD in decoded text, with the invalid bytes it
+/// replaced.
+pub(crate) type Replacement<'a> = (usize, &'a [u8]);
+
+/// Like [`decode`], but replaces each invalid sequence of UTF-8 input
+/// with U+FFFD instead of failing, also returning the replacements.
+pub(crate) fn decode_lossy(
+    bytes: &[u8],
+) -> Result<(Cow<'_, str>, Vec<Replacement<'_>>), Box<dyn core::error::Error>> {
+    let (encoding, bom) = detect(bytes);
+    if encoding != Encoding::Utf8 {
+        return decode(bytes).map(|text| (text, Vec::new()));
+    }
+
+    let bytes = &bytes[bom..];
+    if let Ok(text) = core::str::from_utf8(bytes) {
+        return Ok((Cow::Borrowed(text), Vec::new()));
+    }
+    let mut text = String::with_capacity(bytes.len());
+    let mut replaced = Vec::new();
+    for chunk in bytes.utf8_chunks() {
+        text.push_str(chunk.valid());
+        if !chunk.invalid().is_empty() {
+            replaced.push((text.len(), chunk.invalid()));
+            text.push(char::REPLACEMENT_CHARACTER);
+        }
+    }
+    Ok((Cow::Owned(text), replaced))
+}
@@ -0,0 +1,188 @@
+//! Field-level encryption of documents.
+//!
+//! Selected values are serialized, handed to a user supplied [`Cipher`] and
+//! replaced by an `{"$enc": "<ciphertext>"}` wrapper, so that a document
+//! with sensitive fields can be stored as plain JSON. Decryption parses the
+//! deciphered text back into the original value.
+use super::container::Container;
+use super::error::{EncryptionError, Error};
+use super::parser::parse_str;
+use super::pointer;
+
+/// Member name of the object wrapping an encrypted value.
+pub const ENCRYPTED_KEY: &str = "$enc";
+
+/// A pluggable cipher: the crate never sees keys or algorithms, only the
+/// text going in and out.
+pub trait Cipher {
+    /// Encrypts the JSON text of a value.
+    fn encrypt(
+        &self,
+        plaintext: &str,
+    ) -> Result<String, Box<dyn core::error::Error>>;
+
+    /// Reverses [`Cipher::encrypt`].
+    fn decrypt(
+        &self,
+        ciphertext: &str,
+    ) -> Result<String, Box<dyn core::error::Error>>;
+}
+
+/// Returns the ciphertext if `value` is an `{"$enc": "..."}` wrapper.
+fn ciphertext(value: &Container) -> Option<&str> {
+    match value {
+        Container::Object(map) if map.len() == 1 => {
+            match map.get(ENCRYPTED_KEY) {
+                Some(Container::String(text)) => Some(text),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn encrypt_value(
+    value: &Container,
+    cipher: &dyn Cipher,
+) -> Result<Container, Box<dyn core::error::Error>> {
+    let mut wrapper = Container::new_object();
+    wrapper.insert_str(
+        ENCRYPTED_KEY,
+        Container::String(cipher.encrypt(&value.to_string())?.into()),
+    );
+    Ok(wrapper)
+}
+
+fn decrypt_value(
+    text: &str,
+    cipher: &dyn Cipher,
+) -> Result<Container, Box<dyn core::error::Error>> {
+    parse_str(&cipher.decrypt(text)?)
+}
+
+/// Resolves `path` in `document`, reporting pointer errors.
+fn locate<'a>(
+    document: &'a mut Container,
+    path: &str,
+) -> Result<&'a mut Container, Box<dyn core::error::Error>> {
+    let tokens = pointer::split(path).ok_or_else(|| {
+        Error::Encryption(EncryptionError::InvalidPointer(path.to_owned()))
+    })?;
+    pointer::resolve_mut(document, &tokens).ok_or_else(|| {
+        Error::Encryption(EncryptionError::PathNotFound(path.to_owned())).into()
+    })
+}
+
+/// Decrypts every wrapper found in `value`, including wrappers revealed
+/// by decrypting an outer one.
+fn decrypt_into(
+    value: &mut Container,
+    cipher: &dyn Cipher,
+) -> Result<(), Box<dyn core::error::Error>> {
+    while let Some(text) = ciphertext(value) {
+        *value = decrypt_value(text, cipher)?;
+    }
+
+    match value {
+        Container::Array(values) => values
+            .iter_mut()
+            .try_for_each(|value| decrypt_into(value, cipher)),
+        Container::Object(map) => map
+            .values_mut()
+            .try_for_each(|value| decrypt_into(value, cipher)),
+        _ => Ok(()),
+    }
+}
+
+impl Container {
+    /// Replaces the values at the given JSON Pointers with encrypted
+    /// wrappers.
+    ///
+    /// Fails without modifying self if a path is invalid or missing, or if
+    /// the cipher reports an error.
+    ///
+    /// ```
+    /// use json_parser::encrypt::Cipher;
+    /// use json_parser::parser::parse_str;
+    ///
+    /// /// Not a cipher at all: reverses the text.
+    /// struct Reverse;
+    ///
+    /// impl Cipher for Reverse {
+    ///     fn encrypt(
+    ///         &self,
+    ///         text: &str,
+    ///     ) -> Result<String, Box<dyn core::error::Error>> {
+    ///         Ok(text.chars().rev().collect())
+    ///     }
+    ///
+    ///     fn decrypt(
+    ///         &self,
+    ///         text: &str,
+    ///     ) -> Result<String, Box<dyn core::error::Error>> {
+    ///         self.encrypt(text)
+    ///     }
+    /// }
+    ///
+    /// let mut doc = parse_str(r#"{"user": "ada", "password": "hunter2"}"#)
+    ///     .unwrap();
+    /// doc.encrypt_paths(&["/password"], &Reverse).unwrap();
+    /// let wrapped = doc["password"]["$enc"].get_string().unwrap();
+    /// assert_eq!(wrapped, "\"2retnuh\"");
+    ///
+    /// doc.decrypt_paths(&["/password"], &Reverse).unwrap();
+    /// assert_eq!(doc["password"].get_string().unwrap(), "hunter2");
+    /// ```
+    pub fn encrypt_paths(
+        &mut self,
+        paths: &[&str],
+        cipher: &dyn Cipher,
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        let mut document = self.clone();
+        for path in paths {
+            let target = locate(&mut document, path)?;
+            *target = encrypt_value(target, cipher)?;
+        }
+
+        *self = document;
+        Ok(())
+    }
+
+    /// Decrypts the wrappers at the given JSON Pointers.
+    ///
+    /// Fails without modifying self if a path is invalid or missing, holds
+    /// anything other than a wrapper, or cannot be decrypted.
+    pub fn decrypt_paths(
+        &mut self,
+        paths: &[&str],
+        cipher: &dyn Cipher,
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        let mut document = self.clone();
+        for path in paths {
+            let target = locate(&mut document, path)?;
+            let text = ciphertext(target).ok_or_else(|| {
+                Error::Encryption(EncryptionError::NotEncrypted(
+                    (*path).to_owned(),
+                ))
+            })?;
+            *target = decrypt_value(text, cipher)?;
+        }
+
+        *self = document;
+        Ok(())
+    }
+
+    /// Decrypts every wrapper in the document, wherever it is.
+    ///
+    /// Fails without modifying self if any of them cannot be decrypted.
+    pub fn decrypt_all(
+        &mut self,
+        cipher: &dyn Cipher,
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        let mut document = self.clone();
+        decrypt_into(&mut document, cipher)?;
+
+        *self = document;
+        Ok(())
+    }
+}
@@ -1,27 +1,294 @@
+use super::parser::Span;
+
+/// Evaluates `$some` with `$span` bound to the span of a `ParseError`, or
+/// `$none` for errors found in all of the input.
+macro_rules! with_span {
+    ($error: expr, $span: ident => $some: expr, $none: expr) => {
+        match $error {
+            ParseError::UnexpectedToken(_, $span)
+            | ParseError::NestedDepthExceeded(_, $span)
+            | ParseError::ContainerParanthesisMismatch {
+                span: $span, ..
+            }
+            | ParseError::InvalidKeyValueFormat { span: $span, .. }
+            | ParseError::InvalidNumberParse(_, $span)
+            | ParseError::InvalidUnicodeEscape($span)
+            | ParseError::InvalidEscape(_, $span)
+            | ParseError::LoneSurrogate(_, $span)
+            | ParseError::NumberOutOfRange(_, $span)
+            | ParseError::UnknownLiteral { span: $span, .. }
+            | ParseError::StringTooLong(_, $span)
+            | ParseError::TooManyElements(_, $span)
+            | ParseError::TooManyNodes(_, $span)
+            | ParseError::Timeout($span)
+            | ParseError::Cancelled($span)
+            | ParseError::InvalidUTF8Parsing($span)
+            | ParseError::EndOfBuffer($span) => $some,
+            ParseError::InvalidEncoding(_) => $none,
+        }
+    };
+}
+
 /// An error service whenever parser encounters certain discrepancies.
+///
+/// Errors found at a place of the input carry the [`Span`] of the
+/// offending text: its byte range, and the line and column where it
+/// starts.
 #[derive(Debug, Clone)]
 #[allow(unused)]
 pub enum ParseError {
     /// Raised whenever a certain token is not accepted
-    UnexpectedToken(char, usize, usize),
-    /// Invalid UTF-8 character
-    InvalidUTF8Parsing,
-    /// Nested Depth Exceeded
-    NestedDepthExceeded(u16),
+    UnexpectedToken(char, Span),
+    /// Invalid UTF-8 character, at its bytes
+    InvalidUTF8Parsing(Span),
+    /// Nested Depth Exceeded, at the bracket opening one level too many
+    NestedDepthExceeded(u16, Span),
     /// Raised whenever parser reaches the end of the
     /// buffer without proper handling, but might allow
-    /// creating the object even after failure.
-    EndOfBuffer,
+    /// creating the object even after failure. The span is empty, at the
+    /// end of the input.
+    EndOfBuffer(Span),
     /// On Parsing Object, Array, or Set, raises an error when
     /// parathesis are mismatched
     ContainerParanthesisMismatch {
+        /// The bracket that opened the container, `[` or `{`
         opening_container: char,
+        /// The bracket found, which closes the other kind
         closing_container: char,
+        /// The closing bracket
+        span: Span,
     },
     /// Invalid key value formatting, while reading key
-    InvalidKeyValueFormat { reading_key: String },
+    InvalidKeyValueFormat {
+        reading_key: String,
+        /// The byte found instead of the value
+        span: Span,
+    },
     /// Invalid token while parsing number
-    InvalidNumberParse(char),
+    InvalidNumberParse(char, Span),
+    /// A `\u` escape not followed by four hexadecimal digits
+    InvalidUnicodeEscape(Span),
+    /// A backslash followed by a character that is not an escape
+    InvalidEscape(char, Span),
+    /// A UTF-16 surrogate escape without its other half
+    LoneSurrogate(u16, Span),
+    /// Raw input that is not valid in the named encoding
+    InvalidEncoding(&'static str),
+    /// A number beyond the range of every numeric variant, as written;
+    /// see `ParserOptions::number_overflow`
+    NumberOutOfRange(String, Span),
+    /// A word that is no literal of the dialect, with the literal it is
+    /// likely a typo of
+    UnknownLiteral {
+        found: String,
+        suggestion: Option<String>,
+        span: Span,
+    },
+    /// A string or key longer than `ParserOptions::max_string_length`
+    /// bytes, at the whole string
+    StringTooLong(usize, Span),
+    /// An array or object with more than `ParserOptions::max_elements`
+    /// elements or members, at the first one too many
+    TooManyElements(usize, Span),
+    /// A document of more than `ParserOptions::max_nodes` values, at the
+    /// first one too many
+    TooManyNodes(usize, Span),
+    /// Parsing went on past `ParserOptions::deadline`, at the value where
+    /// it stopped
+    Timeout(Span),
+    /// Parsing was stopped through `ParserOptions::cancel`, at the value
+    /// where it stopped
+    Cancelled(Span),
+}
+
+impl ParseError {
+    /// Where the error was found, if at a place of the input rather than
+    /// in all of it.
+    ///
+    /// ```
+    /// use json_parser::error::Error;
+    /// use json_parser::parser::parse_str;
+    ///
+    /// let input = "[1, 2,\n 3 4]";
+    /// let error = parse_str(input).unwrap_err();
+    /// let Some(Error::Parsing(error)) = error.downcast_ref::<Error>() else {
+    ///     panic!("not a parse error");
+    /// };
+    /// let span = error.span().unwrap();
+    /// assert_eq!((span.start, span.end), (10, 11));
+    /// assert_eq!((span.line, span.col), (2, 4));
+    /// assert_eq!(error.offset(), Some(10));
+    /// ```
+    pub fn span(&self) -> Option<Span> {
+        with_span!(self, span => Some(span.clone()), None)
+    }
+
+    /// For errors raised inside an array or object, the JSONPath of the
+    /// value being read.
+    ///
+    /// ```
+    /// use json_parser::error::{Error, ErrorCode};
+    /// use json_parser::parser::parse_str;
+    ///
+    /// let input = r#"{"users": [{"id": 1}, {"id": 2, "zip": 0123}]}"#;
+    /// let error = parse_str(input).unwrap_err();
+    /// let Some(Error::Parsing(error)) = error.downcast_ref::<Error>() else {
+    ///     panic!("not a parse error");
+    /// };
+    /// assert_eq!(error.json_path(), Some("$.users[1].zip"));
+    /// assert_eq!(error.code(), ErrorCode::InvalidNumber);
+    /// ```
+    pub fn json_path(&self) -> Option<&str> {
+        with_span!(self, span => span.path.as_deref(), None)
+    }
+
+    /// Records the JSONPath of the value being read.
+    pub(crate) fn at_path(&mut self, path: String) {
+        with_span!(self, span => span.path = Some(path.into()), ())
+    }
+
+    /// Byte offset of the input where the error was found.
+    pub fn offset(&self) -> Option<usize> {
+        self.span().map(|span| span.start)
+    }
+
+    /// The stable code of the kind of error.
+    ///
+    /// ```
+    /// use json_parser::error::{Error, ErrorCode};
+    /// use json_parser::parser::parse_str;
+    ///
+    /// let error = parse_str("[1 2]").unwrap_err();
+    /// let Some(Error::Parsing(error)) = error.downcast_ref::<Error>() else {
+    ///     panic!("not a parse error");
+    /// };
+    /// assert_eq!(error.code(), ErrorCode::UnexpectedToken);
+    /// assert_eq!(error.code().to_string(), "E0001 UnexpectedToken");
+    /// assert!(error.is_syntax() && !error.is_limit());
+    /// ```
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ParseError::UnexpectedToken(..) => ErrorCode::UnexpectedToken,
+            ParseError::InvalidUTF8Parsing(_) => ErrorCode::InvalidUtf8,
+            ParseError::NestedDepthExceeded(..) => {
+                ErrorCode::NestedDepthExceeded
+            }
+            ParseError::EndOfBuffer(_) => ErrorCode::EndOfBuffer,
+            ParseError::ContainerParanthesisMismatch { .. } => {
+                ErrorCode::BracketMismatch
+            }
+            ParseError::InvalidKeyValueFormat { .. } => ErrorCode::MissingValue,
+            ParseError::InvalidNumberParse(..) => ErrorCode::InvalidNumber,
+            ParseError::InvalidUnicodeEscape(..) => {
+                ErrorCode::InvalidUnicodeEscape
+            }
+            ParseError::InvalidEscape(..) => ErrorCode::InvalidEscape,
+            ParseError::LoneSurrogate(..) => ErrorCode::LoneSurrogate,
+            ParseError::InvalidEncoding(_) => ErrorCode::InvalidEncoding,
+            ParseError::NumberOutOfRange(..) => ErrorCode::NumberOutOfRange,
+            ParseError::UnknownLiteral { .. } => ErrorCode::UnknownLiteral,
+            ParseError::StringTooLong(..) => ErrorCode::StringTooLong,
+            ParseError::TooManyElements(..) => ErrorCode::TooManyElements,
+            ParseError::TooManyNodes(..) => ErrorCode::TooManyNodes,
+            ParseError::Timeout(_) => ErrorCode::Timeout,
+            ParseError::Cancelled(_) => ErrorCode::Cancelled,
+        }
+    }
+
+    /// Whether the input breaks the grammar of the dialect.
+    pub fn is_syntax(&self) -> bool {
+        !self.is_limit()
+            && !self.is_encoding()
+            && !matches!(self, ParseError::Cancelled(_))
+    }
+
+    /// Whether the input is well-formed but beyond what the parser is
+    /// configured to hold: nesting, number range, size, or parsing time.
+    pub fn is_limit(&self) -> bool {
+        matches!(
+            self,
+            ParseError::NestedDepthExceeded(..)
+                | ParseError::NumberOutOfRange(..)
+                | ParseError::StringTooLong(..)
+                | ParseError::TooManyElements(..)
+                | ParseError::TooManyNodes(..)
+                | ParseError::Timeout(_)
+        )
+    }
+
+    /// Whether the raw input is not valid text.
+    pub fn is_encoding(&self) -> bool {
+        matches!(
+            self,
+            ParseError::InvalidUTF8Parsing(_) | ParseError::InvalidEncoding(_)
+        )
+    }
+}
+
+/// A stable identifier of a kind of [`ParseError`], e.g. for monitoring
+/// systems bucketing failures. Numbers are never reused or changed; new
+/// kinds get new numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ErrorCode {
+    UnexpectedToken = 1,
+    InvalidUtf8 = 2,
+    NestedDepthExceeded = 3,
+    EndOfBuffer = 4,
+    BracketMismatch = 5,
+    MissingValue = 6,
+    InvalidNumber = 7,
+    InvalidUnicodeEscape = 8,
+    InvalidEscape = 9,
+    LoneSurrogate = 10,
+    InvalidEncoding = 11,
+    NumberOutOfRange = 12,
+    UnknownLiteral = 13,
+    StringTooLong = 14,
+    TooManyElements = 15,
+    TooManyNodes = 16,
+    Timeout = 17,
+    Cancelled = 18,
+}
+
+impl ErrorCode {
+    /// The number of the code.
+    pub fn number(self) -> u16 {
+        self as u16
+    }
+
+    /// The code as written in reports, e.g. `E0001`.
+    pub fn code(self) -> String {
+        format!("E{:04}", self.number())
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ErrorCode::UnexpectedToken => "UnexpectedToken",
+            ErrorCode::InvalidUtf8 => "InvalidUtf8",
+            ErrorCode::NestedDepthExceeded => "NestedDepthExceeded",
+            ErrorCode::EndOfBuffer => "EndOfBuffer",
+            ErrorCode::BracketMismatch => "BracketMismatch",
+            ErrorCode::MissingValue => "MissingValue",
+            ErrorCode::InvalidNumber => "InvalidNumber",
+            ErrorCode::InvalidUnicodeEscape => "InvalidUnicodeEscape",
+            ErrorCode::InvalidEscape => "InvalidEscape",
+            ErrorCode::LoneSurrogate => "LoneSurrogate",
+            ErrorCode::InvalidEncoding => "InvalidEncoding",
+            ErrorCode::NumberOutOfRange => "NumberOutOfRange",
+            ErrorCode::UnknownLiteral => "UnknownLiteral",
+            ErrorCode::StringTooLong => "StringTooLong",
+            ErrorCode::TooManyElements => "TooManyElements",
+            ErrorCode::TooManyNodes => "TooManyNodes",
+            ErrorCode::Timeout => "Timeout",
+            ErrorCode::Cancelled => "Cancelled",
+        }
+    }
+}
+
+impl core::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str(format!("{} {}", self.code(), self.name()).as_str())
+    }
 }
 
 impl core::error::Error for ParseError {}
@@ -29,18 +296,19 @@ impl core::error::Error for ParseError {}
 impl core::fmt::Display for ParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
-            ParseError::UnexpectedToken(chr, line, col) => f.write_str(
+            ParseError::UnexpectedToken(chr, span) => f.write_str(
                 format!(
                     "Unexpected character found: {} at line {}, col: {}",
-                    chr, line, col
+                    chr, span.line, span.col
                 )
                 .as_str(),
             ),
-            ParseError::NestedDepthExceeded(c) => f.write_str(format!("NestedDepthExceeded, >{c}").as_str()),
-            ParseError::InvalidUTF8Parsing => f.write_str("Invalid UTF-8 Value found while decoding strings."),
+            ParseError::NestedDepthExceeded(c, _) => f.write_str(format!("NestedDepthExceeded, >{c}").as_str()),
+            ParseError::InvalidUTF8Parsing(_) => f.write_str("Invalid UTF-8 Value found while decoding strings."),
             ParseError::ContainerParanthesisMismatch {
                 opening_container,
                 closing_container,
+                ..
             } => f.write_str(
                 format!(
                     "The opening bracket '{}' and closing bracket '{}' do not match",
@@ -48,23 +316,513 @@ impl core::fmt::Display for ParseError {
                 )
                 .as_str(),
             ),
-            ParseError::InvalidKeyValueFormat { reading_key } => f.write_str(
+            ParseError::InvalidKeyValueFormat { reading_key, .. } => f.write_str(
                 format!(
                     "Error while reading value while reading key: {}",
                     reading_key
                 )
                 .as_str(),
             ),
-            ParseError::InvalidNumberParse(invalid_char) => f.write_str(
+            ParseError::InvalidNumberParse(invalid_char, _) => f.write_str(
                 format!(
                     "Error while reading number: found character {}",
                     invalid_char
                 )
                 .as_str(),
             ),
-            ParseError::EndOfBuffer => {
+            ParseError::EndOfBuffer(_) => {
                 f.write_str("The buffer ended before operating on storage.")
             }
+            ParseError::InvalidUnicodeEscape(span) => f.write_str(
+                format!(
+                    "Expected four hex digits after \\u at line {}, col: {}",
+                    span.line, span.col
+                )
+                .as_str(),
+            ),
+            ParseError::InvalidEscape(chr, span) => f.write_str(
+                format!(
+                    "Invalid escape sequence \\{} at line {}, col: {}",
+                    chr, span.line, span.col
+                )
+                .as_str(),
+            ),
+            ParseError::InvalidEncoding(encoding) => f.write_str(
+                format!("Input is not valid {}", encoding).as_str(),
+            ),
+            ParseError::LoneSurrogate(unit, span) => f.write_str(
+                format!(
+                    "Unpaired surrogate \\u{:04x} at line {}, col: {}",
+                    unit, span.line, span.col
+                )
+                .as_str(),
+            ),
+            ParseError::NumberOutOfRange(text, span) => f.write_str(
+                format!(
+                    "Number {} is out of range at line {}, col: {}",
+                    text, span.line, span.col
+                )
+                .as_str(),
+            ),
+            ParseError::StringTooLong(limit, span) => f.write_str(
+                format!(
+                    "String longer than {} bytes at line {}, col: {}",
+                    limit, span.line, span.col
+                )
+                .as_str(),
+            ),
+            ParseError::TooManyElements(limit, span) => f.write_str(
+                format!(
+                    "Container with more than {} elements at line {}, col: {}",
+                    limit, span.line, span.col
+                )
+                .as_str(),
+            ),
+            ParseError::TooManyNodes(limit, span) => f.write_str(
+                format!(
+                    "Document with more than {} values at line {}, col: {}",
+                    limit, span.line, span.col
+                )
+                .as_str(),
+            ),
+            ParseError::Timeout(span) => f.write_str(
+                format!(
+                    "Parsing timed out at line {}, col: {}",
+                    span.line, span.col
+                )
+                .as_str(),
+            ),
+            ParseError::Cancelled(span) => f.write_str(
+                format!(
+                    "Parsing cancelled at line {}, col: {}",
+                    span.line, span.col
+                )
+                .as_str(),
+            ),
+            ParseError::UnknownLiteral {
+                found, suggestion, span
+            } => {
+                f.write_str(
+                    format!(
+                        "Unknown literal {} at line {}, col: {}",
+                        found, span.line, span.col
+                    )
+                    .as_str(),
+                )?;
+                match suggestion {
+                    Some(literal) => f.write_str(
+                        format!(", did you mean `{}`?", literal).as_str(),
+                    ),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+/// Writes the JSONPath `error` was raised at, if any, on a line of its
+/// own.
+fn at_path(
+    f: &mut core::fmt::Formatter,
+    error: &ParseError,
+) -> core::fmt::Result {
+    match error.json_path() {
+        Some(path) => f.write_str(format!("\nAt {}", path).as_str()),
+        None => Ok(()),
+    }
+}
+
+/// An irregularity accepted by a lenient parser option, at line and
+/// column of the input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Warning {
+    pub kind: WarningKind,
+    pub line: usize,
+    pub col: usize,
+}
+
+/// The kinds of [`Warning`]. Warnings that are not about the input text,
+/// such as `DeprecatedField`, have a line and col of 0.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WarningKind {
+    /// A number written with locale separators, as found in the input
+    LocaleNumber(String),
+    /// A string value stored as the number or boolean it spells
+    CoercedString(String),
+    /// Bytes of invalid UTF-8 replaced with U+FFFD
+    InvalidUtf8(Vec<u8>),
+    /// A value provided for a field an `Extractor` was told is deprecated
+    DeprecatedField {
+        path: String,
+        replacement: Option<String>,
+    },
+}
+
+impl core::fmt::Display for Warning {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match &self.kind {
+            WarningKind::LocaleNumber(original) => f.write_str(
+                format!(
+                    "Normalized number {} at line {}, col: {}",
+                    original, self.line, self.col
+                )
+                .as_str(),
+            ),
+            WarningKind::CoercedString(original) => f.write_str(
+                format!(
+                    "Coerced string {:?} at line {}, col: {}",
+                    original, self.line, self.col
+                )
+                .as_str(),
+            ),
+            WarningKind::InvalidUtf8(bytes) => f.write_str(
+                format!(
+                    "Replaced invalid UTF-8 {:x?} at line {}, col: {}",
+                    bytes, self.line, self.col
+                )
+                .as_str(),
+            ),
+            WarningKind::DeprecatedField {
+                path,
+                replacement: None,
+            } => f.write_str(
+                format!("Deprecated field {:?} is provided", path).as_str(),
+            ),
+            WarningKind::DeprecatedField {
+                path,
+                replacement: Some(replacement),
+            } => f.write_str(
+                format!(
+                    "Deprecated field {:?} is provided, use {:?} instead",
+                    path, replacement
+                )
+                .as_str(),
+            ),
+        }
+    }
+}
+
+/// Errors raised while applying a JSON Patch document to a container.
+#[derive(Debug, Clone)]
+pub enum PatchError {
+    /// The patch document is not an array of operations.
+    InvalidPatch,
+    /// Operation at given index is missing members or has an unknown `op`.
+    MalformedOperation { index: usize, reason: String },
+    /// A `path` or `from` member is not a valid JSON Pointer.
+    InvalidPointer { index: usize, pointer: String },
+    /// The location referenced by the operation does not exist.
+    PathNotFound { index: usize, path: String },
+    /// A `test` operation found a value different from the expected one.
+    TestFailed { index: usize, path: String },
+}
+
+impl core::error::Error for PatchError {}
+
+impl core::fmt::Display for PatchError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            PatchError::InvalidPatch => {
+                f.write_str("A patch must be an array of operation objects.")
+            }
+            PatchError::MalformedOperation { index, reason } => f.write_str(
+                format!("Malformed operation at index {}: {}", index, reason)
+                    .as_str(),
+            ),
+            PatchError::InvalidPointer { index, pointer } => f.write_str(
+                format!(
+                    "Invalid JSON pointer {:?} in operation at index {}",
+                    pointer, index
+                )
+                .as_str(),
+            ),
+            PatchError::PathNotFound { index, path } => f.write_str(
+                format!(
+                    "Path {:?} referenced by operation at index {} does not exist",
+                    path, index
+                )
+                .as_str(),
+            ),
+            PatchError::TestFailed { index, path } => f.write_str(
+                format!(
+                    "Test operation at index {} failed: value at {:?} differs",
+                    index, path
+                )
+                .as_str(),
+            ),
+        }
+    }
+}
+
+/// Errors raised when a `ContainerBuilder` is driven with events that do
+/// not describe a well-formed document.
+#[derive(Debug, Clone)]
+pub enum BuildError {
+    /// An event arrived where the document structure does not allow it.
+    UnexpectedEvent(&'static str),
+    /// The builder was finished before the document was complete.
+    Incomplete,
+}
+
+impl core::error::Error for BuildError {}
+
+impl core::fmt::Display for BuildError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            BuildError::UnexpectedEvent(event) => f.write_str(
+                format!("Unexpected builder event: {}", event).as_str(),
+            ),
+            BuildError::Incomplete => f.write_str(
+                "The builder was finished before the document was complete.",
+            ),
+        }
+    }
+}
+
+/// An error raised while encrypting or decrypting document fields.
+#[derive(Debug, Clone)]
+pub enum EncryptionError {
+    /// A path is not a valid JSON Pointer
+    InvalidPointer(String),
+    /// No value exists at the given path
+    PathNotFound(String),
+    /// The value at the given path is not an `{"$enc": ...}` wrapper
+    NotEncrypted(String),
+}
+
+impl core::error::Error for EncryptionError {}
+
+impl core::fmt::Display for EncryptionError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            EncryptionError::InvalidPointer(path) => {
+                f.write_str(format!("Invalid JSON pointer {:?}", path).as_str())
+            }
+            EncryptionError::PathNotFound(path) => {
+                f.write_str(format!("No value found at {:?}", path).as_str())
+            }
+            EncryptionError::NotEncrypted(path) => f.write_str(
+                format!("Value at {:?} is not an encrypted field", path)
+                    .as_str(),
+            ),
+        }
+    }
+}
+
+/// An error raised while converting application types to and from
+/// `$type` wrappers.
+#[derive(Debug, Clone)]
+pub enum ExtensionError {
+    /// No tag is registered for the named type
+    Unregistered(&'static str),
+    /// The value is not a `{"$type": ..., "$value": ...}` wrapper
+    NotTagged,
+    /// The wrapper has a tag that is not registered
+    UnknownTag(String),
+    /// The wrapper holds another type than the one requested
+    TypeMismatch { tag: String, expected: &'static str },
+}
+
+impl core::error::Error for ExtensionError {}
+
+impl core::fmt::Display for ExtensionError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            ExtensionError::Unregistered(name) => f.write_str(
+                format!("No $type tag is registered for {}", name).as_str(),
+            ),
+            ExtensionError::NotTagged => {
+                f.write_str("Value is not a $type wrapper")
+            }
+            ExtensionError::UnknownTag(tag) => {
+                f.write_str(format!("Unknown $type tag {:?}", tag).as_str())
+            }
+            ExtensionError::TypeMismatch { tag, expected } => f.write_str(
+                format!("The $type tag {:?} does not hold {}", tag, expected)
+                    .as_str(),
+            ),
+        }
+    }
+}
+
+/// An error raised while dumping a container to text.
+#[derive(Debug, Clone)]
+pub enum DumpError {
+    /// A `NaN` or infinite `Decimal` was found with `NonFinite::Error`
+    NonFiniteNumber,
+}
+
+impl core::error::Error for DumpError {}
+
+impl core::fmt::Display for DumpError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            DumpError::NonFiniteNumber => f.write_str(
+                "NaN and infinite numbers cannot be written as JSON",
+            ),
+        }
+    }
+}
+
+/// A field that could not be read by an `Extractor`.
+#[derive(Debug, Clone)]
+pub enum FieldError {
+    /// No value exists at the path
+    Missing(String),
+    /// The value at the path is not of the requested type
+    Mismatch {
+        path: String,
+        expected: &'static str,
+        found: &'static str,
+    },
+}
+
+impl core::error::Error for FieldError {}
+
+impl core::fmt::Display for FieldError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            FieldError::Missing(path) => {
+                f.write_str(format!("Missing field {:?}", path).as_str())
+            }
+            FieldError::Mismatch {
+                path,
+                expected,
+                found,
+            } => f.write_str(
+                format!(
+                    "Field {:?} should be {}, found {}",
+                    path, expected, found
+                )
+                .as_str(),
+            ),
+        }
+    }
+}
+
+/// An error raised while converting a document with a `Mapping`.
+#[derive(Debug, Clone)]
+pub enum MappingError {
+    /// A path of a rule is not a valid JSON Pointer
+    InvalidPointer(String),
+    /// The target path goes through a value that is not an object, or
+    /// past the end of an array
+    Conflict(String),
+}
+
+impl core::error::Error for MappingError {}
+
+impl core::fmt::Display for MappingError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            MappingError::InvalidPointer(path) => {
+                f.write_str(format!("Invalid JSON pointer {:?}", path).as_str())
+            }
+            MappingError::Conflict(path) => f.write_str(
+                format!("Cannot write a value at {:?}", path).as_str(),
+            ),
+        }
+    }
+}
+
+/// An error raised while compiling or running a transform `Script`.
+#[derive(Debug, Clone)]
+pub enum ScriptError {
+    /// The script is malformed at the given byte offset
+    Syntax {
+        pos: usize,
+        expected: &'static str,
+    },
+    /// An operator or function was applied to a value of the wrong type
+    TypeMismatch {
+        op: &'static str,
+        found: &'static str,
+    },
+    DivisionByZero,
+    /// A `set` statement targets a path that cannot be written
+    InvalidTarget(String),
+    /// The script ran more steps than allowed for one document
+    StepLimit(usize),
+    /// The script built a string longer than allowed
+    StringLimit(usize),
+}
+
+impl core::error::Error for ScriptError {}
+
+impl core::fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            ScriptError::Syntax { pos, expected } => f.write_str(
+                format!("Expected {} at offset {}", expected, pos).as_str(),
+            ),
+            ScriptError::TypeMismatch { op, found } => f.write_str(
+                format!("Cannot apply {} to {}", op, found).as_str(),
+            ),
+            ScriptError::DivisionByZero => f.write_str("Division by zero"),
+            ScriptError::InvalidTarget(path) => f.write_str(
+                format!("Cannot write a value at {:?}", path).as_str(),
+            ),
+            ScriptError::StepLimit(limit) => f.write_str(
+                format!("Script exceeded its limit of {} steps", limit)
+                    .as_str(),
+            ),
+            ScriptError::StringLimit(limit) => f.write_str(
+                format!(
+                    "Script built a string longer than its limit of {} bytes",
+                    limit
+                )
+                .as_str(),
+            ),
+        }
+    }
+}
+
+/// An error raised while watching or changing a `Watched` document.
+#[derive(Debug, Clone)]
+pub enum WatchError {
+    /// A pattern or path is not a valid JSON Pointer
+    InvalidPointer(String),
+    /// The path goes through a value that is not a container, or does
+    /// not exist
+    PathNotFound(String),
+}
+
+impl core::error::Error for WatchError {}
+
+impl core::fmt::Display for WatchError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            WatchError::InvalidPointer(path) => {
+                f.write_str(format!("Invalid JSON pointer {:?}", path).as_str())
+            }
+            WatchError::PathNotFound(path) => {
+                f.write_str(format!("No value at {:?}", path).as_str())
+            }
+        }
+    }
+}
+
+/// An error raised while parsing a file, naming the file.
+#[derive(Debug, Clone)]
+pub enum FileError {
+    /// The file cannot be read
+    Read { path: String, reason: String },
+    /// The file does not hold a valid document
+    Parse { path: String, error: ParseError },
+}
+
+impl core::error::Error for FileError {}
+
+impl core::fmt::Display for FileError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            FileError::Read { path, reason } => f.write_str(
+                format!("Cannot read {:?}: {}", path, reason).as_str(),
+            ),
+            FileError::Parse { path, error } => {
+                f.write_str(format!("In {:?}: {}", path, error).as_str())?;
+                at_path(f, error)
+            }
         }
     }
 }
@@ -76,17 +834,125 @@ pub enum Error {
     /// Raised whenever the errors are raised are
     /// related to parsing
     Parsing(ParseError),
+    /// Raised whenever a patch cannot be applied
+    Patch(PatchError),
+    /// Raised whenever a builder receives malformed events
+    Build(BuildError),
+    /// Raised whenever a field cannot be encrypted or decrypted
+    Encryption(EncryptionError),
+    /// Raised whenever a `$type` wrapper cannot be written or read
+    Extension(ExtensionError),
+    /// Raised whenever a container cannot be dumped to text
+    Dump(DumpError),
+    /// Raised whenever fields cannot be extracted, listing every one of
+    /// them
+    Extract(Vec<FieldError>),
+    /// Raised whenever a document cannot be converted by a mapping
+    Mapping(MappingError),
+    /// Raised whenever a transform script cannot be compiled or run
+    Script(ScriptError),
+    /// Raised whenever a watched document cannot be watched or changed
+    Watch(WatchError),
+    /// Raised whenever a file cannot be read or parsed
+    File(FileError),
 }
 
 impl core::error::Error for Error {}
 
+impl Error {
+    /// The parse error, possibly raised in a file.
+    ///
+    /// ```
+    /// use json_parser::error::{Error, ErrorCode};
+    /// use json_parser::parser::parse_file;
+    ///
+    /// let file = std::env::temp_dir().join("json_parser_parse_error.json");
+    /// std::fs::write(&file, r#"{"users": [{"id": 1}, {"zip": 0123}]}"#).unwrap();
+    /// let error = parse_file(&file).unwrap_err();
+    /// let error = error.downcast_ref::<Error>().unwrap();
+    /// assert_eq!(error.json_path(), Some("$.users[1].zip"));
+    /// assert_eq!(error.parse_error().unwrap().code(), ErrorCode::InvalidNumber);
+    /// # std::fs::remove_file(&file).unwrap();
+    /// ```
+    pub fn parse_error(&self) -> Option<&ParseError> {
+        match self {
+            Error::Parsing(error) => Some(error),
+            Error::File(FileError::Parse { error, .. }) => Some(error),
+            _ => None,
+        }
+    }
+
+    /// The JSONPath of the value being read when the error was raised,
+    /// if inside an array or object.
+    pub fn json_path(&self) -> Option<&str> {
+        self.parse_error()?.json_path()
+    }
+
+    /// The error in a form that can move to another thread: errors of
+    /// this crate as they are, any other by its message.
+    pub(crate) fn sendable(
+        error: Box<dyn core::error::Error>,
+    ) -> Box<dyn core::error::Error + Send + Sync> {
+        match error.downcast::<Error>() {
+            Ok(error) => error,
+            Err(error) => error.to_string().into(),
+        }
+    }
+}
+
 impl core::fmt::Display for Error {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         match self {
-            Error::Parsing(ref error_value) => f.write_str(
-                format!("\x1b[1;31mParse Error\x1b[0m:\n{}", error_value)
+            Error::Parsing(ref error_value) => {
+                f.write_str(
+                    format!("\x1b[1;31mParse Error\x1b[0m:\n{}", error_value)
+                        .as_str(),
+                )?;
+                at_path(f, error_value)
+            }
+            Error::Patch(ref error_value) => f.write_str(
+                format!("\x1b[1;31mPatch Error\x1b[0m:\n{}", error_value)
+                    .as_str(),
+            ),
+            Error::Build(ref error_value) => f.write_str(
+                format!("\x1b[1;31mBuild Error\x1b[0m:\n{}", error_value)
+                    .as_str(),
+            ),
+            Error::Encryption(ref error_value) => f.write_str(
+                format!("\x1b[1;31mEncryption Error\x1b[0m:\n{}", error_value)
+                    .as_str(),
+            ),
+            Error::Dump(ref error_value) => f.write_str(
+                format!("\x1b[1;31mDump Error\x1b[0m:\n{}", error_value)
+                    .as_str(),
+            ),
+            Error::Extension(ref error_value) => f.write_str(
+                format!("\x1b[1;31mExtension Error\x1b[0m:\n{}", error_value)
                     .as_str(),
             ),
+            Error::Script(ref error_value) => f.write_str(
+                format!("\x1b[1;31mScript Error\x1b[0m:\n{}", error_value)
+                    .as_str(),
+            ),
+            Error::Mapping(ref error_value) => f.write_str(
+                format!("\x1b[1;31mMapping Error\x1b[0m:\n{}", error_value)
+                    .as_str(),
+            ),
+            Error::Watch(ref error_value) => f.write_str(
+                format!("\x1b[1;31mWatch Error\x1b[0m:\n{}", error_value)
+                    .as_str(),
+            ),
+            Error::File(ref error_value) => f.write_str(
+                format!("\x1b[1;31mFile Error\x1b[0m:\n{}", error_value)
+                    .as_str(),
+            ),
+            Error::Extract(ref errors) => {
+                f.write_str("\x1b[1;31mExtract Error\x1b[0m:")?;
+                for error in errors {
+                    f.write_str(format!("\n{}", error).as_str())?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -0,0 +1,366 @@
+//! Plain-language explanations of parse failures, for users who are not
+//! familiar with the JSON grammar.
+use super::analyze::Path;
+use super::container::Container;
+use super::dialect::Json;
+use super::error::{Error, FileError, ParseError};
+use super::parser::{self, Observer, Span};
+
+/// The last token reported before the failure.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Token {
+    Open,
+    Key,
+    Value,
+}
+
+/// Follows the parser up to the point where it fails.
+#[derive(Default)]
+struct Trace {
+    path: Path,
+    /// Open containers (`true` for objects), innermost last
+    open: Vec<(bool, Span)>,
+    /// The last token and where it ends
+    last: Option<(Token, usize)>,
+    failed: Option<Span>,
+}
+
+impl Observer for Trace {
+    fn open(&mut self, object: bool, span: Span) {
+        self.path.open(object);
+        self.last = Some((Token::Open, span.end));
+        self.open.push((object, span));
+    }
+
+    fn close(&mut self, span: Span) {
+        self.path.close();
+        self.open.pop();
+        self.last = Some((Token::Value, span.end));
+    }
+
+    fn key(&mut self, key: &str, span: Span) {
+        self.path.key(key);
+        self.last = Some((Token::Key, span.end));
+    }
+
+    fn scalar(&mut self, _value: &Container, span: Span) {
+        self.path.value_done();
+        self.last = Some((Token::Value, span.end));
+    }
+
+    fn failed(&mut self, span: Span) {
+        self.failed = Some(span);
+    }
+}
+
+impl Trace {
+    /// What the grammar allows where parsing stopped, given the text read
+    /// since the last token.
+    fn expected(&self, input: &str) -> &'static str {
+        let (last, end) = match self.last {
+            Some((last, end)) => (Some(last), end),
+            None => (None, 0),
+        };
+        let stop = self.failed.as_ref().map_or(input.len(), |span| span.start);
+        let between = input.get(end..stop.max(end)).unwrap_or("").trim_start();
+        let seen = |byte: char| between.starts_with(byte);
+
+        match (self.open.last().map(|(object, _)| *object), last) {
+            _ if seen('"') => "a `\"` closing the string",
+            (None, None) => "a JSON value",
+            (None, _) => "the end of the input",
+            (Some(false), Some(Token::Value)) if seen(',') => "a value",
+            (Some(false), Some(Token::Value)) => "a `,` or a `]`",
+            (Some(false), _) => "a value or a `]`",
+            (Some(true), Some(Token::Key)) if seen(':') => "a value",
+            (Some(true), Some(Token::Key)) => "a `:`",
+            (Some(true), Some(Token::Value)) if seen(',') => {
+                "a key in double quotes"
+            }
+            (Some(true), Some(Token::Value)) => "a `,` or a `}`",
+            (Some(true), _) => "a key in double quotes or a `}`",
+        }
+    }
+
+    /// Names the innermost open container and where it starts.
+    fn context(&self) -> String {
+        match self.open.last() {
+            Some((object, span)) => {
+                let kind = if *object { "object" } else { "array" };
+                let pointer = self.path.container_pointer();
+                let at = if pointer.is_empty() {
+                    String::new()
+                } else {
+                    format!(" (at {})", pointer)
+                };
+                format!(
+                    "Inside the {} started at line {}, column {}{}.",
+                    kind, span.line, span.col, at
+                )
+            }
+            None => "At the top level of the document.".to_owned(),
+        }
+    }
+}
+
+/// Describes a parse error without its position.
+fn describe(error: &ParseError) -> String {
+    match error {
+        ParseError::UnexpectedToken(chr, ..) => {
+            format!("Found `{}` where it is not allowed", chr)
+        }
+        ParseError::EndOfBuffer(_) => "The input ends too early".to_owned(),
+        ParseError::ContainerParanthesisMismatch {
+            opening_container,
+            closing_container,
+            ..
+        } => {
+            let (kind, bracket) = match opening_container {
+                '[' => ("an array", ']'),
+                _ => ("an object", '}'),
+            };
+            format!(
+                "Found `{}` closing {}, which ends with `{}`",
+                closing_container, kind, bracket
+            )
+        }
+        ParseError::InvalidKeyValueFormat { reading_key, .. } => {
+            format!("The member {:?} has no value", reading_key)
+        }
+        ParseError::InvalidNumberParse(..) => {
+            "A number is not written correctly (e.g. a leading zero, or \
+             nothing after `.` or `e`)"
+                .to_owned()
+        }
+        ParseError::InvalidUnicodeEscape(..) => {
+            "A `\\u` escape is not followed by four hexadecimal digits"
+                .to_owned()
+        }
+        ParseError::InvalidEscape(chr, ..) => format!(
+            "`\\{}` is not an escape sequence; a backslash itself is \
+             written `\\\\`",
+            chr
+        ),
+        ParseError::LoneSurrogate(unit, ..) => format!(
+            "The escape `\\u{:04X}` is half of a UTF-16 surrogate pair, \
+             written without its other half",
+            unit
+        ),
+        ParseError::NestedDepthExceeded(depth, _) => format!(
+            "Arrays and objects are nested {} levels deep, more than \
+             allowed",
+            depth
+        ),
+        ParseError::InvalidUTF8Parsing(_) => {
+            "The input is not valid UTF-8 text".to_owned()
+        }
+        ParseError::InvalidEncoding(encoding) => {
+            format!("The input is not valid {} text", encoding)
+        }
+        ParseError::NumberOutOfRange(text, ..) => {
+            format!("The number {} is too large to be stored", text)
+        }
+        ParseError::UnknownLiteral {
+            found,
+            suggestion: Some(literal),
+            ..
+        } => format!("`{}` is not a value; did you mean `{}`?", found, literal),
+        ParseError::UnknownLiteral { found, .. } => format!(
+            "`{}` is not a value; strings are written in double quotes",
+            found
+        ),
+        ParseError::StringTooLong(limit, _) => {
+            format!("The string is longer than the {} bytes allowed", limit)
+        }
+        ParseError::TooManyElements(limit, _) => format!(
+            "The array or object has more than the {} elements allowed",
+            limit
+        ),
+        ParseError::TooManyNodes(limit, _) => {
+            format!("The document has more than the {} values allowed", limit)
+        }
+        ParseError::Timeout(_) => {
+            "Parsing took longer than allowed and stopped here".to_owned()
+        }
+        ParseError::Cancelled(_) => {
+            "Parsing was cancelled and stopped here".to_owned()
+        }
+    }
+}
+
+/// Explains in plain language why `input` failed to parse with `error`,
+/// as returned by [`parse_str`](super::parser::parse_str): what went
+/// wrong and where, what the parser expected there, and the innermost
+/// array or object being read.
+///
+/// Errors other than parse errors are described by their message.
+///
+/// ```
+/// use json_parser::error::Error;
+/// use json_parser::explain::explain_error;
+/// use json_parser::parser::parse_str;
+///
+/// let input = "{\"items\": [\n  1,\n  2\n  3\n]}";
+/// let error = parse_str(input).unwrap_err();
+/// let error = error.downcast_ref::<Error>().unwrap();
+/// assert_eq!(
+///     explain_error(input, error),
+///     "Found `3` where it is not allowed at line 4, column 3.\n\
+///      Expected a `,` or a `]`.\n\
+///      Inside the array started at line 1, column 11 (at /items)."
+/// );
+/// ```
+pub fn explain_error(input: &str, error: &Error) -> String {
+    let error = match error {
+        Error::Parsing(error) => error,
+        Error::Patch(error) => return error.to_string(),
+        Error::Build(error) => return error.to_string(),
+        Error::Encryption(error) => return error.to_string(),
+        Error::Extension(error) => return error.to_string(),
+        Error::Dump(error) => return error.to_string(),
+        Error::Mapping(error) => return error.to_string(),
+        Error::Script(error) => return error.to_string(),
+        Error::Watch(error) => return error.to_string(),
+        Error::File(FileError::Parse { path, error }) => {
+            let error = Error::Parsing(error.clone());
+            return format!("In {:?}:\n{}", path, explain_error(input, &error));
+        }
+        Error::File(error) => return error.to_string(),
+        Error::Extract(errors) => {
+            let errors: Vec<String> =
+                errors.iter().map(ToString::to_string).collect();
+            return errors.join("\n");
+        }
+    };
+
+    let mut trace = Trace::default();
+    // Only the trace is of interest: the error is the caller's.
+    let _ = parser::observe(input, &Json, &mut trace);
+
+    let position = match error {
+        ParseError::EndOfBuffer(_) => None,
+        _ => error
+            .span()
+            .or(trace.failed.clone())
+            .map(|span| (span.line, span.col)),
+    };
+    let mut out = describe(error);
+    match position {
+        Some((line, col)) => {
+            out.push_str(&format!(" at line {}, column {}.", line, col))
+        }
+        None => out.push('.'),
+    }
+
+    let expected = match error {
+        ParseError::UnexpectedToken(..)
+        | ParseError::EndOfBuffer(_)
+        | ParseError::ContainerParanthesisMismatch { .. }
+        | ParseError::InvalidKeyValueFormat { .. } => trace.expected(input),
+        _ => "",
+    };
+    if !expected.is_empty() {
+        out.push_str(&format!("\nExpected {}.", expected));
+    }
+    out.push('\n');
+    out.push_str(&trace.context());
+    out
+}
+
+/// Lines of the input shown before the failing one by [`render_error`].
+const CONTEXT_LINES: usize = 2;
+
+/// Renders a parse error in the manner of compiler diagnostics: the
+/// message, then the failing line of `input` and the lines before it, with
+/// a caret under the offending text.
+///
+/// Errors without a place in the input are rendered as their message.
+///
+/// ```
+/// use json_parser::error::Error;
+/// use json_parser::explain::render_error;
+/// use json_parser::parser::parse_str;
+///
+/// let input = "{\n  \"a\": 1,\n  \"b\": tru\n}";
+/// let error = parse_str(input).unwrap_err();
+/// let error = error.downcast_ref::<Error>().unwrap();
+/// assert_eq!(
+///     render_error(input, error),
+///     "error: Unknown literal tru at line 3, col: 8, did you mean `true`?\n \
+///      --> line 3, column 8\n  \
+///        |\n\
+///      1 | {\n\
+///      2 |   \"a\": 1,\n\
+///      3 |   \"b\": tru\n  \
+///        |        ^^^ `tru` is not a value; did you mean `true`?\n  \
+///        = at $.b\n"
+/// );
+/// ```
+pub fn render_error(input: &str, error: &Error) -> String {
+    let (parse, path) = match error {
+        Error::Parsing(error) => (error, None),
+        Error::File(FileError::Parse { path, error }) => (error, Some(path)),
+        _ => return format!("error: {}\n", error),
+    };
+    let (start, end) = match (parse, parse.span()) {
+        // Points past the last token read.
+        (ParseError::EndOfBuffer(_), _) => {
+            let end = input.trim_end().len();
+            (end, end)
+        }
+        (_, Some(span)) => (span.start, span.end),
+        _ => return format!("error: {}\n", parse),
+    };
+    // Spans may point inside a character the parser stopped in.
+    let mut start = start.min(input.len());
+    while !input.is_char_boundary(start) {
+        start -= 1;
+    }
+    let mut end = end.clamp(start, input.len());
+    while !input.is_char_boundary(end) {
+        end += 1;
+    }
+
+    let line_start = input[..start].rfind('\n').map_or(0, |idx| idx + 1);
+    let line_end = input[start..]
+        .find('\n')
+        .map_or(input.len(), |idx| start + idx);
+    let line = input[..start].matches('\n').count() + 1;
+    let col = input[line_start..start].chars().count() + 1;
+    let location = match path {
+        Some(path) => format!("{}:{}:{}", path, line, col),
+        None => format!("line {}, column {}", line, col),
+    };
+    let width = line.to_string().len();
+    let gutter = " ".repeat(width);
+
+    let mut out = format!("error: {}\n", parse);
+    out.push_str(&format!("{} --> {}\n", &gutter[1..], location));
+    out.push_str(&format!("{} |\n", gutter));
+    let first = line.saturating_sub(CONTEXT_LINES).max(1);
+    let shown = input[..line_end].lines().skip(first - 1);
+    for (number, text) in (first..).zip(shown) {
+        out.push_str(&format!("{:>width$} | {}\n", number, text));
+    }
+
+    // Tabs are kept so that the caret lines up with the text above.
+    let padding: String = input[line_start..start]
+        .chars()
+        .map(|chr| if chr == '\t' { '\t' } else { ' ' })
+        .collect();
+    let carets = input[start..end.clamp(start, line_end)]
+        .chars()
+        .count()
+        .max(1);
+    out.push_str(&format!(
+        "{} | {}{} {}\n",
+        gutter,
+        padding,
+        "^".repeat(carets),
+        describe(parse)
+    ));
+    if let Some(json_path) = parse.json_path() {
+        out.push_str(&format!("{} = at {}\n", gutter, json_path));
+    }
+    out
+}
@@ -0,0 +1,177 @@
+//! Application types stored as tagged values.
+//!
+//! A value of a registered type is stored as an
+//! `{"$type": "<tag>", "$value": <payload>}` object, where the payload is
+//! whatever the type encodes itself to. The wrapper is plain JSON, so it
+//! goes through parsing, dumping and every serializer unchanged, and any
+//! program knowing the tag can read it back.
+use super::container::Container;
+use super::error::{Error, ExtensionError};
+use core::any::{Any, TypeId};
+use std::collections::HashMap;
+
+/// Member name holding the tag of a wrapped value.
+pub const TYPE_KEY: &str = "$type";
+/// Member name holding the payload of a wrapped value.
+pub const VALUE_KEY: &str = "$value";
+
+type Encoder = Box<dyn Fn(&dyn Any) -> Container>;
+type Decoder = Box<
+    dyn Fn(&Container) -> Result<Box<dyn Any>, Box<dyn core::error::Error>>,
+>;
+
+/// The tag of a wrapper and its decoded value.
+type Decoded<'a> = (&'a str, Box<dyn Any>);
+
+struct Entry {
+    type_id: TypeId,
+    encode: Encoder,
+    decode: Decoder,
+}
+
+/// Returns the tag and the payload if `value` is a
+/// `{"$type": "...", "$value": ...}` wrapper.
+pub fn tagged(value: &Container) -> Option<(&str, &Container)> {
+    match value {
+        Container::Object(map) if map.len() == 2 => {
+            match (map.get(TYPE_KEY), map.get(VALUE_KEY)) {
+                (Some(Container::String(tag)), Some(payload)) => {
+                    Some((tag, payload))
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Associates `$type` tags with the functions converting a type to and
+/// from its payload.
+///
+/// ```
+/// use json_parser::container::Container;
+/// use json_parser::extension::Registry;
+/// use json_parser::parser::parse_str;
+/// use std::time::Duration;
+///
+/// let mut registry = Registry::new();
+/// registry.register(
+///     "duration",
+///     |duration: &Duration| Container::from(duration.as_millis() as u64),
+///     |payload| match payload.get_uint() {
+///         Some(millis) => Ok(Duration::from_millis(millis)),
+///         None => Err("a duration is a number of milliseconds".into()),
+///     },
+/// );
+///
+/// let value = registry.encode(&Duration::from_secs(2)).unwrap();
+/// let text = value.to_string();
+/// assert_eq!(text, r#"{"$type":"duration","$value":2000}"#);
+///
+/// let parsed = parse_str(&text).unwrap();
+/// let duration: Duration = registry.decode(&parsed).unwrap();
+/// assert_eq!(duration, Duration::from_secs(2));
+/// ```
+#[derive(Default)]
+pub struct Registry {
+    entries: HashMap<String, Entry>,
+    /// Tag of each registered type
+    tags: HashMap<TypeId, String>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `T` under `tag`, replacing any type registered under the
+    /// same tag, and any tag registered for `T`.
+    ///
+    /// `decode` receives the payload, and reports values it cannot read
+    /// with an error of its own.
+    pub fn register<T, E, D>(
+        &mut self,
+        tag: &str,
+        encode: E,
+        decode: D,
+    ) -> &mut Self
+    where
+        T: Any,
+        E: Fn(&T) -> Container + 'static,
+        D: Fn(&Container) -> Result<T, Box<dyn core::error::Error>> + 'static,
+    {
+        let type_id = TypeId::of::<T>();
+        if let Some(previous) = self.tags.remove(&type_id) {
+            self.entries.remove(&previous);
+        }
+        if let Some(replaced) = self.entries.remove(tag) {
+            self.tags.remove(&replaced.type_id);
+        }
+        self.tags.insert(type_id, tag.to_owned());
+
+        self.entries.insert(
+            tag.to_owned(),
+            Entry {
+                type_id,
+                encode: Box::new(move |value| match value.downcast_ref() {
+                    Some(value) => encode(value),
+                    None => unreachable!("entries are looked up by type"),
+                }),
+                decode: Box::new(move |payload| {
+                    decode(payload).map(|value| Box::new(value) as Box<dyn Any>)
+                }),
+            },
+        );
+        self
+    }
+
+    /// The tag registered for `T`, if any.
+    pub fn tag_of<T: Any>(&self) -> Option<&str> {
+        self.tags.get(&TypeId::of::<T>()).map(String::as_str)
+    }
+
+    /// Wraps `value` with the tag of its type.
+    pub fn encode<T: Any>(
+        &self,
+        value: &T,
+    ) -> Result<Container, Box<dyn core::error::Error>> {
+        let tag = self.tag_of::<T>().ok_or(Error::Extension(
+            ExtensionError::Unregistered(core::any::type_name::<T>()),
+        ))?;
+
+        let mut wrapper = Container::new_object();
+        wrapper.insert_str(TYPE_KEY, Container::String(tag.into()));
+        wrapper.insert_str(VALUE_KEY, (self.entries[tag].encode)(value));
+        Ok(wrapper)
+    }
+
+    /// Reads back a wrapper written by [`Registry::encode`] for `T`.
+    pub fn decode<T: Any>(
+        &self,
+        value: &Container,
+    ) -> Result<T, Box<dyn core::error::Error>> {
+        let (tag, any) = self.decode_any(value)?;
+        match any.downcast() {
+            Ok(value) => Ok(*value),
+            Err(_) => Err(Error::Extension(ExtensionError::TypeMismatch {
+                tag: tag.to_owned(),
+                expected: core::any::type_name::<T>(),
+            })
+            .into()),
+        }
+    }
+
+    /// Reads back a wrapper of any registered type, returning its tag and
+    /// the decoded value.
+    pub fn decode_any<'a>(
+        &self,
+        value: &'a Container,
+    ) -> Result<Decoded<'a>, Box<dyn core::error::Error>> {
+        let (tag, payload) =
+            tagged(value).ok_or(Error::Extension(ExtensionError::NotTagged))?;
+        let entry = self.entries.get(tag).ok_or_else(|| {
+            Error::Extension(ExtensionError::UnknownTag(tag.to_owned()))
+        })?;
+        Ok((tag, (entry.decode)(payload)?))
+    }
+}
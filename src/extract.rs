@@ -0,0 +1,403 @@
+//! Reading many typed fields of a document at once.
+//!
+//! Validation code usually reads a handful of fields and gives up on the
+//! first one that is missing or of the wrong type, so that fixing a
+//! request takes as many round trips as it has bad fields. An
+//! [`Extractor`] reads every field regardless and reports all the bad
+//! ones together.
+//!
+//! Fields are required by default; [`Extractor::field`] declares them
+//! optional or gives them a default instead.
+use super::container::Container;
+use super::error::{Error, FieldError, Warning, WarningKind};
+use super::lint::type_name;
+use super::pointer;
+use std::collections::HashMap;
+
+/// Collects typed reads of the fields of a document, and the errors of
+/// those that fail.
+///
+/// A field is a member name of the document, or a JSON Pointer into it
+/// when it starts with `/`. A read that fails returns the default value
+/// of its type: values are only meaningful once [`Extractor::finish`]
+/// succeeded.
+///
+/// ```
+/// use json_parser::error::{Error, FieldError};
+/// use json_parser::extract::Extractor;
+/// use json_parser::parser::parse_str;
+///
+/// let user = parse_str(r#"{"name": "Ada", "address": {"zip": "1000"}}"#)
+///     .unwrap();
+/// let mut ex = Extractor::new(&user);
+/// let name = ex.str("name");
+/// let age = ex.u64("age");
+/// let zip = ex.u64("/address/zip");
+///
+/// let error = ex.finish().unwrap_err();
+/// let Some(Error::Extract(errors)) = error.downcast_ref::<Error>() else {
+///     panic!("fields are reported together");
+/// };
+/// assert_eq!(name, "Ada");
+/// assert_eq!((age, zip), (0, 0));
+/// assert!(matches!(&errors[0], FieldError::Missing(path) if path == "/age"));
+/// assert_eq!(
+///     errors[1].to_string(),
+///     "Field \"/address/zip\" should be an unsigned integer, found string"
+/// );
+/// ```
+#[derive(Debug)]
+pub struct Extractor<'a> {
+    root: &'a Container,
+    errors: Vec<FieldError>,
+    /// Replacement of each deprecated path, if any
+    deprecated: HashMap<String, Option<String>>,
+    warnings: Vec<Warning>,
+}
+
+/// The JSON Pointer to `field`.
+fn path(field: &str) -> String {
+    if field.starts_with('/') {
+        field.to_owned()
+    } else {
+        format!("/{}", pointer::escape(field))
+    }
+}
+
+impl<'a> Extractor<'a> {
+    pub fn new(root: &'a Container) -> Self {
+        Self {
+            root,
+            errors: Vec::new(),
+            deprecated: HashMap::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Marks `field` as deprecated, optionally in favor of `replacement`:
+    /// the first read finding a value there reports a
+    /// `WarningKind::DeprecatedField`.
+    ///
+    /// ```
+    /// use json_parser::error::WarningKind;
+    /// use json_parser::extract::Extractor;
+    /// use json_parser::parser::parse_str;
+    ///
+    /// let request = parse_str(r#"{"user_name": "ada"}"#).unwrap();
+    /// let mut ex = Extractor::new(&request);
+    /// ex.deprecate("user_name", Some("username"));
+    /// let name = ex.field("username").optional().str();
+    /// let name = name.or_else(|| ex.field("user_name").optional().str());
+    /// let warnings = ex.finish_with_warnings().unwrap();
+    /// assert_eq!(name.as_deref(), Some("ada"));
+    /// assert_eq!(
+    ///     warnings[0].kind,
+    ///     WarningKind::DeprecatedField {
+    ///         path: "/user_name".into(),
+    ///         replacement: Some("/username".into()),
+    ///     }
+    /// );
+    /// ```
+    pub fn deprecate(
+        &mut self,
+        field: &str,
+        replacement: Option<&str>,
+    ) -> &mut Self {
+        self.deprecated.insert(path(field), replacement.map(path));
+        self
+    }
+
+    /// The JSON Pointer to `field` and the value it references, if any.
+    fn lookup(&mut self, field: &str) -> (String, Option<&'a Container>) {
+        let path = path(field);
+        let value = if field.starts_with('/') {
+            pointer::split(field)
+                .and_then(|tokens| pointer::resolve(self.root, &tokens))
+        } else {
+            match self.root {
+                Container::Object(map) => map.get(field),
+                _ => None,
+            }
+        };
+        if value.is_some() {
+            self.warn_deprecated(&path);
+        }
+        (path, value)
+    }
+
+    /// Reports that a value was found at `path`, if it is deprecated and
+    /// not reported yet.
+    fn warn_deprecated(&mut self, path: &str) {
+        let Some(replacement) = self.deprecated.remove(path) else {
+            return;
+        };
+        self.warnings.push(Warning {
+            kind: WarningKind::DeprecatedField {
+                path: path.to_owned(),
+                replacement,
+            },
+            line: 0,
+            col: 0,
+        });
+    }
+
+    /// Starts reading `field`, which is required unless a policy is
+    /// declared on the returned [`Field`].
+    pub fn field<'e>(&'e mut self, field: &str) -> Field<'e, 'a> {
+        Field {
+            extractor: self,
+            field: field.to_owned(),
+            default: None,
+        }
+    }
+
+    /// Reads the required `field` with `read`, which returns `None` for
+    /// values that are not `expected`, such as `"a duration"`.
+    ///
+    /// ```
+    /// use json_parser::extract::Extractor;
+    /// use json_parser::parser::parse_str;
+    /// use std::time::Duration;
+    ///
+    /// let config = parse_str(r#"{"timeout": "1m30s"}"#).unwrap();
+    /// let mut ex = Extractor::new(&config);
+    /// let timeout = ex.get("timeout", "a duration", |v| v.get_duration());
+    /// ex.finish().unwrap();
+    /// assert_eq!(timeout, Duration::from_secs(90));
+    /// ```
+    pub fn get<T, F>(
+        &mut self,
+        field: &str,
+        expected: &'static str,
+        read: F,
+    ) -> T
+    where
+        T: Default,
+        F: FnOnce(&Container) -> Option<T>,
+    {
+        self.field(field).get(expected, read)
+    }
+
+    pub fn str(&mut self, field: &str) -> String {
+        self.field(field).str()
+    }
+
+    pub fn bool(&mut self, field: &str) -> bool {
+        self.field(field).bool()
+    }
+
+    /// Reads an integer that fits in a `u64`, whatever its variant.
+    pub fn u64(&mut self, field: &str) -> u64 {
+        self.field(field).u64()
+    }
+
+    /// Reads an integer that fits in an `i64`, whatever its variant.
+    pub fn i64(&mut self, field: &str) -> i64 {
+        self.field(field).i64()
+    }
+
+    /// Reads any number, integers being converted.
+    pub fn f64(&mut self, field: &str) -> f64 {
+        self.field(field).f64()
+    }
+
+    /// Records that `value`, found at `path`, is not `expected`.
+    fn mismatch(
+        &mut self,
+        path: String,
+        expected: &'static str,
+        value: &Container,
+    ) {
+        self.errors.push(FieldError::Mismatch {
+            path,
+            expected,
+            found: type_name(value),
+        });
+    }
+
+    /// Errors of the reads so far.
+    pub fn errors(&self) -> &[FieldError] {
+        &self.errors
+    }
+
+    /// Warnings of the reads so far.
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// Fails with `Error::Extract` listing every field that could not be
+    /// read, in the order of the reads.
+    pub fn finish(self) -> Result<(), Box<dyn core::error::Error>> {
+        self.finish_with_warnings().map(|_| ())
+    }
+
+    /// Same as [`Extractor::finish`], returning the warnings of the reads
+    /// on success.
+    pub fn finish_with_warnings(
+        self,
+    ) -> Result<Vec<Warning>, Box<dyn core::error::Error>> {
+        if self.errors.is_empty() {
+            Ok(self.warnings)
+        } else {
+            Err(Error::Extract(self.errors).into())
+        }
+    }
+}
+
+fn to_u64(value: &Container) -> Option<u64> {
+    match *value {
+        Container::Unsigned(value) => Some(value),
+        Container::Number(value) => u64::try_from(value).ok(),
+        Container::Number128(value) => u64::try_from(value).ok(),
+        Container::Unsigned128(value) => u64::try_from(value).ok(),
+        _ => None,
+    }
+}
+
+fn to_i64(value: &Container) -> Option<i64> {
+    match *value {
+        Container::Number(value) => Some(value),
+        Container::Unsigned(value) => i64::try_from(value).ok(),
+        Container::Number128(value) => i64::try_from(value).ok(),
+        Container::Unsigned128(value) => i64::try_from(value).ok(),
+        _ => None,
+    }
+}
+
+fn to_f64(value: &Container) -> Option<f64> {
+    match *value {
+        Container::Decimal(value) => Some(value),
+        Container::Number(value) => Some(value as f64),
+        Container::Unsigned(value) => Some(value as f64),
+        Container::Number128(value) => Some(value as f64),
+        Container::Unsigned128(value) => Some(value as f64),
+        _ => None,
+    }
+}
+
+/// A field being read by an [`Extractor`], required unless it is given a
+/// default.
+///
+/// ```
+/// use json_parser::extract::Extractor;
+/// use json_parser::parser::parse_str;
+///
+/// let request = parse_str(r#"{"user": "ada", "limit": null}"#).unwrap();
+/// let mut ex = Extractor::new(&request);
+/// let user = ex.field("user").str();
+/// let limit = ex.field("limit").or(50u64).u64();
+/// let cursor = ex.field("cursor").optional().str();
+/// ex.finish().unwrap();
+/// assert_eq!((user.as_str(), limit, cursor), ("ada", 50, None));
+/// ```
+#[derive(Debug)]
+pub struct Field<'e, 'a> {
+    extractor: &'e mut Extractor<'a>,
+    field: String,
+    default: Option<Container>,
+}
+
+impl<'e, 'a> Field<'e, 'a> {
+    /// Reads `default` when the field is missing or `null`. The default
+    /// must be of the type read.
+    pub fn or(mut self, default: impl Into<Container>) -> Self {
+        self.default = Some(default.into());
+        self
+    }
+
+    /// Makes the field optional: reads return `None` when it is missing
+    /// or `null`.
+    pub fn optional(self) -> OptionalField<'e, 'a> {
+        OptionalField {
+            extractor: self.extractor,
+            field: self.field,
+        }
+    }
+
+    /// Reads the field with `read`, which returns `None` for values that
+    /// are not `expected`.
+    pub fn get<T, F>(self, expected: &'static str, read: F) -> T
+    where
+        T: Default,
+        F: FnOnce(&Container) -> Option<T>,
+    {
+        let (path, value) = self.extractor.lookup(&self.field);
+        let value = match (value, self.default.as_ref()) {
+            (None | Some(Container::Null), Some(default)) => default,
+            (Some(value), _) => value,
+            (None, None) => {
+                self.extractor.errors.push(FieldError::Missing(path));
+                return T::default();
+            }
+        };
+        read(value).unwrap_or_else(|| {
+            self.extractor.mismatch(path, expected, value);
+            T::default()
+        })
+    }
+
+    pub fn str(self) -> String {
+        self.get("a string", Container::get_string)
+    }
+
+    pub fn bool(self) -> bool {
+        self.get("a boolean", Container::get_bool)
+    }
+
+    pub fn u64(self) -> u64 {
+        self.get("an unsigned integer", to_u64)
+    }
+
+    pub fn i64(self) -> i64 {
+        self.get("an integer", to_i64)
+    }
+
+    pub fn f64(self) -> f64 {
+        self.get("a number", to_f64)
+    }
+}
+
+/// A field that may be missing or `null`, returned by
+/// [`Field::optional`].
+#[derive(Debug)]
+pub struct OptionalField<'e, 'a> {
+    extractor: &'e mut Extractor<'a>,
+    field: String,
+}
+
+impl OptionalField<'_, '_> {
+    /// Reads the field with `read`, which returns `None` for values that
+    /// are not `expected`. Only such values are reported.
+    pub fn get<T, F>(self, expected: &'static str, read: F) -> Option<T>
+    where
+        F: FnOnce(&Container) -> Option<T>,
+    {
+        let (path, value) = self.extractor.lookup(&self.field);
+        let value = value.filter(|value| !value.is_null())?;
+        let read = read(value);
+        if read.is_none() {
+            self.extractor.mismatch(path, expected, value);
+        }
+        read
+    }
+
+    pub fn str(self) -> Option<String> {
+        self.get("a string", Container::get_string)
+    }
+
+    pub fn bool(self) -> Option<bool> {
+        self.get("a boolean", Container::get_bool)
+    }
+
+    pub fn u64(self) -> Option<u64> {
+        self.get("an unsigned integer", to_u64)
+    }
+
+    pub fn i64(self) -> Option<i64> {
+        self.get("an integer", to_i64)
+    }
+
+    pub fn f64(self) -> Option<f64> {
+        self.get("a number", to_f64)
+    }
+}
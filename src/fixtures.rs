@@ -0,0 +1,286 @@
+//! A corpus of representative documents, for testing code built on
+//! `Container` against realistic data.
+//!
+//! Small fixtures are kept as text in [`FIXTURES`]. Large ones, such as
+//! deep nesting or big arrays, are generated on demand at any size, the
+//! same for every call.
+use super::container::Container;
+use super::parser::{parse_str, NEST_LIMIT};
+
+/// A fixture document, kept as text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fixture {
+    pub name: &'static str,
+    /// What the document exercises
+    pub description: &'static str,
+    pub text: &'static str,
+}
+
+impl Fixture {
+    /// Parses the fixture, which is always valid JSON.
+    pub fn load(&self) -> Container {
+        parse_str(self.text).expect("fixtures are valid JSON")
+    }
+}
+
+/// A response of a paginated web API, with nested records and ids.
+pub const API_RESPONSE: &str = r#"{
+  "data": [
+    {
+      "id": 1024,
+      "type": "user",
+      "attributes": {
+        "login": "ada",
+        "email": "ada@example.com",
+        "created_at": "2021-03-14T15:09:26Z",
+        "roles": ["admin", "billing"],
+        "active": true,
+        "last_seen": null
+      },
+      "relationships": {"team": {"id": 7, "type": "team"}}
+    },
+    {
+      "id": 1025,
+      "type": "user",
+      "attributes": {
+        "login": "grace",
+        "email": "grace@example.com",
+        "created_at": "2022-11-02T08:00:00.250+01:00",
+        "roles": [],
+        "active": false,
+        "last_seen": "2023-01-01T00:00:00Z"
+      },
+      "relationships": {"team": null}
+    }
+  ],
+  "meta": {"page": 1, "per_page": 2, "total": 5318, "ratio": 0.000376},
+  "links": {
+    "self": "https://api.example.com/users?page=1",
+    "next": "https://api.example.com/users?page=2"
+  }
+}"#;
+
+/// A configuration file, objects nested a few levels deep.
+pub const CONFIG: &str = r#"{
+  "service": "checkout",
+  "version": "2.4.1",
+  "listen": {"host": "0.0.0.0", "port": 8443, "tls": true},
+  "database": {
+    "primary": {"url": "postgres://db-1:5432/shop", "pool": 32},
+    "replicas": [
+      {"url": "postgres://db-2:5432/shop", "pool": 16, "weight": 0.75},
+      {"url": "postgres://db-3:5432/shop", "pool": 16, "weight": 0.25}
+    ],
+    "timeouts": {"connect_ms": 500, "statement_ms": 30000}
+  },
+  "features": {"new_cart": true, "legacy_tax": false, "beta": []},
+  "limits": {"max_body_bytes": 1048576, "rate_per_second": 250.5}
+}"#;
+
+/// A GeoJSON feature collection, dense with real numbers.
+pub const GEOJSON: &str = r#"{
+  "type": "FeatureCollection",
+  "features": [
+    {
+      "type": "Feature",
+      "geometry": {"type": "Point", "coordinates": [2.2945, 48.8584, 35.5]},
+      "properties": {"name": "Tour Eiffel", "height_m": 330}
+    },
+    {
+      "type": "Feature",
+      "geometry": {
+        "type": "Polygon",
+        "coordinates": [[
+          [-73.9819, 40.7681], [-73.9580, 40.8006], [-73.9498, 40.7968],
+          [-73.9737, 40.7641], [-73.9819, 40.7681]
+        ]]
+      },
+      "properties": {"name": "Central Park", "area_km2": 3.41}
+    }
+  ],
+  "bbox": [-73.9819, 40.7641, 2.2945, 48.8584]
+}"#;
+
+/// Text in many scripts, with characters beyond the Basic Multilingual
+/// Plane, written both as is and as escaped surrogate pairs.
+pub const UNICODE: &str = r#"{
+  "latin": "Ça a été très facile, naïve café",
+  "greek": "Καλημέρα κόσμε",
+  "cyrillic": "Съешь же ещё этих мягких французских булок",
+  "cjk": "日本語のテキスト、中文文本、한국어 텍스트",
+  "rtl": "مرحبا بالعالم שלום עולם",
+  "emoji": "👩‍💻 🚀 🇫🇷 👍🏽",
+  "escaped_emoji": "\ud83d\ude80 \ud83d\udc4d\ud83c\udffd",
+  "combining": "e\u0301 a\u0308 n\u0303",
+  "keys": {"ключ": 1, "キー": 2, "🔑": 3, "": 4}
+}"#;
+
+/// Every escape of JSON strings, and characters that need them.
+pub const ESCAPES: &str = r#"{
+  "quotes": "she said \"hi\"",
+  "backslashes": "C:\\Program Files\\App\\",
+  "slashes": "a\/b/c",
+  "whitespace": "tab\there\nnew line\r\ncarriage",
+  "control": "\b\f\u0000\u001f\u007f",
+  "unicode": "\u00e9\u4e2d\uFFFD",
+  "empty": "",
+  "only_escapes": "\\\"\\\"",
+  "key \"with\" escapes\n": true
+}"#;
+
+/// Numbers at and beyond the limits of machine types.
+pub const NUMBERS: &str = r#"{
+  "zero": 0,
+  "negative_zero": -0.0,
+  "i64_min": -9223372036854775808,
+  "i64_max": 9223372036854775807,
+  "u64_max": 18446744073709551615,
+  "beyond_u64": 18446744073709551616,
+  "i128_min": -170141183460469231731687303715884105728,
+  "u128_max": 340282366920938463463374607431768211455,
+  "beyond_u128": 123456789012345678901234567890123456789012345678901234567890,
+  "tiny": 5e-324,
+  "below_f64": 1e-400,
+  "huge": 1.7976931348623157e308,
+  "beyond_f64": 1e400,
+  "precise": 0.1000000000000000055511151231257827,
+  "exponents": [1E2, 1e+2, 1e-2, -1.5E-7, 12.5e0]
+}"#;
+
+/// Empty and single element containers, and values of every kind.
+pub const EDGE_CASES: &str = r#"[
+  {}, [], [[]], [{}], {"": {"": {}}},
+  null, true, false, 0, -1, 1.5, "",
+  {"duplicate": 1, "duplicate": 2},
+  [null, [null, [null]]],
+  {"a": {"b": {"c": {"d": {"e": "deep enough"}}}}}
+]"#;
+
+/// The fixtures kept as text.
+pub const FIXTURES: &[Fixture] = &[
+    Fixture {
+        name: "api_response",
+        description: "paginated web API response with nested records",
+        text: API_RESPONSE,
+    },
+    Fixture {
+        name: "config",
+        description: "service configuration nested a few levels deep",
+        text: CONFIG,
+    },
+    Fixture {
+        name: "geojson",
+        description: "GeoJSON feature collection dense with real numbers",
+        text: GEOJSON,
+    },
+    Fixture {
+        name: "unicode",
+        description: "many scripts, emoji and escaped surrogate pairs",
+        text: UNICODE,
+    },
+    Fixture {
+        name: "escapes",
+        description: "every string escape, in values and in keys",
+        text: ESCAPES,
+    },
+    Fixture {
+        name: "numbers",
+        description: "numbers at and beyond the limits of machine types",
+        text: NUMBERS,
+    },
+    Fixture {
+        name: "edge_cases",
+        description: "empty containers, duplicate keys, every kind of value",
+        text: EDGE_CASES,
+    },
+];
+
+/// The fixture named `name`.
+///
+/// ```
+/// use json_parser::fixtures;
+///
+/// let config = fixtures::get("config").unwrap().load();
+/// assert_eq!(config["listen"]["port"].get_uint(), Some(8443));
+/// assert!(fixtures::get("missing").is_none());
+/// ```
+pub fn get(name: &str) -> Option<&'static Fixture> {
+    FIXTURES.iter().find(|fixture| fixture.name == name)
+}
+
+/// Arrays nested `depth` levels deep around a string, as text.
+///
+/// Depths above the parser's default limit, 500, make documents that
+/// fail to parse unless `ParserOptions::max_depth` is raised.
+pub fn deep_nesting(depth: usize) -> String {
+    let mut text = String::with_capacity(2 * depth + 8);
+    (0..depth).for_each(|_| text.push('['));
+    text.push_str("\"core\"");
+    (0..depth).for_each(|_| text.push(']'));
+    text
+}
+
+/// An array of `len` records of a log, as text.
+///
+/// ```
+/// use json_parser::fixtures;
+/// use json_parser::parser::parse_str;
+///
+/// let logs = parse_str(&fixtures::big_array(1000)).unwrap();
+/// assert_eq!(logs.len(), 1000);
+/// assert_eq!(logs[999]["seq"].get_uint(), Some(999));
+/// ```
+pub fn big_array(len: usize) -> String {
+    const LEVELS: [&str; 4] = ["debug", "info", "warn", "error"];
+    let mut text = String::with_capacity(len * 120 + 2);
+    text.push('[');
+    for seq in 0..len {
+        if seq > 0 {
+            text.push(',');
+        }
+        text.push_str(&format!(
+            concat!(
+                r#"{{"seq": {}, "level": "{}", "latency_ms": {}.{:03}, "#,
+                r#""path": "/api/v1/items/{}", "ok": {}, "tags": [{}]}}"#
+            ),
+            seq,
+            LEVELS[seq % 4],
+            seq * 7 % 1000,
+            seq * 37 % 1000,
+            seq * 13 % 10007,
+            seq % 4 != 3,
+            match seq % 3 {
+                0 => "",
+                1 => r#""cache""#,
+                _ => r#""cache", "retry""#,
+            },
+        ));
+    }
+    text.push(']');
+    text
+}
+
+/// Every fixture parsed, by name: those of [`FIXTURES`], then
+/// `deep_nesting` at the parser's depth limit and `big_array` of 10000
+/// records.
+///
+/// ```
+/// use json_parser::fixtures;
+///
+/// let all = fixtures::load_all();
+/// assert_eq!(all.len(), fixtures::FIXTURES.len() + 2);
+/// assert!(all.iter().any(|(name, _)| *name == "deep_nesting"));
+/// ```
+pub fn load_all() -> Vec<(&'static str, Container)> {
+    let generated = [
+        ("deep_nesting", deep_nesting(NEST_LIMIT as usize)),
+        ("big_array", big_array(10_000)),
+    ];
+    FIXTURES
+        .iter()
+        .map(|fixture| (fixture.name, fixture.load()))
+        .chain(generated.into_iter().map(|(name, text)| {
+            (name, parse_str(&text).expect("fixtures are valid JSON"))
+        }))
+        .collect()
+}
@@ -0,0 +1,370 @@
+//! Generation of random documents, e.g. for load tests or for fuzzing
+//! consumers of `Container`, and of corrupted inputs for negative tests.
+use super::container::Container;
+use super::map::Map;
+
+/// Source of randomness used by the generators.
+///
+/// Implement it over any generator already used by the application, or
+/// use the bundled [`SplitMix64`].
+pub trait Rng {
+    /// Returns the next 64 random bits.
+    fn next_u64(&mut self) -> u64;
+
+    /// Returns a value uniformly drawn from `0..bound` (`bound > 0`).
+    fn below(&mut self, bound: u64) -> u64 {
+        // Multiply-shift keeps the bias negligible for the small bounds
+        // used here.
+        ((self.next_u64() as u128 * bound as u128) >> 64) as u64
+    }
+
+    /// Returns a real number uniformly drawn from `0.0..1.0`.
+    fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Small, seedable generator (SplitMix64): deterministic for a given seed,
+/// which keeps generated fixtures reproducible.
+#[derive(Debug, Clone)]
+pub struct SplitMix64(u64);
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+}
+
+impl Rng for SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+const ALPHABET: &[u8] =
+    b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+/// Generates a random document that validates against `schema`.
+///
+/// Supported keywords: `type` (a name or a list of names), `enum`,
+/// `const`, `anyOf`/`oneOf`, `minimum`/`maximum`, `minLength`/`maxLength`,
+/// `items`/`minItems`/`maxItems` and `properties`/`required`. Optional
+/// properties are included with even odds. A schema without `type` is
+/// inferred from `properties`/`items`, falling back to a random scalar;
+/// unknown type names produce `Null`.
+///
+/// ```
+/// use json_parser::generate::{from_schema, SplitMix64};
+/// use json_parser::parser::parse_str;
+///
+/// let schema = parse_str(
+///     r#"{"type": "object", "required": ["id"],
+///         "properties": {"id": {"type": "integer", "minimum": 1}}}"#,
+/// )
+/// .unwrap();
+///
+/// let doc = from_schema(&schema, &mut SplitMix64::new(7));
+/// assert!(doc["id"].get_uint().is_some_and(|id| id >= 1));
+/// ```
+pub fn from_schema<R: Rng>(schema: &Container, rng: &mut R) -> Container {
+    let Container::Object(keywords) = schema else {
+        // `true` and `{}` accept anything.
+        return random_scalar(rng);
+    };
+
+    if let Some(value) = keywords.get("const") {
+        return value.clone();
+    }
+    if let Some(Container::Array(choices)) = keywords.get("enum") {
+        if !choices.is_empty() {
+            return choices[rng.below(choices.len() as u64) as usize].clone();
+        }
+    }
+    for combinator in ["anyOf", "oneOf"] {
+        if let Some(Container::Array(choices)) = keywords.get(combinator) {
+            if !choices.is_empty() {
+                let idx = rng.below(choices.len() as u64) as usize;
+                return from_schema(&choices[idx], rng);
+            }
+        }
+    }
+
+    let type_name = match keywords.get("type") {
+        Some(Container::String(name)) => name.as_str(),
+        Some(Container::Array(names)) if !names.is_empty() => {
+            match &names[rng.below(names.len() as u64) as usize] {
+                Container::String(name) => name.as_str(),
+                _ => "null",
+            }
+        }
+        _ if keywords.contains_key("properties") => "object",
+        _ if keywords.contains_key("items") => "array",
+        _ => return random_scalar(rng),
+    };
+
+    match type_name {
+        "boolean" => Container::Boolean(rng.below(2) == 1),
+        "integer" => random_integer(keywords, rng),
+        "number" => random_number(keywords, rng),
+        "string" => random_string(keywords, rng),
+        "array" => random_array(keywords, rng),
+        "object" => random_object(keywords, rng),
+        _ => Container::Null,
+    }
+}
+
+fn random_scalar<R: Rng>(rng: &mut R) -> Container {
+    match rng.below(5) {
+        0 => Container::Null,
+        1 => Container::Boolean(rng.below(2) == 1),
+        2 => Container::Unsigned(rng.below(1000)),
+        3 => Container::Decimal(rng.unit() * 1000.0),
+        _ => random_string(&Map::new(), rng),
+    }
+}
+
+/// Reads a numeric keyword as a real number.
+fn bound(keywords: &Map, name: &str) -> Option<f64> {
+    match keywords.get(name)? {
+        Container::Number(v) => Some(*v as f64),
+        Container::Unsigned(v) => Some(*v as f64),
+        Container::Number128(v) => Some(*v as f64),
+        Container::Unsigned128(v) => Some(*v as f64),
+        Container::Decimal(v) => Some(*v),
+        Container::RawNumber(v) | Container::BigNumber(v) => v.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Reads a non-negative integer keyword.
+fn count(keywords: &Map, name: &str) -> Option<u64> {
+    keywords.get(name)?.get_uint()
+}
+
+fn random_integer<R: Rng>(keywords: &Map, rng: &mut R) -> Container {
+    let min = bound(keywords, "minimum").map(|v| v.ceil() as i64);
+    let max = bound(keywords, "maximum").map(|v| v.floor() as i64);
+    let (min, max) = match (min, max) {
+        (Some(min), Some(max)) => (min, max.max(min)),
+        (Some(min), None) => (min, min.saturating_add(1000)),
+        (None, Some(max)) => (max.saturating_sub(1000).min(0), max),
+        (None, None) => (0, 1000),
+    };
+
+    let span = (max as i128 - min as i128 + 1) as u128;
+    let offset = (rng.next_u64() as u128 * span) >> 64;
+    Container::from((min as i128 + offset as i128) as i64)
+}
+
+fn random_number<R: Rng>(keywords: &Map, rng: &mut R) -> Container {
+    let (min, max) =
+        match (bound(keywords, "minimum"), bound(keywords, "maximum")) {
+            (Some(min), Some(max)) => (min, max.max(min)),
+            (Some(min), None) => (min, min + 1000.0),
+            (None, Some(max)) => (max - 1000.0, max),
+            (None, None) => (0.0, 1000.0),
+        };
+    Container::Decimal(min + rng.unit() * (max - min))
+}
+
+fn random_string<R: Rng>(keywords: &Map, rng: &mut R) -> Container {
+    let min = count(keywords, "minLength").unwrap_or(0);
+    let max = count(keywords, "maxLength").unwrap_or(min + 8).max(min);
+    let len = min + rng.below(max - min + 1);
+
+    Container::String(
+        (0..len)
+            .map(|_| {
+                ALPHABET[rng.below(ALPHABET.len() as u64) as usize] as char
+            })
+            .collect(),
+    )
+}
+
+fn random_array<R: Rng>(keywords: &Map, rng: &mut R) -> Container {
+    let min = count(keywords, "minItems").unwrap_or(0);
+    let max = count(keywords, "maxItems").unwrap_or(min + 4).max(min);
+    let len = min + rng.below(max - min + 1);
+    let items = keywords.get("items").unwrap_or(&Container::Boolean(true));
+
+    Container::Array((0..len).map(|_| from_schema(items, rng)).collect())
+}
+
+fn random_object<R: Rng>(keywords: &Map, rng: &mut R) -> Container {
+    let required: Vec<&str> = match keywords.get("required") {
+        Some(Container::Array(names)) => names
+            .iter()
+            .filter_map(|name| match name {
+                Container::String(name) => Some(name.as_str()),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let mut object = Container::new_object();
+    if let Some(Container::Object(properties)) = keywords.get("properties") {
+        // Sorted so that a seed always yields the same document.
+        let mut names: Vec<&str> = properties.keys().collect();
+        names.sort_unstable();
+
+        for name in names {
+            if required.contains(&name) || rng.below(2) == 1 {
+                object.insert_str(name, from_schema(&properties[name], rng));
+            }
+        }
+    }
+    for name in required {
+        if !object.is_object_and(|map| map.contains_key(name)) {
+            object.insert_str(name, random_scalar(rng));
+        }
+    }
+    object
+}
+
+/// Kind of corruption applied by [`mutate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MutationKind {
+    /// Document cut inside a string or before a closing bracket.
+    Truncated,
+    /// An opening or closing bracket swapped with the other bracket type.
+    FlippedBracket,
+    /// An unknown escape sequence inserted into a string.
+    BadEscape,
+    /// The closing quote of a string removed.
+    UnterminatedString,
+    /// The `:` between a key and its value removed.
+    MissingColon,
+    /// A `,` inserted right before a closing bracket.
+    TrailingComma,
+}
+
+/// A corrupted variant of a valid document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mutation {
+    pub kind: MutationKind,
+    /// Byte offset in the original document where the corruption applies.
+    pub offset: usize,
+    pub text: String,
+}
+
+/// Upper bound of mutations generated per kind, spread evenly across the
+/// document so that large inputs do not produce quadratic output.
+const MUTATIONS_PER_KIND: usize = 16;
+
+/// Produces systematically corrupted variants of `valid_doc`, each of
+/// which a strict JSON parser must reject.
+///
+/// ```
+/// use json_parser::generate::mutate;
+/// use json_parser::parser::parse_str;
+///
+/// let mutations = mutate(r#"{"key": ["value", 1]}"#);
+/// assert!(!mutations.is_empty());
+/// assert!(mutations.iter().all(|m| parse_str(&m.text).is_err()));
+/// ```
+pub fn mutate(valid_doc: &str) -> Vec<Mutation> {
+    let mut strings = Vec::new();
+    let mut brackets = Vec::new();
+    let mut colons = Vec::new();
+
+    let (mut open, mut escaped) = (None, false);
+    for (offset, byte) in valid_doc.bytes().enumerate() {
+        match (open, byte) {
+            (Some(_), _) if escaped => escaped = false,
+            (Some(_), b'\\') => escaped = true,
+            (Some(start), b'"') => {
+                strings.push((start, offset));
+                open = None;
+            }
+            (Some(_), _) => {}
+            (None, b'"') => open = Some(offset),
+            (None, b'[' | b']' | b'{' | b'}') => brackets.push((offset, byte)),
+            (None, b':') => colons.push(offset),
+            _ => {}
+        }
+    }
+    let closings: Vec<usize> = brackets
+        .iter()
+        .filter(|(_, byte)| matches!(byte, b']' | b'}'))
+        .map(|(offset, _)| *offset)
+        .collect();
+
+    let replaced = |offset: usize, len: usize, with: &str| {
+        let mut text = String::with_capacity(valid_doc.len() + with.len());
+        text.push_str(&valid_doc[..offset]);
+        text.push_str(with);
+        text.push_str(&valid_doc[offset + len..]);
+        text
+    };
+
+    let mut mutations = Vec::new();
+    let mut add = |kind: MutationKind, candidates: Vec<(usize, String)>| {
+        let step = candidates.len().div_ceil(MUTATIONS_PER_KIND).max(1);
+        mutations.extend(
+            candidates
+                .into_iter()
+                .step_by(step)
+                .map(|(offset, text)| Mutation { kind, offset, text }),
+        );
+    };
+
+    add(
+        MutationKind::Truncated,
+        strings
+            .iter()
+            .map(|(start, _)| start + 1)
+            .chain(closings.iter().copied())
+            .map(|offset| (offset, valid_doc[..offset].to_owned()))
+            .collect(),
+    );
+    add(
+        MutationKind::FlippedBracket,
+        brackets
+            .iter()
+            .map(|(offset, byte)| {
+                let flipped = match byte {
+                    b'[' => "{",
+                    b'{' => "[",
+                    b']' => "}",
+                    _ => "]",
+                };
+                (*offset, replaced(*offset, 1, flipped))
+            })
+            .collect(),
+    );
+    add(
+        MutationKind::BadEscape,
+        strings
+            .iter()
+            .map(|(start, _)| (start + 1, replaced(start + 1, 0, "\\q")))
+            .collect(),
+    );
+    add(
+        MutationKind::UnterminatedString,
+        strings
+            .iter()
+            .map(|(_, end)| (*end, replaced(*end, 1, "")))
+            .collect(),
+    );
+    add(
+        MutationKind::MissingColon,
+        colons
+            .iter()
+            .map(|offset| (*offset, replaced(*offset, 1, "")))
+            .collect(),
+    );
+    add(
+        MutationKind::TrailingComma,
+        closings
+            .iter()
+            .map(|offset| (*offset, replaced(*offset, 0, ",")))
+            .collect(),
+    );
+
+    mutations
+}
@@ -0,0 +1,260 @@
+//! Parsing of input handed over in pieces, for network protocols that
+//! receive documents in partial frames.
+//!
+//! An [`IncrementalParser`] is fed bytes as they arrive. It keeps track of
+//! where the values of the input end, resuming where the previous piece
+//! stopped, and parses each value once all of it has arrived.
+use super::container::Container;
+use super::encoding::decode;
+use super::parser::{parse_with_options, ParserOptions};
+use core::ops::Range;
+use std::collections::VecDeque;
+
+/// What feeding bytes to an [`IncrementalParser`] completed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Feed {
+    /// No value was completed
+    NeedMoreData,
+    /// The values completed, in input order
+    Values(Vec<Container>),
+}
+
+/// A parser of concatenated UTF-8 documents, such as `{"a":1}{"b":2} [3]`,
+/// fed in pieces of any size.
+///
+/// A number, or a literal, written last is only known to be complete once
+/// a byte that cannot be part of it follows, or [`finish`] is called.
+///
+/// [`finish`]: IncrementalParser::finish
+///
+/// ```
+/// use json_parser::incremental::{Feed, IncrementalParser};
+///
+/// let mut parser = IncrementalParser::new();
+/// assert_eq!(parser.feed(br#"{"id": 1, "na"#).unwrap(), Feed::NeedMoreData);
+/// let Feed::Values(values) = parser.feed(br#"me": "a"} [2] 3"#).unwrap()
+/// else {
+///     panic!("two values are complete");
+/// };
+/// assert_eq!(values[0]["name"].get_string().unwrap(), "a");
+/// assert_eq!(values[1][0].get_uint(), Some(2));
+/// assert_eq!(parser.buffered(), 1);
+/// let Feed::Values(values) = parser.finish().unwrap() else {
+///     panic!("the number is complete");
+/// };
+/// assert_eq!(values[0].get_uint(), Some(3));
+/// ```
+#[derive(Debug, Clone)]
+pub struct IncrementalParser {
+    options: ParserOptions,
+    buffer: Vec<u8>,
+    /// Complete values not parsed yet
+    frames: VecDeque<Range<usize>>,
+    /// Bytes of the buffer looked at
+    scanned: usize,
+    /// Start of the value being completed
+    value: Option<usize>,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    /// Whether the value being completed is a number or a literal
+    in_scalar: bool,
+    /// Whether bytes were fed since the start of the input
+    started: bool,
+}
+
+impl Default for IncrementalParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Whether `byte` ends a number or a literal at the top level.
+fn ends_scalar(byte: u8) -> bool {
+    byte.is_ascii_whitespace() || b"[]{}\"".contains(&byte)
+}
+
+impl IncrementalParser {
+    pub fn new() -> Self {
+        Self::with_options(ParserOptions::default())
+    }
+
+    /// A parser building each value with `options`.
+    pub fn with_options(options: ParserOptions) -> Self {
+        Self {
+            options,
+            buffer: Vec::new(),
+            frames: VecDeque::new(),
+            scanned: 0,
+            value: None,
+            depth: 0,
+            in_string: false,
+            escaped: false,
+            in_scalar: false,
+            started: false,
+        }
+    }
+
+    /// Number of bytes held, of values not returned yet.
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Adds the next bytes of the input, returning the values they
+    /// complete.
+    ///
+    /// A value that fails to parse raises its error, once the values
+    /// before it have been returned, and is then left out: the next call
+    /// goes on after it.
+    pub fn feed(
+        &mut self,
+        bytes: &[u8],
+    ) -> Result<Feed, Box<dyn core::error::Error>> {
+        self.buffer.extend_from_slice(bytes);
+        if self.started {
+            self.advance();
+        } else {
+            // Waits to know whether the input starts with a byte order mark.
+            if self.buffer.len() < BOM.len() && BOM.starts_with(&self.buffer) {
+                return Ok(Feed::NeedMoreData);
+            }
+            self.start();
+        }
+        self.take_values()
+    }
+
+    /// Skips a byte order mark, then follows the bytes held.
+    fn start(&mut self) {
+        if self.buffer.starts_with(BOM) {
+            self.buffer.drain(..BOM.len());
+        }
+        self.started = true;
+        self.advance();
+    }
+
+    /// Follows the bytes not looked at yet.
+    fn advance(&mut self) {
+        while self.scanned < self.buffer.len() {
+            self.scan(self.scanned);
+            self.scanned += 1;
+        }
+    }
+
+    /// Ends the input, returning the values it completes.
+    ///
+    /// A value left incomplete raises its parse error. The parser is then
+    /// ready for a new input.
+    pub fn finish(&mut self) -> Result<Feed, Box<dyn core::error::Error>> {
+        if !self.started {
+            self.start();
+        }
+        if let Some(start) = self.value.take() {
+            self.frames.push_back(start..self.buffer.len());
+            self.depth = 0;
+            self.in_string = false;
+            self.escaped = false;
+            self.in_scalar = false;
+        }
+        let taken = self.take_values();
+        if self.frames.is_empty() {
+            self.buffer.clear();
+            self.scanned = 0;
+            self.started = false;
+        }
+        taken
+    }
+
+    /// Follows the byte at `idx` of the buffer.
+    fn scan(&mut self, idx: usize) {
+        let byte = self.buffer[idx];
+        if self.in_string {
+            if self.escaped {
+                self.escaped = false;
+            } else if byte == b'\\' {
+                self.escaped = true;
+            } else if byte == b'"' {
+                self.in_string = false;
+                if self.depth == 0 {
+                    self.end_value(idx + 1);
+                }
+            }
+            return;
+        }
+        if self.in_scalar {
+            if !ends_scalar(byte) {
+                return;
+            }
+            self.in_scalar = false;
+            self.end_value(idx);
+        }
+        if self.value.is_none() {
+            if byte.is_ascii_whitespace() {
+                return;
+            }
+            self.value = Some(idx);
+        }
+        match byte {
+            b'"' => self.in_string = true,
+            b'[' | b'{' => self.depth += 1,
+            b']' | b'}' => {
+                // A closing bracket without an opening one is a value of
+                // its own, which fails to parse.
+                self.depth = self.depth.saturating_sub(1);
+                if self.depth == 0 {
+                    self.end_value(idx + 1);
+                }
+            }
+            _ if self.depth == 0 => self.in_scalar = true,
+            _ => {}
+        }
+    }
+
+    fn end_value(&mut self, end: usize) {
+        if let Some(start) = self.value.take() {
+            self.frames.push_back(start..end);
+        }
+    }
+
+    /// Parses the complete values, then lets go of their bytes.
+    fn take_values(&mut self) -> Result<Feed, Box<dyn core::error::Error>> {
+        let mut values = Vec::new();
+        while let Some(frame) = self.frames.front() {
+            let parsed = decode(&self.buffer[frame.clone()])
+                .and_then(|text| parse_with_options(&text, &self.options));
+            match parsed {
+                Ok(value) => values.push(value),
+                // Raised on the next call, after these values.
+                Err(_) if !values.is_empty() => break,
+                Err(error) => {
+                    self.frames.pop_front();
+                    self.compact();
+                    return Err(error);
+                }
+            }
+            self.frames.pop_front();
+        }
+        self.compact();
+        Ok(match values.is_empty() {
+            true => Feed::NeedMoreData,
+            false => Feed::Values(values),
+        })
+    }
+
+    /// Drops the bytes before the first value still needed.
+    fn compact(&mut self) {
+        let keep = self
+            .frames
+            .front()
+            .map(|frame| frame.start)
+            .or(self.value)
+            .unwrap_or(self.scanned);
+        self.buffer.drain(..keep);
+        self.scanned -= keep;
+        self.value = self.value.map(|start| start - keep);
+        for frame in &mut self.frames {
+            *frame = frame.start - keep..frame.end - keep;
+        }
+    }
+}
@@ -0,0 +1,175 @@
+//! Structural indexes of JSON text, the first stage of
+//! [`Dom::parse_indexed`](super::dom::Dom::parse_indexed).
+//!
+//! The text is classified 64 bytes at a time into bit masks, as simdjson
+//! does: escaped characters and the inside of strings are found with
+//! carries and prefix sums over the masks instead of a branch per byte,
+//! leaving the offset of every token to the second stage, which no longer
+//! looks at whitespace or at the bytes inside strings it skips.
+
+/// Class of each byte, one bit each: brackets, `:` and `,`, whitespace,
+/// `"`, and `\`.
+const CLASSES: [u8; 256] = {
+    let mut classes = [0; 256];
+    let mut idx = 0;
+    while idx < 6 {
+        classes[b"{}[]:,"[idx] as usize] = 1;
+        idx += 1;
+    }
+    classes[b' ' as usize] = 2;
+    classes[b'\t' as usize] = 2;
+    classes[b'\n' as usize] = 2;
+    classes[b'\r' as usize] = 2;
+    classes[0x0c] = 2;
+    classes[b'"' as usize] = 4;
+    classes[b'\\' as usize] = 8;
+    classes
+};
+
+/// Masks of the operators, whitespace, quotes and backslashes of up to 64
+/// bytes, one byte at a time.
+fn classify_bytes(bytes: &[u8]) -> [u64; 4] {
+    let mut masks = [0; 4];
+    for (idx, byte) in bytes.iter().enumerate() {
+        let class = CLASSES[*byte as usize] as u64;
+        for (kind, mask) in masks.iter_mut().enumerate() {
+            *mask |= (class >> kind & 1) << idx;
+        }
+    }
+    masks
+}
+
+/// Masks of the operators, whitespace, quotes and backslashes of a block,
+/// 16 bytes at a time.
+#[cfg(target_arch = "x86_64")]
+fn classify(block: &[u8; 64]) -> [u64; 4] {
+    use core::arch::x86_64::*;
+
+    let mut masks = [0; 4];
+    for (part, bytes) in block.chunks_exact(16).enumerate() {
+        // SSE2 is part of every x86_64 target, and the load is unaligned.
+        let bytes = unsafe { _mm_loadu_si128(bytes.as_ptr().cast()) };
+        let eq = |bytes, byte: u8| unsafe {
+            let equal = _mm_cmpeq_epi8(bytes, _mm_set1_epi8(byte as i8));
+            _mm_movemask_epi8(equal) as u16 as u64
+        };
+        // `[` and `]` are `{` and `}` without the 0x20 bit.
+        let folded = unsafe { _mm_or_si128(bytes, _mm_set1_epi8(0x20)) };
+        let parts = [
+            eq(folded, b'{')
+                | eq(folded, b'}')
+                | eq(bytes, b':')
+                | eq(bytes, b','),
+            eq(bytes, b' ')
+                | eq(bytes, b'\t')
+                | eq(bytes, b'\n')
+                | eq(bytes, b'\r')
+                | eq(bytes, 0x0c),
+            eq(bytes, b'"'),
+            eq(bytes, b'\\'),
+        ];
+        for (mask, bits) in masks.iter_mut().zip(parts) {
+            *mask |= bits << (part * 16);
+        }
+    }
+    masks
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn classify(block: &[u8; 64]) -> [u64; 4] {
+    classify_bytes(block)
+}
+
+/// Bits at even positions.
+const EVEN: u64 = 0x5555_5555_5555_5555;
+
+/// Each bit set to the parity of the bits up to it in `mask`.
+fn prefix_xor(mut mask: u64) -> u64 {
+    for shift in [1, 2, 4, 8, 16, 32] {
+        mask ^= mask << shift;
+    }
+    mask
+}
+
+/// The characters escaped by the backslashes of `backslash`, carrying
+/// whether the first one of the next block is escaped in `carry`.
+fn escaped(backslash: u64, carry: &mut u64) -> u64 {
+    let backslash = backslash & !*carry;
+    let follows_escape = backslash << 1 | *carry;
+    // Runs of backslashes starting at an odd bit, moved past their end.
+    let odd_starts = backslash & !EVEN & !follows_escape;
+    let (even_starts, overflow) = odd_starts.overflowing_add(backslash);
+    *carry = overflow as u64;
+    (EVEN ^ (even_starts << 1)) & follows_escape
+}
+
+/// The offsets where the tokens of a text start: brackets, `:`, `,`,
+/// opening quotes of strings and first bytes of other scalars, in order.
+///
+/// Building an index for each text reuses the memory of the previous one.
+///
+/// ```
+/// use json_parser::index::StructuralIndex;
+///
+/// let mut index = StructuralIndex::new();
+/// index.build(r#"{"a\"]": [1, true]}"#);
+/// assert_eq!(index.offsets(), [0, 1, 7, 9, 10, 11, 13, 17, 18]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct StructuralIndex {
+    offsets: Vec<usize>,
+}
+
+impl StructuralIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// An index with room for `tokens` offsets.
+    pub fn with_capacity(tokens: usize) -> Self {
+        Self {
+            offsets: Vec::with_capacity(tokens),
+        }
+    }
+
+    /// Indexes `text`, replacing the previous offsets.
+    ///
+    /// Nothing is checked here: a string left open runs to the end of the
+    /// text, and the second stage reports it.
+    pub fn build(&mut self, text: &str) {
+        self.offsets.clear();
+        let (mut escape, mut in_string, mut in_scalar) = (0, 0, 0);
+        for (block, bytes) in text.as_bytes().chunks(64).enumerate() {
+            let [operator, space, mut quote, backslash] = match bytes.try_into()
+            {
+                Ok(block) => classify(block),
+                Err(_) => classify_bytes(bytes),
+            };
+            let valid = match bytes.len() {
+                64 => u64::MAX,
+                len => (1 << len) - 1,
+            };
+
+            quote &= !escaped(backslash, &mut escape);
+            // Set from each opening quote up to its closing one, excluded.
+            let string = prefix_xor(quote) ^ in_string;
+            in_string = ((string as i64) >> 63) as u64;
+            let scalar = !(operator | space | quote | string) & valid;
+            let follows_scalar = scalar << 1 | in_scalar;
+            in_scalar = scalar >> 63;
+
+            let mut starts = (operator & !string)
+                | (quote & string)
+                | (scalar & !follows_scalar);
+            while starts != 0 {
+                self.offsets
+                    .push(block * 64 + starts.trailing_zeros() as usize);
+                starts &= starts - 1;
+            }
+        }
+    }
+
+    pub fn offsets(&self) -> &[usize] {
+        &self.offsets
+    }
+}
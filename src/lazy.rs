@@ -0,0 +1,129 @@
+//! Documents parsed on demand, for workloads reading a small part of
+//! large documents.
+//!
+//! A [`LazyDocument`] checks the syntax of its text once, without building
+//! anything. Values are then found by JSON Pointer, skipping over the
+//! subtrees on the way, and only the values asked for are parsed.
+use super::container::Container;
+use super::dialect::Json;
+use super::parser::{self, parse_str, NEST_LIMIT};
+use super::pointer;
+use super::tokens::{inner_token, unescape, Token, Tokens};
+use core::ops::Range;
+use std::collections::HashMap;
+
+/// Byte range of the value at `path` in the valid document `text`.
+fn locate(
+    text: &str,
+    path: &[String],
+) -> Result<Option<Range<usize>>, Box<dyn core::error::Error>> {
+    let mut tokens = Tokens::new(text, &Json);
+    let mut token = inner_token(&mut tokens)?;
+    for wanted in path {
+        let found = match token {
+            Token::BeginObject => {
+                // A key written twice keeps its last value, as when
+                // parsing: read on from where that value starts.
+                let mut last = None;
+                while let Token::Key(key) = inner_token(&mut tokens)? {
+                    let value = inner_token(&mut tokens)?;
+                    if unescape(&key[1..key.len() - 1]).eq(wanted.chars()) {
+                        last = Some(tokens.value_start(value));
+                    }
+                    tokens.finish_value(value)?;
+                }
+                match last {
+                    Some(start) => {
+                        tokens =
+                            Tokens::nested(text, start, &Json, 0, NEST_LIMIT);
+                        Some(inner_token(&mut tokens)?)
+                    }
+                    None => None,
+                }
+            }
+            Token::BeginArray => {
+                let wanted = pointer::array_index(wanted);
+                let mut idx = 0;
+                loop {
+                    let element = inner_token(&mut tokens)?;
+                    if element == Token::EndArray {
+                        break None;
+                    }
+                    if wanted == Some(idx) {
+                        break Some(element);
+                    }
+                    tokens.finish_value(element)?;
+                    idx += 1;
+                }
+            }
+            _ => None,
+        };
+        match found {
+            Some(found) => token = found,
+            None => return Ok(None),
+        }
+    }
+    let start = tokens.value_start(token);
+    Ok(Some(start..tokens.finish_value(token)?))
+}
+
+/// A JSON document kept as text, whose values are parsed when first
+/// asked for and then kept.
+///
+/// A key written twice is found at its last occurrence, as when parsing.
+///
+/// ```
+/// use json_parser::lazy::LazyDocument;
+///
+/// let text = r#"{"meta": {"id": 7}, "rows": [[1, 2], [3, 4]]}"#;
+/// let mut doc = LazyDocument::new(text).unwrap();
+/// assert_eq!(doc.raw("/rows/1"), Some("[3, 4]"));
+/// assert_eq!(doc.get("/meta/id").unwrap().get_uint(), Some(7));
+/// assert!(doc.get("/rows/2").is_none());
+/// assert!(LazyDocument::new(r#"{"rows": [}"#).is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct LazyDocument<'a> {
+    text: &'a str,
+    values: HashMap<String, Option<Container>>,
+}
+
+impl<'a> LazyDocument<'a> {
+    /// Checks that `text` is one JSON document, without building it.
+    pub fn new(text: &'a str) -> Result<Self, Box<dyn core::error::Error>> {
+        parser::validate(text)?;
+        Ok(Self {
+            text,
+            values: HashMap::new(),
+        })
+    }
+
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+
+    /// Byte range of the value at the JSON Pointer `path` in the text.
+    pub fn span(&self, path: &str) -> Option<Range<usize>> {
+        // The text is valid, so only a missing value is left to fail.
+        locate(self.text, &pointer::split(path)?).ok().flatten()
+    }
+
+    /// Text of the value at the JSON Pointer `path`, as written.
+    pub fn raw(&self, path: &str) -> Option<&'a str> {
+        self.span(path).map(|span| &self.text[span])
+    }
+
+    /// The value at the JSON Pointer `path`, parsed on the first call.
+    pub fn get(&mut self, path: &str) -> Option<&Container> {
+        if !self.values.contains_key(path) {
+            let value = self.raw(path).and_then(|raw| parse_str(raw).ok());
+            self.values.insert(path.to_owned(), value);
+        }
+        self.values[path].as_ref()
+    }
+
+    /// Parses the whole document.
+    pub fn to_container(&self) -> Container {
+        parse_str(self.text).unwrap_or(Container::Null)
+    }
+}
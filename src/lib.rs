@@ -1,6 +1,57 @@
 // #![no_std]
+pub mod analyze;
+pub mod arc_container;
+#[cfg(feature = "arena")]
+pub mod arena;
+#[cfg(feature = "async")]
+pub mod asynchronous;
+pub mod builder;
+pub mod cache;
 pub mod container;
+pub mod dialect;
+pub mod diff;
+pub mod dom;
+mod dump;
+pub mod encoding;
+pub mod encrypt;
 pub mod error;
+pub mod explain;
+pub mod extension;
+pub mod extract;
+#[cfg(feature = "fixtures")]
+pub mod fixtures;
+pub mod generate;
+pub mod incremental;
+pub mod index;
+pub mod lazy;
+pub mod lint;
+mod macros;
+pub mod map;
+pub mod mapping;
+pub mod memory;
+pub mod merge;
+pub mod net;
 pub mod parser;
+pub mod patch;
+pub mod pipeline;
+mod pointer;
+pub mod preview;
+#[cfg(feature = "regex")]
+mod regex;
+pub mod repair;
+pub mod script;
+pub mod search;
+pub mod semver;
+pub mod serializer;
+pub mod small_string;
+pub mod spanned;
+pub mod spill;
+pub mod split;
+pub mod static_container;
 #[cfg(test)]
 mod test;
+mod tokens;
+pub mod units;
+#[cfg(feature = "uuid")]
+mod uuid;
+pub mod watch;
@@ -0,0 +1,558 @@
+//! Configurable checks of JSON text, e.g. for a pre-commit hook or a CI
+//! gate.
+//!
+//! Each rule produces [`Finding`]s locating the offending part of the
+//! input. The input is scanned without building the document.
+//!
+//! Single quotes, trailing commas and raw control characters in strings
+//! are accepted and reported, with [`Edit`]s that [`apply_fixes`] uses
+//! to turn the input into standard JSON.
+use super::analyze::DuplicateScan;
+use super::container::Container;
+use super::dialect::Dialect;
+use super::parser::{self, Observer, Span};
+
+/// Largest integer that an IEEE 754 double (a JavaScript number) holds
+/// exactly.
+const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+/// The checks performed by [`lint`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rule {
+    /// A key written more than once in the same object
+    DuplicateKey,
+    /// Keys of one object written in different styles, e.g. `snake_case`
+    /// and `camelCase`
+    KeyCasing,
+    /// An array holding values of different types (`null` aside)
+    MixedArrayTypes,
+    /// A container nested deeper than [`LintConfig::max_depth`]
+    DeepNesting,
+    /// An integer that JavaScript cannot represent exactly
+    UnsafeInteger,
+    /// A `,` after the last element of an array or object
+    TrailingComma,
+    /// A string or key quoted with `'`
+    SingleQuotes,
+    /// A control character written as is in a string
+    ControlCharacter,
+}
+
+impl Rule {
+    /// Every rule, in declaration order.
+    pub const ALL: [Rule; 8] = [
+        Rule::DuplicateKey,
+        Rule::KeyCasing,
+        Rule::MixedArrayTypes,
+        Rule::DeepNesting,
+        Rule::UnsafeInteger,
+        Rule::TrailingComma,
+        Rule::SingleQuotes,
+        Rule::ControlCharacter,
+    ];
+
+    /// A stable kebab-case name, e.g. for configuration files.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rule::DuplicateKey => "duplicate-key",
+            Rule::KeyCasing => "key-casing",
+            Rule::MixedArrayTypes => "mixed-array-types",
+            Rule::DeepNesting => "deep-nesting",
+            Rule::UnsafeInteger => "unsafe-integer",
+            Rule::TrailingComma => "trailing-comma",
+            Rule::SingleQuotes => "single-quotes",
+            Rule::ControlCharacter => "control-character",
+        }
+    }
+}
+
+/// Which rules [`lint`] runs, and their parameters.
+#[derive(Debug, Clone)]
+pub struct LintConfig {
+    pub rules: Vec<Rule>,
+    /// Deepest nesting accepted by [`Rule::DeepNesting`]
+    pub max_depth: usize,
+}
+
+impl Default for LintConfig {
+    /// Every rule, with a nesting limit of 32.
+    fn default() -> Self {
+        Self {
+            rules: Rule::ALL.to_vec(),
+            max_depth: 32,
+        }
+    }
+}
+
+/// Replacement of the bytes `start..end` of the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    pub start: usize,
+    pub end: usize,
+    pub replacement: String,
+}
+
+/// A problem found by a rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub rule: Rule,
+    /// JSON Pointer of the offending value or key
+    pub path: String,
+    pub span: Span,
+    pub message: String,
+    /// Edits fixing the problem, empty if it needs a human decision
+    pub fix: Vec<Edit>,
+}
+
+impl core::fmt::Display for Finding {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str(
+            format!(
+                "{}:{}: {} ({})",
+                self.span.line,
+                self.span.col,
+                self.message,
+                self.rule.name()
+            )
+            .as_str(),
+        )
+    }
+}
+
+/// Naming conventions told apart by [`Rule::KeyCasing`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Casing {
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    Camel,
+    Pascal,
+}
+
+impl Casing {
+    /// The convention of `key`, or `None` if it fits any of them (e.g. a
+    /// single lowercase word) or none at all.
+    fn of(key: &str) -> Option<Self> {
+        let has_lower = key.chars().any(char::is_lowercase);
+        let has_upper = key.chars().any(char::is_uppercase);
+        match (key.contains('_'), key.contains('-')) {
+            (true, false) if !has_upper => Some(Self::Snake),
+            (true, false) if !has_lower => Some(Self::ScreamingSnake),
+            (false, true) if !has_upper => Some(Self::Kebab),
+            (false, false) if has_lower && has_upper => {
+                if key.starts_with(char::is_uppercase) {
+                    Some(Self::Pascal)
+                } else {
+                    Some(Self::Camel)
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Snake => "snake_case",
+            Self::ScreamingSnake => "SCREAMING_SNAKE_CASE",
+            Self::Kebab => "kebab-case",
+            Self::Camel => "camelCase",
+            Self::Pascal => "PascalCase",
+        }
+    }
+}
+
+/// JSON type of a value, as compared by [`Rule::MixedArrayTypes`].
+pub(crate) fn type_name(value: &Container) -> &'static str {
+    match value {
+        Container::Null => "null",
+        Container::Boolean(_) => "boolean",
+        Container::Number(_)
+        | Container::Unsigned(_)
+        | Container::Number128(_)
+        | Container::Unsigned128(_)
+        | Container::Decimal(_)
+        | Container::RawNumber(_)
+        | Container::BigNumber(_) => "number",
+        Container::String(_) => "string",
+        #[cfg(feature = "uuid")]
+        Container::Uuid(_) => "string",
+        Container::Array(_) => "array",
+        Container::Object(_) => "object",
+    }
+}
+
+/// The syntax accepted by [`lint`]: JSON with single-quoted strings and
+/// trailing commas.
+struct Tolerant;
+
+impl Dialect for Tolerant {
+    fn quotes(&self) -> &[u8] {
+        b"\"'"
+    }
+
+    fn trailing_commas(&self) -> bool {
+        true
+    }
+}
+
+/// Escape sequence standing for the control character `byte`.
+fn escape_control(byte: u8) -> String {
+    match byte {
+        b'\n' => "\\n".to_owned(),
+        b'\r' => "\\r".to_owned(),
+        b'\t' => "\\t".to_owned(),
+        0x08 => "\\b".to_owned(),
+        0x0c => "\\f".to_owned(),
+        _ => format!("\\u{:04x}", byte),
+    }
+}
+
+/// The span of `len` bytes at `offset`, found after `from` in `input`.
+fn span_after(input: &str, from: &Span, offset: usize, len: usize) -> Span {
+    let between = &input[from.start..offset];
+    let col = match between.rfind('\n') {
+        Some(idx) => offset - (from.start + idx + 1) + 1,
+        None => from.col + offset - from.start,
+    };
+    Span {
+        start: offset,
+        end: offset + len,
+        line: from.line + between.matches('\n').count(),
+        col,
+        path: None,
+    }
+}
+
+/// State of an open container.
+struct Scope {
+    open: Span,
+    object: bool,
+    /// Type of the first non-null element, and of another one differing
+    /// from it
+    types: (Option<&'static str>, Option<&'static str>),
+    /// Casing of the first key with one, for objects
+    casing: Option<Casing>,
+}
+
+struct Linter<'a> {
+    input: &'a str,
+    config: &'a LintConfig,
+    scan: DuplicateScan,
+    scopes: Vec<Scope>,
+    findings: Vec<Finding>,
+}
+
+impl Linter<'_> {
+    fn enabled(&self, rule: Rule) -> bool {
+        self.config.rules.contains(&rule)
+    }
+
+    fn report(
+        &mut self,
+        rule: Rule,
+        path: String,
+        span: Span,
+        message: String,
+    ) {
+        self.report_fix(rule, path, span, message, Vec::new());
+    }
+
+    fn report_fix(
+        &mut self,
+        rule: Rule,
+        path: String,
+        span: Span,
+        message: String,
+        fix: Vec<Edit>,
+    ) {
+        self.findings.push(Finding {
+            rule,
+            path,
+            span,
+            message,
+            fix,
+        });
+    }
+
+    /// Checks the text of a string or key, quotes included.
+    fn quoted(&mut self, span: &Span) {
+        let raw = &self.input.as_bytes()[span.start..span.end];
+        let single = raw[0] == b'\'';
+
+        let mut fix = Vec::new();
+        let mut idx = 1;
+        while idx + 1 < raw.len() {
+            let offset = span.start + idx;
+            match raw[idx] {
+                b'\\' if single && raw[idx + 1] == b'\'' => fix.push(Edit {
+                    start: offset,
+                    end: offset + 2,
+                    replacement: "'".to_owned(),
+                }),
+                b'"' if single => fix.push(Edit {
+                    start: offset,
+                    end: offset + 1,
+                    replacement: "\\\"".to_owned(),
+                }),
+                byte if byte < 0x20 && self.enabled(Rule::ControlCharacter) => {
+                    let message =
+                        format!("control character U+{:04X} in a string", byte);
+                    let edit = Edit {
+                        start: offset,
+                        end: offset + 1,
+                        replacement: escape_control(byte),
+                    };
+                    let at = span_after(self.input, span, offset, 1);
+                    let path = self.scan.path.pointer();
+                    self.report_fix(
+                        Rule::ControlCharacter,
+                        path,
+                        at,
+                        message,
+                        vec![edit],
+                    );
+                }
+                _ => {}
+            }
+            // Escaped bytes are never special.
+            idx += if raw[idx] == b'\\' { 2 } else { 1 };
+        }
+
+        if single && self.enabled(Rule::SingleQuotes) {
+            for offset in [span.start, span.end - 1] {
+                fix.push(Edit {
+                    start: offset,
+                    end: offset + 1,
+                    replacement: "\"".to_owned(),
+                });
+            }
+            fix.sort_by_key(|edit| edit.start);
+            let message = "string quoted with ' instead of \"".to_owned();
+            let path = self.scan.path.pointer();
+            self.report_fix(
+                Rule::SingleQuotes,
+                path,
+                span.clone(),
+                message,
+                fix,
+            );
+        }
+    }
+
+    /// Records the type of an element of the innermost array.
+    fn element(&mut self, kind: &'static str) {
+        if kind == "null" {
+            return;
+        }
+        if let Some(scope) =
+            self.scopes.last_mut().filter(|scope| !scope.object)
+        {
+            match scope.types {
+                (None, _) => scope.types.0 = Some(kind),
+                (Some(first), None) if first != kind => {
+                    scope.types.1 = Some(kind)
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Observer for Linter<'_> {
+    fn open(&mut self, object: bool, span: Span) {
+        self.element(if object { "object" } else { "array" });
+        self.scan.open(object, span.clone());
+        self.scopes.push(Scope {
+            open: span,
+            object,
+            types: (None, None),
+            casing: None,
+        });
+    }
+
+    fn close(&mut self, span: Span) {
+        let path = self.scan.path.container_pointer();
+        let depth = self.scan.path.depth();
+        if let Some(scope) = self.scopes.pop() {
+            let whole = Span {
+                end: span.end,
+                ..scope.open
+            };
+            if let (Some(first), Some(other)) = scope.types {
+                if self.enabled(Rule::MixedArrayTypes) {
+                    let message =
+                        format!("array mixes {} and {} elements", first, other);
+                    self.report(
+                        Rule::MixedArrayTypes,
+                        path.clone(),
+                        whole.clone(),
+                        message,
+                    );
+                }
+            }
+            if depth == self.config.max_depth + 1
+                && self.enabled(Rule::DeepNesting)
+            {
+                let message = format!(
+                    "nested {} levels deep, more than {}",
+                    depth, self.config.max_depth
+                );
+                self.report(Rule::DeepNesting, path, whole, message);
+            }
+        }
+        self.scan.close(span);
+    }
+
+    fn key(&mut self, key: &str, span: Span) {
+        self.scan.key(key, span.clone());
+        self.quoted(&span);
+        let Some(casing) = Casing::of(key) else {
+            return;
+        };
+        let Some(scope) = self.scopes.last_mut() else {
+            return;
+        };
+        let expected = *scope.casing.get_or_insert(casing);
+        if expected != casing && self.enabled(Rule::KeyCasing) {
+            let message = format!(
+                "key {:?} is {}, earlier keys of this object are {}",
+                key,
+                casing.name(),
+                expected.name()
+            );
+            let path = self.scan.path.pointer();
+            self.report(Rule::KeyCasing, path, span, message);
+        }
+    }
+
+    fn scalar(&mut self, value: &Container, span: Span) {
+        self.element(type_name(value));
+        if value.is_str() {
+            self.quoted(&span);
+        }
+        let unsafe_integer = match value {
+            Container::Number(value) => value.unsigned_abs() > MAX_SAFE_INTEGER,
+            Container::Unsigned(value) => *value > MAX_SAFE_INTEGER,
+            Container::Number128(_)
+            | Container::Unsigned128(_)
+            | Container::BigNumber(_) => true,
+            _ => false,
+        };
+        if unsafe_integer && self.enabled(Rule::UnsafeInteger) {
+            let message = format!(
+                "integer {} cannot be represented exactly in JavaScript",
+                value
+            );
+            let path = self.scan.path.pointer();
+            self.report(Rule::UnsafeInteger, path, span.clone(), message);
+        }
+        self.scan.scalar(value, span);
+    }
+
+    fn trailing_comma(&mut self, span: Span) {
+        if self.enabled(Rule::TrailingComma) {
+            let message = "trailing comma".to_owned();
+            let edit = Edit {
+                start: span.start,
+                end: span.end,
+                replacement: String::new(),
+            };
+            let path = self.scan.path.container_pointer();
+            self.report_fix(
+                Rule::TrailingComma,
+                path,
+                span,
+                message,
+                vec![edit],
+            );
+        }
+    }
+}
+
+/// Runs the rules of `config` over `input`, returning the findings in
+/// input order. Syntax errors, other than those reported by rules, fail
+/// instead.
+///
+/// ```
+/// use json_parser::lint::{lint, LintConfig, Rule};
+///
+/// let input = r#"{"user_id": 9007199254740993, "userName": "ada"}"#;
+/// let findings = lint(input, &LintConfig::default()).unwrap();
+/// let rules: Vec<Rule> = findings.iter().map(|finding| finding.rule).collect();
+/// assert_eq!(rules, [Rule::UnsafeInteger, Rule::KeyCasing]);
+/// assert_eq!(findings[1].path, "/userName");
+/// ```
+pub fn lint(
+    input: &str,
+    config: &LintConfig,
+) -> Result<Vec<Finding>, Box<dyn core::error::Error>> {
+    let mut linter = Linter {
+        input,
+        config,
+        scan: DuplicateScan::default(),
+        scopes: Vec::new(),
+        findings: Vec::new(),
+    };
+    parser::observe(input, &Tolerant, &mut linter)?;
+
+    let mut findings = linter.findings;
+    if config.rules.contains(&Rule::DuplicateKey) {
+        for duplicate in linter.scan.duplicates {
+            let path = format!(
+                "{}/{}",
+                duplicate.path,
+                super::pointer::escape(&duplicate.key)
+            );
+            for span in &duplicate.spans[1..] {
+                findings.push(Finding {
+                    rule: Rule::DuplicateKey,
+                    path: path.clone(),
+                    span: span.clone(),
+                    message: format!(
+                        "key {:?} already appears at line {}, col {}",
+                        duplicate.key,
+                        duplicate.spans[0].line,
+                        duplicate.spans[0].col
+                    ),
+                    fix: Vec::new(),
+                });
+            }
+        }
+    }
+    findings.sort_by_key(|finding| finding.span.start);
+    Ok(findings)
+}
+
+/// Applies the fixes of `findings` to `input`. An edit overlapping one
+/// that starts earlier is skipped, so linting the result again may find
+/// more to fix.
+///
+/// ```
+/// use json_parser::lint::{apply_fixes, lint, LintConfig};
+///
+/// let input = "{'name': 'ada', \"tags\": [1, 2,],}";
+/// let findings = lint(input, &LintConfig::default()).unwrap();
+/// assert_eq!(findings.len(), 4);
+/// assert_eq!(
+///     apply_fixes(input, &findings),
+///     r#"{"name": "ada", "tags": [1, 2]}"#
+/// );
+/// ```
+pub fn apply_fixes(input: &str, findings: &[Finding]) -> String {
+    let mut edits: Vec<&Edit> =
+        findings.iter().flat_map(|finding| &finding.fix).collect();
+    edits.sort_by_key(|edit| (edit.start, edit.end));
+
+    let mut out = String::with_capacity(input.len());
+    let mut copied = 0;
+    for edit in edits {
+        if edit.start < copied {
+            continue;
+        }
+        out.push_str(&input[copied..edit.start]);
+        out.push_str(&edit.replacement);
+        copied = edit.end;
+    }
+    out.push_str(&input[copied..]);
+    out
+}
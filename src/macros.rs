@@ -0,0 +1,99 @@
+/// Builds a `Container` from JSON-like syntax.
+///
+/// Values can be `null`, nested arrays and objects, or any expression
+/// convertible into a `Container` (numbers, booleans, strings, other
+/// containers). Object keys are string literals.
+///
+/// ```
+/// use json_parser::json;
+/// use json_parser::parser::parse_str;
+///
+/// let name = "parser";
+/// let value = json!({
+///     "name": name,
+///     "tags": ["json", 1, -2, 3.5, true, null],
+///     "nested": {"empty": [], "object": {}},
+/// });
+///
+/// let parsed = parse_str(
+///     r#"{
+///         "name": "parser",
+///         "tags": ["json", 1, -2, 3.5, true, null],
+///         "nested": {"empty": [], "object": {}}
+///     }"#,
+/// )
+/// .unwrap();
+/// assert_eq!(value, parsed);
+/// ```
+#[macro_export]
+macro_rules! json {
+    // Array elements: munch one element at a time into `$elems`.
+    (@array [$($elems:expr,)*]) => {
+        ::std::vec![$($elems,)*]
+    };
+    (@array [$($elems:expr,)*] , $($rest:tt)*) => {
+        $crate::json!(@array [$($elems,)*] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] null $($rest:tt)*) => {
+        $crate::json!(@array [$($elems,)* $crate::json!(null),] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] [$($array:tt)*] $($rest:tt)*) => {
+        $crate::json!(
+            @array [$($elems,)* $crate::json!([$($array)*]),] $($rest)*
+        )
+    };
+    (@array [$($elems:expr,)*] {$($map:tt)*} $($rest:tt)*) => {
+        $crate::json!(
+            @array [$($elems,)* $crate::json!({$($map)*}),] $($rest)*
+        )
+    };
+    (@array [$($elems:expr,)*] $next:expr , $($rest:tt)*) => {
+        $crate::json!(@array [$($elems,)* $crate::json!($next),] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] $last:expr) => {
+        $crate::json!(@array [$($elems,)* $crate::json!($last),])
+    };
+
+    // Object members: insert one `"key": value` pair at a time.
+    (@object $object:ident ()) => {};
+    (@object $object:ident (, $($rest:tt)*)) => {
+        $crate::json!(@object $object ($($rest)*));
+    };
+    (@object $object:ident ($key:literal : null $($rest:tt)*)) => {
+        $object.insert_str($key, $crate::json!(null));
+        $crate::json!(@object $object ($($rest)*));
+    };
+    (@object $object:ident ($key:literal : [$($array:tt)*] $($rest:tt)*)) => {
+        $object.insert_str($key, $crate::json!([$($array)*]));
+        $crate::json!(@object $object ($($rest)*));
+    };
+    (@object $object:ident ($key:literal : {$($map:tt)*} $($rest:tt)*)) => {
+        $object.insert_str($key, $crate::json!({$($map)*}));
+        $crate::json!(@object $object ($($rest)*));
+    };
+    (@object $object:ident ($key:literal : $value:expr , $($rest:tt)*)) => {
+        $object.insert_str($key, $crate::json!($value));
+        $crate::json!(@object $object ($($rest)*));
+    };
+    (@object $object:ident ($key:literal : $value:expr)) => {
+        $object.insert_str($key, $crate::json!($value));
+    };
+
+    (null) => {
+        $crate::container::Container::Null
+    };
+    ([$($tt:tt)*]) => {
+        $crate::container::Container::Array($crate::json!(@array [] $($tt)*))
+    };
+    ({}) => {
+        $crate::container::Container::new_object()
+    };
+    ({$($tt:tt)*}) => {{
+        let mut object = $crate::container::Container::new_object();
+        $crate::json!(@object object ($($tt)*));
+        object
+    }};
+    ($other:expr) => {
+        $crate::container::Container::from($other)
+    };
+}
@@ -0,0 +1,477 @@
+//! The map of object members.
+//!
+//! Members are kept in insertion order, which for parsed documents is the
+//! order of the input (or sorted by key, see
+//! [`ObjectOrder`](super::parser::ObjectOrder)). Dumping, diffing and
+//! every other walk over an object follow that order, so the output of
+//! the crate does not depend on hashing. The `preserve_order` feature,
+//! which other JSON crates need for this, changes nothing here.
+//!
+//! Keys are shared strings: a key is allocated once for the entry and
+//! the index of its map, and the parser hands out one allocation for all
+//! the occurrences of a key in a document.
+//!
+//! Small objects, the most common, have no index: their keys are found
+//! by comparing them in turn, which is faster for a few keys and keeps
+//! all the members in one allocation.
+use super::container::Container;
+use core::ops::{Index, IndexMut};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// The key of an object member, shared by every map holding it.
+pub type Key = Arc<str>;
+
+/// Members of a map found without an index.
+pub(crate) const SMALL_LEN: usize = 8;
+
+/// An insertion-ordered map from keys to values.
+///
+/// Replacing the value of a key keeps its position, and removing a key
+/// keeps the order of the others.
+///
+/// ```
+/// use json_parser::map::Map;
+///
+/// let mut map = Map::new();
+/// map.insert("b".to_owned(), 1.into());
+/// map.insert("a".to_owned(), 2.into());
+/// map.insert("b".to_owned(), 3.into());
+/// let keys: Vec<&str> = map.keys().collect();
+/// assert_eq!(keys, ["b", "a"]);
+/// assert_eq!(map["b"].get_uint(), Some(3));
+/// ```
+#[derive(Clone, Default)]
+pub struct Map {
+    entries: Vec<(Key, Container)>,
+    /// Position of each key in `entries`, for maps of more than
+    /// `SMALL_LEN` members only. Boxed to keep small maps, and so every
+    /// `Container`, narrow.
+    #[allow(clippy::box_collection)]
+    index: Option<Box<HashMap<Key, usize>>>,
+}
+
+impl Map {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+            index: None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.index = None;
+    }
+
+    fn position<Q>(&self, key: &Q) -> Option<usize>
+    where
+        Q: AsRef<str> + ?Sized,
+    {
+        let key = key.as_ref();
+        match &self.index {
+            Some(index) => index.get(key).copied(),
+            None => self.entries.iter().position(|(other, _)| &**other == key),
+        }
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&Container>
+    where
+        Q: AsRef<str> + ?Sized,
+    {
+        self.position(key).map(|idx| &self.entries[idx].1)
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut Container>
+    where
+        Q: AsRef<str> + ?Sized,
+    {
+        self.position(key).map(|idx| &mut self.entries[idx].1)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        Q: AsRef<str> + ?Sized,
+    {
+        self.position(key).is_some()
+    }
+
+    /// Inserts a member at the end, or replaces the value of an existing
+    /// key in place, returning the previous value.
+    pub fn insert(
+        &mut self,
+        key: impl Into<Key>,
+        value: Container,
+    ) -> Option<Container> {
+        let key = key.into();
+        match self.position(&*key) {
+            Some(idx) => {
+                Some(core::mem::replace(&mut self.entries[idx].1, value))
+            }
+            None => {
+                self.push(key, value);
+                None
+            }
+        }
+    }
+
+    /// Adds a member for a key that is not in the map, returning its
+    /// position.
+    fn push(&mut self, key: Key, value: Container) -> usize {
+        let idx = self.entries.len();
+        if let Some(index) = &mut self.index {
+            index.insert(key.clone(), idx);
+        }
+        self.entries.push((key, value));
+        if self.entries.len() == SMALL_LEN + 1 {
+            self.reindex();
+        }
+        idx
+    }
+
+    /// The member `key`, to insert or update it with a single lookup.
+    ///
+    /// ```
+    /// use json_parser::container::Container;
+    /// use json_parser::map::Map;
+    ///
+    /// let mut counts = Map::new();
+    /// for word in ["a", "b", "a"] {
+    ///     counts
+    ///         .entry(word)
+    ///         .and_modify(|count| *count = (count.get_uint().unwrap() + 1).into())
+    ///         .or_insert(1u64.into());
+    /// }
+    /// assert_eq!(counts["a"].get_uint(), Some(2));
+    /// assert_eq!(counts["b"].get_uint(), Some(1));
+    /// ```
+    pub fn entry(&mut self, key: impl Into<Key>) -> Entry<'_> {
+        let key = key.into();
+        match self.position(&*key) {
+            Some(idx) => Entry::Occupied(OccupiedEntry { map: self, idx }),
+            None => Entry::Vacant(VacantEntry { map: self, key }),
+        }
+    }
+
+    /// Removes a member, keeping the order of the others.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<Container>
+    where
+        Q: AsRef<str> + ?Sized,
+    {
+        let idx = self.position(key)?;
+        Some(self.remove_at(idx))
+    }
+
+    fn remove_at(&mut self, idx: usize) -> Container {
+        let (key, value) = self.entries.remove(idx);
+        if self.entries.len() <= SMALL_LEN {
+            self.index = None;
+        } else if let Some(index) = &mut self.index {
+            index.remove(&key);
+            for (_, position) in index.iter_mut() {
+                if *position > idx {
+                    *position -= 1;
+                }
+            }
+        }
+        value
+    }
+
+    /// Builds the index of a map past `SMALL_LEN` members.
+    fn reindex(&mut self) {
+        let mut index = HashMap::with_capacity(self.entries.capacity());
+        index.extend(
+            self.entries
+                .iter()
+                .enumerate()
+                .map(|(idx, (key, _))| (key.clone(), idx)),
+        );
+        self.index = Some(Box::new(index));
+    }
+
+    /// Orders the members by key.
+    pub fn sort_keys(&mut self) {
+        self.entries.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        if self.index.is_some() {
+            self.reindex();
+        }
+    }
+
+    /// Members with their shared keys.
+    pub(crate) fn entries(&self) -> &[(Key, Container)] {
+        &self.entries
+    }
+
+    /// Bytes allocated for the entries and the index, keys and values
+    /// aside. Each slot of the index also takes a control byte.
+    pub(crate) fn table_bytes(&self) -> usize {
+        self.entries.capacity() * size_of::<(Key, Container)>()
+            + self.index.as_ref().map_or(0, |index| {
+                size_of::<HashMap<Key, usize>>()
+                    + index.capacity() * (size_of::<(Key, usize)>() + 1)
+            })
+    }
+
+    pub fn iter(&self) -> Iter<'_> {
+        Iter(self.entries.iter())
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_> {
+        IterMut(self.entries.iter_mut())
+    }
+
+    pub fn keys(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = &str> + ExactSizeIterator {
+        self.entries.iter().map(|(key, _)| &**key)
+    }
+
+    pub fn values(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = &Container> + ExactSizeIterator {
+        self.entries.iter().map(|(_, value)| value)
+    }
+
+    pub fn values_mut(
+        &mut self,
+    ) -> impl DoubleEndedIterator<Item = &mut Container> + ExactSizeIterator
+    {
+        self.entries.iter_mut().map(|(_, value)| value)
+    }
+}
+
+/// A member of a [`Map`] that may be missing, from [`Map::entry`].
+pub enum Entry<'a> {
+    Occupied(OccupiedEntry<'a>),
+    Vacant(VacantEntry<'a>),
+}
+
+impl<'a> Entry<'a> {
+    pub fn key(&self) -> &str {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// The value of the member, inserting `default` if missing.
+    pub fn or_insert(self, default: Container) -> &'a mut Container {
+        self.or_insert_with(|| default)
+    }
+
+    /// The value of the member, inserting the result of `default` if
+    /// missing.
+    pub fn or_insert_with(
+        self,
+        default: impl FnOnce() -> Container,
+    ) -> &'a mut Container {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// The value of the member, inserting `Null` if missing.
+    pub fn or_default(self) -> &'a mut Container {
+        self.or_insert(Container::Null)
+    }
+
+    /// Changes the value of the member with `modify` if present.
+    pub fn and_modify(mut self, modify: impl FnOnce(&mut Container)) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            modify(entry.get_mut());
+        }
+        self
+    }
+}
+
+/// A member present in a [`Map`].
+pub struct OccupiedEntry<'a> {
+    map: &'a mut Map,
+    idx: usize,
+}
+
+impl<'a> OccupiedEntry<'a> {
+    pub fn key(&self) -> &str {
+        &self.map.entries[self.idx].0
+    }
+
+    pub fn get(&self) -> &Container {
+        &self.map.entries[self.idx].1
+    }
+
+    pub fn get_mut(&mut self) -> &mut Container {
+        &mut self.map.entries[self.idx].1
+    }
+
+    pub fn into_mut(self) -> &'a mut Container {
+        &mut self.map.entries[self.idx].1
+    }
+
+    /// Replaces the value, returning the previous one.
+    pub fn insert(&mut self, value: Container) -> Container {
+        core::mem::replace(self.get_mut(), value)
+    }
+
+    /// Removes the member, keeping the order of the others.
+    pub fn remove(self) -> Container {
+        self.map.remove_at(self.idx)
+    }
+}
+
+/// A member missing from a [`Map`].
+pub struct VacantEntry<'a> {
+    map: &'a mut Map,
+    key: Key,
+}
+
+impl<'a> VacantEntry<'a> {
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Adds the member at the end of the map.
+    pub fn insert(self, value: Container) -> &'a mut Container {
+        let idx = self.map.push(self.key, value);
+        &mut self.map.entries[idx].1
+    }
+}
+
+/// Iterator over the members of a [`Map`], in order.
+#[derive(Clone)]
+pub struct Iter<'a>(core::slice::Iter<'a, (Key, Container)>);
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a str, &'a Container);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(key, value)| (&**key, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for Iter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(key, value)| (&**key, value))
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {}
+
+/// Iterator over the members of a [`Map`], with mutable values.
+pub struct IterMut<'a>(core::slice::IterMut<'a, (Key, Container)>);
+
+impl<'a> Iterator for IterMut<'a> {
+    type Item = (&'a str, &'a mut Container);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(key, value)| (&**key, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for IterMut<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(key, value)| (&**key, value))
+    }
+}
+
+impl ExactSizeIterator for IterMut<'_> {}
+
+impl IntoIterator for Map {
+    type Item = (Key, Container);
+    type IntoIter = std::vec::IntoIter<(Key, Container)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Map {
+    type Item = (&'a str, &'a Container);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut Map {
+    type Item = (&'a str, &'a mut Container);
+    type IntoIter = IterMut<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K: Into<Key>> FromIterator<(K, Container)> for Map {
+    fn from_iter<I: IntoIterator<Item = (K, Container)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Into<Key>> Extend<(K, Container)> for Map {
+    fn extend<I: IntoIterator<Item = (K, Container)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<Q> Index<&Q> for Map
+where
+    Q: AsRef<str> + ?Sized,
+{
+    type Output = Container;
+
+    /// Panics if the key is missing, like `HashMap`.
+    fn index(&self, key: &Q) -> &Container {
+        self.get(key).expect("no member with this key")
+    }
+}
+
+impl<Q> IndexMut<&Q> for Map
+where
+    Q: AsRef<str> + ?Sized,
+{
+    fn index_mut(&mut self, key: &Q) -> &mut Container {
+        self.get_mut(key).expect("no member with this key")
+    }
+}
+
+impl PartialEq for Map {
+    /// Maps are equal when they hold the same members, in any order.
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+impl core::fmt::Debug for Map {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
@@ -0,0 +1,141 @@
+//! Conversion of documents between two shapes of the same data, such as
+//! the payloads of two versions of an API.
+//!
+//! A [`Mapping`] lists where each value of the source shape goes in the
+//! target shape, as pairs of JSON Pointers, and converts documents both
+//! ways with the same rules.
+use super::container::Container;
+use super::error::{Error, MappingError};
+use super::pointer;
+
+type Transform =
+    Box<dyn Fn(&Container) -> Result<Container, Box<dyn core::error::Error>>>;
+
+struct Rule {
+    source: String,
+    target: String,
+    forward: Option<Transform>,
+    backward: Option<Transform>,
+}
+
+/// Rules moving values between a source and a target shape.
+///
+/// Values missing from the converted document are skipped, and only the
+/// mapped values make it into the result, in the order of the rules.
+/// Containers missing on the way to a target are created: an array when
+/// the next token is an index or `-`, an object otherwise.
+///
+/// ```
+/// use json_parser::container::Container;
+/// use json_parser::mapping::Mapping;
+/// use json_parser::parser::parse_str;
+///
+/// let mut mapping = Mapping::new();
+/// mapping.field("/name", "/user/display_name").field_with(
+///     "/age",
+///     "/user/birth_year",
+///     |age| Ok(Container::from(2024 - age.get_uint().ok_or("no age")?)),
+///     |year| Ok(Container::from(2024 - year.get_uint().ok_or("no year")?)),
+/// );
+///
+/// let v1 = parse_str(r#"{"name": "Ada", "age": 36}"#).unwrap();
+/// let v2 = mapping.forward(&v1).unwrap();
+/// let user = &v2["user"];
+/// assert_eq!(user["display_name"], Container::from("Ada"));
+/// assert_eq!(user["birth_year"], Container::from(1988u64));
+/// assert_eq!(mapping.backward(&v2).unwrap(), v1);
+/// ```
+#[derive(Default)]
+pub struct Mapping {
+    rules: Vec<Rule>,
+}
+
+impl Mapping {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Moves the value at `source` to `target` unchanged.
+    pub fn field(&mut self, source: &str, target: &str) -> &mut Self {
+        self.rules.push(Rule {
+            source: source.to_owned(),
+            target: target.to_owned(),
+            forward: None,
+            backward: None,
+        });
+        self
+    }
+
+    /// Moves the value at `source` to `target` through `forward`, and back
+    /// through `backward`, which should undo it.
+    pub fn field_with<F, B>(
+        &mut self,
+        source: &str,
+        target: &str,
+        forward: F,
+        backward: B,
+    ) -> &mut Self
+    where
+        F: Fn(&Container) -> Result<Container, Box<dyn core::error::Error>>
+            + 'static,
+        B: Fn(&Container) -> Result<Container, Box<dyn core::error::Error>>
+            + 'static,
+    {
+        self.rules.push(Rule {
+            source: source.to_owned(),
+            target: target.to_owned(),
+            forward: Some(Box::new(forward)),
+            backward: Some(Box::new(backward)),
+        });
+        self
+    }
+
+    /// Converts a document of the source shape to the target shape.
+    pub fn forward(
+        &self,
+        document: &Container,
+    ) -> Result<Container, Box<dyn core::error::Error>> {
+        self.apply(document, false)
+    }
+
+    /// Converts a document of the target shape back to the source shape.
+    pub fn backward(
+        &self,
+        document: &Container,
+    ) -> Result<Container, Box<dyn core::error::Error>> {
+        self.apply(document, true)
+    }
+
+    fn apply(
+        &self,
+        document: &Container,
+        reverse: bool,
+    ) -> Result<Container, Box<dyn core::error::Error>> {
+        let mut converted = Container::new_object();
+        for rule in &self.rules {
+            let (from, to, transform) = if reverse {
+                (&rule.target, &rule.source, &rule.backward)
+            } else {
+                (&rule.source, &rule.target, &rule.forward)
+            };
+            let from_tokens = tokens(from)?;
+            let Some(value) = pointer::resolve(document, &from_tokens) else {
+                continue;
+            };
+            let value = match transform {
+                Some(transform) => transform(value)?,
+                None => value.clone(),
+            };
+            pointer::insert(&mut converted, &tokens(to)?, value).ok_or_else(
+                || Error::Mapping(MappingError::Conflict(to.clone())),
+            )?;
+        }
+        Ok(converted)
+    }
+}
+
+fn tokens(path: &str) -> Result<Vec<String>, Error> {
+    pointer::split(path).ok_or_else(|| {
+        Error::Mapping(MappingError::InvalidPointer(path.to_owned()))
+    })
+}
@@ -0,0 +1,67 @@
+//! Estimates of the heap memory held by documents, for deciding which
+//! parsed documents to keep cached.
+//!
+//! Buffers are counted at their capacity rather than their length, and
+//! the index of large objects is counted with them. Allocator overhead
+//! and alignment are not, so the memory actually in use is somewhat
+//! higher.
+use super::container::Container;
+use super::map::Key;
+use std::collections::HashSet;
+
+/// Bytes of the allocation of `key`: its counts and its text.
+fn key_bytes(key: &Key) -> usize {
+    2 * size_of::<usize>() + key.len()
+}
+
+/// Heap bytes held by `value`, counting the keys not in `keys` yet.
+fn heap_bytes(value: &Container, keys: &mut HashSet<*const u8>) -> usize {
+    match value {
+        Container::RawNumber(text) | Container::BigNumber(text) => {
+            text.capacity()
+        }
+        Container::String(text) => text.heap_capacity(),
+        Container::Array(array) => {
+            array.capacity() * size_of::<Container>()
+                + array
+                    .iter()
+                    .map(|element| heap_bytes(element, keys))
+                    .sum::<usize>()
+        }
+        Container::Object(map) => {
+            let mut bytes = map.table_bytes();
+            for (key, value) in map.entries() {
+                if keys.insert(key.as_ptr()) {
+                    bytes += key_bytes(key);
+                }
+                bytes += heap_bytes(value, keys);
+            }
+            bytes
+        }
+        _ => 0,
+    }
+}
+
+impl Container {
+    /// Estimated bytes of heap memory held by the value, besides the
+    /// `size_of::<Container>()` bytes of the value itself.
+    ///
+    /// A key shared by several members, as the parser shares the keys
+    /// repeated in a document, is counted once.
+    ///
+    /// ```
+    /// use json_parser::container::Container;
+    /// use json_parser::parser::parse_str;
+    ///
+    /// assert_eq!(Container::from(7).memory_usage(), 0);
+    /// assert_eq!(Container::from("short").memory_usage(), 0);
+    /// assert!(Container::from("a".repeat(100)).memory_usage() >= 100);
+    ///
+    /// let one = parse_str(r#"[{"name": "a"}]"#).unwrap();
+    /// let many = parse_str(r#"[{"name": "a"}, {"name": "b"}]"#).unwrap();
+    /// assert!(many.memory_usage() > one.memory_usage());
+    /// ```
+    pub fn memory_usage(&self) -> usize {
+        heap_bytes(self, &mut HashSet::new())
+    }
+}
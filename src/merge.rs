@@ -0,0 +1,210 @@
+//! Combining several documents into one, either in memory or straight
+//! from files on disk.
+use super::container::Container;
+use super::map::Map;
+use super::parser::parse_file;
+use super::pointer;
+use std::path::Path;
+
+/// How documents are combined by [`Container::merge`] and [`merge_files`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// Append elements of arrays into one array; a non-array document is
+    /// appended as a single element.
+    ConcatArrays,
+    /// Merge objects member by member, recursing into members present on
+    /// both sides. Any other pair of values is resolved by keeping the
+    /// later one.
+    DeepMerge,
+}
+
+impl Container {
+    /// Merges `other` into self according to `strategy`.
+    ///
+    /// ```
+    /// use json_parser::merge::MergeStrategy;
+    /// use json_parser::parser::parse_str;
+    ///
+    /// let mut base = parse_str(r#"{"a": {"x": 1, "y": 2}, "b": [1]}"#).unwrap();
+    /// let other = parse_str(r#"{"a": {"y": 3}, "b": [2]}"#).unwrap();
+    ///
+    /// base.merge(other, MergeStrategy::DeepMerge);
+    /// assert_eq!(base, parse_str(r#"{"a": {"x": 1, "y": 3}, "b": [2]}"#).unwrap());
+    /// ```
+    pub fn merge(&mut self, other: Container, strategy: MergeStrategy) {
+        match strategy {
+            MergeStrategy::ConcatArrays => {
+                if !self.is_array() {
+                    let first = core::mem::replace(self, Self::new_array());
+                    if !first.is_null() {
+                        self.push(first);
+                    }
+                }
+                if let Self::Array(values) = self {
+                    match other {
+                        Self::Array(others) => values.extend(others),
+                        other => values.push(other),
+                    }
+                }
+            }
+            MergeStrategy::DeepMerge => match (self, other) {
+                (Self::Object(map), Self::Object(others)) => {
+                    for (key, value) in others {
+                        match map.get_mut(&key) {
+                            Some(existing) => existing.merge(value, strategy),
+                            None => {
+                                map.insert(key, value);
+                            }
+                        }
+                    }
+                }
+                (this, other) => *this = other,
+            },
+        }
+    }
+}
+
+/// A value changed differently by both sides of a [`merge3`]. Values
+/// missing on a side are `None`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    /// JSON Pointer to the value
+    pub path: String,
+    pub base: Option<Container>,
+    pub ours: Option<Container>,
+    pub theirs: Option<Container>,
+}
+
+/// Three-way merge of the members at `path`, recording conflicts.
+fn merge_value(
+    path: &mut String,
+    base: Option<&Container>,
+    ours: Option<&Container>,
+    theirs: Option<&Container>,
+    conflicts: &mut Vec<Conflict>,
+) -> Option<Container> {
+    if ours == theirs || theirs == base {
+        return ours.cloned();
+    }
+    if ours == base {
+        return theirs.cloned();
+    }
+
+    let (Some(Container::Object(ours)), Some(Container::Object(theirs))) =
+        (ours, theirs)
+    else {
+        conflicts.push(Conflict {
+            path: path.clone(),
+            base: base.cloned(),
+            ours: ours.cloned(),
+            theirs: theirs.cloned(),
+        });
+        return ours.cloned();
+    };
+    // Objects added on both sides are merged member by member.
+    let empty = Map::new();
+    let base = match base {
+        Some(Container::Object(base)) => base,
+        _ => &empty,
+    };
+    let keys = ours
+        .iter()
+        .map(|(key, _)| key)
+        .chain(
+            theirs
+                .iter()
+                .map(|(key, _)| key)
+                .filter(|key| !ours.contains_key(*key)),
+        )
+        .chain(base.iter().map(|(key, _)| key).filter(|key| {
+            !ours.contains_key(*key) && !theirs.contains_key(*key)
+        }));
+
+    let mut merged = Map::new();
+    let len = path.len();
+    for key in keys {
+        path.push('/');
+        path.push_str(&pointer::escape(key));
+        let value = merge_value(
+            path,
+            base.get(key),
+            ours.get(key),
+            theirs.get(key),
+            conflicts,
+        );
+        path.truncate(len);
+        if let Some(value) = value {
+            merged.insert(key, value);
+        }
+    }
+    Some(Container::Object(merged))
+}
+
+/// Merges the changes made by `ours` and `theirs` to their common
+/// ancestor `base`.
+///
+/// A value changed on one side only takes that change, objects changed on
+/// both sides are merged member by member, and other values changed
+/// differently on both sides, including arrays, are conflicts: the merge
+/// keeps our side and lists each of them, in document order.
+///
+/// ```
+/// use json_parser::merge::merge3;
+/// use json_parser::parser::parse_str;
+///
+/// let base = parse_str(r#"{"a": 1, "b": 2, "c": 3}"#).unwrap();
+/// let ours = parse_str(r#"{"a": 10, "b": 2, "c": 30}"#).unwrap();
+/// let theirs = parse_str(r#"{"a": 1, "b": 20, "c": 31}"#).unwrap();
+///
+/// let (merged, conflicts) = merge3(&base, &ours, &theirs);
+/// assert_eq!(merged, parse_str(r#"{"a": 10, "b": 20, "c": 30}"#).unwrap());
+/// assert_eq!(conflicts.len(), 1);
+/// assert_eq!(conflicts[0].path, "/c");
+/// ```
+pub fn merge3(
+    base: &Container,
+    ours: &Container,
+    theirs: &Container,
+) -> (Container, Vec<Conflict>) {
+    let mut conflicts = Vec::new();
+    let merged = merge_value(
+        &mut String::new(),
+        Some(base),
+        Some(ours),
+        Some(theirs),
+        &mut conflicts,
+    );
+    (merged.unwrap_or(Container::Null), conflicts)
+}
+
+/// Parses and merges the files at `paths`, in order.
+///
+/// Files are read and folded into the result one at a time, so only the
+/// accumulated result and a single file are held in memory at once. An
+/// empty list yields an empty array for `ConcatArrays` and `Null` for
+/// `DeepMerge`.
+///
+/// A file that cannot be read or parsed fails the merge with an
+/// [`Error::File`] naming it, as in [`parse_file`].
+///
+/// [`Error::File`]: crate::error::Error::File
+pub fn merge_files<I, P>(
+    paths: I,
+    strategy: MergeStrategy,
+) -> Result<Container, Box<dyn core::error::Error>>
+where
+    I: IntoIterator<Item = P>,
+    P: AsRef<Path>,
+{
+    let mut merged = match strategy {
+        MergeStrategy::ConcatArrays => Container::new_array(),
+        MergeStrategy::DeepMerge => Container::Null,
+    };
+
+    for path in paths {
+        let document = parse_file(path)?;
+        merged.merge(document, strategy);
+    }
+
+    Ok(merged)
+}
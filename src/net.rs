@@ -0,0 +1,145 @@
+//! Network addresses stored in string values.
+//!
+//! Addresses are parsed on demand. Infrastructure documents name the same
+//! hosts and networks over and over, so parse results are cached per
+//! thread, keyed by the string.
+use super::container::Container;
+use core::cell::RefCell;
+use core::fmt;
+use core::str::FromStr;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::thread::LocalKey;
+
+/// Strings remembered per cache before it is emptied.
+const CACHE_LIMIT: usize = 4096;
+
+pub(crate) type Cache<T> = RefCell<HashMap<String, Option<T>>>;
+
+thread_local! {
+    static ADDRESSES: Cache<IpAddr> = RefCell::new(HashMap::new());
+    static NETWORKS: Cache<Cidr> = RefCell::new(HashMap::new());
+}
+
+/// Parses `text` with `parse`, or returns the result of a previous call.
+pub(crate) fn cached<T: Clone>(
+    cache: &'static LocalKey<Cache<T>>,
+    text: &str,
+    parse: fn(&str) -> Option<T>,
+) -> Option<T> {
+    cache.with(|cache| {
+        if let Some(parsed) = cache.borrow().get(text) {
+            return parsed.clone();
+        }
+        let parsed = parse(text);
+        let mut cache = cache.borrow_mut();
+        if cache.len() >= CACHE_LIMIT {
+            cache.clear();
+        }
+        cache.insert(text.to_owned(), parsed.clone());
+        parsed
+    })
+}
+
+/// An IP network in CIDR notation, e.g. `10.0.0.0/8` or `fe80::/10`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cidr {
+    /// The address as written, which may have bits set past the prefix
+    pub addr: IpAddr,
+    /// Number of leading bits making up the network
+    pub prefix: u8,
+}
+
+impl Cidr {
+    /// The address with every bit past the prefix cleared.
+    pub fn network(&self) -> IpAddr {
+        match self.addr {
+            IpAddr::V4(addr) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32);
+                IpAddr::V4((u32::from(addr) & mask.unwrap_or(0)).into())
+            }
+            IpAddr::V6(addr) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32);
+                IpAddr::V6((u128::from(addr) & mask.unwrap_or(0)).into())
+            }
+        }
+    }
+
+    /// Whether `addr` belongs to the network. Addresses of the other IP
+    /// version never do.
+    pub fn contains(&self, addr: &IpAddr) -> bool {
+        let other = Cidr {
+            addr: *addr,
+            prefix: self.prefix,
+        };
+        self.addr.is_ipv4() == addr.is_ipv4()
+            && self.network() == other.network()
+    }
+}
+
+impl FromStr for Cidr {
+    type Err = std::net::AddrParseError;
+
+    /// Parses `address/prefix`, the prefix being at most 32 bits for IPv4
+    /// and 128 bits for IPv6.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        // An address that fails to parse provides the error.
+        let invalid = || "".parse::<IpAddr>().unwrap_err();
+        let (addr, prefix) = text.split_once('/').ok_or_else(invalid)?;
+        let addr: IpAddr = addr.parse()?;
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        let digits = prefix.bytes().all(|byte| byte.is_ascii_digit())
+            && (prefix == "0" || !prefix.starts_with('0'));
+        match prefix.parse::<u8>() {
+            Ok(prefix) if digits && prefix <= max => Ok(Self { addr, prefix }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl fmt::Display for Cidr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(format!("{}/{}", self.addr, self.prefix).as_str())
+    }
+}
+
+impl Container {
+    /// Returns the IP address held by a string value.
+    ///
+    /// ```
+    /// use json_parser::parser::parse_str;
+    /// use std::net::{IpAddr, Ipv4Addr};
+    ///
+    /// let doc = parse_str(r#"{"host": "10.1.2.3", "name": "db"}"#).unwrap();
+    /// let addr = IpAddr::V4(Ipv4Addr::new(10, 1, 2, 3));
+    /// assert_eq!(doc["host"].get_ipaddr(), Some(addr));
+    /// assert_eq!(doc["name"].get_ipaddr(), None);
+    /// ```
+    pub fn get_ipaddr(&self) -> Option<IpAddr> {
+        match self {
+            Self::String(text) => {
+                cached(&ADDRESSES, text, |text| text.parse().ok())
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the network held by a string value in CIDR notation.
+    ///
+    /// ```
+    /// use json_parser::parser::parse_str;
+    ///
+    /// let doc = parse_str(r#"["10.0.0.0/8", "10.0.0.0"]"#).unwrap();
+    /// let network = doc[0].get_cidr().unwrap();
+    /// assert!(network.contains(&"10.20.30.40".parse().unwrap()));
+    /// assert_eq!(doc[1].get_cidr(), None);
+    /// ```
+    pub fn get_cidr(&self) -> Option<Cidr> {
+        match self {
+            Self::String(text) => {
+                cached(&NETWORKS, text, |text| text.parse().ok())
+            }
+            _ => None,
+        }
+    }
+}
@@ -1,14 +1,51 @@
-use super::container::Container;
+use super::container::{Container, DumpOptions};
+use super::dialect::{Dialect, Json, Json5, Lenient, NON_FINITE_LITERALS};
+use super::dom::Reader;
+use super::dump;
+use super::encoding::{bom_len, decode, decode_lossy};
 use super::error::Error;
+use super::error::FileError;
 use super::error::ParseError;
+use super::error::{Warning, WarningKind};
+use super::map::{Key, Map};
+use super::small_string::SmallString;
+use super::static_container::StaticContainer;
+use super::tokens::{Token, Tokens};
+#[cfg(feature = "uuid")]
+use super::uuid;
 use core::result::Result;
+use std::collections::{HashSet, VecDeque};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Instant;
 
-const NEST_LIMIT: u16 = 500;
+pub(crate) const NEST_LIMIT: u16 = 500;
+/// Distinct keys shared by a parser before it starts over.
+const KEY_LIMIT: usize = 4096;
+/// Bytes read between two checks of `ParserOptions::deadline` and
+/// `ParserOptions::cancel`.
+const INTERRUPT_INTERVAL: usize = 64 * 1024;
+
+/// The key equal to `text` among `keys`, added if missing.
+fn intern(keys: &mut HashSet<Key>, text: &str) -> Key {
+    if let Some(key) = keys.get(text) {
+        return key.clone();
+    }
+    if keys.len() >= KEY_LIMIT {
+        keys.clear();
+    }
+    let key = Key::from(text);
+    keys.insert(key.clone());
+    key
+}
 
 /// Single-threaded parsing module, with an intent to parse the
 /// files faster with handling run-time errors (hopefully), considering two modes
 /// of parsing:
-
+///
 /// - JSON parsing
 /// - Binary Data Parsing (where integers are of fixed 4 bytes)
 ///
@@ -16,7 +53,7 @@ const NEST_LIMIT: u16 = 500;
 ///
 /// This is invoked when a user requests loading into memory, called via
 /// function `parse_str`
-pub struct Parser {
+pub struct Parser<'a> {
     /// Raw pointer for the actual input
     container: *const u8,
     /// For parsing the file, counting offset
@@ -29,26 +66,32 @@ pub struct Parser {
     len: usize,
     /// Adjustment when a certain number is read.
     num_read: bool,
-    // Nesting Count: If too many nested objects, just quit
-    nested_count: u16,
-}
-
-macro_rules! expect_next_bytes {
-    ($parser:ident, $( $next_char:expr ),*) => ({
-        $(
-            match $parser.get_byte() {
-                Some($next_char) => {}
-                None => return Err(Error::Parsing(ParseError::EndOfBuffer).into()),
-                Some(r) => {
-                    return Err(Error::Parsing(ParseError::UnexpectedToken(
-                        r as char,
-                        $parser.curr_line,
-                        $parser.curr_column
-                    )).into());
-                }
-            }
-        )*
-    })
+    /// Containers being read, innermost last: replaces recursion so that
+    /// nesting is bounded by `max_depth` rather than the native stack.
+    stack: Vec<Frame>,
+    /// Configuration, e.g. the nesting limit
+    options: ParserOptions,
+    /// Irregularities accepted by lenient options so far
+    warnings: Vec<Warning>,
+    /// Strings with escapes are put together here
+    scratch: String,
+    /// Keys read so far, shared by all their occurrences
+    keys: HashSet<Key>,
+    /// Values read so far in the current document, for `max_nodes`
+    nodes: usize,
+    /// Offset from which the deadline and cancellation are checked again
+    interrupt_check: usize,
+    /// Receives the structure of the document; values are then dropped as
+    /// soon as they are read.
+    observer: Option<&'a mut dyn Observer>,
+    /// Syntax being parsed
+    dialect: &'a dyn Dialect,
+    /// Cached from the dialect: these are consulted for every token.
+    quotes: &'a [u8],
+    comment_starts: &'a [u8],
+    literals: &'a [(&'a str, StaticContainer)],
+    trailing_commas: bool,
+    bare_keys: bool,
 }
 
 macro_rules! equals_in {
@@ -60,32 +103,521 @@ macro_rules! equals_in {
     };
 }
 
-impl Parser {
+/// A container whose elements are still being read.
+enum Frame {
+    Array(Vec<Container>),
+    /// Members read so far, and the key of the member being read, if
+    /// there is one.
+    Object(Map, Option<Key>),
+}
+
+/// JSONPath of the value being read in the containers of `stack`: the
+/// element after those read, the member of the key being read, or the
+/// object itself between members.
+fn json_path(stack: &[Frame]) -> String {
+    let mut path = String::from("$");
+    for frame in stack {
+        match frame {
+            Frame::Array(values) => {
+                path.push_str(&format!("[{}]", values.len()))
+            }
+            Frame::Object(_, None) => break,
+            Frame::Object(_, Some(key)) if is_name(key) => {
+                path.push('.');
+                path.push_str(key);
+            }
+            Frame::Object(_, Some(key)) => {
+                let key = Container::String(key.as_ref().into());
+                path.push_str(&format!("[{}]", key));
+            }
+        }
+    }
+    path
+}
+
+/// Whether `key` can be written after a `.` in a JSONPath.
+fn is_name(key: &str) -> bool {
+    let mut bytes = key.bytes();
+    bytes.next().is_some_and(|byte| is_identifier(byte, true))
+        && bytes.all(|byte| is_identifier(byte, false))
+}
+
+/// A region of the input: the byte range `start..end`, and the line and
+/// column where it starts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub col: usize,
+    /// For errors raised inside an array or object, the JSONPath of the
+    /// value being read, e.g. `$.users[3].address.zip`
+    pub path: Option<Box<str>>,
+}
+
+/// Receives the structure of a document while it is parsed, to analyze
+/// its text without keeping the values.
+pub(crate) trait Observer {
+    /// An array, or an object if `object` is set, opens at `span`.
+    fn open(&mut self, _object: bool, _span: Span) {}
+    /// The innermost open container closes at `span`.
+    fn close(&mut self, _span: Span) {}
+    /// A key of the innermost open object.
+    fn key(&mut self, _key: &str, _span: Span) {}
+    /// A value other than an array or object.
+    fn scalar(&mut self, _value: &Container, _span: Span) {}
+    /// Whether to skip the value about to be read, at the position told
+    /// by the events so far. A skipped value is only checked, as by
+    /// [`Parser::skip_value`], and reported to [`Observer::skipped`].
+    fn skip(&mut self) -> bool {
+        false
+    }
+    /// A value skipped at `span`.
+    fn skipped(&mut self, _span: Span) {}
+    /// A `,` after the last element of the innermost container, accepted
+    /// by the dialect.
+    fn trailing_comma(&mut self, _span: Span) {}
+    /// Parsing failed after reading the byte at `span`.
+    fn failed(&mut self, _span: Span) {}
+}
+
+/// Separators of numbers written for humans, e.g. in spreadsheet exports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberLocale {
+    /// `1,234.5`: `,` groups thousands and `.` starts the fraction.
+    PointDecimal,
+    /// `1.234,5`: `.` groups thousands and `,` starts the fraction.
+    CommaDecimal,
+}
+
+impl NumberLocale {
+    /// The decimal and the group separator.
+    fn separators(self) -> (u8, u8) {
+        match self {
+            Self::PointDecimal => (b'.', b','),
+            Self::CommaDecimal => (b',', b'.'),
+        }
+    }
+}
+
+/// What the parser does with a number beyond the range of every numeric
+/// variant: an integer beyond `i128` and `u128`, or a real number beyond
+/// `f64`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NumberOverflow {
+    /// Keep the number as written: integers as `BigNumber`, real numbers
+    /// as `RawNumber`.
+    #[default]
+    Text,
+    /// Round the number to the nearest `Decimal`, which is infinite for
+    /// real numbers beyond `f64`.
+    Decimal,
+    /// Fail with `NumberOutOfRange`.
+    Error,
+}
+
+/// Order of the members of parsed objects, as seen when iterating over
+/// them, dumping or diffing.
+///
+/// A key written more than once keeps the position of its first
+/// occurrence and the value of its last.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ObjectOrder {
+    /// The order of the input.
+    #[default]
+    Insertion,
+    /// Sorted by key, comparing bytes.
+    Sorted,
+}
+
+/// Configuration of the parser.
+#[derive(Debug, Clone)]
+pub struct ParserOptions {
+    /// Maximum nesting of arrays and objects. Deeper input fails with
+    /// `NestedDepthExceeded`. The parser itself does not recurse, so this
+    /// only bounds memory use; dropping a `Container` does, once per
+    /// level, so drop deep values with `Container::drop_deep`.
+    pub max_depth: u16,
+    /// Accept numbers with locale separators and `_` between digits,
+    /// normalizing them to `Decimal` and reporting a warning for each.
+    ///
+    /// A group separator must be followed by exactly three digits. Since
+    /// `,` also separates elements, write a space after separators (`[1, 2]`)
+    /// when `,` can start a fraction or group digits.
+    pub locale_numbers: Option<NumberLocale>,
+    /// Enforce RFC 8259 to the letter: raw control characters in strings
+    /// and form feeds between tokens are rejected, and lenient syntax
+    /// options such as `locale_numbers` are ignored.
+    ///
+    /// The syntax of the dialect is kept: single-quoted strings are still
+    /// read with [`Lenient`] and [`Json5`], and only [`Json`] rejects them.
+    /// Leading `+` and leading zeros are rejected by every dialect, in
+    /// every mode.
+    pub strict: bool,
+    /// Store string values spelling a JSON number or boolean (`"42"`,
+    /// `"true"`) as typed values, reporting a warning with the original
+    /// string for each. Object keys are left alone.
+    pub coerce_strings: bool,
+    /// In [`parse_bytes_with_warnings`], replace invalid UTF-8 sequences
+    /// with U+FFFD instead of failing, reporting a warning with the
+    /// replaced bytes for each. Replacements outside strings still fail
+    /// to parse.
+    pub lossy_utf8: bool,
+    /// Keep numbers as `RawNumber` holding their text, so that they are
+    /// dumped exactly as written, instead of converting them. Numbers are
+    /// still validated; `locale_numbers` are converted as usual.
+    pub raw_numbers: bool,
+    /// Order of the members of every parsed object.
+    pub object_order: ObjectOrder,
+    /// Handling of numbers too large for every numeric variant.
+    pub number_overflow: NumberOverflow,
+    /// Store string values holding a UUID in canonical form as `Uuid`.
+    /// Object keys are left alone.
+    #[cfg(feature = "uuid")]
+    pub uuids: bool,
+    /// Accept hexadecimal integers (`0xFF`, `-0x1F`) and `_` between the
+    /// digits of numbers (`1_000_000`, `0.000_1`), converting them to the
+    /// usual numeric variants. Ignored in `strict` mode.
+    ///
+    /// With `raw_numbers`, such numbers are kept as decimal JSON text.
+    pub extended_numbers: bool,
+    /// Accept `NaN`, `Infinity` and `-Infinity` as `Decimal` values, in
+    /// addition to the literals of the dialect. Ignored in `strict` mode.
+    pub non_finite_numbers: bool,
+    /// Maximum length in bytes of a string or key, once unescaped. Longer
+    /// strings fail with `StringTooLong`; `None` for no limit.
+    pub max_string_length: Option<usize>,
+    /// Maximum number of elements of an array or members of an object.
+    /// Larger containers fail with `TooManyElements`; `None` for no limit.
+    pub max_elements: Option<usize>,
+    /// Maximum number of values in a document, containers and their
+    /// elements included. Larger documents fail with `TooManyNodes`;
+    /// `None` for no limit.
+    pub max_nodes: Option<usize>,
+    /// Time after which parsing fails with `Timeout`. It is checked as a
+    /// value starts, once every 64 KiB of input, so a document may take
+    /// somewhat longer: as long as one long string or number takes to
+    /// read.
+    pub deadline: Option<Instant>,
+    /// Token whose cancellation makes parsing fail with `Cancelled`,
+    /// checked as often as `deadline`.
+    pub cancel: Option<CancelToken>,
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: NEST_LIMIT,
+            locale_numbers: None,
+            strict: false,
+            coerce_strings: false,
+            lossy_utf8: false,
+            raw_numbers: false,
+            object_order: ObjectOrder::Insertion,
+            number_overflow: NumberOverflow::Text,
+            non_finite_numbers: false,
+            extended_numbers: false,
+            max_string_length: None,
+            max_elements: None,
+            max_nodes: None,
+            deadline: None,
+            cancel: None,
+            #[cfg(feature = "uuid")]
+            uuids: false,
+        }
+    }
+}
+
+/// A flag shared between threads to stop a parse from another one, e.g.
+/// when the client waiting for it disconnects.
+///
+/// ```
+/// use json_parser::error::{Error, ParseError};
+/// use json_parser::parser::{parse_with_options, CancelToken, ParserOptions};
+///
+/// let token = CancelToken::new();
+/// let options = ParserOptions {
+///     cancel: Some(token.clone()),
+///     ..ParserOptions::default()
+/// };
+/// assert!(parse_with_options("[1, 2]", &options).is_ok());
+///
+/// // Typically called by the thread handling the client.
+/// token.cancel();
+/// let error = parse_with_options("[1, 2]", &options).unwrap_err();
+/// assert!(matches!(
+///     error.downcast_ref::<Error>(),
+///     Some(Error::Parsing(ParseError::Cancelled(_)))
+/// ));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Makes the parses holding the token fail at their next check.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl From<Arc<AtomicBool>> for CancelToken {
+    fn from(flag: Arc<AtomicBool>) -> Self {
+        Self(flag)
+    }
+}
+
+/// Presets of the syntax accepted, bundling a [`Dialect`] with the lenient
+/// [`ParserOptions`] that go with it.
+///
+/// ```
+/// use json_parser::parser::{parse_with_strictness, Strictness};
+///
+/// let input = "{port: 0x1F90, /* hex */ hosts: ['a', 'b',]}";
+/// let doc = parse_with_strictness(input, Strictness::Json5).unwrap();
+/// assert_eq!(doc["port"].get_uint(), Some(8080));
+/// assert!(parse_with_strictness(input, Strictness::Lenient).is_err());
+/// assert!(parse_with_strictness("[1, 2,]", Strictness::Lenient).is_ok());
+/// assert!(parse_with_strictness("[1, 2,]", Strictness::Standard).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Strictness {
+    /// RFC 8259 to the letter, as with `ParserOptions::strict`
+    Strict,
+    /// Standard JSON, with raw control characters in strings
+    #[default]
+    Standard,
+    /// The syntax of [`Lenient`]: comments, trailing commas, single
+    /// quotes, `NaN` and the infinities.
+    ///
+    /// `extended_numbers` is left off: hexadecimal numbers and `_`
+    /// between digits are not part of that syntax, and are only read with
+    /// `Json5`. Set it on the options of the preset to accept them too.
+    Lenient,
+    /// The syntax of [`Json5`], with hexadecimal numbers
+    Json5,
+}
+
+impl Strictness {
+    /// The syntax of the preset.
+    pub fn dialect(self) -> &'static dyn Dialect {
+        match self {
+            Strictness::Strict | Strictness::Standard => &Json,
+            Strictness::Lenient => &Lenient,
+            Strictness::Json5 => &Json5,
+        }
+    }
+
+    /// The options of the preset, to adjust further if needed.
+    pub fn options(self) -> ParserOptions {
+        ParserOptions {
+            strict: self == Strictness::Strict,
+            extended_numbers: self == Strictness::Json5,
+            ..ParserOptions::default()
+        }
+    }
+}
+
+/// Bytes allowed in bare object keys.
+#[inline(always)]
+pub(crate) fn is_identifier(byte: u8, first: bool) -> bool {
+    byte.is_ascii_alphabetic()
+        || byte == b'_'
+        || byte == b'$'
+        || (!first && byte.is_ascii_digit())
+}
+
+/// Edits between `a` and `b`: insertions, deletions, substitutions and
+/// swaps of two adjacent characters.
+fn edit_distance(a: &[u8], b: &[u8]) -> usize {
+    // Rows of the distances from the prefixes of `a` to those of `b`.
+    let (mut before, mut prev): (Vec<usize>, Vec<usize>) =
+        (Vec::new(), (0..=b.len()).collect());
+    for (i, x) in a.iter().enumerate() {
+        let mut row = vec![i + 1; b.len() + 1];
+        for (j, y) in b.iter().enumerate() {
+            row[j + 1] = (prev[j] + usize::from(x != y))
+                .min(prev[j + 1] + 1)
+                .min(row[j] + 1);
+            if i > 0 && j > 0 && *x == b[j - 1] && a[i - 1] == *y {
+                row[j + 1] = row[j + 1].min(before[j - 1] + 1);
+            }
+        }
+        before = core::mem::replace(&mut prev, row);
+    }
+    prev[b.len()]
+}
+
+/// Error for the word at the start of `rest`, which is none of the
+/// `literals`, read from byte `start` at line and column. The closest
+/// literal is suggested if the word is a likely typo of it.
+pub(crate) fn unknown_literal(
+    rest: &[u8],
+    literals: &[&str],
+    start: usize,
+    line: usize,
+    col: usize,
+) -> Box<dyn core::error::Error> {
+    let len = rest
+        .iter()
+        .position(|byte| !byte.is_ascii_alphanumeric() && *byte != b'_')
+        .unwrap_or(rest.len());
+    let word = String::from_utf8_lossy(&rest[..len]).into_owned();
+    let suggestion = literals
+        .iter()
+        .filter(|literal| !literal.is_empty())
+        .map(|literal| {
+            (edit_distance(word.as_bytes(), literal.as_bytes()), *literal)
+        })
+        .filter(|(distance, _)| *distance <= 2 && *distance < word.len())
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, literal)| literal.to_owned());
+    let span = Span {
+        start,
+        end: start + len,
+        line,
+        col,
+        path: None,
+    };
+    Error::Parsing(ParseError::UnknownLiteral {
+        found: word,
+        suggestion,
+        span,
+    })
+    .into()
+}
+
+impl<'a> Parser<'a> {
     /// Creates a new JSON parser.
     #[inline(always)]
-    fn new(str_stream: &str) -> Self {
+    pub fn new(str_stream: &'a str) -> Self {
+        Self::with_dialect(str_stream, &Json)
+    }
+
+    /// Creates a parser for the given dialect.
+    pub fn with_dialect(str_stream: &'a str, dialect: &'a dyn Dialect) -> Self {
+        Self::with_options(str_stream, dialect, &ParserOptions::default())
+    }
+
+    /// Creates a parser for the syntax of a [`Strictness`] preset.
+    pub fn with_strictness(
+        str_stream: &'a str,
+        strictness: Strictness,
+    ) -> Self {
+        Self::with_options(
+            str_stream,
+            strictness.dialect(),
+            &strictness.options(),
+        )
+    }
+
+    /// Creates a parser for the given dialect and configuration.
+    pub fn with_options(
+        str_stream: &'a str,
+        dialect: &'a dyn Dialect,
+        options: &ParserOptions,
+    ) -> Self {
         Self {
             container: str_stream.as_ptr(),
-            offset: 0,
+            offset: bom_len(str_stream),
             curr_line: 1,
-            curr_column: 1,
+            curr_column: 0,
             len: str_stream.len(),
             num_read: false,
-            nested_count: 0,
+            stack: Vec::new(),
+            options: options.clone(),
+            warnings: Vec::new(),
+            scratch: String::new(),
+            keys: HashSet::new(),
+            nodes: 0,
+            interrupt_check: 0,
+            observer: None,
+            dialect,
+            quotes: dialect.quotes(),
+            comment_starts: dialect.comment_starts(),
+            literals: dialect.literals(),
+            trailing_commas: dialect.trailing_commas(),
+            bare_keys: dialect.bare_keys(),
+        }
+    }
+
+    /// Starts over on `str_stream`, with the same dialect and options.
+    ///
+    /// The buffers of the parser are kept, so that parsing many small
+    /// documents with one parser does not allocate them for each, and so
+    /// are its keys: documents of the parser share the keys they repeat.
+    ///
+    /// ```
+    /// use json_parser::parser::Parser;
+    ///
+    /// let messages = [r#"{"seq": 1}"#, r#"{"seq": 2, "note": "a\tb"}"#];
+    /// let mut parser = Parser::new("");
+    /// for (idx, message) in messages.iter().enumerate() {
+    ///     parser.reset(message);
+    ///     let doc = parser.parse_str().unwrap();
+    ///     assert_eq!(doc["seq"].get_uint(), Some(idx as u64 + 1));
+    /// }
+    /// ```
+    pub fn reset(&mut self, str_stream: &'a str) {
+        self.container = str_stream.as_ptr();
+        self.offset = bom_len(str_stream);
+        self.curr_line = 1;
+        self.curr_column = 0;
+        self.len = str_stream.len();
+        self.num_read = false;
+        self.stack.clear();
+        self.warnings.clear();
+        self.interrupt_check = 0;
+    }
+
+    /// Unread part of the buffer, starting at `offset`.
+    #[inline(always)]
+    fn rest_from(&self, offset: usize) -> &'a [u8] {
+        unsafe {
+            core::slice::from_raw_parts(
+                self.container.add(offset),
+                self.len - offset,
+            )
         }
     }
 
+    /// Returns the next byte without consuming it.
+    #[inline(always)]
+    fn peek_byte(&self) -> Option<u8> {
+        (self.offset < self.len)
+            .then(|| unsafe { *self.container.add(self.offset) })
+    }
+
+    /// Get the next significant byte, skipping whitespace and comments of
+    /// the dialect.
     #[inline]
     fn get_byte(&mut self) -> Option<u8> {
         loop {
-            let resp = match self.get_next_byte() {
-                Some(value) if (value as char).is_ascii_whitespace() => None,
-                None => return None,
-                val @ Some(_) => val,
-            };
-            if resp.is_some() {
-                return resp;
+            let value = self.get_next_byte()?;
+            // Form feeds are only whitespace outside strict mode.
+            if matches!(value, b' ' | b'\t' | b'\n' | b'\r')
+                || value == 0x0c && !self.options.strict
+            {
+                continue;
             }
+            if self.comment_starts.contains(&value) {
+                let rest = self.rest_from(self.offset - 1);
+                if let Some(len) = self.dialect.skip_comment(rest) {
+                    // The first byte of the comment is already consumed.
+                    for _ in 1..len.min(rest.len()) {
+                        self.get_next_byte();
+                    }
+                    continue;
+                }
+            }
+            return Some(value);
         }
     }
 
@@ -109,387 +641,912 @@ impl Parser {
         })
     }
 
+    /// Steps back over the byte just read by `get_next_byte`.
+    fn unread_byte(&mut self) {
+        self.offset -= 1;
+        if self.rest_from(self.offset)[0] == b'\n' {
+            let before = &self.rest_from(0)[..self.offset];
+            self.curr_line -= 1;
+            self.curr_column = self.offset
+                - before
+                    .iter()
+                    .rposition(|b| *b == b'\n')
+                    .map_or(0, |i| i + 1);
+        } else {
+            self.curr_column -= 1;
+        }
+    }
+
+    fn unexpected_token(&self, chr: u8) -> Box<dyn core::error::Error> {
+        Error::Parsing(ParseError::UnexpectedToken(
+            chr as char,
+            self.last_byte_span(),
+        ))
+        .into()
+    }
+
+    /// Error for the byte `chr` ending a number, just read.
+    fn number_error(&self, chr: u8) -> Box<dyn core::error::Error> {
+        Error::Parsing(ParseError::InvalidNumberParse(
+            chr as char,
+            self.last_byte_span(),
+        ))
+        .into()
+    }
+
+    /// Error for the byte `chr` after a number, not read yet.
+    fn number_end_error(&self, chr: u8) -> Box<dyn core::error::Error> {
+        Error::Parsing(ParseError::InvalidNumberParse(
+            chr as char,
+            Span {
+                start: self.offset,
+                end: self.offset + 1,
+                line: self.curr_line,
+                col: self.curr_column + 1,
+                path: None,
+            },
+        ))
+        .into()
+    }
+
+    /// Error for the closing bracket `closing` just read, in a container
+    /// opened by `opening`.
+    fn mismatch(
+        &self,
+        opening: char,
+        closing: char,
+    ) -> Box<dyn core::error::Error> {
+        Error::Parsing(ParseError::ContainerParanthesisMismatch {
+            opening_container: opening,
+            closing_container: closing,
+            span: self.last_byte_span(),
+        })
+        .into()
+    }
+
     /// Parsing bytestream
     /// Parse the file from an input stream: taking unsafe route
     #[inline(always)]
     pub fn parse_str(
         &mut self,
     ) -> Result<Container, Box<dyn core::error::Error>> {
-        let answer = match self.get_next_byte() {
-            Some(b'\'' | b'"') => Ok(self.read_string_in_quotes()?),
-            Some(b'[') => Ok(self.read_array()?),
-            Some(b'{') => Ok(self.read_objects()?),
-            val @ Some(b'0'..=b'9' | b'-') => self.read_number(val.unwrap()),
-            Some(b't') => {
-                expect_next_bytes!(self, b'r', b'u', b'e');
-
-                if let Some(chr) = self.get_byte() {
-                    Err(Error::Parsing(ParseError::UnexpectedToken(
-                        chr as char,
-                        self.curr_line,
-                        self.curr_column,
-                    ))
-                    .into())
-                } else {
-                    Ok(Container::Boolean(true))
-                }
-            }
-            Some(b'f') => {
-                expect_next_bytes!(self, b'a', b'l', b's', b'e');
-
-                if let Some(chr) = self.get_byte() {
-                    Err(Error::Parsing(ParseError::UnexpectedToken(
-                        chr as char,
-                        self.curr_line,
-                        self.curr_column,
-                    ))
-                    .into())
-                } else {
-                    Ok(Container::Boolean(false))
-                }
-            }
-            Some(b'n') => {
-                expect_next_bytes!(self, b'u', b'l', b'l');
-
-                if let Some(chr) = self.get_byte() {
-                    Err(Error::Parsing(ParseError::UnexpectedToken(
-                        chr as char,
-                        self.curr_line,
-                        self.curr_column,
-                    ))
-                    .into())
-                } else {
-                    Ok(Container::Null)
-                }
-            }
-            None => Err(Error::Parsing(ParseError::EndOfBuffer).into()),
-            Some(c) => Err(Error::Parsing(ParseError::UnexpectedToken(
-                c as char,
-                self.curr_line,
-                self.curr_column,
-            ))
-            .into()),
+        let answer = match self.get_byte() {
+            Some(byte) => self.read_value(byte)?,
+            None => return Err(self.end_of_buffer()),
         };
 
         if let Some(chr) = self.get_byte() {
-            Err(Error::Parsing(ParseError::UnexpectedToken(
-                chr as char,
-                self.curr_line,
-                self.curr_column,
-            ))
-            .into())
+            Err(self.unexpected_token(chr))
         } else {
-            answer
+            Ok(answer)
         }
     }
 
-    fn slice_to_utf8(
-        slice: &[u8],
-    ) -> Result<&str, Box<dyn core::error::Error>> {
-        match core::str::from_utf8(slice) {
-            Ok(sl) => Ok(sl),
-            Err(_) => {
-                Err(Error::Parsing(ParseError::InvalidUTF8Parsing).into())
+    /// Read a single value whose first byte has already been consumed.
+    fn read_value(
+        &mut self,
+        byte: u8,
+    ) -> Result<Container, Box<dyn core::error::Error>> {
+        self.nodes = 0;
+        let value = self.read_nested(byte);
+        if let Err(error) = value {
+            let path = (!self.stack.is_empty()).then(|| json_path(&self.stack));
+            // The values read so far may be deep too.
+            for frame in self.stack.drain(..) {
+                match frame {
+                    Frame::Array(values) => Container::Array(values),
+                    Frame::Object(map, _) => Container::Object(map),
+                }
+                .drop_deep();
             }
+            return Err(match (path, error.downcast::<Error>()) {
+                (Some(path), Ok(mut error)) => {
+                    if let Error::Parsing(error) = error.as_mut() {
+                        error.at_path(path);
+                    }
+                    error
+                }
+                (_, Ok(error)) => error,
+                (_, Err(error)) => error,
+            });
         }
+        value
     }
 
-    /// Read string values that are stored
-    fn read_string_in_quotes(
+    /// Read a value of any depth, keeping the containers being read on
+    /// `self.stack` instead of recursing for every nesting level.
+    fn read_nested(
         &mut self,
+        mut byte: u8,
     ) -> Result<Container, Box<dyn core::error::Error>> {
-        // Current byte is a quote, read and move to next one
-        let (mut start, mut final_string) = (self.offset, "".to_owned());
-
-        loop {
-            match self.get_byte() {
-                // Handle this by storing current slice and create a new slice again.
-                Some(b'\\') => {
-                    unsafe {
-                        final_string.push_str(Self::slice_to_utf8(
-                            core::slice::from_raw_parts(
-                                self.container.add(start),
-                                self.offset - start - 1,
-                            ),
-                        )?);
+        'value: loop {
+            self.check_interrupts()?;
+            self.count_node()?;
+            let mut value = match byte {
+                _ if self.observer.as_mut().is_some_and(|o| o.skip()) => {
+                    let mut span = self.last_byte_span();
+                    self.skip_value()?;
+                    if let Some(observer) = self.observer.as_mut() {
+                        span.end = self.offset;
+                        observer.skipped(span);
                     }
-
-                    match self.get_byte() {
-                        Some(b'"') => final_string.push('"'),
-                        Some(b'r') => final_string.push('\r'),
-                        Some(b't') => final_string.push('\t'),
-                        Some(b'n') => final_string.push('\n'),
+                    Container::Null
+                }
+                b'[' => {
+                    self.enter_container()?;
+                    let span = self.last_byte_span();
+                    if let Some(observer) = self.observer.as_mut() {
+                        observer.open(false, span);
+                    }
+                    match self.array_element(true)? {
+                        Some(next) => {
+                            self.check_elements(0)?;
+                            self.stack.push(Frame::Array(Vec::new()));
+                            byte = next;
+                            continue 'value;
+                        }
                         None => {
-                            return Err(
-                                Error::Parsing(ParseError::EndOfBuffer).into()
-                            )
+                            self.notify_close();
+                            Container::Array(Vec::new())
                         }
-                        Some(c) => {
-                            return Err(Error::Parsing(
-                                ParseError::UnexpectedToken(
-                                    c as char,
-                                    self.curr_line,
-                                    self.curr_column,
-                                ),
-                            )
-                            .into())
+                    }
+                }
+                b'{' => {
+                    self.enter_container()?;
+                    let span = self.last_byte_span();
+                    if let Some(observer) = self.observer.as_mut() {
+                        observer.open(true, span);
+                    }
+                    self.stack.push(Frame::Object(Map::new(), None));
+                    match self.object_member(true)? {
+                        Some(next) => {
+                            self.check_elements(0)?;
+                            byte = next;
+                            continue 'value;
+                        }
+                        None => {
+                            self.stack.pop();
+                            self.notify_close();
+                            Container::Object(Map::new())
                         }
                     }
-                    start = self.offset;
                 }
-                Some(b'"') => {
-                    unsafe {
-                        final_string.push_str(Self::slice_to_utf8(
-                            core::slice::from_raw_parts(
-                                self.container.add(start),
-                                self.offset - start - 1,
-                            ),
-                        )?);
+                _ => {
+                    let mut span = self.last_byte_span();
+                    let value = self.read_scalar(byte)?;
+                    if let Some(observer) = self.observer.as_mut() {
+                        span.end = self.offset;
+                        observer.scalar(&value, span);
                     }
-                    break;
+                    value
                 }
-                None => {
-                    return Err(Error::Parsing(ParseError::EndOfBuffer).into())
+            };
+
+            // Store the value into its parent, then close every container
+            // that ends right after it.
+            loop {
+                if self.observer.is_some() && !self.stack.is_empty() {
+                    // Observers get the values: keep memory bounded.
+                    value = Container::Null;
                 }
-                _ => {}
+                let (in_object, len) = match self.stack.last_mut() {
+                    None => return Ok(value),
+                    Some(Frame::Array(values)) => {
+                        values.push(value);
+                        (false, values.len())
+                    }
+                    Some(Frame::Object(map, key)) => {
+                        let key = key.take().expect("a key was just read");
+                        map.insert(key, value);
+                        (true, map.len())
+                    }
+                };
+
+                if in_object {
+                    if let Some(next) = self.next_object_member()? {
+                        self.check_elements(len)?;
+                        byte = next;
+                        continue 'value;
+                    }
+                } else if let Some(next) = self.next_array_element()? {
+                    self.check_elements(len)?;
+                    byte = next;
+                    continue 'value;
+                }
+
+                self.notify_close();
+                value = match self.stack.pop() {
+                    Some(Frame::Array(values)) => Container::Array(values),
+                    Some(Frame::Object(mut map, _)) => {
+                        if self.options.object_order == ObjectOrder::Sorted {
+                            map.sort_keys();
+                        }
+                        Container::Object(map)
+                    }
+                    None => unreachable!("a container was just filled"),
+                };
             }
         }
+    }
 
-        Ok(Container::String(final_string))
+    /// Reads past a value whose first byte has already been consumed,
+    /// checking its syntax without building it: nothing is allocated.
+    ///
+    /// Only the syntax of the dialect is accepted, not that of lenient
+    /// options such as `locale_numbers`.
+    pub(crate) fn skip_value(
+        &mut self,
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        // The parser reads a `str`.
+        let input =
+            unsafe { core::str::from_utf8_unchecked(self.rest_from(0)) };
+        let mut tokens = Tokens::nested(
+            input,
+            self.offset - 1,
+            self.dialect,
+            self.stack.len(),
+            self.options.max_depth,
+        );
+        let end = tokens.skip_value()?;
+        while self.offset < end {
+            self.get_next_byte();
+        }
+        Ok(())
     }
 
-    /// Parse values to store in an array
-    fn read_array(&mut self) -> Result<Container, Box<dyn core::error::Error>> {
-        // Current byte is a quote, read and move to next one
-        self.nested_count += 1;
-        if self.nested_count > NEST_LIMIT {
+    /// Span of the byte that was just read.
+    fn last_byte_span(&self) -> Span {
+        Span {
+            start: self.offset.saturating_sub(1),
+            end: self.offset,
+            line: self.curr_line,
+            col: self.curr_column,
+            path: None,
+        }
+    }
+
+    /// Error for the input ending where more of it is expected.
+    fn end_of_buffer(&self) -> Box<dyn core::error::Error> {
+        Error::Parsing(ParseError::EndOfBuffer(Span {
+            start: self.offset,
+            end: self.offset,
+            line: self.curr_line,
+            col: self.curr_column + 1,
+            path: None,
+        }))
+        .into()
+    }
+
+    /// Tells the observer that the bracket just read closes a container.
+    fn notify_close(&mut self) {
+        let span = self.last_byte_span();
+        if let Some(observer) = self.observer.as_mut() {
+            observer.close(span);
+        }
+    }
+
+    /// Tells the observer about a trailing comma read at `span`, if the
+    /// container closed right after it.
+    fn notify_trailing_comma(&mut self, span: Span, closed: bool) {
+        if let (true, Some(observer)) = (closed, self.observer.as_mut()) {
+            observer.trailing_comma(span);
+        }
+    }
+
+    /// Checks the nesting limit before a new container is opened.
+    fn enter_container(&self) -> Result<(), Box<dyn core::error::Error>> {
+        let depth = self.stack.len() + 1;
+        if depth > self.options.max_depth as usize {
             return Err(Error::Parsing(ParseError::NestedDepthExceeded(
-                self.nested_count,
+                depth.min(u16::MAX as usize) as u16,
+                self.last_byte_span(),
             ))
             .into());
         }
+        Ok(())
+    }
 
-        let mut array_container: Vec<Container> = Vec::new();
-        let mut recorded_one = false;
+    /// Checks the deadline and cancellation once every
+    /// `INTERRUPT_INTERVAL` bytes, as the value whose first byte was just
+    /// read starts.
+    fn check_interrupts(&mut self) -> Result<(), Box<dyn core::error::Error>> {
+        if self.offset < self.interrupt_check
+            || (self.options.deadline.is_none()
+                && self.options.cancel.is_none())
+        {
+            return Ok(());
+        }
+        self.interrupt_check = self.offset + INTERRUPT_INTERVAL;
+        let span = self.last_byte_span();
+        if self
+            .options
+            .cancel
+            .as_ref()
+            .is_some_and(CancelToken::is_cancelled)
+        {
+            return Err(Error::Parsing(ParseError::Cancelled(span)).into());
+        }
+        if self.options.deadline.is_some_and(|at| Instant::now() >= at) {
+            return Err(Error::Parsing(ParseError::Timeout(span)).into());
+        }
+        Ok(())
+    }
 
-        'parsing_array: loop {
-            let curr_container = match self.get_byte() {
-                Some(b'"') => self.read_string_in_quotes(),
-                Some(b'[') => self.read_array(),
-                Some(b'{') => self.read_objects(),
-                Some(b't') => {
-                    expect_next_bytes!(self, b'r', b'u', b'e');
-                    Ok(Container::Boolean(true))
-                }
-                Some(b'f') => {
-                    expect_next_bytes!(self, b'a', b'l', b's', b'e');
-                    Ok(Container::Boolean(false))
-                }
-                Some(b'n') => {
-                    expect_next_bytes!(self, b'u', b'l', b'l');
-                    Ok(Container::Null)
-                }
-                Some(b']') if !recorded_one => break,
-                Some(b']') if recorded_one => {
-                    Err(Error::Parsing(ParseError::UnexpectedToken(
-                        ']',
-                        self.curr_column,
-                        self.curr_line,
-                    ))
-                    .into())
-                }
-                Some(b'}') => Err(Error::Parsing(
-                    ParseError::ContainerParanthesisMismatch {
-                        opening_container: ']',
-                        closing_container: '}',
+    /// Counts the value whose first byte was just read against
+    /// `max_nodes`.
+    fn count_node(&mut self) -> Result<(), Box<dyn core::error::Error>> {
+        self.nodes += 1;
+        match self.options.max_nodes {
+            Some(limit) if self.nodes > limit => Err(Error::Parsing(
+                ParseError::TooManyNodes(limit, self.last_byte_span()),
+            )
+            .into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks `max_elements` before the element or member whose first
+    /// byte was just read is added after `len` others.
+    fn check_elements(
+        &self,
+        len: usize,
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        match self.options.max_elements {
+            Some(limit) if len >= limit => Err(Error::Parsing(
+                ParseError::TooManyElements(limit, self.last_byte_span()),
+            )
+            .into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks `max_string_length` for a string of `len` bytes read from
+    /// `span` to the byte just read.
+    fn check_string(
+        &self,
+        len: usize,
+        span: Span,
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        match self.options.max_string_length {
+            Some(limit) if len > limit => {
+                Err(Error::Parsing(ParseError::StringTooLong(
+                    limit,
+                    Span {
+                        end: self.offset,
+                        ..span
                     },
-                )
-                .into()),
-                val @ Some(b'0'..=b'9' | b'-') => {
-                    self.read_number(val.unwrap())
-                }
-                None => Err(Error::Parsing(ParseError::EndOfBuffer).into()),
-                Some(c) => Err(Error::Parsing(ParseError::UnexpectedToken(
-                    c as char,
-                    self.curr_line,
-                    self.curr_column,
                 ))
-                .into()),
-            }?;
-            array_container.push(curr_container);
-            recorded_one = true;
-
-            match self.get_byte() {
-                Some(b',') => continue 'parsing_array,
-                Some(b']') => break,
-                Some(b'}') => {
-                    return Err(Error::Parsing(
-                        ParseError::ContainerParanthesisMismatch {
-                            opening_container: ']',
-                            closing_container: '}',
-                        },
-                    )
-                    .into());
+                .into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Read a scalar value whose first byte has already been consumed.
+    fn read_scalar(
+        &mut self,
+        byte: u8,
+    ) -> Result<Container, Box<dyn core::error::Error>> {
+        match byte {
+            quote if self.quotes.contains(&quote) => {
+                let (line, col) = (self.curr_line, self.curr_column);
+                let text = self.read_string_in_quotes(quote)?;
+                #[cfg(feature = "uuid")]
+                if let Some(bytes) =
+                    self.options.uuids.then(|| uuid::parse(&text)).flatten()
+                {
+                    return Ok(Container::Uuid(bytes));
                 }
-                None => {
-                    return Err(Error::Parsing(ParseError::EndOfBuffer).into())
+                match self.options.coerce_strings.then(|| coerce(&text)) {
+                    Some(Some(value)) => {
+                        self.warnings.push(Warning {
+                            kind: WarningKind::CoercedString(text.into()),
+                            line,
+                            col,
+                        });
+                        Ok(value)
+                    }
+                    _ => Ok(Container::String(text)),
                 }
-                Some(c) => {
-                    return Err(Error::Parsing(ParseError::UnexpectedToken(
-                        c as char,
-                        self.curr_line,
-                        self.curr_column,
-                    ))
-                    .into());
+            }
+            _ => {
+                let span = self.last_byte_span();
+                let word = byte.is_ascii_alphabetic();
+                match self.read_literal() {
+                    // A literal that is the start of a longer word.
+                    Some(_)
+                        if word
+                            && self
+                                .peek_byte()
+                                .is_some_and(|b| b.is_ascii_alphanumeric()) =>
+                    {
+                        Err(self.unknown_literal(span))
+                    }
+                    Some(value) => Ok(value),
+                    None if byte.is_ascii_digit() || byte == b'-' => {
+                        if self.options.extended_numbers && !self.options.strict
+                        {
+                            self.read_extended_number(byte)
+                        } else {
+                            self.read_plain_number(byte)
+                        }
+                    }
+                    None if word => Err(self.unknown_literal(span)),
+                    None => Err(self.unexpected_token(byte)),
                 }
             }
         }
+    }
 
-        self.nested_count -= 1;
-        Ok(Container::Array(array_container))
+    /// Error for the word starting at `span`, which is no literal.
+    fn unknown_literal(&self, span: Span) -> Box<dyn core::error::Error> {
+        let literals: Vec<&str> = self
+            .literals
+            .iter()
+            .chain(self.non_finite_literals())
+            .map(|(word, _)| *word)
+            .collect();
+        unknown_literal(
+            self.rest_from(span.start),
+            &literals,
+            span.start,
+            span.line,
+            span.col,
+        )
     }
 
-    /// Parsing through the object.
-    fn read_objects(
+    /// Reads up to the first byte of the next array element, returning
+    /// `None` if the array closes instead.
+    fn array_element(
         &mut self,
-    ) -> Result<Container, Box<dyn core::error::Error>> {
-        self.nested_count += 1;
-        if self.nested_count > NEST_LIMIT {
-            return Err(Error::Parsing(ParseError::NestedDepthExceeded(
-                self.nested_count,
-            ))
-            .into());
+        first: bool,
+    ) -> Result<Option<u8>, Box<dyn core::error::Error>> {
+        match self.get_byte() {
+            Some(b']') if first || self.trailing_commas => Ok(None),
+            Some(b']') => Err(self.unexpected_token(b']')),
+            Some(b'}') => Err(self.mismatch('[', '}')),
+            Some(byte) => Ok(Some(byte)),
+            None => Err(self.end_of_buffer()),
+        }
+    }
+
+    /// Reads the separator after an array element and the start of the
+    /// next one, returning `None` if the array closes.
+    fn next_array_element(
+        &mut self,
+    ) -> Result<Option<u8>, Box<dyn core::error::Error>> {
+        match self.get_byte() {
+            Some(b',') => {
+                let comma = self.last_byte_span();
+                let next = self.array_element(false)?;
+                self.notify_trailing_comma(comma, next.is_none());
+                Ok(next)
+            }
+            Some(b']') => Ok(None),
+            Some(b'}') => Err(self.mismatch('[', '}')),
+            None => Err(self.end_of_buffer()),
+            Some(c) => Err(self.unexpected_token(c)),
+        }
+    }
+
+    /// Reads a member key into the innermost frame, the `:` and the first
+    /// byte of the member value, returning `None` if the object closes
+    /// instead.
+    fn object_member(
+        &mut self,
+        first: bool,
+    ) -> Result<Option<u8>, Box<dyn core::error::Error>> {
+        // First: read the key
+        let key_byte = self.get_byte();
+        let mut span = self.last_byte_span();
+        let key = match key_byte {
+            Some(quote) if self.quotes.contains(&quote) => {
+                self.read_key(quote)?
+            }
+            Some(b'}') if first || self.trailing_commas => return Ok(None),
+            Some(c) if self.bare_keys && is_identifier(c, true) => {
+                self.read_bare_key()?
+            }
+            None => return Err(self.end_of_buffer()),
+            Some(c) => return Err(self.unexpected_token(c)),
+        };
+        if let Some(observer) = self.observer.as_mut() {
+            span.end = self.offset;
+            observer.key(&key, span);
+        }
+        if let Some(Frame::Object(_, pending)) = self.stack.last_mut() {
+            *pending = Some(key.clone());
+        }
+
+        match self.get_byte() {
+            Some(b':') => {}
+            None => return Err(self.end_of_buffer()),
+            Some(other) => return Err(self.unexpected_token(other)),
+        }
+
+        match self.get_byte() {
+            Some(b'}') => {
+                Err(Error::Parsing(ParseError::InvalidKeyValueFormat {
+                    reading_key: key.to_string(),
+                    span: self.last_byte_span(),
+                })
+                .into())
+            }
+            Some(b']') => Err(self.mismatch('{', ']')),
+            Some(byte) => Ok(Some(byte)),
+            None => Err(self.end_of_buffer()),
+        }
+    }
+
+    /// Reads the separator after an object member and the next member,
+    /// returning `None` if the object closes.
+    fn next_object_member(
+        &mut self,
+    ) -> Result<Option<u8>, Box<dyn core::error::Error>> {
+        match self.get_byte() {
+            Some(b',') => {
+                let comma = self.last_byte_span();
+                let next = self.object_member(false)?;
+                self.notify_trailing_comma(comma, next.is_none());
+                Ok(next)
+            }
+            Some(b'}') => Ok(None),
+            Some(b']') => Err(self.mismatch('{', ']')),
+            None => Err(self.end_of_buffer()),
+            Some(c) => Err(self.unexpected_token(c)),
+        }
+    }
+
+    /// `NaN` and the infinities, if `non_finite_numbers` accepts them.
+    fn non_finite_literals(
+        &self,
+    ) -> &'static [(&'static str, StaticContainer)] {
+        if self.options.non_finite_numbers && !self.options.strict {
+            NON_FINITE_LITERALS
+        } else {
+            &[]
+        }
+    }
+
+    /// Match a literal keyword of the dialect starting at the byte just
+    /// consumed, preferring the longest match.
+    fn read_literal(&mut self) -> Option<Container> {
+        let rest = self.rest_from(self.offset - 1);
+        let (word, value) = self
+            .literals
+            .iter()
+            .chain(self.non_finite_literals())
+            .filter(|(word, _)| {
+                !word.is_empty() && rest.starts_with(word.as_bytes())
+            })
+            .max_by_key(|(word, _)| word.len())?;
+
+        for _ in 1..word.len() {
+            self.get_next_byte();
+        }
+        Some(value.to_container())
+    }
+
+    /// The input from `start` to `end`.
+    fn slice_to_utf8(
+        &self,
+        start: usize,
+        end: usize,
+    ) -> Result<&'a str, Box<dyn core::error::Error>> {
+        core::str::from_utf8(&self.rest_from(start)[..end - start]).map_err(
+            |error| {
+                let at = start + error.valid_up_to();
+                let len = error.error_len().unwrap_or(end - at);
+                let span = Reader::new(self.rest_from(0), 0).span(at, at + len);
+                Error::Parsing(ParseError::InvalidUTF8Parsing(span)).into()
+            },
+        )
+    }
+
+    /// Read string values that are stored, until the closing `quote`.
+    fn read_string_in_quotes(
+        &mut self,
+        quote: u8,
+    ) -> Result<SmallString, Box<dyn core::error::Error>> {
+        Ok(match self.read_quoted(quote)? {
+            Some(text) => text.into(),
+            None => self.scratch.as_str().into(),
+        })
+    }
+
+    /// Read a member key until the closing `quote`, sharing the key
+    /// read before if it is the same.
+    fn read_key(
+        &mut self,
+        quote: u8,
+    ) -> Result<Key, Box<dyn core::error::Error>> {
+        Ok(match self.read_quoted(quote)? {
+            Some(text) => intern(&mut self.keys, text),
+            None => intern(&mut self.keys, &self.scratch),
+        })
+    }
+
+    /// Reads a string until the closing `quote`: the text between the
+    /// quotes if it has no escapes, else `None` with the decoded text in
+    /// the scratch buffer.
+    fn read_quoted(
+        &mut self,
+        quote: u8,
+    ) -> Result<Option<&'a str>, Box<dyn core::error::Error>> {
+        // Current byte is a quote, read and move to next one
+        let (mut start, mut escaped) = (self.offset, false);
+        let span = self.last_byte_span();
+        // Strings with escapes are put together in the scratch buffer,
+        // kept from one string to the next.
+        self.scratch.clear();
+
+        loop {
+            match self.get_next_byte() {
+                // Handle this by storing current slice and create a new slice again.
+                Some(b'\\') => {
+                    escaped = true;
+                    let text = self.slice_to_utf8(start, self.offset - 1)?;
+                    self.scratch.push_str(text);
+
+                    let chr = match self.get_next_byte() {
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'n') => '\n',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(c) if matches!(c, b'"' | b'\\' | b'/') => {
+                            c as char
+                        }
+                        Some(c) if c == quote => c as char,
+                        Some(b'u') => self.read_unicode()?,
+                        None => return Err(self.end_of_buffer()),
+                        Some(_) => {
+                            return Err(Error::Parsing(
+                                ParseError::InvalidEscape(
+                                    self.escaped_char(),
+                                    self.escaped_span(),
+                                ),
+                            )
+                            .into())
+                        }
+                    };
+                    self.scratch.push(chr);
+                    start = self.offset;
+                }
+                Some(c) if c == quote => {
+                    let rest = self.slice_to_utf8(start, self.offset - 1)?;
+                    if !escaped {
+                        self.check_string(rest.len(), span)?;
+                        return Ok(Some(rest));
+                    }
+                    self.scratch.push_str(rest);
+                    self.check_string(self.scratch.len(), span)?;
+                    break;
+                }
+                None => return Err(self.end_of_buffer()),
+                Some(c) if c < 0x20 && self.options.strict => {
+                    return Err(self.unexpected_token(c))
+                }
+                _ => {}
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// The escaped character that was just read, for error reporting.
+    fn escaped_char(&self) -> char {
+        // The input is a `str` and the previous byte was a backslash, so
+        // the rest starts on a character boundary.
+        unsafe {
+            core::str::from_utf8_unchecked(self.rest_from(self.offset - 1))
         }
+        .chars()
+        .next()
+        .unwrap_or(char::REPLACEMENT_CHARACTER)
+    }
 
-        let mut object_container = std::collections::HashMap::new();
-        let mut recorded_one = false;
-        'parsing_objects: loop {
-            // First: read the key
-            let verification = match self.get_byte() {
-                Some(b'"') => self.read_string_in_quotes(),
-                Some(b'}') if !recorded_one => break,
-                Some(b'}') if recorded_one => {
-                    Err(Error::Parsing(ParseError::UnexpectedToken(
-                        '}',
-                        self.curr_line,
-                        self.curr_column,
-                    ))
-                    .into())
-                }
-                None => {
-                    return Err(Error::Parsing(ParseError::EndOfBuffer).into())
-                }
-                Some(c) => Err(Error::Parsing(ParseError::UnexpectedToken(
-                    c as char,
-                    self.curr_line,
-                    self.curr_column,
-                ))
-                .into()),
-            }?;
-
-            // Skip inverted commas or brackets
-            match self.get_byte() {
-                Some(b':') => {}
-                None => {
-                    return Err(Error::Parsing(ParseError::EndOfBuffer).into())
+    /// Span of the escaped character that was just read.
+    fn escaped_span(&self) -> Span {
+        Span {
+            end: self.offset - 1 + self.escaped_char().len_utf8(),
+            ..self.last_byte_span()
+        }
+    }
+
+    /// Decodes the code point of a `\u` escape whose `u` was just read,
+    /// combining a UTF-16 surrogate pair written as two escapes.
+    fn read_unicode(&mut self) -> Result<char, Box<dyn core::error::Error>> {
+        // The escape starts at the backslash before the `u`.
+        let start = Span {
+            start: self.offset - 2,
+            end: self.offset + 4,
+            line: self.curr_line,
+            col: self.curr_column - 1,
+            path: None,
+        };
+        let lone =
+            |unit| Error::Parsing(ParseError::LoneSurrogate(unit, start));
+
+        let unit = self.read_hex_unit()?;
+        let code = match unit {
+            0xD800..=0xDBFF => {
+                if !self.rest_from(self.offset).starts_with(b"\\u") {
+                    return Err(lone(unit).into());
                 }
-                Some(other) => {
-                    return Err(Error::Parsing(ParseError::UnexpectedToken(
-                        other as char,
-                        self.curr_line,
-                        self.curr_column,
-                    ))
-                    .into())
+                self.offset += 2;
+                self.curr_column += 2;
+                let low = self.read_hex_unit()?;
+                if !(0xDC00..=0xDFFF).contains(&low) {
+                    return Err(lone(unit).into());
                 }
+                0x10000 + ((unit as u32 - 0xD800) << 10) + (low as u32 - 0xDC00)
             }
+            0xDC00..=0xDFFF => return Err(lone(unit).into()),
+            _ => unit as u32,
+        };
 
-            let assoc_value = match self.get_byte() {
-                Some(b'"') => self.read_string_in_quotes(),
-                Some(b'{') => self.read_objects(),
-                Some(b'[') => self.read_array(),
-                Some(b'}') => {
-                    Err(Error::Parsing(ParseError::InvalidKeyValueFormat {
-                        reading_key: verification.get_string().unwrap(),
-                    })
-                    .into())
-                }
-                Some(b']') => Err(Error::Parsing(
-                    ParseError::ContainerParanthesisMismatch {
-                        opening_container: '{',
-                        closing_container: ']',
-                    },
-                )
-                .into()),
-                Some(b't') => {
-                    expect_next_bytes!(self, b'r', b'u', b'e');
-                    Ok(Container::Boolean(true))
-                }
-                Some(b'f') => {
-                    expect_next_bytes!(self, b'a', b'l', b's', b'e');
-                    Ok(Container::Boolean(false))
-                }
-                Some(b'n') => {
-                    expect_next_bytes!(self, b'u', b'l', b'l');
-                    Ok(Container::Null)
-                }
-                val @ Some(b'0'..=b'9' | b'-') => {
-                    self.read_number(val.unwrap())
-                }
-                None => {
-                    return Err(Error::Parsing(ParseError::EndOfBuffer).into())
-                }
-                Some(c) => Err(Error::Parsing(ParseError::UnexpectedToken(
-                    c as char,
-                    self.curr_line,
-                    self.curr_column,
-                ))
-                .into()),
-            }?;
-            object_container
-                .insert(verification.get_string().unwrap(), assoc_value);
-            recorded_one = true;
-
-            match self.get_byte() {
-                Some(b',') => continue 'parsing_objects,
-                Some(b'}') => break,
-                Some(b']') => {
-                    return Err(Error::Parsing(
-                        ParseError::ContainerParanthesisMismatch {
-                            opening_container: '{',
-                            closing_container: ']',
-                        },
-                    )
-                    .into());
-                }
-                None => {
-                    return Err(Error::Parsing(ParseError::EndOfBuffer).into())
-                }
-                Some(c) => {
-                    return Err(Error::Parsing(ParseError::UnexpectedToken(
-                        c as char,
-                        self.curr_line,
-                        self.curr_column,
+        // Surrogates are excluded above, so every code is a valid char.
+        Ok(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER))
+    }
+
+    /// Reads the four hexadecimal digits of a `\u` escape.
+    fn read_hex_unit(&mut self) -> Result<u16, Box<dyn core::error::Error>> {
+        let mut unit = 0;
+        for _ in 0..4 {
+            let digit = self
+                .get_next_byte()
+                .and_then(|byte| (byte as char).to_digit(16))
+                .ok_or_else(|| {
+                    Error::Parsing(ParseError::InvalidUnicodeEscape(
+                        self.last_byte_span(),
                     ))
-                    .into());
-                }
-            }
+                })?;
+            unit = unit << 4 | digit as u16;
         }
+        Ok(unit)
+    }
 
-        self.nested_count -= 1;
-        Ok(Container::Object(object_container))
+    /// Read an unquoted object key, whose first byte is already consumed.
+    fn read_bare_key(&mut self) -> Result<Key, Box<dyn core::error::Error>> {
+        let (start, span) = (self.offset - 1, self.last_byte_span());
+        while self.peek_byte().is_some_and(|b| is_identifier(b, false)) {
+            self.get_next_byte();
+        }
+        // Identifier bytes are ASCII, hence valid UTF-8.
+        let key = unsafe {
+            core::str::from_utf8_unchecked(core::slice::from_raw_parts(
+                self.container.add(start),
+                self.offset - start,
+            ))
+        };
+        self.check_string(key.len(), span)?;
+        Ok(intern(&mut self.keys, key))
     }
 
+    /// Parses the number `slice`, read at `span`.
     #[inline(always)]
-    fn parse_number<T>(slice: &str) -> Result<T, Box<dyn core::error::Error>>
+    fn parse_number<T>(
+        slice: &str,
+        span: &Span,
+    ) -> Result<T, Box<dyn core::error::Error>>
     where
         T: core::str::FromStr,
     {
         match slice.parse::<T>() {
             Ok(val) => Ok(val),
-            Err(_) => {
-                Err(Error::Parsing(ParseError::InvalidNumberParse('0')).into())
+            Err(_) => Err(Error::Parsing(ParseError::InvalidNumberParse(
+                '0',
+                span.clone(),
+            ))
+            .into()),
+        }
+    }
+
+    /// Span of the number read from byte `start` at line and column.
+    fn number_span(&self, start: usize, line: usize, col: usize) -> Span {
+        Span {
+            start,
+            end: self.offset,
+            line,
+            col,
+            path: None,
+        }
+    }
+
+    /// Read a number that may use the separators of `locale` or `_`
+    /// between digits, falling back to `read_number` if it uses neither.
+    fn read_locale_number(
+        &mut self,
+        byte_read: u8,
+        locale: NumberLocale,
+    ) -> Result<Container, Box<dyn core::error::Error>> {
+        let (decimal, group) = locale.separators();
+        let (start, line, col) =
+            (self.offset - 1, self.curr_line, self.curr_column);
+        let (mut in_fraction, mut in_exponent) = (false, false);
+        let (mut prev_byte, mut irregular) = (byte_read, false);
+        let mut normalized = String::from(byte_read as char);
+
+        while let Some(byte) = self.peek_byte() {
+            let ahead = &self.rest_from(self.offset)[1..];
+            let digit_follows = ahead.first().is_some_and(u8::is_ascii_digit);
+            let in_integer = !in_fraction && !in_exponent;
+
+            match byte {
+                b'0'..=b'9' => normalized.push(byte as char),
+                b'_' if prev_byte.is_ascii_digit() && digit_follows => {}
+                _ if byte == group
+                    && in_integer
+                    && prev_byte.is_ascii_digit()
+                    && ahead.len() >= 3
+                    && ahead[..3].iter().all(u8::is_ascii_digit)
+                    && !ahead.get(3).is_some_and(u8::is_ascii_digit) => {}
+                _ if byte == decimal
+                    && in_integer
+                    && prev_byte.is_ascii_digit()
+                    && digit_follows =>
+                {
+                    in_fraction = true;
+                    normalized.push('.');
+                }
+                b'e' | b'E' if !in_exponent && prev_byte.is_ascii_digit() => {
+                    in_exponent = true;
+                    normalized.push('e');
+                }
+                b'+' | b'-' if equals_in!(prev_byte, b'e', b'E') => {
+                    normalized.push(byte as char)
+                }
+                _ => break,
             }
+
+            irregular |= byte == b'_'
+                || byte == group && in_integer
+                || byte == decimal && decimal != b'.' && in_fraction;
+            prev_byte = byte;
+            self.get_next_byte();
+        }
+
+        if !irregular {
+            // Plain JSON number: read it again with the usual checks.
+            (self.offset, self.curr_column) = (start + 1, col);
+            return self.read_number(byte_read);
+        }
+
+        match self.peek_byte() {
+            Some(c)
+                if !matches!(c, b' ' | 9..=13 | b',' | b']' | b'}')
+                    && !self.comment_starts.contains(&c) =>
+            {
+                return Err(self.number_end_error(c));
+            }
+            _ if !prev_byte.is_ascii_digit() => {
+                return Err(self.number_error(prev_byte));
+            }
+            _ => {}
+        }
+
+        let digits = normalized.trim_start_matches('-').as_bytes();
+        if digits.len() > 1 && digits[0] == b'0' && digits[1].is_ascii_digit() {
+            return Err(Error::Parsing(ParseError::InvalidNumberParse(
+                digits[1] as char,
+                self.number_span(start, line, col),
+            ))
+            .into());
         }
+
+        let span = self.number_span(start, line, col);
+        let value = Self::parse_number(&normalized, &span)?;
+        let original = String::from_utf8_lossy(
+            &self.rest_from(start)[..self.offset - start],
+        )
+        .into_owned();
+        self.warnings.push(Warning {
+            kind: WarningKind::LocaleNumber(original),
+            line,
+            col,
+        });
+        Ok(Container::Decimal(value))
     }
 
     /// Read a number from given input
@@ -498,13 +1555,13 @@ impl Parser {
         &mut self,
         byte_read: u8,
     ) -> Result<Container, Box<dyn core::error::Error>> {
-        let (mut read_dot, sign, mut prev_byte, is_sign) = (
+        let (mut read_dot, mut prev_byte, is_sign) = (
             byte_read == b'.',
-            if byte_read == b'-' { b'-' } else { b'+' },
             byte_read,
             byte_read == b'+' || byte_read == b'-',
         );
         let abrupt_end;
+        let (line, col) = (self.curr_line, self.curr_column);
         let mut is_leading_zero = byte_read == b'0';
         let (mut read_exp, mut sign_exp, start, mut expect_number_after_exp) =
             (false, false, self.offset - 1, false);
@@ -512,10 +1569,7 @@ impl Parser {
         loop {
             prev_byte = match self.get_next_byte() {
                 Some(b'.') if read_dot => {
-                    return Err(Error::Parsing(
-                        ParseError::InvalidNumberParse(b'.' as char),
-                    )
-                    .into());
+                    return Err(self.number_error(b'.'));
                 }
                 Some(b'0') if !read_exp && prev_byte == b'-' => {
                     is_leading_zero = true;
@@ -526,10 +1580,7 @@ impl Parser {
                         && prev_byte == b'0'
                         && (!read_dot && !read_exp) =>
                 {
-                    return Err(Error::Parsing(
-                        ParseError::InvalidNumberParse(val.unwrap() as char),
-                    )
-                    .into());
+                    return Err(self.number_error(val.unwrap()));
                 }
                 val @ Some(b'e' | b'E' | b'.')
                     if is_leading_zero
@@ -539,26 +1590,26 @@ impl Parser {
                     let chr = val.unwrap();
                     read_exp |= equals_in!(chr, b'e', b'E');
                     read_dot |= read_exp || chr == b'.';
+                    expect_number_after_exp = true;
                     chr
                 }
+                // A digit must follow the decimal point
                 val @ Some(b'.' | b'e' | b'E')
-                    if (read_exp || prev_byte == b'-') =>
+                    if (read_exp || equals_in!(prev_byte, b'-', b'.')) =>
                 {
-                    return Err(Error::Parsing(
-                        ParseError::InvalidNumberParse(val.unwrap() as char),
-                    )
-                    .into());
+                    return Err(self.number_error(val.unwrap()));
                 }
                 val @ Some(b'-' | b'+')
                     if (is_sign && prev_byte == b'-'
                         || read_exp && !equals_in!(prev_byte, b'e', b'E')) =>
                 {
-                    return Err(Error::Parsing(ParseError::UnexpectedToken(
-                        val.unwrap() as char,
-                        self.curr_line,
-                        self.curr_column,
-                    ))
-                    .into());
+                    return Err(self.unexpected_token(val.unwrap()));
+                }
+                // A sign only starts the number or its exponent
+                val @ Some(b'-' | b'+')
+                    if !equals_in!(prev_byte, b'e', b'E') =>
+                {
+                    return Err(self.number_error(val.unwrap()));
                 }
                 val @ Some(b'0'..=b'9' | b'.' | b'e' | b'E' | b'+' | b'-') => {
                     let chr = val.unwrap();
@@ -578,28 +1629,36 @@ impl Parser {
 
                     chr
                 }
-                val @ Some(b' ' | 9..=13 | b',' | b']' | b'}') | val @ None => {
+                Some(c)
+                    if !matches!(c, b' ' | 9..=13 | b',' | b']' | b'}')
+                        && !self.comment_starts.contains(&c) =>
+                {
+                    return Err(self.number_error(c));
+                }
+                // A sign without digits
+                val if equals_in!(prev_byte, b'-', b'+') => {
+                    return Err(self.number_error(val.unwrap_or(b'\0')));
+                }
+                // Whitespace, separators, comments or end of buffer
+                val => {
                     (self.num_read, abrupt_end) = (true, val.is_none());
 
                     if !expect_number_after_exp {
                         break;
                     } else {
                         return Err(Error::Parsing(
-                            ParseError::InvalidNumberParse(b'\0' as char),
+                            ParseError::InvalidNumberParse(
+                                b'\0' as char,
+                                self.last_byte_span(),
+                            ),
                         )
                         .into());
                     }
                 }
-                Some(c) => {
-                    return Err(Error::Parsing(
-                        ParseError::InvalidNumberParse(c as char),
-                    )
-                    .into());
-                }
             };
         }
         if !abrupt_end {
-            self.offset -= 1;
+            self.unread_byte();
         }
         let str_slice = unsafe {
             core::str::from_utf8_unchecked(
@@ -611,14 +1670,237 @@ impl Parser {
             )
         };
 
-        if read_dot || read_exp {
-            Ok(Container::Decimal(Self::parse_number(str_slice)?))
-        } else if sign == b'-' {
-            Ok(Container::Number(Self::parse_number(str_slice)?))
+        let span = Span {
+            end: start + str_slice.len(),
+            ..self.number_span(start, line, col)
+        };
+        self.number(str_slice, read_dot || read_exp, span)
+    }
+
+    /// Converts the well-formed JSON number `text`, read at `span`, to
+    /// the variant holding it.
+    fn number(
+        &self,
+        text: &str,
+        real: bool,
+        span: Span,
+    ) -> Result<Container, Box<dyn core::error::Error>> {
+        if self.options.raw_numbers {
+            Ok(Container::RawNumber(text.to_owned()))
+        } else if real {
+            match Self::parse_number::<f64>(text, &span)? {
+                value if value.is_finite() => Ok(Container::Decimal(value)),
+                _ => self.overflow(text, true, span),
+            }
         } else {
-            Ok(Container::Unsigned(Self::parse_number(str_slice)?))
+            match integer(text) {
+                Container::BigNumber(_) => self.overflow(text, false, span),
+                value => Ok(value),
+            }
+        }
+    }
+
+    /// Reads a number written in hexadecimal (`0xFF`) or with `_` between
+    /// digits (`1_000.5`), falling back to the other number readers if it
+    /// uses neither.
+    fn read_extended_number(
+        &mut self,
+        byte_read: u8,
+    ) -> Result<Container, Box<dyn core::error::Error>> {
+        let (start, line, col) =
+            (self.offset - 1, self.curr_line, self.curr_column);
+        let Some((len, text, real)) =
+            extended_number(self.rest_from(self.offset - 1))
+        else {
+            return self.read_plain_number(byte_read);
+        };
+
+        for _ in 1..len {
+            self.get_next_byte();
+        }
+        match self.peek_byte() {
+            Some(c)
+                if !matches!(c, b' ' | 9..=13 | b',' | b']' | b'}')
+                    && !self.comment_starts.contains(&c) =>
+            {
+                Err(self.number_end_error(c))
+            }
+            _ => self.number(&text, real, self.number_span(start, line, col)),
+        }
+    }
+
+    /// Reads a number in the syntax of JSON or of `locale_numbers`.
+    fn read_plain_number(
+        &mut self,
+        byte: u8,
+    ) -> Result<Container, Box<dyn core::error::Error>> {
+        match self.options.locale_numbers {
+            Some(locale) if !self.options.strict => {
+                self.read_locale_number(byte, locale)
+            }
+            _ => self.read_number(byte),
+        }
+    }
+
+    /// Applies `ParserOptions::number_overflow` to the number `text` read
+    /// at `span`.
+    fn overflow(
+        &self,
+        text: &str,
+        real: bool,
+        span: Span,
+    ) -> Result<Container, Box<dyn core::error::Error>> {
+        match self.options.number_overflow {
+            NumberOverflow::Text if real => {
+                Ok(Container::RawNumber(text.to_owned()))
+            }
+            NumberOverflow::Text => Ok(Container::BigNumber(text.to_owned())),
+            NumberOverflow::Decimal => {
+                Ok(Container::Decimal(Self::parse_number(text, &span)?))
+            }
+            NumberOverflow::Error => Err(Error::Parsing(
+                ParseError::NumberOutOfRange(text.to_owned(), span),
+            )
+            .into()),
+        }
+    }
+}
+
+/// The smallest variant holding the well-formed integer `text`: parsing
+/// can only fail by overflowing.
+fn integer(text: &str) -> Container {
+    if let Ok(value) = text.parse() {
+        Container::Unsigned(value)
+    } else if let Ok(value) = text.parse() {
+        Container::Number(value)
+    } else if let Ok(value) = text.parse() {
+        Container::Unsigned128(value)
+    } else if let Ok(value) = text.parse() {
+        Container::Number128(value)
+    } else {
+        Container::BigNumber(text.to_owned())
+    }
+}
+
+/// End of the run of `digits` starting at `at`, where single `_` may
+/// separate digits, and the run without them.
+fn digit_run(
+    text: &[u8],
+    at: usize,
+    digits: fn(&u8) -> bool,
+) -> Option<(usize, String)> {
+    let mut run = String::new();
+    let mut end = at;
+    while let Some(byte) = text.get(end) {
+        match byte {
+            b'_' if !run.is_empty()
+                && text.get(end + 1).is_some_and(digits) => {}
+            _ if digits(byte) => run.push(*byte as char),
+            _ => break,
+        }
+        end += 1;
+    }
+    (!run.is_empty()).then_some((end, run))
+}
+
+/// Length, equivalent JSON number and realness of the number starting
+/// `text`, if it is written in hexadecimal or with `_` between digits.
+///
+/// A hexadecimal number beyond `u128` is written in decimal as a
+/// `BigNumber` would be.
+fn extended_number(text: &[u8]) -> Option<(usize, String, bool)> {
+    let negative = text.first() == Some(&b'-');
+    let at = usize::from(negative);
+    let mut number = String::from(if negative { "-" } else { "" });
+
+    if text.get(at) == Some(&b'0')
+        && matches!(text.get(at + 1), Some(b'x' | b'X'))
+    {
+        let (end, digits) = digit_run(text, at + 2, u8::is_ascii_hexdigit)?;
+        match u128::from_str_radix(&digits, 16) {
+            Ok(value) => number.push_str(&value.to_string()),
+            Err(_) => number.push_str(&big_hex(&digits)),
+        }
+        return Some((end, number, false));
+    }
+
+    let is_digit: fn(&u8) -> bool = u8::is_ascii_digit;
+    let (mut end, digits) = digit_run(text, at, is_digit)?;
+    if digits.len() > 1 && digits.starts_with('0') {
+        return None;
+    }
+    number.push_str(&digits);
+    let mut real = false;
+    if text.get(end) == Some(&b'.') {
+        let (after, digits) = digit_run(text, end + 1, is_digit)?;
+        number.push('.');
+        number.push_str(&digits);
+        (end, real) = (after, true);
+    }
+    if matches!(text.get(end), Some(b'e' | b'E')) {
+        let sign = matches!(text.get(end + 1), Some(b'+' | b'-'));
+        let exponent = end + 1 + usize::from(sign);
+        let (after, digits) = digit_run(text, exponent, is_digit)?;
+        number.push('e');
+        number.push_str(core::str::from_utf8(&text[end + 1..exponent]).ok()?);
+        number.push_str(&digits);
+        (end, real) = (after, true);
+    }
+    text[..end].contains(&b'_').then_some((end, number, real))
+}
+
+/// The decimal digits of the hexadecimal `digits`.
+fn big_hex(digits: &str) -> String {
+    // Little-endian base 10^9 limbs.
+    let mut limbs: Vec<u64> = vec![0];
+    for digit in digits.bytes() {
+        let mut carry = (digit as char).to_digit(16).unwrap_or(0) as u64;
+        for limb in limbs.iter_mut() {
+            let value = *limb * 16 + carry;
+            (*limb, carry) = (value % 1_000_000_000, value / 1_000_000_000);
+        }
+        if carry > 0 {
+            limbs.push(carry);
+        }
+    }
+    let mut text = limbs.pop().unwrap_or(0).to_string();
+    for limb in limbs.iter().rev() {
+        text.push_str(&format!("{:09}", limb));
+    }
+    text
+}
+
+/// The number or boolean spelled by `text`, if any.
+fn coerce(text: &str) -> Option<Container> {
+    match text {
+        "true" => Some(Container::Boolean(true)),
+        "false" => Some(Container::Boolean(false)),
+        _ if text.starts_with(|c: char| c.is_ascii_digit() || c == '-')
+            && text.ends_with(|c: char| c.is_ascii_digit()) =>
+        {
+            Parser::new(text).parse_str().ok()
+        }
+        _ => None,
+    }
+}
+
+/// Parses `input`, reporting its structure to `observer` instead of
+/// building the document.
+pub(crate) fn observe(
+    input_str: &str,
+    dialect: &dyn Dialect,
+    observer: &mut dyn Observer,
+) -> Result<(), Box<dyn core::error::Error>> {
+    let mut parser = Parser::with_dialect(input_str, dialect);
+    parser.observer = Some(observer);
+    let result = parser.parse_str().map(|_| ());
+    if result.is_err() {
+        let span = parser.last_byte_span();
+        if let Some(observer) = parser.observer.as_mut() {
+            observer.failed(span);
         }
     }
+    result
 }
 
 /// Read the files in byte form
@@ -638,3 +1920,500 @@ pub fn parse_str(
 ) -> Result<Container, Box<dyn core::error::Error>> {
     Parser::new(input_str).parse_str()
 }
+
+/// Parse raw bytes in UTF-8, UTF-16 or UTF-32, as detected by
+/// [`encoding::decode`](super::encoding::decode), skipping a byte order
+/// mark.
+///
+/// ```
+/// use json_parser::parser::parse_bytes;
+///
+/// let doc = parse_bytes(b"\xFF\xFE[\x001\x00]\x00").unwrap();
+/// assert_eq!(doc[0].get_uint(), Some(1));
+/// ```
+pub fn parse_bytes(
+    input: &[u8],
+) -> Result<Container, Box<dyn core::error::Error>> {
+    parse_str(&decode(input)?)
+}
+
+/// Parse the file at `path`, in any encoding [`parse_bytes`] reads.
+///
+/// Failures to read the file, and parse errors, are raised as
+/// `Error::File` naming the file.
+///
+/// ```
+/// use json_parser::error::{Error, FileError};
+/// use json_parser::parser::parse_file;
+///
+/// let path = std::env::temp_dir().join("parse_file_example.json");
+/// std::fs::write(&path, "\u{feff}{\"port\": 8080}").unwrap();
+/// assert_eq!(parse_file(&path).unwrap()["port"].get_uint(), Some(8080));
+///
+/// std::fs::write(&path, "{\"port\": }").unwrap();
+/// let error = parse_file(&path).unwrap_err();
+/// assert!(matches!(
+///     error.downcast_ref::<Error>(),
+///     Some(Error::File(FileError::Parse { .. }))
+/// ));
+/// assert!(error.to_string().contains("parse_file_example.json"));
+/// std::fs::remove_file(&path).unwrap();
+/// ```
+pub fn parse_file<P: AsRef<Path>>(
+    path: P,
+) -> Result<Container, Box<dyn core::error::Error>> {
+    let path = path.as_ref();
+    let name = || path.display().to_string();
+    let bytes = std::fs::read(path).map_err(|error| {
+        Error::File(FileError::Read {
+            path: name(),
+            reason: error.to_string(),
+        })
+    })?;
+    parse_bytes(&bytes).map_err(|error| match error.downcast::<Error>() {
+        Ok(error) => match *error {
+            Error::Parsing(error) => Error::File(FileError::Parse {
+                path: name(),
+                error,
+            })
+            .into(),
+            error => error.into(),
+        },
+        Err(error) => error,
+    })
+}
+
+/// Parse with a non-default configuration, e.g. a custom nesting limit.
+///
+/// ```
+/// use json_parser::parser::{parse_with_options, ParserOptions};
+///
+/// let options = ParserOptions {
+///     max_depth: 2,
+///     ..Default::default()
+/// };
+/// assert!(parse_with_options("[[1]]", &options).is_ok());
+/// assert!(parse_with_options("[[[1]]]", &options).is_err());
+/// ```
+pub fn parse_with_options(
+    input_str: &str,
+    options: &ParserOptions,
+) -> Result<Container, Box<dyn core::error::Error>> {
+    Parser::with_options(input_str, &Json, options).parse_str()
+}
+
+/// Like [`parse_with_options`], also returning the irregularities that
+/// lenient options accepted, in input order.
+///
+/// ```
+/// use json_parser::parser::{parse_with_warnings, NumberLocale, ParserOptions};
+///
+/// let options = ParserOptions {
+///     locale_numbers: Some(NumberLocale::CommaDecimal),
+///     ..Default::default()
+/// };
+/// let (doc, warnings) =
+///     parse_with_warnings(r#"{"total": 1.234,5, "count": 3}"#, &options)
+///         .unwrap();
+/// assert_eq!(doc["total"].get_real(), Some(1234.5));
+/// assert_eq!(doc["count"].get_uint(), Some(3));
+/// assert_eq!(warnings.len(), 1);
+/// ```
+pub fn parse_with_warnings(
+    input_str: &str,
+    options: &ParserOptions,
+) -> Result<(Container, Vec<Warning>), Box<dyn core::error::Error>> {
+    let mut parser = Parser::with_options(input_str, &Json, options);
+    let value = parser.parse_str()?;
+    Ok((value, parser.warnings))
+}
+
+/// Like [`parse_bytes`], with a non-default configuration, also
+/// returning warnings in input order.
+///
+/// With `lossy_utf8`, the number of invalid UTF-8 sequences replaced is
+/// the number of `InvalidUtf8` warnings.
+///
+/// ```
+/// use json_parser::error::WarningKind;
+/// use json_parser::parser::{parse_bytes_with_warnings, ParserOptions};
+///
+/// let options = ParserOptions {
+///     lossy_utf8: true,
+///     ..Default::default()
+/// };
+/// let (doc, warnings) =
+///     parse_bytes_with_warnings(b"[\"caf\xE9\", \"ok\"]", &options).unwrap();
+/// assert_eq!(doc[0].get_string().unwrap(), "caf\u{FFFD}");
+/// assert_eq!(warnings[0].kind, WarningKind::InvalidUtf8(vec![0xE9]));
+/// assert_eq!((warnings[0].line, warnings[0].col), (1, 6));
+/// ```
+pub fn parse_bytes_with_warnings(
+    input: &[u8],
+    options: &ParserOptions,
+) -> Result<(Container, Vec<Warning>), Box<dyn core::error::Error>> {
+    let (text, replaced) = if options.lossy_utf8 {
+        decode_lossy(input)?
+    } else {
+        (decode(input)?, Vec::new())
+    };
+    let (value, mut warnings) = parse_with_warnings(&text, options)?;
+
+    warnings.extend(replaced.into_iter().map(|(offset, bytes)| {
+        let line_start = text[..offset].rfind('\n').map_or(0, |idx| idx + 1);
+        Warning {
+            kind: WarningKind::InvalidUtf8(bytes.to_vec()),
+            line: 1 + text[..offset].matches('\n').count(),
+            col: offset - line_start + 1,
+        }
+    }));
+    warnings.sort_by_key(|warning| (warning.line, warning.col));
+    Ok((value, warnings))
+}
+
+/// Parse a document written in a JSON-like `dialect`.
+///
+/// Nesting limits and error reporting are the same as for `parse_str`.
+pub fn parse_with_dialect(
+    input_str: &str,
+    dialect: &dyn Dialect,
+) -> Result<Container, Box<dyn core::error::Error>> {
+    Parser::with_dialect(input_str, dialect).parse_str()
+}
+
+/// Parses `input` with the syntax of a [`Strictness`] preset.
+pub fn parse_with_strictness(
+    input_str: &str,
+    strictness: Strictness,
+) -> Result<Container, Box<dyn core::error::Error>> {
+    Parser::with_strictness(input_str, strictness).parse_str()
+}
+
+/// Removes the whitespace between the tokens of `input`, without building
+/// the document. Strings and numbers are copied as written.
+///
+/// ```
+/// use json_parser::parser::minify;
+///
+/// let text = "{\n  \"id\": 7,\n  \"tags\": [\"a\", \"b c\"]\n}";
+/// assert_eq!(minify(text).unwrap(), r#"{"id":7,"tags":["a","b c"]}"#);
+/// assert!(minify("[1, 2").is_err());
+/// ```
+pub fn minify(input_str: &str) -> Result<String, Box<dyn core::error::Error>> {
+    minify_with_dialect(input_str, &Json)
+}
+
+/// Like [`minify`], for a document written in a JSON-like `dialect`:
+/// comments and trailing commas are removed as well, and the remaining
+/// tokens are copied as written.
+pub fn minify_with_dialect(
+    input_str: &str,
+    dialect: &dyn Dialect,
+) -> Result<String, Box<dyn core::error::Error>> {
+    let mut tokens = Tokens::new(input_str, dialect);
+    let mut output = String::with_capacity(input_str.len());
+    let mut after_value = false;
+    while let Some(token) = tokens.next_token()? {
+        if after_value && !matches!(token, Token::EndArray | Token::EndObject) {
+            output.push(',');
+        }
+        after_value = true;
+        match token {
+            Token::BeginArray => output.push('['),
+            Token::BeginObject => output.push('{'),
+            Token::EndArray => output.push(']'),
+            Token::EndObject => output.push('}'),
+            Token::Key(key) => output.push_str(key),
+            Token::String(text)
+            | Token::Number(text)
+            | Token::Literal(text) => output.push_str(text),
+        }
+        if matches!(token, Token::BeginArray | Token::BeginObject) {
+            after_value = false;
+        } else if let Token::Key(_) = token {
+            output.push(':');
+            after_value = false;
+        }
+    }
+    Ok(output)
+}
+
+/// Checks that `input` is one JSON document, without building it: on
+/// success, nothing is allocated. Errors are those of `parse_str`.
+///
+/// ```
+/// use json_parser::parser::validate;
+///
+/// assert!(validate(r#"{"event": "push", "commits": [1, 2]}"#).is_ok());
+/// assert!(validate(r#"{"event": "push",}"#).is_err());
+/// ```
+pub fn validate(input_str: &str) -> Result<(), Box<dyn core::error::Error>> {
+    let mut tokens = Tokens::new(input_str, &Json);
+    while tokens.next_token()?.is_some() {}
+    Ok(())
+}
+
+/// Lays out the document `input` as told by `options`, token by token and
+/// without building it, for formatting tools.
+///
+/// Members keep their order and numbers their text, so `sort_keys` and
+/// `non_finite` do not apply.
+///
+/// ```
+/// use json_parser::container::DumpOptions;
+/// use json_parser::parser::reformat;
+///
+/// let text = r#"{"b": [1.50, {}], "a": []}"#;
+/// assert_eq!(
+///     reformat(text, &DumpOptions::new().indent(2)).unwrap(),
+///     "{\n  \"b\": [\n    1.50,\n    {}\n  ],\n  \"a\": []\n}"
+/// );
+/// ```
+pub fn reformat(
+    input_str: &str,
+    options: &DumpOptions,
+) -> Result<String, Box<dyn core::error::Error>> {
+    let mut output = String::with_capacity(input_str.len());
+    dump::reformat(input_str, &mut output, options)?;
+    Ok(output)
+}
+
+/// Iterator over the whitespace-separated top-level values of a buffer,
+/// created by [`parse_documents`].
+///
+/// Iteration stops after the first error, since the parser cannot know
+/// where the next document starts.
+pub struct Documents<'a> {
+    parser: Parser<'a>,
+    failed: bool,
+}
+
+impl Iterator for Documents<'_> {
+    type Item = Result<Container, Box<dyn core::error::Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let byte = self.parser.get_byte()?;
+        let document = self.parser.read_value(byte);
+        self.failed = document.is_err();
+        Some(document)
+    }
+}
+
+/// Parse concatenated documents such as `{"a":1}{"b":2} [3]`, yielding
+/// each top-level value in turn.
+///
+/// ```
+/// use json_parser::parser::parse_documents;
+///
+/// let docs: Vec<_> = parse_documents(r#"{"a": 1}{"b": 2} [3]"#)
+///     .collect::<Result<_, _>>()
+///     .unwrap();
+/// assert_eq!(docs.len(), 3);
+/// assert_eq!(docs[2][0].get_uint(), Some(3));
+/// ```
+pub fn parse_documents(input_str: &str) -> Documents<'_> {
+    Documents {
+        parser: Parser::new(input_str),
+        failed: false,
+    }
+}
+
+/// Iterator over the documents of a JSON Lines buffer, one per line,
+/// created by [`parse_lines`].
+///
+/// Blank lines are skipped. A line that fails to parse yields its error,
+/// and iteration goes on with the next line.
+pub struct Lines<'a> {
+    lines: core::str::Lines<'a>,
+}
+
+impl<'a> Lines<'a> {
+    /// The next line holding a document.
+    fn next_line(&mut self) -> Option<&'a str> {
+        self.lines.find(|line| !line.trim().is_empty())
+    }
+}
+
+impl Iterator for Lines<'_> {
+    type Item = Result<Container, Box<dyn core::error::Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_line().map(parse_str)
+    }
+}
+
+/// Parse a JSON Lines (NDJSON) buffer such as `{"a":1}\n{"a":2}\n`,
+/// yielding the document of each line in turn.
+///
+/// ```
+/// use json_parser::parser::parse_lines;
+///
+/// let docs: Vec<_> = parse_lines("{\"a\": 1}\n\n[2\n3\r\n").collect();
+/// assert_eq!(docs.len(), 3);
+/// assert_eq!(docs[0].as_ref().unwrap()["a"].get_uint(), Some(1));
+/// assert!(docs[1].is_err());
+/// assert_eq!(docs[2].as_ref().unwrap().get_uint(), Some(3));
+/// ```
+pub fn parse_lines(input_str: &str) -> Lines<'_> {
+    Lines {
+        lines: input_str.lines(),
+    }
+}
+
+/// Lines parsed per thread in each batch of a [`ParallelLines`].
+const LINES_PER_THREAD: usize = 4096;
+
+/// Iterator over the documents of a JSON Lines buffer parsed across
+/// threads, created by [`parse_lines_parallel`].
+///
+/// Lines are parsed in batches, each thread taking a run of lines, and
+/// yielded in input order, as [`Lines`] yields them. The threads are
+/// started with the first batch and parse every batch that follows, so
+/// a long input does not pay for starting threads over and over. Only
+/// one batch is held at a time, so the memory used does not grow with
+/// the input.
+pub struct ParallelLines<'a> {
+    lines: Lines<'a>,
+    threads: usize,
+    parsed: VecDeque<Result<Container, Box<dyn core::error::Error>>>,
+    workers: Option<Workers>,
+}
+
+/// Documents parsed from a run of lines, sent back by a worker.
+type ParsedRun =
+    Vec<Result<Container, Box<dyn core::error::Error + Send + Sync>>>;
+
+/// The threads of a [`ParallelLines`], taking runs of lines along with
+/// their place in the batch, and sending back their place and documents,
+/// or the panic that stopped parsing them.
+struct Workers {
+    runs: Option<Sender<(usize, Vec<String>)>>,
+    parsed: Receiver<(usize, std::thread::Result<ParsedRun>)>,
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl Workers {
+    fn start(threads: usize) -> Self {
+        let (runs, queue) = mpsc::channel::<(usize, Vec<String>)>();
+        let (done, parsed) = mpsc::channel();
+        let queue = Arc::new(Mutex::new(queue));
+        let handles = (0..threads)
+            .map(|_| {
+                let (queue, done) = (Arc::clone(&queue), done.clone());
+                std::thread::spawn(move || loop {
+                    let next = match queue.lock() {
+                        Ok(queue) => queue.recv(),
+                        Err(_) => return,
+                    };
+                    let Ok((place, lines)) = next else {
+                        return;
+                    };
+                    let documents = std::panic::catch_unwind(|| {
+                        lines
+                            .iter()
+                            .map(|line| {
+                                parse_str(line).map_err(Error::sendable)
+                            })
+                            .collect()
+                    });
+                    if done.send((place, documents)).is_err() {
+                        return;
+                    }
+                })
+            })
+            .collect();
+        Workers {
+            runs: Some(runs),
+            parsed,
+            handles,
+        }
+    }
+}
+
+impl Drop for Workers {
+    fn drop(&mut self) {
+        // Closing the queue ends the threads once their runs are done.
+        self.runs.take();
+        for handle in self.handles.drain(..) {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl ParallelLines<'_> {
+    /// Parses the next batch of lines into `parsed`.
+    fn parse_batch(&mut self) {
+        let batch: Vec<&str> = core::iter::from_fn(|| self.lines.next_line())
+            .take(self.threads * LINES_PER_THREAD)
+            .collect();
+        if batch.is_empty() {
+            return;
+        }
+        let threads = self.threads;
+        let workers =
+            self.workers.get_or_insert_with(|| Workers::start(threads));
+        let runs = workers
+            .runs
+            .as_ref()
+            .expect("the queue is open until the workers drop");
+        let mut sent = 0;
+        for (place, run) in
+            batch.chunks(batch.len().div_ceil(threads)).enumerate()
+        {
+            let run = run.iter().map(|line| line.to_string()).collect();
+            runs.send((place, run))
+                .expect("workers outlive the iterator");
+            sent += 1;
+        }
+        let mut documents: Vec<_> = (0..sent)
+            .map(|_| {
+                workers.parsed.recv().expect("workers outlive the iterator")
+            })
+            .collect();
+        documents.sort_unstable_by_key(|(place, _)| *place);
+        for (_, run) in documents {
+            let run =
+                run.unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+            for document in run {
+                self.parsed.push_back(document.map_err(|error| error as _));
+            }
+        }
+    }
+}
+
+impl Iterator for ParallelLines<'_> {
+    type Item = Result<Container, Box<dyn core::error::Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.parsed.is_empty() {
+            self.parse_batch();
+        }
+        self.parsed.pop_front()
+    }
+}
+
+/// Parse a JSON Lines (NDJSON) buffer like [`parse_lines`], across as
+/// many threads as the machine runs in parallel.
+///
+/// ```
+/// use json_parser::parser::parse_lines_parallel;
+///
+/// let input: String = (0..10_000).map(|n| format!("{{\"n\": {}}}\n", n)).collect();
+/// let docs: Vec<_> = parse_lines_parallel(&input)
+///     .collect::<Result<_, _>>()
+///     .unwrap();
+/// assert_eq!(docs.len(), 10_000);
+/// assert_eq!(docs[9_999]["n"].get_uint(), Some(9_999));
+/// ```
+pub fn parse_lines_parallel(input_str: &str) -> ParallelLines<'_> {
+    let threads = std::thread::available_parallelism().map_or(1, usize::from);
+    ParallelLines {
+        lines: parse_lines(input_str),
+        threads,
+        parsed: VecDeque::new(),
+        workers: None,
+    }
+}
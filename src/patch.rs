@@ -0,0 +1,494 @@
+//! JSON Patch (RFC 6902) support for `Container`.
+//!
+//! A patch is an array of operation objects, each carrying an `op`
+//! member (`add`, `remove`, `replace`, `move`, `copy` or `test`) and the
+//! pointers/values that operation requires.
+//!
+//! [`invert`] and [`compose`] work on patches themselves, to roll back and
+//! squash journals of changes.
+use super::container::Container;
+use super::diff::operation;
+use super::error::{Error, PatchError};
+use super::pointer;
+
+/// A location referenced by an operation: the pointer as written in the
+/// patch together with its unescaped tokens.
+pub(crate) struct Location<'a> {
+    pub(crate) raw: &'a str,
+    pub(crate) tokens: Vec<String>,
+}
+
+/// A single decoded patch operation.
+pub(crate) enum Operation<'a> {
+    Add {
+        path: Location<'a>,
+        value: &'a Container,
+    },
+    Remove {
+        path: Location<'a>,
+    },
+    Replace {
+        path: Location<'a>,
+        value: &'a Container,
+    },
+    Move {
+        from: Location<'a>,
+        path: Location<'a>,
+    },
+    Copy {
+        from: Location<'a>,
+        path: Location<'a>,
+    },
+    Test {
+        path: Location<'a>,
+        value: &'a Container,
+    },
+}
+
+impl<'a> Operation<'a> {
+    /// Decodes the operation object found at `index` of a patch.
+    pub(crate) fn decode(
+        index: usize,
+        operation: &'a Container,
+    ) -> Result<Self, PatchError> {
+        let Container::Object(members) = operation else {
+            return Err(PatchError::MalformedOperation {
+                index,
+                reason: "operation must be an object".to_owned(),
+            });
+        };
+
+        let location = |member: &str| -> Result<Location<'a>, PatchError> {
+            match members.get(member) {
+                Some(Container::String(raw)) => match pointer::split(raw) {
+                    Some(tokens) => Ok(Location { raw, tokens }),
+                    None => Err(PatchError::InvalidPointer {
+                        index,
+                        pointer: raw.to_string(),
+                    }),
+                },
+                _ => Err(PatchError::MalformedOperation {
+                    index,
+                    reason: format!("missing string member {:?}", member),
+                }),
+            }
+        };
+        let value = || -> Result<&'a Container, PatchError> {
+            members
+                .get("value")
+                .ok_or_else(|| PatchError::MalformedOperation {
+                    index,
+                    reason: "missing member \"value\"".to_owned(),
+                })
+        };
+
+        match members.get("op") {
+            Some(Container::String(op)) => match op.as_str() {
+                "add" => Ok(Self::Add {
+                    path: location("path")?,
+                    value: value()?,
+                }),
+                "remove" => Ok(Self::Remove {
+                    path: location("path")?,
+                }),
+                "replace" => Ok(Self::Replace {
+                    path: location("path")?,
+                    value: value()?,
+                }),
+                "move" => Ok(Self::Move {
+                    from: location("from")?,
+                    path: location("path")?,
+                }),
+                "copy" => Ok(Self::Copy {
+                    from: location("from")?,
+                    path: location("path")?,
+                }),
+                "test" => Ok(Self::Test {
+                    path: location("path")?,
+                    value: value()?,
+                }),
+                other => Err(PatchError::MalformedOperation {
+                    index,
+                    reason: format!("unknown operation {:?}", other),
+                }),
+            },
+            _ => Err(PatchError::MalformedOperation {
+                index,
+                reason: "missing string member \"op\"".to_owned(),
+            }),
+        }
+    }
+
+    /// Applies this operation to `document`, `index` being the position
+    /// of the operation inside its patch (used for error reporting).
+    pub(crate) fn apply(
+        &self,
+        document: &mut Container,
+        index: usize,
+    ) -> Result<(), PatchError> {
+        let not_found = |location: &Location| PatchError::PathNotFound {
+            index,
+            path: location.raw.to_owned(),
+        };
+
+        match self {
+            Self::Add { path, value } => {
+                add(document, &path.tokens, (*value).clone())
+                    .ok_or_else(|| not_found(path))
+            }
+            Self::Remove { path } => pointer::remove(document, &path.tokens)
+                .map(|_| ())
+                .ok_or_else(|| not_found(path)),
+            Self::Replace { path, value } => {
+                let target = pointer::resolve_mut(document, &path.tokens)
+                    .ok_or_else(|| not_found(path))?;
+                *target = (*value).clone();
+                Ok(())
+            }
+            Self::Move { from, path } => {
+                if from.tokens == path.tokens {
+                    return pointer::resolve(document, &from.tokens)
+                        .map(|_| ())
+                        .ok_or_else(|| not_found(from));
+                }
+                if path.tokens.starts_with(&from.tokens) {
+                    return Err(PatchError::MalformedOperation {
+                        index,
+                        reason: "cannot move a value into one of its children"
+                            .to_owned(),
+                    });
+                }
+                let value = pointer::remove(document, &from.tokens)
+                    .ok_or_else(|| not_found(from))?;
+                add(document, &path.tokens, value)
+                    .ok_or_else(|| not_found(path))
+            }
+            Self::Copy { from, path } => {
+                let value = pointer::resolve(document, &from.tokens)
+                    .cloned()
+                    .ok_or_else(|| not_found(from))?;
+                add(document, &path.tokens, value)
+                    .ok_or_else(|| not_found(path))
+            }
+            Self::Test { path, value } => {
+                match pointer::resolve(document, &path.tokens) {
+                    Some(found) if found == *value => Ok(()),
+                    Some(_) => Err(PatchError::TestFailed {
+                        index,
+                        path: path.raw.to_owned(),
+                    }),
+                    None => Err(not_found(path)),
+                }
+            }
+        }
+    }
+}
+
+/// Adds `value` at `tokens`: replaces object members, inserts into arrays
+/// (`-` appends) and replaces the whole document for an empty pointer.
+fn add(
+    document: &mut Container,
+    tokens: &[String],
+    value: Container,
+) -> Option<()> {
+    let Some((last, parent)) = tokens.split_last() else {
+        *document = value;
+        return Some(());
+    };
+
+    match pointer::resolve_mut(document, parent)? {
+        Container::Object(map) => {
+            map.insert(last.to_owned(), value);
+            Some(())
+        }
+        Container::Array(array) => {
+            let idx = if last == "-" {
+                array.len()
+            } else {
+                pointer::array_index(last).filter(|idx| *idx <= array.len())?
+            };
+            array.insert(idx, value);
+            Some(())
+        }
+        _ => None,
+    }
+}
+
+impl Container {
+    /// Applies a JSON Patch (RFC 6902) document to self.
+    ///
+    /// Operations run in order on a copy of the document, and self is
+    /// replaced only when all of them succeed: a failing operation (e.g.
+    /// an unsuccessful `test`) leaves the document untouched and reports
+    /// the index of the offending operation.
+    ///
+    /// ```
+    /// use json_parser::parser::parse_str;
+    /// let mut doc = parse_str(r#"{"a": 1}"#).unwrap();
+    /// let patch = parse_str(
+    ///     r#"[{"op": "add", "path": "/b", "value": [2]},
+    ///         {"op": "test", "path": "/a", "value": 1}]"#,
+    /// )
+    /// .unwrap();
+    ///
+    /// doc.apply_patch(&patch).unwrap();
+    /// assert_eq!(doc["b"][0].get_uint(), Some(2));
+    /// ```
+    pub fn apply_patch(
+        &mut self,
+        patch: &Container,
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        let Container::Array(operations) = patch else {
+            return Err(Error::Patch(PatchError::InvalidPatch).into());
+        };
+
+        let mut document = self.clone();
+        for (index, operation) in operations.iter().enumerate() {
+            Operation::decode(index, operation)
+                .and_then(|decoded| decoded.apply(&mut document, index))
+                .map_err(Error::Patch)?;
+        }
+
+        *self = document;
+        Ok(())
+    }
+
+    /// Checks that a JSON Patch applies to self, without changing self:
+    /// every operation that would fail is reported, not only the first.
+    ///
+    /// Operations run in order on a copy of the document. One that fails
+    /// is left out, so the issues of the operations after it may follow
+    /// from it.
+    ///
+    /// ```
+    /// use json_parser::error::PatchError;
+    /// use json_parser::parser::parse_str;
+    ///
+    /// let doc = parse_str(r#"{"a": 1}"#).unwrap();
+    /// let patch = parse_str(
+    ///     r#"[{"op": "remove", "path": "/b"},
+    ///         {"op": "add", "path": "/b", "value": 2},
+    ///         {"op": "test", "path": "/a", "value": 2},
+    ///         {"op": "replace", "path": "/b", "value": 3}]"#,
+    /// )
+    /// .unwrap();
+    ///
+    /// let issues = doc.check_patch(&patch);
+    /// assert_eq!(issues.len(), 2);
+    /// assert!(matches!(issues[0], PatchError::PathNotFound { index: 0, .. }));
+    /// assert!(matches!(issues[1], PatchError::TestFailed { index: 2, .. }));
+    /// ```
+    pub fn check_patch(&self, patch: &Container) -> Vec<PatchIssue> {
+        let Container::Array(operations) = patch else {
+            return vec![PatchError::InvalidPatch];
+        };
+
+        let mut document = self.clone();
+        operations
+            .iter()
+            .enumerate()
+            .filter_map(|(index, operation)| {
+                Operation::decode(index, operation)
+                    .and_then(|decoded| decoded.apply(&mut document, index))
+                    .err()
+            })
+            .collect()
+    }
+}
+
+/// A reason why an operation of a patch does not apply, found by
+/// [`Container::check_patch`].
+pub type PatchIssue = PatchError;
+
+/// The operations of `patch`, with their objects.
+fn decode_all(
+    patch: &Container,
+) -> Result<Vec<(&Container, Operation<'_>)>, Error> {
+    let Container::Array(operations) = patch else {
+        return Err(Error::Patch(PatchError::InvalidPatch));
+    };
+    operations
+        .iter()
+        .enumerate()
+        .map(|(index, operation)| {
+            Ok((operation, Operation::decode(index, operation)?))
+        })
+        .collect::<Result<_, _>>()
+        .map_err(Error::Patch)
+}
+
+/// The JSON Pointer made of `tokens`.
+fn join(tokens: &[String]) -> String {
+    tokens
+        .iter()
+        .map(|token| format!("/{}", pointer::escape(token)))
+        .collect()
+}
+
+/// Where an addition at `tokens` to `document` puts its value, `-` being
+/// resolved, and the value it replaces there, if any.
+fn added_at(
+    document: &Container,
+    tokens: &[String],
+) -> (String, Option<Container>) {
+    let Some((last, parent)) = tokens.split_last() else {
+        return (String::new(), Some(document.clone()));
+    };
+    match pointer::resolve(document, parent) {
+        Some(Container::Object(map)) => {
+            (join(tokens), map.get(last.as_str()).cloned())
+        }
+        Some(Container::Array(array)) if last == "-" => {
+            (format!("{}/{}", join(parent), array.len()), None)
+        }
+        _ => (join(tokens), None),
+    }
+}
+
+/// Returns the patch undoing `patch`, which turns `original` into another
+/// document: applied to that document, it gives `original` back.
+///
+/// Each operation is undone from what it overwrote in `original`, as
+/// changed by the operations before it, so `patch` must apply to
+/// `original`.
+///
+/// ```
+/// use json_parser::parser::parse_str;
+/// use json_parser::patch::invert;
+///
+/// let original = parse_str(r#"{"a": 1, "b": 0, "list": [1, 2]}"#).unwrap();
+/// let patch = parse_str(
+///     r#"[{"op": "replace", "path": "/a", "value": 2},
+///         {"op": "add", "path": "/list/-", "value": 3},
+///         {"op": "move", "from": "/list", "path": "/b"}]"#,
+/// )
+/// .unwrap();
+///
+/// let mut document = original.clone();
+/// document.apply_patch(&patch).unwrap();
+/// document.apply_patch(&invert(&patch, &original).unwrap()).unwrap();
+/// assert_eq!(document, original);
+/// ```
+pub fn invert(
+    patch: &Container,
+    original: &Container,
+) -> Result<Container, Box<dyn core::error::Error>> {
+    let mut document = original.clone();
+    let mut undo = Vec::new();
+    for (index, (object, decoded)) in decode_all(patch)?.iter().enumerate() {
+        let steps = match decoded {
+            Operation::Add { path, .. } | Operation::Copy { path, .. } => {
+                match added_at(&document, &path.tokens) {
+                    (path, Some(old)) => {
+                        vec![operation("replace", &path, Some(&old))]
+                    }
+                    (path, None) => vec![operation("remove", &path, None)],
+                }
+            }
+            Operation::Remove { path } => {
+                pointer::resolve(&document, &path.tokens)
+                    .map(|old| operation("add", path.raw, Some(old)))
+                    .into_iter()
+                    .collect()
+            }
+            Operation::Replace { path, .. } => {
+                pointer::resolve(&document, &path.tokens)
+                    .map(|old| operation("replace", path.raw, Some(old)))
+                    .into_iter()
+                    .collect()
+            }
+            Operation::Move { from, path } if from.tokens == path.tokens => {
+                Vec::new()
+            }
+            Operation::Move { path, .. } if path.tokens.is_empty() => {
+                vec![operation("replace", "", Some(&document))]
+            }
+            Operation::Move { from, path } => {
+                let mut moved = document.clone();
+                pointer::remove(&mut moved, &from.tokens);
+                let (to, old) = added_at(&moved, &path.tokens);
+                let mut back = operation("move", from.raw, None);
+                back.insert_str("from", Container::from(to.as_str()));
+                let mut steps = vec![back];
+                steps.extend(old.map(|old| operation("add", &to, Some(&old))));
+                steps
+            }
+            Operation::Test { .. } => vec![(*object).clone()],
+        };
+        decoded.apply(&mut document, index).map_err(Error::Patch)?;
+        undo.push(steps);
+    }
+    Ok(Container::Array(undo.into_iter().rev().flatten().collect()))
+}
+
+/// Returns one patch doing what `first` then `second` do.
+///
+/// The operations are chained, and an operation followed by another one
+/// on the same path that makes it useless is dropped: a `replace` by
+/// another `replace` or by a `remove`, and the value of an `add` by a
+/// `replace`.
+///
+/// ```
+/// use json_parser::parser::parse_str;
+/// use json_parser::patch::compose;
+///
+/// let first = parse_str(
+///     r#"[{"op": "add", "path": "/list/0", "value": 1},
+///         {"op": "replace", "path": "/a", "value": 1}]"#,
+/// )
+/// .unwrap();
+/// let second = parse_str(
+///     r#"[{"op": "replace", "path": "/a", "value": 2},
+///         {"op": "replace", "path": "/a", "value": 3}]"#,
+/// )
+/// .unwrap();
+/// let expected = parse_str(
+///     r#"[{"op": "add", "path": "/list/0", "value": 1},
+///         {"op": "replace", "path": "/a", "value": 3}]"#,
+/// )
+/// .unwrap();
+/// assert_eq!(compose(&first, &second).unwrap(), expected);
+/// ```
+pub fn compose(
+    first: &Container,
+    second: &Container,
+) -> Result<Container, Box<dyn core::error::Error>> {
+    let (first, second) = (decode_all(first)?, decode_all(second)?);
+    let mut composed: Vec<Container> = Vec::new();
+    let mut last: Option<Operation> = None;
+    for (object, decoded) in first.into_iter().chain(second) {
+        let merged = match (&last, &decoded) {
+            (
+                Some(Operation::Replace { path: before, .. }),
+                Operation::Replace { path, .. } | Operation::Remove { path },
+            ) if before.tokens == path.tokens => Some(object.clone()),
+            (
+                Some(Operation::Add { path: before, .. }),
+                Operation::Replace { path, value },
+            ) if before.tokens == path.tokens
+                && path.tokens.last().is_some_and(|last| last != "-") =>
+            {
+                Some(operation("add", before.raw, Some(value)))
+            }
+            _ => None,
+        };
+        match merged {
+            Some(merged) => {
+                if let Some(previous) = composed.last_mut() {
+                    *previous = merged;
+                }
+                // An `add` stays one, and is matched as one by what follows.
+                if let (
+                    Some(Operation::Add { .. }),
+                    Operation::Replace { .. },
+                ) = (&last, &decoded)
+                {
+                    continue;
+                }
+            }
+            None => composed.push(object.clone()),
+        }
+        last = Some(decoded);
+    }
+    Ok(Container::Array(composed))
+}
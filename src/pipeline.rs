@@ -0,0 +1,270 @@
+//! Bulk processing of JSON Lines across threads: a byte source is split
+//! into lines, parsed and transformed by a pool of workers, and written
+//! to a sink, one compact document per line.
+//!
+//! [`run`] reads the source on its own thread and writes the sink on the
+//! calling one. Lines are handed to the workers, and their documents to
+//! the sink, through bounded queues, so a slow sink holds back reading
+//! instead of piling up documents. In [`Order::Input`] the workers also
+//! stay within a window of lines past the next one to write, so a slow
+//! line cannot pile up the documents finished after it.
+use super::container::Container;
+use super::error::Error;
+use super::parser::{parse_with_options, ParserOptions};
+use std::collections::BTreeMap;
+use std::io::{BufRead, Write};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+
+/// Lines queued per worker before reading waits.
+const QUEUE_PER_WORKER: usize = 64;
+
+/// Order in which [`run`] writes the documents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Order {
+    /// The order of the input lines, holding back documents finished
+    /// early until those before them are written.
+    #[default]
+    Input,
+    /// As soon as each document is finished.
+    Completion,
+}
+
+/// What a run of [`run`] did.
+#[derive(Debug, Default)]
+pub struct Summary {
+    /// Documents read from the source
+    pub read: usize,
+    /// Documents written to the sink
+    pub written: usize,
+    /// Documents the transform dropped
+    pub dropped: usize,
+    /// Line number, from 1, and error of every line that failed to parse
+    pub errors: Vec<(usize, Box<dyn core::error::Error>)>,
+}
+
+/// A line to handle: its place among the documents, its number and its
+/// text.
+type Job = (usize, usize, String);
+
+/// What a worker made of a line.
+enum Outcome {
+    Written(String),
+    Dropped,
+    Failed(Box<dyn core::error::Error + Send + Sync>),
+}
+
+/// The places of the lines the workers may handle: fewer than `size`
+/// past the next one to write.
+struct Window {
+    next: Mutex<usize>,
+    moved: Condvar,
+    size: usize,
+}
+
+impl Window {
+    fn new(size: usize) -> Self {
+        Window {
+            next: Mutex::new(0),
+            moved: Condvar::new(),
+            size,
+        }
+    }
+
+    fn next(&self) -> MutexGuard<'_, usize> {
+        self.next.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Waits until the line at `place` is inside the window.
+    fn enter(&self, place: usize) {
+        let mut next = self.next();
+        while place >= next.saturating_add(self.size) {
+            next = self
+                .moved
+                .wait(next)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+
+    /// Moves the window to start at `place`.
+    fn advance(&self, place: usize) {
+        *self.next() = place;
+        self.moved.notify_all();
+    }
+
+    /// Lets every line in, once nothing more is written.
+    fn close(&self) {
+        self.advance(usize::MAX);
+    }
+}
+
+/// Runs the documents of `source`, one per line, through `transform` on
+/// `workers` threads, and writes the documents it returns to `sink` as
+/// JSON Lines, in the given `order`. Blank lines are skipped, and so are
+/// lines that fail to parse, which are reported in the summary. Every
+/// document is written as valid JSON, non-finite numbers as `null`.
+///
+/// Reading or writing failures stop the run, and are returned once the
+/// workers are done.
+///
+/// ```
+/// use json_parser::parser::ParserOptions;
+/// use json_parser::pipeline::{run, Order};
+///
+/// let input = "{\"n\": 1}\n{\"n\": 2}\n{oops}\n{\"n\": 4}\n";
+/// let mut output = Vec::new();
+/// let summary = run(
+///     input.as_bytes(),
+///     &ParserOptions::default(),
+///     |mut doc| {
+///         let n = doc["n"].get_uint()?;
+///         (n % 2 == 0).then(|| {
+///             doc.insert_str("half", (n / 2).into());
+///             doc
+///         })
+///     },
+///     &mut output,
+///     4,
+///     Order::Input,
+/// )
+/// .unwrap();
+///
+/// assert_eq!(
+///     String::from_utf8(output).unwrap(),
+///     "{\"n\":2,\"half\":1}\n{\"n\":4,\"half\":2}\n"
+/// );
+/// assert_eq!((summary.read, summary.written, summary.dropped), (4, 2, 1));
+/// assert_eq!(summary.errors[0].0, 3);
+/// ```
+pub fn run<F>(
+    source: impl BufRead + Send,
+    options: &ParserOptions,
+    transform: F,
+    sink: &mut impl Write,
+    workers: usize,
+    order: Order,
+) -> Result<Summary, Box<dyn core::error::Error>>
+where
+    F: Fn(Container) -> Option<Container> + Sync,
+{
+    let workers = workers.max(1);
+    let (lines, queue) = mpsc::sync_channel(workers * QUEUE_PER_WORKER);
+    // Owned by the workers only, so that reading stops once they are gone.
+    let queue = Arc::new(Mutex::new(queue));
+    let (outcomes, finished) = mpsc::sync_channel(workers * QUEUE_PER_WORKER);
+    let window = Window::new(match order {
+        Order::Input => workers * QUEUE_PER_WORKER,
+        Order::Completion => usize::MAX,
+    });
+
+    let (written, read) = std::thread::scope(|scope| {
+        let reader = scope.spawn(move || -> std::io::Result<usize> {
+            let mut read = 0;
+            for (idx, text) in source.lines().enumerate() {
+                let text = text?;
+                if text.trim().is_empty() {
+                    continue;
+                }
+                // Fails once the workers stopped, the sink having failed.
+                if lines.send((read, idx + 1, text)).is_err() {
+                    break;
+                }
+                read += 1;
+            }
+            Ok(read)
+        });
+        for _ in 0..workers {
+            let (queue, outcomes) = (queue.clone(), outcomes.clone());
+            let (transform, window) = (&transform, &window);
+            scope.spawn(move || {
+                work(&queue, options, transform, window, &outcomes)
+            });
+        }
+        drop((queue, outcomes));
+
+        let written = write(finished, sink, order, &window);
+        window.close();
+        let read = reader
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+        (written, read)
+    });
+
+    let mut summary = written?;
+    summary.read = read?;
+    summary.errors.sort_by_key(|(line, _)| *line);
+    Ok(summary)
+}
+
+/// Handles the lines of `queue` until there are none left.
+fn work<F>(
+    queue: &Mutex<Receiver<Job>>,
+    options: &ParserOptions,
+    transform: &F,
+    window: &Window,
+    outcomes: &SyncSender<(usize, usize, Outcome)>,
+) where
+    F: Fn(Container) -> Option<Container>,
+{
+    loop {
+        let next = match queue.lock() {
+            Ok(queue) => queue.recv(),
+            Err(_) => return,
+        };
+        let Ok((place, line, text)) = next else {
+            return;
+        };
+        window.enter(place);
+        let outcome = match parse_with_options(&text, options) {
+            Ok(document) => match transform(document) {
+                Some(document) => Outcome::Written(document.to_json_string()),
+                None => Outcome::Dropped,
+            },
+            Err(error) => Outcome::Failed(Error::sendable(error)),
+        };
+        if outcomes.send((place, line, outcome)).is_err() {
+            return;
+        }
+    }
+}
+
+/// Writes the outcomes to `sink` in `order`, moving the `window` along,
+/// and returns the summary.
+fn write(
+    finished: Receiver<(usize, usize, Outcome)>,
+    sink: &mut impl Write,
+    order: Order,
+    window: &Window,
+) -> Result<Summary, Box<dyn core::error::Error>> {
+    let mut summary = Summary::default();
+    // Outcomes held back in input order, and the place of the next one.
+    let mut pending = BTreeMap::new();
+    let mut next = 0;
+    for (place, line, outcome) in finished {
+        let ready = match order {
+            Order::Completion => vec![(line, outcome)],
+            Order::Input => {
+                pending.insert(place, (line, outcome));
+                let mut ready = Vec::new();
+                while let Some(entry) = pending.remove(&next) {
+                    ready.push(entry);
+                    next += 1;
+                }
+                window.advance(next);
+                ready
+            }
+        };
+        for (line, outcome) in ready {
+            match outcome {
+                Outcome::Written(text) => {
+                    writeln!(sink, "{}", text)?;
+                    summary.written += 1;
+                }
+                Outcome::Dropped => summary.dropped += 1,
+                Outcome::Failed(error) => summary.errors.push((line, error)),
+            }
+        }
+    }
+    sink.flush()?;
+    Ok(summary)
+}
@@ -0,0 +1,204 @@
+//! Resolution of JSON Pointers (RFC 6901) against a `Container`.
+//!
+//! A pointer is either empty (the whole document) or a sequence of
+//! `/`-prefixed reference tokens, where `~1` stands for `/` and `~0`
+//! stands for `~` inside a token.
+use super::container::Container;
+
+/// Splits a pointer into its unescaped reference tokens.
+///
+/// Returns `None` if the pointer is non-empty and does not start with `/`.
+pub(crate) fn split(pointer: &str) -> Option<Vec<String>> {
+    if pointer.is_empty() {
+        return Some(Vec::new());
+    }
+
+    pointer.strip_prefix('/').map(|rest| {
+        rest.split('/')
+            .map(|token| token.replace("~1", "/").replace("~0", "~"))
+            .collect()
+    })
+}
+
+/// Escapes a single key so that it can be appended to a pointer.
+pub(crate) fn escape(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+/// Parses an array index token: only plain digits without leading
+/// zeros are accepted.
+pub(crate) fn array_index(token: &str) -> Option<usize> {
+    let valid = !token.is_empty()
+        && token.bytes().all(|b| b.is_ascii_digit())
+        && (token.len() == 1 || !token.starts_with('0'));
+
+    valid.then(|| token.parse().ok()).flatten()
+}
+
+/// Walks the tokens from `root`, returning the referenced value.
+pub(crate) fn resolve<'a>(
+    root: &'a Container,
+    tokens: &[String],
+) -> Option<&'a Container> {
+    tokens.iter().try_fold(root, |node, token| match node {
+        Container::Object(map) => map.get(token),
+        Container::Array(array) => {
+            array_index(token).and_then(|idx| array.get(idx))
+        }
+        _ => None,
+    })
+}
+
+/// Mutable counterpart of [`resolve`].
+pub(crate) fn resolve_mut<'a>(
+    root: &'a mut Container,
+    tokens: &[String],
+) -> Option<&'a mut Container> {
+    tokens.iter().try_fold(root, |node, token| match node {
+        Container::Object(map) => map.get_mut(token),
+        Container::Array(array) => {
+            array_index(token).and_then(move |idx| array.get_mut(idx))
+        }
+        _ => None,
+    })
+}
+
+/// Removes and returns the value at `tokens`; the document root cannot
+/// be removed.
+pub(crate) fn remove(
+    root: &mut Container,
+    tokens: &[String],
+) -> Option<Container> {
+    let (last, parent) = tokens.split_last()?;
+
+    match resolve_mut(root, parent)? {
+        Container::Object(map) => map.remove(last),
+        Container::Array(array) => array_index(last)
+            .filter(|idx| *idx < array.len())
+            .map(|idx| array.remove(idx)),
+        _ => None,
+    }
+}
+
+/// A new container to hold the member `token`: an array if it is an index
+/// or `-`, an object otherwise.
+fn new_parent(token: &str) -> Container {
+    if token == "-" || array_index(token).is_some() {
+        Container::new_array()
+    } else {
+        Container::new_object()
+    }
+}
+
+/// Writes `value` at `tokens`, creating the containers on the way.
+/// Arrays are only written into by index, or appended to with `-`.
+pub(crate) fn insert(
+    root: &mut Container,
+    tokens: &[String],
+    value: Container,
+) -> Option<()> {
+    let Some((last, parents)) = tokens.split_last() else {
+        *root = value;
+        return Some(());
+    };
+    let mut node = root;
+    for (idx, token) in parents.iter().enumerate() {
+        node = match node {
+            Container::Object(map) => {
+                if !map.contains_key(token) {
+                    map.insert(token.clone(), new_parent(&tokens[idx + 1]));
+                }
+                map.get_mut(token)?
+            }
+            Container::Array(array) => array.get_mut(array_index(token)?)?,
+            _ => return None,
+        };
+    }
+    match node {
+        Container::Object(map) => {
+            map.insert(last.clone(), value);
+        }
+        Container::Array(array) if last == "-" => array.push(value),
+        Container::Array(array) => {
+            let idx = array_index(last)?;
+            match idx.cmp(&array.len()) {
+                core::cmp::Ordering::Less => array[idx] = value,
+                core::cmp::Ordering::Equal => array.push(value),
+                core::cmp::Ordering::Greater => return None,
+            }
+        }
+        _ => return None,
+    }
+    Some(())
+}
+
+impl Container {
+    /// Looks up a value by a JSON Pointer such as `/users/0/name`.
+    ///
+    /// Returns `None` if the pointer is malformed or does not resolve.
+    ///
+    /// ```
+    /// use json_parser::parser::parse_str;
+    /// let doc = parse_str(r#"{"a/b": [10, 20]}"#).unwrap();
+    /// assert_eq!(doc.pointer("/a~1b/1").and_then(|c| c.get_uint()), Some(20));
+    /// assert!(doc.pointer("/a~1b/2").is_none());
+    /// ```
+    pub fn pointer(&self, pointer: &str) -> Option<&Container> {
+        split(pointer).and_then(|tokens| resolve(self, &tokens))
+    }
+
+    /// Looks up a value by a JSON Pointer to change it in place.
+    ///
+    /// Returns `None` if the pointer is malformed or does not resolve;
+    /// unlike `IndexMut`, nothing is created on the way.
+    ///
+    /// ```
+    /// use json_parser::parser::parse_str;
+    /// let mut doc = parse_str(r#"{"a": {"b": [1, 2]}}"#).unwrap();
+    /// *doc.pointer_mut("/a/b/0").unwrap() = 10.into();
+    /// assert_eq!(doc, parse_str(r#"{"a": {"b": [10, 2]}}"#).unwrap());
+    /// assert!(doc.pointer_mut("/a/c").is_none());
+    /// assert_eq!(doc["a"].len(), 1);
+    /// ```
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut Container> {
+        split(pointer).and_then(|tokens| resolve_mut(self, &tokens))
+    }
+
+    /// Removes the subtree at `pointer` and returns it, avoiding a clone of
+    /// the part being split off. Detaching the root (`""`) leaves `Null`
+    /// behind.
+    ///
+    /// Returns `None` (leaving self untouched) if the pointer is malformed
+    /// or does not resolve.
+    ///
+    /// ```
+    /// use json_parser::parser::parse_str;
+    /// let mut doc = parse_str(r#"{"keep": 1, "split": {"big": [1, 2]}}"#).unwrap();
+    ///
+    /// let split = doc.detach("/split").unwrap();
+    /// assert_eq!(split, parse_str(r#"{"big": [1, 2]}"#).unwrap());
+    /// assert_eq!(doc, parse_str(r#"{"keep": 1}"#).unwrap());
+    /// ```
+    pub fn detach(&mut self, pointer: &str) -> Option<Container> {
+        let tokens = split(pointer)?;
+        if tokens.is_empty() {
+            return Some(core::mem::replace(self, Container::Null));
+        }
+        remove(self, &tokens)
+    }
+
+    /// Detaches every pointer in `pointers`, returning the subtrees in the
+    /// same order (`None` for pointers that did not resolve).
+    ///
+    /// Pointers are applied one after another, so array indices refer to
+    /// the document as left by the previous extractions.
+    pub fn extract_paths(
+        &mut self,
+        pointers: &[&str],
+    ) -> Vec<Option<Container>> {
+        pointers
+            .iter()
+            .map(|pointer| self.detach(pointer))
+            .collect()
+    }
+}
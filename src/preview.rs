@@ -0,0 +1,121 @@
+//! Truncated previews of documents, e.g. for logging payload samples.
+use super::container::Container;
+use super::split::{compact_len, string_len};
+
+/// Appended to cut strings, and starting the markers of elided elements.
+const ELLIPSIS: &str = "…";
+
+/// Buffer kept for the marker of elided elements while filling a
+/// container, e.g. `,"… 12 more"`.
+const MARKER_RESERVE: usize = 20;
+
+/// Builds the preview of `value` within `budget` bytes of compact JSON.
+fn preview_into(value: &Container, budget: usize) -> Container {
+    if compact_len(value) <= budget {
+        return value.clone();
+    }
+
+    match value {
+        Container::String(text) => Container::String(cut(text, budget).into()),
+        Container::Array(values) => {
+            let mut remaining = budget.saturating_sub(2 + MARKER_RESERVE);
+            let mut kept = Vec::new();
+            for value in values {
+                let Some(child) = fit(value, &mut remaining) else {
+                    break;
+                };
+                kept.push(child);
+            }
+
+            let elided = values.len() - kept.len();
+            if elided > 0 {
+                kept.push(Container::String(marker(elided).into()));
+            }
+            Container::Array(kept)
+        }
+        Container::Object(map) => {
+            let mut keys: Vec<&str> = map.keys().collect();
+            keys.sort_unstable();
+
+            let mut remaining = budget.saturating_sub(2 + MARKER_RESERVE);
+            let mut kept = Container::new_object();
+            let mut count = 0;
+            for key in keys {
+                let key_len = string_len(key) + 1;
+                let Some(left) = remaining.checked_sub(key_len) else {
+                    break;
+                };
+                remaining = left;
+                let Some(child) = fit(&map[key], &mut remaining) else {
+                    break;
+                };
+                kept.insert_str(key, child);
+                count += 1;
+            }
+
+            let elided = map.len() - count;
+            if elided > 0 {
+                kept.insert_str(
+                    ELLIPSIS,
+                    Container::String(marker(elided).into()),
+                );
+            }
+            kept
+        }
+        // Literals and numbers cannot be shortened.
+        _ => value.clone(),
+    }
+}
+
+/// Previews an element of a container, charging its size and separator
+/// to `remaining`, or returns `None` when not even a preview fits.
+fn fit(value: &Container, remaining: &mut usize) -> Option<Container> {
+    let child = preview_into(value, remaining.checked_sub(1)?);
+    *remaining = remaining.checked_sub(compact_len(&child) + 1)?;
+    Some(child)
+}
+
+/// Describes `count` elided elements.
+fn marker(count: usize) -> String {
+    format!("{} {} more", ELLIPSIS, count)
+}
+
+/// Cuts `text` at a character boundary so that the quoted result,
+/// ellipsis included, takes at most `budget` bytes.
+fn cut(text: &str, budget: usize) -> String {
+    let mut out = String::new();
+    let mut used = 2 + ELLIPSIS.len();
+    for chr in text.chars() {
+        used += string_len(chr.encode_utf8(&mut [0; 4])) - 2;
+        if used > budget {
+            break;
+        }
+        out.push(chr);
+    }
+    out.push_str(ELLIPSIS);
+    out
+}
+
+impl Container {
+    /// Returns a truncated copy of self whose compact JSON takes about
+    /// `max_bytes`: long strings are cut with `…`, and elements of arrays
+    /// and objects that do not fit are replaced by a `"… N more"` marker
+    /// (under a `"…"` key in objects, whose members are kept in sorted
+    /// order).
+    ///
+    /// The preview is always a valid document; markers and numbers are
+    /// kept whole, so very small budgets may be exceeded.
+    ///
+    /// ```
+    /// use json_parser::json;
+    ///
+    /// let doc = json!(["a", "b", "c", "d", "e", "f", "g", "h", "i", "j", "k"]);
+    /// assert_eq!(doc.preview(30), json!(["a", "b", "… 9 more"]));
+    ///
+    /// let doc = json!("a rather long string");
+    /// assert_eq!(doc.preview(12), json!("a rathe…"));
+    /// ```
+    pub fn preview(&self, max_bytes: usize) -> Container {
+        preview_into(self, max_bytes)
+    }
+}
@@ -0,0 +1,456 @@
+//! Regular expressions for validating string values.
+//!
+//! The syntax is the common subset of the usual engines: literals, `.`,
+//! classes such as `[a-z_]` or `[^,]`, the ASCII classes `\d`, `\w`, `\s`
+//! and their negations, anchors `^` and `$`, groups, alternation and the
+//! quantifiers `*`, `+`, `?` and `{n,m}`, with counts of at most
+//! `MAX_REPEAT`.
+//!
+//! Patterns are compiled to a program run as a Pike VM: every way through
+//! the program is followed at once, one character of the string at a
+//! time, so matching takes time linear in the string and never recurses.
+//!
+//! Validation code checks many values against few patterns, so compiled
+//! patterns are cached per thread, keyed by the pattern.
+use super::container::Container;
+use super::net::{cached, Cache};
+use core::cell::RefCell;
+use core::iter::Peekable;
+use core::str::Chars;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+thread_local! {
+    static PATTERNS: Cache<Rc<Regex>> = RefCell::new(HashMap::new());
+}
+
+const DIGIT: &[(char, char)] = &[('0', '9')];
+const WORD: &[(char, char)] = &[('0', '9'), ('A', 'Z'), ('_', '_'), ('a', 'z')];
+const SPACE: &[(char, char)] = &[('\t', '\r'), (' ', ' ')];
+
+/// A literal character, or a class and whether it is negated.
+type Escape = Result<char, (&'static [(char, char)], bool)>;
+
+#[derive(Debug)]
+enum Node {
+    Char(char),
+    Any,
+    Class {
+        ranges: Vec<(char, char)>,
+        negated: bool,
+    },
+    Start,
+    End,
+    Concat(Vec<Node>),
+    Alternation(Vec<Node>),
+    Repeat {
+        node: Box<Node>,
+        min: u32,
+        max: Option<u32>,
+    },
+}
+
+/// Largest count of a `{n,m}` quantifier.
+const MAX_REPEAT: u32 = 1000;
+
+/// Most instructions a pattern compiles to; larger patterns are invalid.
+const MAX_PROGRAM: usize = 1 << 16;
+
+/// An instruction of a compiled pattern.
+#[derive(Debug)]
+enum Inst {
+    Char(char),
+    Any,
+    Class {
+        ranges: Vec<(char, char)>,
+        negated: bool,
+    },
+    Start,
+    End,
+    /// Continue at both places
+    Split(usize, usize),
+    Jump(usize),
+    Match,
+}
+
+impl Inst {
+    /// Whether the instruction reads `c`.
+    fn accepts(&self, c: char) -> bool {
+        match self {
+            Inst::Char(expected) => c == *expected,
+            Inst::Any => c != '\n',
+            Inst::Class { ranges, negated } => {
+                let inside = ranges
+                    .iter()
+                    .any(|(start, end)| (*start..=*end).contains(&c));
+                inside != *negated
+            }
+            _ => false,
+        }
+    }
+}
+
+/// A compiled pattern.
+#[derive(Debug)]
+pub(crate) struct Regex {
+    program: Vec<Inst>,
+}
+
+/// Appends the instructions of `node` to `program`.
+fn emit(node: &Node, program: &mut Vec<Inst>) -> Option<()> {
+    if program.len() > MAX_PROGRAM {
+        return None;
+    }
+    match node {
+        Node::Char(c) => program.push(Inst::Char(*c)),
+        Node::Any => program.push(Inst::Any),
+        Node::Class { ranges, negated } => program.push(Inst::Class {
+            ranges: ranges.clone(),
+            negated: *negated,
+        }),
+        Node::Start => program.push(Inst::Start),
+        Node::End => program.push(Inst::End),
+        Node::Concat(nodes) => {
+            for node in nodes {
+                emit(node, program)?;
+            }
+        }
+        Node::Alternation(branches) => {
+            let mut jumps = Vec::new();
+            for (idx, branch) in branches.iter().enumerate() {
+                if idx + 1 == branches.len() {
+                    emit(branch, program)?;
+                    break;
+                }
+                let split = program.len();
+                program.push(Inst::Split(split + 1, 0));
+                emit(branch, program)?;
+                jumps.push(program.len());
+                program.push(Inst::Jump(0));
+                program[split] = Inst::Split(split + 1, program.len());
+            }
+            for jump in jumps {
+                program[jump] = Inst::Jump(program.len());
+            }
+        }
+        Node::Repeat { node, min, max } => {
+            for _ in 0..*min {
+                emit(node, program)?;
+            }
+            match max {
+                None => {
+                    let split = program.len();
+                    program.push(Inst::Split(split + 1, 0));
+                    emit(node, program)?;
+                    program.push(Inst::Jump(split));
+                    program[split] = Inst::Split(split + 1, program.len());
+                }
+                Some(max) => {
+                    let mut splits = Vec::new();
+                    for _ in *min..*max {
+                        splits.push(program.len());
+                        program.push(Inst::Split(0, 0));
+                        emit(node, program)?;
+                    }
+                    for split in splits {
+                        program[split] = Inst::Split(split + 1, program.len());
+                    }
+                }
+            }
+        }
+    }
+    Some(())
+}
+
+/// The instructions reached in a step of the VM, each once.
+struct Threads {
+    /// The reached instructions reading a character
+    list: Vec<usize>,
+    reached: Vec<usize>,
+    seen: Vec<bool>,
+}
+
+impl Threads {
+    fn new(len: usize) -> Self {
+        Self {
+            list: Vec::new(),
+            reached: Vec::new(),
+            seen: vec![false; len],
+        }
+    }
+
+    fn clear(&mut self) {
+        self.list.clear();
+        for pc in self.reached.drain(..) {
+            self.seen[pc] = false;
+        }
+    }
+}
+
+struct Compiler<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl<'a> Compiler<'a> {
+    fn alternation(&mut self) -> Option<Node> {
+        let mut branches = vec![self.concat()?];
+        while self.chars.next_if_eq(&'|').is_some() {
+            branches.push(self.concat()?);
+        }
+        Some(match branches.len() {
+            1 => branches.pop()?,
+            _ => Node::Alternation(branches),
+        })
+    }
+
+    fn concat(&mut self) -> Option<Node> {
+        let mut nodes = Vec::new();
+        while let Some(&c) = self.chars.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let atom = self.atom()?;
+            nodes.push(self.quantified(atom)?);
+        }
+        Some(Node::Concat(nodes))
+    }
+
+    fn atom(&mut self) -> Option<Node> {
+        Some(match self.chars.next()? {
+            '(' => {
+                // Groups do not capture, so `(?:` is the same as `(`.
+                if self.chars.next_if_eq(&'?').is_some() {
+                    self.chars.next_if_eq(&':')?;
+                }
+                let node = self.alternation()?;
+                self.chars.next_if_eq(&')')?;
+                node
+            }
+            '[' => self.class()?,
+            '.' => Node::Any,
+            '^' => Node::Start,
+            '$' => Node::End,
+            '\\' => match self.escape()? {
+                Ok(c) => Node::Char(c),
+                Err((ranges, negated)) => Node::Class {
+                    ranges: ranges.to_vec(),
+                    negated,
+                },
+            },
+            '*' | '+' | '?' | '{' | ')' => return None,
+            c => Node::Char(c),
+        })
+    }
+
+    /// The character or the class named by the escape after a `\`.
+    fn escape(&mut self) -> Option<Escape> {
+        Some(match self.chars.next()? {
+            'd' => Err((DIGIT, false)),
+            'D' => Err((DIGIT, true)),
+            'w' => Err((WORD, false)),
+            'W' => Err((WORD, true)),
+            's' => Err((SPACE, false)),
+            'S' => Err((SPACE, true)),
+            'n' => Ok('\n'),
+            'r' => Ok('\r'),
+            't' => Ok('\t'),
+            c if c.is_ascii_punctuation() => Ok(c),
+            _ => return None,
+        })
+    }
+
+    fn class(&mut self) -> Option<Node> {
+        let negated = self.chars.next_if_eq(&'^').is_some();
+        let mut ranges = Vec::new();
+        let mut first = true;
+        loop {
+            let start = match self.chars.next()? {
+                ']' if !first => break,
+                '\\' => match self.escape()? {
+                    Ok(c) => c,
+                    Err((class, false)) => {
+                        ranges.extend_from_slice(class);
+                        first = false;
+                        continue;
+                    }
+                    Err((_, true)) => return None,
+                },
+                c => c,
+            };
+            first = false;
+            let end = match self.chars.next_if_eq(&'-') {
+                Some(_) if self.chars.peek() == Some(&']') => {
+                    ranges.push(('-', '-'));
+                    start
+                }
+                Some(_) => match self.chars.next()? {
+                    '\\' => self.escape()?.ok()?,
+                    c => c,
+                },
+                None => start,
+            };
+            if end < start {
+                return None;
+            }
+            ranges.push((start, end));
+        }
+        Some(Node::Class { ranges, negated })
+    }
+
+    fn quantified(&mut self, node: Node) -> Option<Node> {
+        let (min, max) = match self.chars.peek() {
+            Some('*') => (0, None),
+            Some('+') => (1, None),
+            Some('?') => (0, Some(1)),
+            Some('{') => {
+                self.chars.next();
+                let min = self.count()?;
+                let max = match self.chars.next_if_eq(&',') {
+                    Some(_) if self.chars.peek() == Some(&'}') => None,
+                    Some(_) => Some(self.count()?),
+                    None => Some(min),
+                };
+                self.chars.next_if_eq(&'}')?;
+                if max.is_some_and(|max| max < min) {
+                    return None;
+                }
+                return self.quantified_with(node, min, max);
+            }
+            _ => return Some(node),
+        };
+        self.chars.next();
+        self.quantified_with(node, min, max)
+    }
+
+    fn quantified_with(
+        &mut self,
+        node: Node,
+        min: u32,
+        max: Option<u32>,
+    ) -> Option<Node> {
+        // Laziness does not change whether a string matches.
+        self.chars.next_if_eq(&'?');
+        if matches!(self.chars.peek(), Some('*' | '+' | '?' | '{')) {
+            return None;
+        }
+        Some(Node::Repeat {
+            node: Box::new(node),
+            min,
+            max,
+        })
+    }
+
+    fn count(&mut self) -> Option<u32> {
+        let mut digits = String::new();
+        while let Some(digit) = self.chars.next_if(char::is_ascii_digit) {
+            digits.push(digit);
+        }
+        digits.parse().ok().filter(|count| *count <= MAX_REPEAT)
+    }
+}
+
+impl Regex {
+    /// Compiles `pattern`, or returns `None` if it is not valid.
+    pub(crate) fn new(pattern: &str) -> Option<Self> {
+        let mut compiler = Compiler {
+            chars: pattern.chars().peekable(),
+        };
+        let root = compiler.alternation()?;
+        compiler.chars.next().is_none().then_some(())?;
+        let mut program = Vec::new();
+        emit(&root, &mut program)?;
+        program.push(Inst::Match);
+        Some(Self { program })
+    }
+
+    /// Whether the pattern matches anywhere in `text`.
+    pub(crate) fn is_match(&self, text: &str) -> bool {
+        let mut current = Threads::new(self.program.len());
+        let mut next = Threads::new(self.program.len());
+        let mut stack = Vec::new();
+        let mut chars = text.chars().peekable();
+        let mut start = true;
+        loop {
+            // A match may start at every position.
+            let end = chars.peek().is_none();
+            if self.add(&mut current, 0, start, end, &mut stack) {
+                return true;
+            }
+            let Some(c) = chars.next() else {
+                return false;
+            };
+            let end = chars.peek().is_none();
+            for &pc in &current.list {
+                if self.program[pc].accepts(c)
+                    && self.add(&mut next, pc + 1, false, end, &mut stack)
+                {
+                    return true;
+                }
+            }
+            current.clear();
+            core::mem::swap(&mut current, &mut next);
+            start = false;
+        }
+    }
+
+    /// Adds the instructions reading a character reached from `pc` to
+    /// `threads`, returning whether the match is complete. `start` and
+    /// `end` tell whether the position is at either end of the text.
+    fn add(
+        &self,
+        threads: &mut Threads,
+        pc: usize,
+        start: bool,
+        end: bool,
+        stack: &mut Vec<usize>,
+    ) -> bool {
+        stack.clear();
+        stack.push(pc);
+        while let Some(pc) = stack.pop() {
+            if threads.seen[pc] {
+                continue;
+            }
+            threads.seen[pc] = true;
+            threads.reached.push(pc);
+            match self.program[pc] {
+                Inst::Split(first, second) => stack.extend([second, first]),
+                Inst::Jump(to) => stack.push(to),
+                Inst::Start if start => stack.push(pc + 1),
+                Inst::End if end => stack.push(pc + 1),
+                Inst::Start | Inst::End => {}
+                Inst::Match => return true,
+                _ => threads.list.push(pc),
+            }
+        }
+        false
+    }
+}
+
+impl Container {
+    /// Returns the string of a string value matched by the regular
+    /// expression `pattern`, anywhere in the string unless anchored with
+    /// `^` and `$`.
+    ///
+    /// Patterns may use literals, `.`, classes such as `[^,]`, the ASCII
+    /// classes `\d`, `\w`, `\s` and their negations, groups, `|` and the
+    /// quantifiers `*`, `+`, `?` and `{n,m}`. An invalid pattern matches
+    /// nothing.
+    ///
+    /// ```
+    /// use json_parser::parser::parse_str;
+    ///
+    /// let user = parse_str(r#"{"name": "ada_99", "zip": "9021"}"#).unwrap();
+    /// let name = user["name"].get_str_matching(r"^[a-z]\w{2,15}$");
+    /// assert_eq!(name, Some("ada_99"));
+    /// assert_eq!(user["zip"].get_str_matching(r"^\d{5}$"), None);
+    /// ```
+    pub fn get_str_matching(&self, pattern: &str) -> Option<&str> {
+        let regex = cached(&PATTERNS, pattern, |pattern| {
+            Regex::new(pattern).map(Rc::new)
+        })?;
+        match self {
+            Self::String(text) if regex.is_match(text) => Some(text),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,344 @@
+//! Best-effort repair of hand-written or truncated JSON, for inputs that
+//! should be read anyway and fixed at their source later.
+//!
+//! The input is rewritten into standard JSON token by token, and every
+//! change is reported as a [`Fix`] at its place of the input. Problems
+//! other than those of [`FixKind`] are left for the parser to report.
+use super::container::Container;
+use super::dom::Reader;
+use super::parser::{is_identifier, parse_str, Span};
+
+/// The problems repaired by [`parse_str_repair`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FixKind {
+    /// A `,` inserted between two elements or members
+    MissingComma,
+    /// A string or key quoted with `'`, quoted with `"` instead
+    SingleQuotes,
+    /// A bare key put in double quotes
+    UnquotedKey,
+    /// A `,` after the last element of an array or object, removed
+    TrailingComma,
+    /// The end of the input reached inside a value: the open strings and
+    /// containers are closed, and a member without a value gets `null`
+    Truncated,
+}
+
+/// A change made to the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fix {
+    pub kind: FixKind,
+    /// The text changed, or the place where text was inserted
+    pub span: Span,
+}
+
+impl core::fmt::Display for Fix {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        let message = match self.kind {
+            FixKind::MissingComma => "Inserted a missing `,`",
+            FixKind::SingleQuotes => "Replaced single quotes",
+            FixKind::UnquotedKey => "Quoted a bare key",
+            FixKind::TrailingComma => "Removed a trailing `,`",
+            FixKind::Truncated => "Completed the truncated document",
+        };
+        f.write_str(
+            format!("{}:{}: {}", self.span.line, self.span.col, message)
+                .as_str(),
+        )
+    }
+}
+
+/// What the rewriter accepts next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Expect {
+    Value,
+    Key,
+    Colon,
+    /// A separator or a closing bracket, after a value
+    After,
+    /// Nothing: the document is complete
+    Done,
+}
+
+/// Rewrites an input into standard JSON.
+struct Repair<'a> {
+    input: &'a str,
+    pos: usize,
+    out: String,
+    /// Closing brackets of the open containers, innermost last
+    stack: Vec<u8>,
+    expect: Expect,
+    /// Where the `,` just written starts, in `out` and in the input
+    comma: Option<(usize, usize)>,
+    fixes: Vec<Fix>,
+}
+
+impl<'a> Repair<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            pos: 0,
+            out: String::with_capacity(input.len()),
+            stack: Vec::new(),
+            expect: Expect::Value,
+            comma: None,
+            fixes: Vec::new(),
+        }
+    }
+
+    fn bytes(&self) -> &'a [u8] {
+        self.input.as_bytes()
+    }
+
+    fn fix(&mut self, kind: FixKind, start: usize, end: usize) {
+        let (line, col) = Reader::new(self.bytes(), 0).position(start);
+        self.fixes.push(Fix {
+            kind,
+            span: Span {
+                start,
+                end,
+                line,
+                col,
+                path: None,
+            },
+        });
+    }
+
+    /// What is expected after a value, given the open containers.
+    fn after_value(&self) -> Expect {
+        if self.stack.is_empty() {
+            Expect::Done
+        } else {
+            Expect::After
+        }
+    }
+
+    /// What is expected after a `,` or an opening bracket.
+    fn next_element(&self) -> Expect {
+        match self.stack.last() {
+            Some(b'}') => Expect::Key,
+            _ => Expect::Value,
+        }
+    }
+
+    /// Writes `text`, which is not a `,`.
+    fn emit(&mut self, text: &str) {
+        self.comma = None;
+        self.out.push_str(text);
+    }
+
+    /// Removes the `,` just written, if any, as a trailing comma.
+    fn drop_comma(&mut self) {
+        if let Some((at, start)) = self.comma.take() {
+            self.out.truncate(at);
+            self.fix(FixKind::TrailingComma, start, start + 1);
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self
+            .bytes()
+            .get(self.pos)
+            .is_some_and(|byte| matches!(byte, b' ' | b'\t' | b'\n' | b'\r'))
+        {
+            self.pos += 1;
+        }
+    }
+
+    fn run(mut self) -> (String, Vec<Fix>) {
+        loop {
+            self.skip_whitespace();
+            let Some(&byte) = self.bytes().get(self.pos) else {
+                break;
+            };
+            match (self.expect, byte) {
+                (Expect::Done, _) => {
+                    // Left for the parser to report.
+                    let rest = &self.input[self.pos..];
+                    self.emit(rest);
+                    break;
+                }
+                (_, b']' | b'}') => {
+                    if matches!(self.expect, Expect::Key | Expect::Value) {
+                        self.drop_comma();
+                    }
+                    self.pos += 1;
+                    self.emit(if byte == b']' { "]" } else { "}" });
+                    self.stack.pop();
+                    self.expect = self.after_value();
+                }
+                (Expect::After, b',') => {
+                    self.comma = Some((self.out.len(), self.pos));
+                    self.out.push(',');
+                    self.pos += 1;
+                    self.expect = self.next_element();
+                }
+                (Expect::Colon, b':') => {
+                    self.pos += 1;
+                    self.emit(":");
+                    self.expect = Expect::Value;
+                }
+                (Expect::After, _) if byte != b':' => {
+                    self.fix(FixKind::MissingComma, self.pos, self.pos);
+                    self.out.push(',');
+                    self.expect = self.next_element();
+                }
+                (Expect::Key, b'"' | b'\'') => {
+                    self.string(byte);
+                    self.expect = Expect::Colon;
+                }
+                (Expect::Key, _) if is_identifier(byte, true) => {
+                    let start = self.pos;
+                    while self
+                        .bytes()
+                        .get(self.pos)
+                        .is_some_and(|byte| is_identifier(*byte, false))
+                    {
+                        self.pos += 1;
+                    }
+                    let key = format!("\"{}\"", &self.input[start..self.pos]);
+                    self.emit(&key);
+                    self.fix(FixKind::UnquotedKey, start, self.pos);
+                    self.expect = Expect::Colon;
+                }
+                (Expect::Value, b'[' | b'{') => {
+                    self.pos += 1;
+                    self.emit(if byte == b'[' { "[" } else { "{" });
+                    self.stack.push(if byte == b'[' { b']' } else { b'}' });
+                    self.expect = self.next_element();
+                }
+                (Expect::Value, b'"' | b'\'') => {
+                    self.string(byte);
+                    self.expect = self.after_value();
+                }
+                (Expect::Value, _) => {
+                    let start = self.pos;
+                    while self
+                        .bytes()
+                        .get(self.pos)
+                        .is_some_and(|byte| !b" \t\n\r,:[]{}\"'".contains(byte))
+                    {
+                        self.pos += 1;
+                    }
+                    // A character that starts no value, left for the
+                    // parser to report.
+                    let end = self.pos.max(start + 1);
+                    let end = (end..=self.input.len())
+                        .find(|end| self.input.is_char_boundary(*end))
+                        .unwrap_or(self.input.len());
+                    let word = &self.input[start..end];
+                    self.pos = end;
+                    self.emit(word);
+                    self.expect = self.after_value();
+                }
+                _ => {
+                    let rest = &self.input[self.pos..];
+                    self.emit(rest);
+                    self.expect = Expect::Done;
+                    break;
+                }
+            }
+        }
+        self.finish();
+        (self.out, self.fixes)
+    }
+
+    /// Reads a string quoted with `quote`, writing it in double quotes.
+    fn string(&mut self, quote: u8) {
+        let start = self.pos;
+        let mut text = String::from('"');
+        let mut chars = self.input[start + 1..].char_indices();
+        let mut end = None;
+        while let Some((idx, chr)) = chars.next() {
+            match chr {
+                _ if chr as u32 == quote as u32 => {
+                    end = Some(start + 1 + idx + 1);
+                    break;
+                }
+                '\\' => match chars.next() {
+                    Some((_, '\'')) => text.push('\''),
+                    Some((_, escaped)) => {
+                        text.push('\\');
+                        text.push(escaped);
+                    }
+                    None => {}
+                },
+                '"' => text.push_str("\\\""),
+                _ => text.push(chr),
+            }
+        }
+        text.push('"');
+        self.emit(&text);
+        match end {
+            Some(end) => {
+                self.pos = end;
+                if quote == b'\'' {
+                    self.fix(FixKind::SingleQuotes, start, end);
+                }
+            }
+            None => {
+                self.pos = self.input.len();
+                self.fix(FixKind::Truncated, self.pos, self.pos);
+            }
+        }
+    }
+
+    /// Completes the document at the end of the input.
+    fn finish(&mut self) {
+        if self.stack.is_empty() {
+            return;
+        }
+        match self.expect {
+            Expect::Colon => self.emit(":null"),
+            Expect::Value if self.comma.is_some() => self.drop_comma(),
+            Expect::Value if self.stack.last() == Some(&b'}') => {
+                self.emit("null")
+            }
+            Expect::Key => self.drop_comma(),
+            _ => {}
+        }
+        while let Some(close) = self.stack.pop() {
+            self.out.push(close as char);
+        }
+        let end = self.input.len();
+        if !self.fixes.last().is_some_and(|fix| {
+            fix.kind == FixKind::Truncated && fix.span.start == end
+        }) {
+            self.fix(FixKind::Truncated, end, end);
+        }
+    }
+}
+
+/// Parses `input`, repairing the problems of [`FixKind`] first, and
+/// returns the document with the fixes applied, in input order.
+///
+/// Errors that remain are those of parsing the repaired text, whose
+/// positions may differ from those of the input.
+///
+/// ```
+/// use json_parser::parser::parse_str;
+/// use json_parser::repair::{parse_str_repair, FixKind};
+///
+/// let input = "{name: 'ada', \"tags\": [1 2,], \"next\": {\"id\": 7";
+/// let (value, fixes) = parse_str_repair(input).unwrap();
+/// let expected = r#"{"name": "ada", "tags": [1, 2], "next": {"id": 7}}"#;
+/// assert_eq!(value, parse_str(expected).unwrap());
+/// let kinds: Vec<FixKind> = fixes.iter().map(|fix| fix.kind).collect();
+/// assert_eq!(
+///     kinds,
+///     [
+///         FixKind::UnquotedKey,
+///         FixKind::SingleQuotes,
+///         FixKind::MissingComma,
+///         FixKind::TrailingComma,
+///         FixKind::Truncated,
+///     ]
+/// );
+/// assert_eq!(fixes[2].to_string(), "1:26: Inserted a missing `,`");
+/// ```
+pub fn parse_str_repair(
+    input: &str,
+) -> Result<(Container, Vec<Fix>), Box<dyn core::error::Error>> {
+    let (text, fixes) = Repair::new(input).run();
+    Ok((parse_str(&text)?, fixes))
+}
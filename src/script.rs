@@ -0,0 +1,649 @@
+//! Small transform scripts, compiled at runtime and applied to documents
+//! one at a time, e.g. in a streaming pipeline.
+//!
+//! A script is a list of statements separated by `;`:
+//!
+//! - `filter <expr>` drops the document unless the expression holds,
+//! - `set <pointer> = <expr>` writes a value, creating the containers on
+//!   the way,
+//! - `del <pointer>` removes a value, if there is one.
+//!
+//! Expressions are made of JSON literals, JSON Pointers reading the
+//! document (`null` when they do not resolve), the operators `||`, `&&`,
+//! `!`, `==`, `!=`, `<`, `<=`, `>`, `>=`, `+`, `-`, `*`, `/`, `%`, and the
+//! functions `len`, `lower` and `upper`. A pointer ends at whitespace or
+//! at an operator other than `-` and `/`. `null` and `false` are false,
+//! every other value is true.
+//!
+//! Scripts are sandboxed: they can only read and write the document they
+//! are applied to, and each run is bounded by a number of steps and by
+//! the length of the strings it builds.
+use super::container::Container;
+use super::error::{Error, ScriptError};
+use super::lint::type_name;
+use super::{dom, parser, pointer};
+use core::cmp::Ordering;
+
+/// Steps allowed per document unless told otherwise.
+const DEFAULT_MAX_STEPS: usize = 10_000;
+/// Bytes allowed in a string built by a run unless told otherwise.
+const DEFAULT_MAX_STRING_LEN: usize = 1 << 20;
+/// Nesting of parentheses, functions and unary operators allowed in a
+/// script, which bounds the recursion of the compiler and of runs: the
+/// operands of binary operators are kept in flat chains, however long.
+const MAX_NESTING: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Or,
+    And,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+/// Operators of each precedence level, loosest first.
+const LEVELS: &[&[(&str, Op)]] = &[
+    &[("||", Op::Or)],
+    &[("&&", Op::And)],
+    &[
+        ("==", Op::Eq),
+        ("!=", Op::Ne),
+        ("<=", Op::Le),
+        (">=", Op::Ge),
+        ("<", Op::Lt),
+        (">", Op::Gt),
+    ],
+    &[("+", Op::Add), ("-", Op::Sub)],
+    &[("*", Op::Mul), ("/", Op::Div), ("%", Op::Rem)],
+];
+
+#[derive(Debug, Clone, Copy)]
+enum Function {
+    Len,
+    Lower,
+    Upper,
+}
+
+#[derive(Debug)]
+enum Expr {
+    Value(Container),
+    Path(Vec<String>),
+    Not(Box<Expr>),
+    Neg(Box<Expr>),
+    /// Operands of the operators of one precedence level, applied from
+    /// left to right
+    Chain(Box<Expr>, Vec<(Op, Expr)>),
+    Call(Function, Box<Expr>),
+}
+
+#[derive(Debug)]
+enum Statement {
+    Filter(Expr),
+    Set(String, Vec<String>, Expr),
+    Delete(Vec<String>),
+}
+
+/// A compiled transform script.
+///
+/// ```
+/// use json_parser::parser::parse_str;
+/// use json_parser::script::Script;
+///
+/// let script = Script::parse(
+///     r#"filter /age >= 18; set /name = upper(/name); del /password"#,
+/// )
+/// .unwrap();
+///
+/// let adult = parse_str(r#"{"name": "ada", "age": 36, "password": "x"}"#);
+/// let adult = script.apply(adult.unwrap()).unwrap().unwrap();
+/// assert_eq!(adult.to_json_string(), r#"{"name":"ADA","age":36}"#);
+///
+/// let minor = parse_str(r#"{"name": "bob", "age": 12}"#).unwrap();
+/// assert_eq!(script.apply(minor).unwrap(), None);
+/// ```
+#[derive(Debug)]
+pub struct Script {
+    statements: Vec<Statement>,
+    max_steps: usize,
+    max_string_len: usize,
+}
+
+impl Script {
+    /// Compiles `source`, allowing 10 000 steps per document and strings
+    /// of up to 1 MiB.
+    pub fn parse(source: &str) -> Result<Self, Box<dyn core::error::Error>> {
+        let mut compiler = Compiler {
+            source,
+            pos: 0,
+            depth: 0,
+        };
+        let mut statements = Vec::new();
+        loop {
+            compiler.skip_whitespace();
+            if compiler.pos == source.len() {
+                break;
+            }
+            statements.push(compiler.statement()?);
+            compiler.skip_whitespace();
+            if !compiler.eat(";") && compiler.pos != source.len() {
+                return Err(compiler.error("`;`").into());
+            }
+        }
+        Ok(Self {
+            statements,
+            max_steps: DEFAULT_MAX_STEPS,
+            max_string_len: DEFAULT_MAX_STRING_LEN,
+        })
+    }
+
+    /// Number of expressions a run may evaluate before failing with
+    /// `ScriptError::StepLimit`.
+    pub fn max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// Number of bytes a string built by `+`, `lower` or `upper` may
+    /// have before the run fails with `ScriptError::StringLimit`.
+    pub fn max_string_len(mut self, max_string_len: usize) -> Self {
+        self.max_string_len = max_string_len;
+        self
+    }
+
+    /// Runs the script on `document`, returning `None` if a `filter`
+    /// dropped it.
+    pub fn apply(
+        &self,
+        mut document: Container,
+    ) -> Result<Option<Container>, Box<dyn core::error::Error>> {
+        let mut run = Run {
+            steps: 0,
+            max_steps: self.max_steps,
+            max_string_len: self.max_string_len,
+        };
+        for statement in &self.statements {
+            match statement {
+                Statement::Filter(condition) => {
+                    if !truthy(&run.eval(condition, &document)?) {
+                        return Ok(None);
+                    }
+                }
+                Statement::Set(path, tokens, value) => {
+                    let value = run.eval(value, &document)?;
+                    pointer::insert(&mut document, tokens, value).ok_or_else(
+                        || {
+                            script_error(ScriptError::InvalidTarget(
+                                path.clone(),
+                            ))
+                        },
+                    )?;
+                }
+                Statement::Delete(tokens) if tokens.is_empty() => {
+                    document = Container::Null;
+                }
+                Statement::Delete(tokens) => {
+                    pointer::remove(&mut document, tokens);
+                }
+            }
+        }
+        Ok(Some(document))
+    }
+}
+
+fn script_error(error: ScriptError) -> Error {
+    Error::Script(error)
+}
+
+struct Compiler<'s> {
+    source: &'s str,
+    pos: usize,
+    /// Nesting of the operand being read
+    depth: usize,
+}
+
+impl Compiler<'_> {
+    fn rest(&self) -> &str {
+        &self.source[self.pos..]
+    }
+
+    fn error(&self, expected: &'static str) -> Error {
+        script_error(ScriptError::Syntax {
+            pos: self.pos,
+            expected,
+        })
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    /// Skips `token` if the input continues with it.
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        let found = self.rest().starts_with(token);
+        if found {
+            self.pos += token.len();
+        }
+        found
+    }
+
+    /// Reads a name made of ASCII letters and `_`.
+    fn identifier(&mut self) -> &str {
+        self.skip_whitespace();
+        let start = self.pos;
+        let len = self
+            .rest()
+            .find(|c: char| !c.is_ascii_alphabetic() && c != '_')
+            .unwrap_or(self.rest().len());
+        self.pos += len;
+        &self.source[start..self.pos]
+    }
+
+    fn statement(&mut self) -> Result<Statement, Error> {
+        let start = self.pos;
+        match self.identifier() {
+            "filter" => Ok(Statement::Filter(self.expr(0)?)),
+            "set" => {
+                let (path, tokens) = self.pointer()?;
+                if !self.eat("=") {
+                    return Err(self.error("`=`"));
+                }
+                Ok(Statement::Set(path, tokens, self.expr(0)?))
+            }
+            "del" => Ok(Statement::Delete(self.pointer()?.1)),
+            _ => {
+                self.pos = start;
+                Err(self.error("`filter`, `set` or `del`"))
+            }
+        }
+    }
+
+    /// Reads a JSON Pointer, returning its text and its tokens.
+    fn pointer(&mut self) -> Result<(String, Vec<String>), Error> {
+        self.skip_whitespace();
+        if !self.rest().starts_with('/') {
+            return Err(self.error("a JSON pointer"));
+        }
+        let len = self
+            .rest()
+            .find(|c: char| c.is_whitespace() || ";,()=!<>&|+*%".contains(c))
+            .unwrap_or(self.rest().len());
+        let path = self.rest()[..len].to_owned();
+        self.pos += len;
+        let tokens =
+            pointer::split(&path).ok_or(self.error("a JSON pointer"))?;
+        Ok((path, tokens))
+    }
+
+    /// Reads an expression whose operators bind at least as tightly as
+    /// those of `LEVELS[level]`.
+    fn expr(&mut self, level: usize) -> Result<Expr, Error> {
+        let Some(operators) = LEVELS.get(level) else {
+            return self.unary();
+        };
+        let first = self.expr(level + 1)?;
+        let mut rest = Vec::new();
+        // Comparisons do not chain.
+        let most = match operators[0].1 {
+            Op::Eq => 1,
+            _ => usize::MAX,
+        };
+        while rest.len() < most {
+            self.skip_whitespace();
+            let Some((token, op)) = operators
+                .iter()
+                .find(|(token, _)| self.rest().starts_with(token))
+            else {
+                break;
+            };
+            self.pos += token.len();
+            rest.push((*op, self.expr(level + 1)?));
+        }
+        Ok(match rest.is_empty() {
+            true => first,
+            false => Expr::Chain(Box::new(first), rest),
+        })
+    }
+
+    fn unary(&mut self) -> Result<Expr, Error> {
+        if self.depth == MAX_NESTING {
+            return Err(self.error("less nesting"));
+        }
+        self.depth += 1;
+        let operand = self.nested_unary();
+        self.depth -= 1;
+        operand
+    }
+
+    fn nested_unary(&mut self) -> Result<Expr, Error> {
+        self.skip_whitespace();
+        // `!=` is an operator, not a negation.
+        if self.rest().starts_with('!') && !self.rest().starts_with("!=") {
+            self.pos += 1;
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        if self.rest().starts_with('-')
+            && !self.rest()[1..].starts_with(|c: char| c.is_ascii_digit())
+        {
+            self.pos += 1;
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, Error> {
+        self.skip_whitespace();
+        if self.rest().starts_with('/') {
+            return Ok(Expr::Path(self.pointer()?.1));
+        }
+        if self.eat("(") {
+            let expr = self.expr(0)?;
+            if !self.eat(")") {
+                return Err(self.error("`)`"));
+            }
+            return Ok(expr);
+        }
+        let rest = self.rest();
+        if rest.starts_with('"') {
+            return self.literal(string_len(rest));
+        }
+        if rest.starts_with(|c: char| c == '-' || c.is_ascii_digit()) {
+            let len = dom::number_len(rest.as_bytes()).map(|(_, len)| len);
+            return self.literal(len);
+        }
+
+        let start = self.pos;
+        let function = match self.identifier() {
+            "true" => return Ok(Expr::Value(Container::Boolean(true))),
+            "false" => return Ok(Expr::Value(Container::Boolean(false))),
+            "null" => return Ok(Expr::Value(Container::Null)),
+            "len" => Function::Len,
+            "lower" => Function::Lower,
+            "upper" => Function::Upper,
+            _ => {
+                self.pos = start;
+                return Err(self.error("a value"));
+            }
+        };
+        if !self.eat("(") {
+            return Err(self.error("`(`"));
+        }
+        let argument = self.expr(0)?;
+        if !self.eat(")") {
+            return Err(self.error("`)`"));
+        }
+        Ok(Expr::Call(function, Box::new(argument)))
+    }
+
+    /// Reads the JSON string or number of `len` bytes starting here.
+    fn literal(&mut self, len: Option<usize>) -> Result<Expr, Error> {
+        let value = len
+            .and_then(|len| parser::parse_str(&self.rest()[..len]).ok())
+            .ok_or(self.error("a value"))?;
+        self.pos += len.unwrap_or(0);
+        Ok(Expr::Value(value))
+    }
+}
+
+/// Length of the JSON string literal at the start of `text`, if closed.
+fn string_len(text: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (idx, byte) in text.bytes().enumerate().skip(1) {
+        match byte {
+            _ if escaped => escaped = false,
+            b'\\' => escaped = true,
+            b'"' => return Some(idx + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn truthy(value: &Container) -> bool {
+    !matches!(value, Container::Null | Container::Boolean(false))
+}
+
+/// A number read by an arithmetic operator.
+#[derive(Debug, Clone, Copy)]
+enum Number {
+    Integer(i128),
+    Real(f64),
+}
+
+impl Number {
+    fn of(value: &Container) -> Option<Self> {
+        Some(match *value {
+            Container::Number(value) => Self::Integer(value.into()),
+            Container::Unsigned(value) => Self::Integer(value.into()),
+            Container::Number128(value) => Self::Integer(value),
+            Container::Unsigned128(value) => match i128::try_from(value) {
+                Ok(value) => Self::Integer(value),
+                Err(_) => Self::Real(value as f64),
+            },
+            Container::Decimal(value) => Self::Real(value),
+            Container::RawNumber(ref text) | Container::BigNumber(ref text) => {
+                Self::Real(text.parse().ok()?)
+            }
+            _ => return None,
+        })
+    }
+
+    fn real(self) -> f64 {
+        match self {
+            Self::Integer(value) => value as f64,
+            Self::Real(value) => value,
+        }
+    }
+
+    fn cmp(self, other: Self) -> Option<Ordering> {
+        match (self, other) {
+            (Self::Integer(a), Self::Integer(b)) => Some(a.cmp(&b)),
+            _ => self.real().partial_cmp(&other.real()),
+        }
+    }
+}
+
+struct Run {
+    steps: usize,
+    max_steps: usize,
+    max_string_len: usize,
+}
+
+impl Run {
+    fn step(&mut self) -> Result<(), Error> {
+        self.steps += 1;
+        if self.steps > self.max_steps {
+            return Err(script_error(ScriptError::StepLimit(self.max_steps)));
+        }
+        Ok(())
+    }
+
+    /// Fails if `value` is a string longer than allowed.
+    fn built(&self, value: Container) -> Result<Container, Error> {
+        match &value {
+            Container::String(text) if text.len() > self.max_string_len => {
+                Err(script_error(ScriptError::StringLimit(self.max_string_len)))
+            }
+            _ => Ok(value),
+        }
+    }
+
+    fn eval(
+        &mut self,
+        expr: &Expr,
+        document: &Container,
+    ) -> Result<Container, Error> {
+        self.step()?;
+        Ok(match expr {
+            Expr::Value(value) => value.clone(),
+            Expr::Path(tokens) => pointer::resolve(document, tokens)
+                .cloned()
+                .unwrap_or(Container::Null),
+            Expr::Not(operand) => {
+                Container::Boolean(!truthy(&self.eval(operand, document)?))
+            }
+            Expr::Neg(operand) => {
+                let operand = self.eval(operand, document)?;
+                arithmetic(Op::Sub, &Container::Unsigned(0), &operand)?
+            }
+            Expr::Chain(first, rest) => {
+                let mut lhs = self.eval(first, document)?;
+                for (op, rhs) in rest {
+                    // Each operator is a step, as the operand it reads.
+                    self.step()?;
+                    lhs = match op {
+                        // `||` and `&&` stop at the first operand deciding
+                        // the result.
+                        Op::Or | Op::And if truthy(&lhs) == (*op == Op::Or) => {
+                            return Ok(Container::Boolean(*op == Op::Or));
+                        }
+                        Op::Or | Op::And => Container::Boolean(truthy(
+                            &self.eval(rhs, document)?,
+                        )),
+                        _ => {
+                            let rhs = self.eval(rhs, document)?;
+                            self.built(binary(*op, &lhs, &rhs)?)?
+                        }
+                    };
+                }
+                lhs
+            }
+            Expr::Call(function, argument) => {
+                let argument = self.eval(argument, document)?;
+                self.built(call(*function, argument)?)?
+            }
+        })
+    }
+}
+
+/// Applies an operator other than `||` and `&&`.
+fn binary(
+    op: Op,
+    lhs: &Container,
+    rhs: &Container,
+) -> Result<Container, Error> {
+    Ok(match op {
+        Op::Eq => Container::Boolean(equal(lhs, rhs)),
+        Op::Ne => Container::Boolean(!equal(lhs, rhs)),
+        Op::Lt | Op::Le | Op::Gt | Op::Ge => compare(op, lhs, rhs)?,
+        _ => arithmetic(op, lhs, rhs)?,
+    })
+}
+
+fn mismatch(op: &'static str, value: &Container) -> Error {
+    script_error(ScriptError::TypeMismatch {
+        op,
+        found: type_name(value),
+    })
+}
+
+/// Equality of values, numbers being compared by value whatever their
+/// variant.
+fn equal(lhs: &Container, rhs: &Container) -> bool {
+    match (Number::of(lhs), Number::of(rhs)) {
+        (Some(lhs), Some(rhs)) => lhs.cmp(rhs) == Some(Ordering::Equal),
+        _ => lhs == rhs,
+    }
+}
+
+/// Orders two numbers or two strings.
+fn compare(
+    op: Op,
+    lhs: &Container,
+    rhs: &Container,
+) -> Result<Container, Error> {
+    let ordering = match (lhs, rhs) {
+        (Container::String(lhs), Container::String(rhs)) => Some(lhs.cmp(rhs)),
+        _ => {
+            let number =
+                |value| Number::of(value).ok_or(mismatch("ordering", value));
+            number(lhs)?.cmp(number(rhs)?)
+        }
+    };
+    // Comparisons with NaN are false.
+    let holds = ordering.is_some_and(|ordering| match op {
+        Op::Lt => ordering.is_lt(),
+        Op::Le => ordering.is_le(),
+        Op::Gt => ordering.is_gt(),
+        _ => ordering.is_ge(),
+    });
+    Ok(Container::Boolean(holds))
+}
+
+fn arithmetic(
+    op: Op,
+    lhs: &Container,
+    rhs: &Container,
+) -> Result<Container, Error> {
+    if let (Op::Add, Container::String(lhs), Container::String(rhs)) =
+        (op, lhs, rhs)
+    {
+        return Ok(Container::String(format!("{}{}", lhs, rhs).into()));
+    }
+    let name = match op {
+        Op::Add => "`+`",
+        Op::Sub => "`-`",
+        Op::Mul => "`*`",
+        Op::Div => "`/`",
+        _ => "`%`",
+    };
+    let a = Number::of(lhs).ok_or(mismatch(name, lhs))?;
+    let b = Number::of(rhs).ok_or(mismatch(name, rhs))?;
+
+    if let (Number::Integer(a), Number::Integer(b)) = (a, b) {
+        if matches!(op, Op::Div | Op::Rem) && b == 0 {
+            return Err(script_error(ScriptError::DivisionByZero));
+        }
+        let exact = match op {
+            Op::Add => a.checked_add(b),
+            Op::Sub => a.checked_sub(b),
+            Op::Mul => a.checked_mul(b),
+            Op::Div => (a % b == 0).then(|| a / b),
+            _ => a.checked_rem(b),
+        };
+        if let Some(value) = exact {
+            return Ok(Container::from(value));
+        }
+    }
+    let (a, b) = (a.real(), b.real());
+    Ok(Container::Decimal(match op {
+        Op::Add => a + b,
+        Op::Sub => a - b,
+        Op::Mul => a * b,
+        Op::Div => a / b,
+        _ => a % b,
+    }))
+}
+
+fn call(function: Function, argument: Container) -> Result<Container, Error> {
+    match (function, argument) {
+        (Function::Len, Container::String(text)) => {
+            Ok(Container::from(text.chars().count() as u64))
+        }
+        (Function::Len, Container::Array(values)) => {
+            Ok(Container::from(values.len() as u64))
+        }
+        (Function::Len, Container::Object(map)) => {
+            Ok(Container::from(map.len() as u64))
+        }
+        (Function::Lower, Container::String(text)) => {
+            Ok(Container::String(text.to_lowercase().into()))
+        }
+        (Function::Upper, Container::String(text)) => {
+            Ok(Container::String(text.to_uppercase().into()))
+        }
+        (Function::Len, argument) => Err(mismatch("`len`", &argument)),
+        (Function::Lower, argument) => Err(mismatch("`lower`", &argument)),
+        (Function::Upper, argument) => Err(mismatch("`upper`", &argument)),
+    }
+}
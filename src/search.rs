@@ -0,0 +1,215 @@
+//! Lookups into arrays of records: binary search over arrays kept sorted
+//! by a field, and hash indexes keyed by a field.
+use super::container::Container;
+use super::pointer;
+#[cfg(feature = "uuid")]
+use super::uuid;
+use core::cmp::Ordering;
+use std::collections::HashMap;
+
+/// Rank of each variant in the ordering used by [`compare`].
+fn type_rank(value: &Container) -> u8 {
+    match value {
+        Container::Null => 0,
+        Container::Boolean(_) => 1,
+        Container::Number(_)
+        | Container::Unsigned(_)
+        | Container::Number128(_)
+        | Container::Unsigned128(_)
+        | Container::Decimal(_)
+        | Container::RawNumber(_)
+        | Container::BigNumber(_) => 2,
+        Container::String(_) => 3,
+        #[cfg(feature = "uuid")]
+        Container::Uuid(_) => 3,
+        Container::Array(_) => 4,
+        Container::Object(_) => 5,
+    }
+}
+
+/// Total order over containers: values are ordered by type first
+/// (`null < bool < number < string < array < object`), integers and reals
+/// are compared numerically, strings lexicographically, arrays element
+/// by element and objects by their number of members.
+pub(crate) fn compare(lhs: &Container, rhs: &Container) -> Ordering {
+    if let (Some(a), Some(b)) = (Integer::of(lhs), Integer::of(rhs)) {
+        return a.cmp(&b);
+    }
+    match (lhs, rhs) {
+        (Container::Boolean(a), Container::Boolean(b)) => a.cmp(b),
+        (Container::RawNumber(_) | Container::BigNumber(_), _)
+        | (_, Container::RawNumber(_) | Container::BigNumber(_))
+            if type_rank(lhs) == 2 && type_rank(rhs) == 2 =>
+        {
+            as_real(lhs).total_cmp(&as_real(rhs))
+        }
+        (Container::Decimal(a), _) if type_rank(rhs) == 2 => {
+            a.total_cmp(&as_real(rhs))
+        }
+        (_, Container::Decimal(b)) if type_rank(lhs) == 2 => {
+            as_real(lhs).total_cmp(b)
+        }
+        (Container::String(a), Container::String(b)) => a.cmp(b),
+        #[cfg(feature = "uuid")]
+        (Container::Uuid(a), Container::Uuid(b)) => a.cmp(b),
+        (Container::Array(a), Container::Array(b)) => a
+            .iter()
+            .zip(b)
+            .map(|(a, b)| compare(a, b))
+            .find(|ord| ord.is_ne())
+            .unwrap_or_else(|| a.len().cmp(&b.len())),
+        (Container::Object(a), Container::Object(b)) => a.len().cmp(&b.len()),
+        _ => type_rank(lhs).cmp(&type_rank(rhs)),
+    }
+}
+
+/// An integer of any of the integer variants, widened to compare exactly.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum Integer {
+    /// Below zero
+    Negative(i128),
+    NonNegative(u128),
+}
+
+impl Integer {
+    fn of(value: &Container) -> Option<Self> {
+        let signed = |value: i128| match u128::try_from(value) {
+            Ok(value) => Integer::NonNegative(value),
+            Err(_) => Integer::Negative(value),
+        };
+        match value {
+            Container::Number(v) => Some(signed(*v as i128)),
+            Container::Number128(v) => Some(signed(*v)),
+            Container::Unsigned(v) => Some(Integer::NonNegative(*v as u128)),
+            Container::Unsigned128(v) => Some(Integer::NonNegative(*v)),
+            _ => None,
+        }
+    }
+}
+
+/// String form of a scalar used as an index key; `None` for null,
+/// arrays and objects.
+fn index_key(value: &Container) -> Option<String> {
+    match value {
+        Container::String(v) => Some(v.to_string()),
+        #[cfg(feature = "uuid")]
+        Container::Uuid(v) => Some(uuid::format(v)),
+        Container::Number(v) => Some(v.to_string()),
+        Container::Unsigned(v) => Some(v.to_string()),
+        Container::Number128(v) => Some(v.to_string()),
+        Container::Unsigned128(v) => Some(v.to_string()),
+        Container::Decimal(v) => Some(v.to_string()),
+        Container::RawNumber(v) | Container::BigNumber(v) => Some(v.to_owned()),
+        Container::Boolean(v) => Some(v.to_string()),
+        _ => None,
+    }
+}
+
+fn as_real(value: &Container) -> f64 {
+    match value {
+        Container::Number(v) => *v as f64,
+        Container::Unsigned(v) => *v as f64,
+        Container::Number128(v) => *v as f64,
+        Container::Unsigned128(v) => *v as f64,
+        Container::Decimal(v) => *v,
+        Container::RawNumber(v) | Container::BigNumber(v) => {
+            v.parse().unwrap_or(f64::NAN)
+        }
+        _ => f64::NAN,
+    }
+}
+
+impl Container {
+    /// Binary searches an array whose elements are sorted by the field at
+    /// `path`, a JSON Pointer relative to each element (`""` searches the
+    /// elements themselves).
+    ///
+    /// Mirrors `slice::binary_search`: returns `Ok(index)` of a matching
+    /// element, or `Err(index)` where `key` could be inserted to keep the
+    /// array sorted. Elements lacking the field compare as `null`, and
+    /// non-array containers always return `Err(0)`.
+    ///
+    /// ```
+    /// use json_parser::container::Container;
+    /// use json_parser::parser::parse_str;
+    ///
+    /// let users = parse_str(
+    ///     r#"[{"id": 3, "name": "a"}, {"id": 8, "name": "b"},
+    ///         {"id": 21, "name": "c"}]"#,
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(users.binary_search_by_path("/id", &Container::from(8)), Ok(1));
+    /// assert_eq!(users.binary_search_by_path("/id", &Container::from(9)), Err(2));
+    /// ```
+    pub fn binary_search_by_path(
+        &self,
+        path: &str,
+        key: &Container,
+    ) -> Result<usize, usize> {
+        let (Self::Array(values), Some(tokens)) = (self, pointer::split(path))
+        else {
+            return Err(0);
+        };
+
+        values.binary_search_by(|element| {
+            let field =
+                pointer::resolve(element, &tokens).unwrap_or(&Container::Null);
+            compare(field, key)
+        })
+    }
+
+    /// Builds a hash index over an array of objects, keyed by the value of
+    /// member `field` of each element.
+    ///
+    /// Scalar field values are keyed by their string form (`7` and `"7"`
+    /// share a key); elements that are not objects, lack the field or hold
+    /// a null/array/object there are skipped. When several elements share
+    /// a key, the first one is kept. Non-array containers give an empty
+    /// index.
+    ///
+    /// ```
+    /// use json_parser::parser::parse_str;
+    ///
+    /// let users = parse_str(
+    ///     r#"[{"id": "u1", "age": 30}, {"id": "u2", "age": 41}]"#,
+    /// )
+    /// .unwrap();
+    /// let index = users.index_by("id");
+    ///
+    /// assert_eq!(index["u2"]["age"].get_uint(), Some(41));
+    /// assert!(index.get("u3").is_none());
+    /// ```
+    pub fn index_by(&self, field: &str) -> HashMap<String, &Container> {
+        let mut index = HashMap::new();
+        if let Self::Array(values) = self {
+            for element in values {
+                if let Some(key) = element_key(element, field) {
+                    index.entry(key).or_insert(element);
+                }
+            }
+        }
+        index
+    }
+
+    /// Owned counterpart of [`Container::index_by`], consuming self so that
+    /// elements are moved into the index instead of borrowed.
+    pub fn into_index_by(self, field: &str) -> HashMap<String, Container> {
+        let mut index = HashMap::new();
+        if let Self::Array(values) = self {
+            for element in values {
+                if let Some(key) = element_key(&element, field) {
+                    index.entry(key).or_insert(element);
+                }
+            }
+        }
+        index
+    }
+}
+
+fn element_key(element: &Container, field: &str) -> Option<String> {
+    match element {
+        Container::Object(map) => map.get(field).and_then(index_key),
+        _ => None,
+    }
+}
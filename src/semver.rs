@@ -0,0 +1,392 @@
+//! Semantic versions (<https://semver.org>) stored in string values, and
+//! the version requirements of package manifests.
+use super::container::Container;
+use core::cmp::Ordering;
+use core::fmt;
+
+/// A dot-separated part of a pre-release.
+///
+/// The derived order is that of the specification: numeric identifiers
+/// compare numerically and before alphanumeric ones, which compare in
+/// ASCII order.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Identifier {
+    Numeric(u64),
+    Alphanumeric(String),
+}
+
+/// A version `MAJOR.MINOR.PATCH`, with an optional pre-release
+/// (`-rc.1`) and build metadata (`+sha.5114f85`).
+///
+/// Versions are ordered by precedence, a pre-release coming before its
+/// release; build metadata only breaks ties.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    pub pre: Vec<Identifier>,
+    pub build: String,
+}
+
+/// A number without leading zeros.
+fn numeric(text: &str) -> Option<u64> {
+    let valid = !text.is_empty()
+        && text.bytes().all(|byte| byte.is_ascii_digit())
+        && (text == "0" || !text.starts_with('0'));
+    valid.then(|| text.parse().ok()).flatten()
+}
+
+/// Whether `text` is made of dot-separated, non-empty identifiers of
+/// `[0-9A-Za-z-]`.
+fn identifiers(text: &str) -> bool {
+    text.split('.').all(|part| {
+        !part.is_empty()
+            && part
+                .bytes()
+                .all(|byte| byte.is_ascii_alphanumeric() || byte == b'-')
+    })
+}
+
+/// The identifiers of the pre-release `text`.
+fn prerelease(text: &str) -> Option<Vec<Identifier>> {
+    if !identifiers(text) {
+        return None;
+    }
+    text.split('.')
+        .map(|part| {
+            if part.bytes().all(|byte| byte.is_ascii_digit()) {
+                numeric(part).map(Identifier::Numeric)
+            } else {
+                Some(Identifier::Alphanumeric(part.to_owned()))
+            }
+        })
+        .collect()
+}
+
+impl Version {
+    pub fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+            pre: Vec::new(),
+            build: String::new(),
+        }
+    }
+
+    /// Parses a version as written by the specification, e.g.
+    /// `1.0.0-alpha.1+001`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let (text, build) = match text.split_once('+') {
+            Some((text, build)) => (text, Some(build)),
+            None => (text, None),
+        };
+        let (text, pre) = match text.split_once('-') {
+            Some((text, pre)) => (text, Some(pre)),
+            None => (text, None),
+        };
+
+        let mut parts = text.split('.');
+        let mut version = Self::new(
+            numeric(parts.next()?)?,
+            numeric(parts.next()?)?,
+            numeric(parts.next()?)?,
+        );
+        if parts.next().is_some() {
+            return None;
+        }
+        if let Some(pre) = pre {
+            version.pre = prerelease(pre)?;
+        }
+        if let Some(build) = build {
+            if !identifiers(build) {
+                return None;
+            }
+            version.build = build.to_owned();
+        }
+        Some(version)
+    }
+
+    /// Whether this is a pre-release.
+    pub fn is_prerelease(&self) -> bool {
+        !self.pre.is_empty()
+    }
+
+    /// Compares by precedence, ignoring build metadata.
+    pub fn cmp_precedence(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (self.pre.is_empty(), other.pre.is_empty()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => self.pre.cmp(&other.pre),
+            })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.cmp_precedence(other)
+            .then_with(|| self.build.cmp(&other.build))
+    }
+}
+
+impl fmt::Display for Identifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Identifier::Numeric(value) => f.write_str(&value.to_string()),
+            Identifier::Alphanumeric(value) => f.write_str(value),
+        }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut text = format!("{}.{}.{}", self.major, self.minor, self.patch);
+        for (idx, part) in self.pre.iter().enumerate() {
+            text.push(if idx == 0 { '-' } else { '.' });
+            text.push_str(&part.to_string());
+        }
+        if !self.build.is_empty() {
+            text.push('+');
+            text.push_str(&self.build);
+        }
+        f.write_str(text.as_str())
+    }
+}
+
+/// Operator of a [`Comparator`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    /// `=1.2.3`, or a wildcard as in `1.2.*`
+    Exact,
+    /// `>1.2.3`
+    Greater,
+    /// `>=1.2.3`
+    GreaterEq,
+    /// `<1.2.3`
+    Less,
+    /// `<=1.2.3`
+    LessEq,
+    /// `~1.2.3`: patch updates only
+    Tilde,
+    /// `^1.2.3`, or no operator: updates not changing the leftmost
+    /// non-zero part
+    Caret,
+}
+
+/// One condition of a [`VersionReq`]. Missing parts (`^1.2`, `1.*`)
+/// match any value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comparator {
+    pub op: Op,
+    pub major: u64,
+    pub minor: Option<u64>,
+    pub patch: Option<u64>,
+    pub pre: Vec<Identifier>,
+}
+
+impl Comparator {
+    fn parse(text: &str) -> Option<Self> {
+        let (op, text) = [
+            (">=", Op::GreaterEq),
+            ("<=", Op::LessEq),
+            ("=", Op::Exact),
+            (">", Op::Greater),
+            ("<", Op::Less),
+            ("~", Op::Tilde),
+            ("^", Op::Caret),
+        ]
+        .into_iter()
+        .find_map(|(token, op)| text.strip_prefix(token).map(|rest| (op, rest)))
+        .unwrap_or((Op::Caret, text));
+        let (text, pre) = match text.split_once('-') {
+            Some((text, pre)) => (text, prerelease(pre)?),
+            None => (text, Vec::new()),
+        };
+
+        let mut parts = text.split('.');
+        let major = numeric(parts.next()?)?;
+        let mut rest = [None, None];
+        let mut wildcard = false;
+        for slot in rest.iter_mut() {
+            match parts.next() {
+                Some("*" | "x" | "X") => wildcard = true,
+                Some(part) if !wildcard => *slot = Some(numeric(part)?),
+                Some(_) => return None,
+                None => break,
+            }
+        }
+        let [minor, patch] = rest;
+        let valid = parts.next().is_none()
+            && (pre.is_empty() || patch.is_some())
+            && (!wildcard || matches!(op, Op::Exact | Op::Caret));
+        valid.then_some(Self {
+            op: if wildcard { Op::Exact } else { op },
+            major,
+            minor,
+            patch,
+            pre,
+        })
+    }
+
+    pub fn matches(&self, version: &Version) -> bool {
+        let Self {
+            major,
+            minor,
+            patch,
+            ..
+        } = *self;
+        let lower = Version {
+            pre: self.pre.clone(),
+            ..Version::new(major, minor.unwrap_or(0), patch.unwrap_or(0))
+        };
+        // First version past those matching the written parts exactly.
+        let next = match (minor, patch) {
+            (Some(minor), Some(patch)) => {
+                Version::new(major, minor, patch.saturating_add(1))
+            }
+            (Some(minor), None) => {
+                Version::new(major, minor.saturating_add(1), 0)
+            }
+            (None, _) => Version::new(major.saturating_add(1), 0, 0),
+        };
+        let below = |bound: &Version| version.cmp_precedence(bound).is_lt();
+        let at_least = |bound: &Version| !below(bound);
+
+        match self.op {
+            Op::Exact if patch.is_some() => {
+                version.cmp_precedence(&lower).is_eq()
+            }
+            Op::Exact => at_least(&lower) && below(&next),
+            Op::Greater if patch.is_some() => {
+                version.cmp_precedence(&lower).is_gt()
+            }
+            Op::Greater => at_least(&next),
+            Op::GreaterEq => at_least(&lower),
+            Op::Less => below(&lower),
+            Op::LessEq if patch.is_some() => {
+                !version.cmp_precedence(&lower).is_gt()
+            }
+            Op::LessEq => below(&next),
+            Op::Tilde => {
+                let next = match minor {
+                    Some(minor) => {
+                        Version::new(major, minor.saturating_add(1), 0)
+                    }
+                    None => Version::new(major.saturating_add(1), 0, 0),
+                };
+                at_least(&lower) && below(&next)
+            }
+            Op::Caret => {
+                let next = match (major, minor, patch) {
+                    (0, Some(0), Some(patch)) => {
+                        Version::new(0, 0, patch.saturating_add(1))
+                    }
+                    (0, Some(minor), _) => {
+                        Version::new(0, minor.saturating_add(1), 0)
+                    }
+                    _ => Version::new(major.saturating_add(1), 0, 0),
+                };
+                at_least(&lower) && below(&next)
+            }
+        }
+    }
+}
+
+/// A version requirement, as written in package manifests: comparators
+/// separated by `,` or spaces, all of which must match, e.g.
+/// `>=1.2, <1.5` or `^0.3`. `*` alone matches every release.
+///
+/// As with Cargo and npm, a pre-release only matches if a comparator
+/// names the same `MAJOR.MINOR.PATCH` with a pre-release.
+///
+/// ```
+/// use json_parser::semver::{Version, VersionReq};
+///
+/// let req = VersionReq::parse("^1.2").unwrap();
+/// assert!(req.matches(&Version::parse("1.9.0").unwrap()));
+/// assert!(!req.matches(&Version::parse("2.0.0").unwrap()));
+/// assert!(!req.matches(&Version::parse("1.3.0-rc.1").unwrap()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionReq {
+    pub comparators: Vec<Comparator>,
+}
+
+impl VersionReq {
+    pub fn parse(text: &str) -> Option<Self> {
+        // Operators may be written apart from their version (`>= 1.2`).
+        let mut tokens: Vec<String> = Vec::new();
+        let mut operator = String::new();
+        for token in text
+            .split(|c: char| c == ',' || c.is_ascii_whitespace())
+            .filter(|token| !token.is_empty())
+        {
+            if token.bytes().all(|byte| b"<>=~^".contains(&byte)) {
+                operator.push_str(token);
+            } else {
+                tokens.push(core::mem::take(&mut operator) + token);
+            }
+        }
+        if !operator.is_empty() {
+            return None;
+        }
+        if tokens == ["*"] {
+            return Some(Self {
+                comparators: Vec::new(),
+            });
+        }
+        let comparators = tokens
+            .iter()
+            .map(|token| Comparator::parse(token))
+            .collect::<Option<Vec<_>>>()?;
+        (!comparators.is_empty()).then_some(Self { comparators })
+    }
+
+    pub fn matches(&self, version: &Version) -> bool {
+        let allowed_pre = !version.is_prerelease()
+            || self.comparators.iter().any(|comparator| {
+                !comparator.pre.is_empty()
+                    && (comparator.major, comparator.minor, comparator.patch)
+                        == (
+                            version.major,
+                            Some(version.minor),
+                            Some(version.patch),
+                        )
+            });
+        allowed_pre
+            && self
+                .comparators
+                .iter()
+                .all(|comparator| comparator.matches(version))
+    }
+}
+
+impl Container {
+    /// Returns the semantic version held by a string value.
+    ///
+    /// ```
+    /// use json_parser::parser::parse_str;
+    /// use json_parser::semver::Version;
+    ///
+    /// let manifest = parse_str(r#"{"version": "1.4.0-beta.2"}"#).unwrap();
+    /// let version = manifest["version"].get_semver().unwrap();
+    /// assert_eq!(version.minor, 4);
+    /// assert!(version < Version::new(1, 4, 0));
+    /// ```
+    pub fn get_semver(&self) -> Option<Version> {
+        match self {
+            Self::String(text) => Version::parse(text),
+            _ => None,
+        }
+    }
+}
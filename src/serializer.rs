@@ -0,0 +1,181 @@
+//! Visitor interface for writing containers in custom output formats.
+use super::container::Container;
+#[cfg(feature = "uuid")]
+use super::uuid;
+
+/// Receives the contents of a `Container` one value at a time, in document
+/// order, when driven by [`Container::serialize`].
+///
+/// Arrays are reported as `begin_array`, the elements, then `end_array`;
+/// objects as `begin_object`, then `visit_key` followed by the value for
+/// every member, then `end_object`. Any error returned aborts the walk and
+/// is handed back to the caller.
+///
+/// ```
+/// use json_parser::parser::parse_str;
+/// use json_parser::serializer::Serializer;
+///
+/// /// Renders scalars as `<type>value</type>`.
+/// struct Xml(String);
+///
+/// type Res = Result<(), Box<dyn core::error::Error>>;
+///
+/// impl Serializer for Xml {
+///     fn visit_null(&mut self) -> Res { self.0 += "<null/>"; Ok(()) }
+///     fn visit_bool(&mut self, v: bool) -> Res { self.0 += &format!("<bool>{v}</bool>"); Ok(()) }
+///     fn visit_number(&mut self, v: i64) -> Res { self.0 += &format!("<int>{v}</int>"); Ok(()) }
+///     fn visit_unsigned(&mut self, v: u64) -> Res { self.0 += &format!("<int>{v}</int>"); Ok(()) }
+///     fn visit_decimal(&mut self, v: f64) -> Res { self.0 += &format!("<real>{v}</real>"); Ok(()) }
+///     fn visit_str(&mut self, v: &str) -> Res { self.0 += &format!("<str>{v}</str>"); Ok(()) }
+///     fn begin_array(&mut self, _: usize) -> Res { self.0 += "<array>"; Ok(()) }
+///     fn end_array(&mut self) -> Res { self.0 += "</array>"; Ok(()) }
+///     fn begin_object(&mut self, _: usize) -> Res { self.0 += "<object>"; Ok(()) }
+///     fn visit_key(&mut self, k: &str) -> Res { self.0 += &format!("<key>{k}</key>"); Ok(()) }
+///     fn end_object(&mut self) -> Res { self.0 += "</object>"; Ok(()) }
+/// }
+///
+/// let mut xml = Xml(String::new());
+/// parse_str(r#"[1, {"a": null}]"#).unwrap().serialize(&mut xml).unwrap();
+/// assert_eq!(xml.0, "<array><int>1</int><object><key>a</key><null/></object></array>");
+/// ```
+pub trait Serializer {
+    fn visit_null(&mut self) -> Result<(), Box<dyn core::error::Error>>;
+
+    fn visit_bool(
+        &mut self,
+        value: bool,
+    ) -> Result<(), Box<dyn core::error::Error>>;
+
+    fn visit_number(
+        &mut self,
+        value: i64,
+    ) -> Result<(), Box<dyn core::error::Error>>;
+
+    fn visit_unsigned(
+        &mut self,
+        value: u64,
+    ) -> Result<(), Box<dyn core::error::Error>>;
+
+    fn visit_decimal(
+        &mut self,
+        value: f64,
+    ) -> Result<(), Box<dyn core::error::Error>>;
+
+    fn visit_str(
+        &mut self,
+        value: &str,
+    ) -> Result<(), Box<dyn core::error::Error>>;
+
+    /// Reports a number kept as written in the input. By default, it is
+    /// converted and reported as an integer if it is one that fits, or as
+    /// a real number otherwise.
+    fn visit_raw_number(
+        &mut self,
+        text: &str,
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        if let Ok(value) = text.parse() {
+            self.visit_unsigned(value)
+        } else if let Ok(value) = text.parse() {
+            self.visit_number(value)
+        } else {
+            self.visit_decimal(text.parse()?)
+        }
+    }
+
+    /// Reports a negative integer beyond the range of `i64`. By default,
+    /// it is reported as by [`Serializer::visit_big_number`].
+    fn visit_number128(
+        &mut self,
+        value: i128,
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        self.visit_big_number(&value.to_string())
+    }
+
+    /// Reports an integer beyond the range of `u64`. By default, it is
+    /// reported as by [`Serializer::visit_big_number`].
+    fn visit_unsigned128(
+        &mut self,
+        value: u128,
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        self.visit_big_number(&value.to_string())
+    }
+
+    /// Reports an integer beyond the range of `i128` and `u128`. By
+    /// default, it is reported as by [`Serializer::visit_raw_number`].
+    fn visit_big_number(
+        &mut self,
+        digits: &str,
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        self.visit_raw_number(digits)
+    }
+
+    /// Reports a UUID. By default, it is reported as a string in
+    /// canonical form.
+    #[cfg(feature = "uuid")]
+    fn visit_uuid(
+        &mut self,
+        bytes: &[u8; 16],
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        self.visit_str(&uuid::format(bytes))
+    }
+
+    /// Starts an array holding `len` elements.
+    fn begin_array(
+        &mut self,
+        len: usize,
+    ) -> Result<(), Box<dyn core::error::Error>>;
+
+    fn end_array(&mut self) -> Result<(), Box<dyn core::error::Error>>;
+
+    /// Starts an object holding `len` members.
+    fn begin_object(
+        &mut self,
+        len: usize,
+    ) -> Result<(), Box<dyn core::error::Error>>;
+
+    /// Reports the key of the member whose value is visited next.
+    fn visit_key(
+        &mut self,
+        key: &str,
+    ) -> Result<(), Box<dyn core::error::Error>>;
+
+    fn end_object(&mut self) -> Result<(), Box<dyn core::error::Error>>;
+}
+
+impl Container {
+    /// Walks self depth-first, reporting every value to `serializer`.
+    pub fn serialize<S: Serializer + ?Sized>(
+        &self,
+        serializer: &mut S,
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        match self {
+            Self::Null => serializer.visit_null(),
+            Self::Boolean(value) => serializer.visit_bool(*value),
+            Self::Number(value) => serializer.visit_number(*value),
+            Self::Unsigned(value) => serializer.visit_unsigned(*value),
+            Self::Number128(value) => serializer.visit_number128(*value),
+            Self::Unsigned128(value) => serializer.visit_unsigned128(*value),
+            Self::Decimal(value) => serializer.visit_decimal(*value),
+            Self::RawNumber(value) => serializer.visit_raw_number(value),
+            Self::BigNumber(value) => serializer.visit_big_number(value),
+            Self::String(value) => serializer.visit_str(value),
+            #[cfg(feature = "uuid")]
+            Self::Uuid(value) => serializer.visit_uuid(value),
+            Self::Array(values) => {
+                serializer.begin_array(values.len())?;
+                for value in values {
+                    value.serialize(serializer)?;
+                }
+                serializer.end_array()
+            }
+            Self::Object(map) => {
+                serializer.begin_object(map.len())?;
+                for (key, value) in map {
+                    serializer.visit_key(key)?;
+                    value.serialize(serializer)?;
+                }
+                serializer.end_object()
+            }
+        }
+    }
+}
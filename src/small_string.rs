@@ -0,0 +1,229 @@
+//! The strings of `Container::String`, kept inline when short.
+//!
+//! Most strings of documents are short: names, ids, enum values. A
+//! [`SmallString`] of up to [`INLINE_LEN`] bytes is stored in the value
+//! itself, without a heap allocation, and takes no more room in a
+//! `Container` than a `String` would.
+use core::borrow::Borrow;
+use core::hash::{Hash, Hasher};
+use core::ops::Deref;
+
+/// Bytes of the longest string kept inline.
+pub const INLINE_LEN: usize = 30;
+
+#[derive(Clone)]
+enum Repr {
+    Inline { len: u8, bytes: [u8; INLINE_LEN] },
+    Heap(String),
+}
+
+/// An immutable string stored inline up to [`INLINE_LEN`] bytes, and on
+/// the heap beyond.
+///
+/// It dereferences to `str`, and compares, hashes and orders as one.
+///
+/// ```
+/// use json_parser::small_string::SmallString;
+///
+/// let name = SmallString::from("ada");
+/// assert!(name.is_inline());
+/// assert_eq!(name, "ada");
+/// assert_eq!(name.to_uppercase(), "ADA");
+///
+/// let long = SmallString::from("a".repeat(100));
+/// assert!(!long.is_inline());
+/// assert_eq!(long.len(), 100);
+/// assert_eq!(String::from(long), "a".repeat(100));
+/// ```
+#[derive(Clone)]
+pub struct SmallString(Repr);
+
+impl SmallString {
+    /// The empty string.
+    pub const fn new() -> Self {
+        Self(Repr::Inline {
+            len: 0,
+            bytes: [0; INLINE_LEN],
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        match &self.0 {
+            Repr::Inline { len, bytes } => {
+                // Inline bytes are copied from a `str`, whole.
+                unsafe {
+                    core::str::from_utf8_unchecked(&bytes[..*len as usize])
+                }
+            }
+            Repr::Heap(text) => text,
+        }
+    }
+
+    /// Whether the string is stored without a heap allocation.
+    pub fn is_inline(&self) -> bool {
+        matches!(self.0, Repr::Inline { .. })
+    }
+
+    /// Bytes allocated on the heap for the string, `0` if inline.
+    pub fn heap_capacity(&self) -> usize {
+        match &self.0 {
+            Repr::Heap(text) => text.capacity(),
+            Repr::Inline { .. } => 0,
+        }
+    }
+
+    pub fn into_string(self) -> String {
+        match self.0 {
+            Repr::Heap(text) => text,
+            Repr::Inline { .. } => self.as_str().to_owned(),
+        }
+    }
+}
+
+impl Default for SmallString {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for SmallString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for SmallString {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Borrow<str> for SmallString {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl From<&str> for SmallString {
+    fn from(text: &str) -> Self {
+        if text.len() > INLINE_LEN {
+            return Self(Repr::Heap(text.to_owned()));
+        }
+        let mut bytes = [0; INLINE_LEN];
+        bytes[..text.len()].copy_from_slice(text.as_bytes());
+        Self(Repr::Inline {
+            len: text.len() as u8,
+            bytes,
+        })
+    }
+}
+
+impl From<String> for SmallString {
+    /// Keeps the allocation of a long `text`.
+    fn from(text: String) -> Self {
+        match text.len() > INLINE_LEN {
+            true => Self(Repr::Heap(text)),
+            false => Self::from(text.as_str()),
+        }
+    }
+}
+
+impl From<&String> for SmallString {
+    fn from(text: &String) -> Self {
+        Self::from(text.as_str())
+    }
+}
+
+impl From<char> for SmallString {
+    fn from(chr: char) -> Self {
+        Self::from(chr.encode_utf8(&mut [0; 4]) as &str)
+    }
+}
+
+impl From<SmallString> for String {
+    fn from(text: SmallString) -> Self {
+        text.into_string()
+    }
+}
+
+impl FromIterator<char> for SmallString {
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        Self::from(iter.into_iter().collect::<String>())
+    }
+}
+
+impl PartialEq for SmallString {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for SmallString {}
+
+impl PartialEq<str> for SmallString {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for SmallString {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<String> for SmallString {
+    fn eq(&self, other: &String) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<SmallString> for str {
+    fn eq(&self, other: &SmallString) -> bool {
+        self == other.as_str()
+    }
+}
+
+impl PartialEq<SmallString> for &str {
+    fn eq(&self, other: &SmallString) -> bool {
+        *self == other.as_str()
+    }
+}
+
+impl PartialEq<SmallString> for String {
+    fn eq(&self, other: &SmallString) -> bool {
+        self == other.as_str()
+    }
+}
+
+impl PartialOrd for SmallString {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SmallString {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl Hash for SmallString {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl core::fmt::Debug for SmallString {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl core::fmt::Display for SmallString {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::Display::fmt(self.as_str(), f)
+    }
+}
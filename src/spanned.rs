@@ -0,0 +1,165 @@
+//! Documents parsed with the place of every value in their text, for
+//! validators reporting where a value breaks a rule rather than only
+//! where the syntax does.
+use super::analyze::Path;
+use super::container::Container;
+use super::dialect::Json;
+use super::map::Map;
+use super::parser::{self, Observer, Span};
+use super::pointer;
+use core::ops::Range;
+use std::collections::HashMap;
+
+/// A value with the place of the text it was read from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spanned<T> {
+    pub value: T,
+    /// Line where the value starts, from 1
+    pub line: usize,
+    /// Column where the value starts, from 1
+    pub col: usize,
+    pub byte_range: Range<usize>,
+}
+
+impl<T> Spanned<T> {
+    fn new(value: T, span: &Span) -> Self {
+        Self {
+            value,
+            line: span.line,
+            col: span.col,
+            byte_range: span.start..span.end,
+        }
+    }
+}
+
+/// A document with the spans of its values and keys by JSON Pointer,
+/// made by [`parse_spanned`].
+///
+/// A key written twice keeps its last value, as when parsing, and the
+/// span of that value.
+#[derive(Debug, Clone)]
+pub struct SpannedDocument {
+    root: Container,
+    values: HashMap<String, Span>,
+    keys: HashMap<String, Span>,
+}
+
+impl SpannedDocument {
+    pub fn root(&self) -> Spanned<&Container> {
+        Spanned::new(&self.root, &self.values[""])
+    }
+
+    /// The value at the JSON Pointer `path`, with its span.
+    pub fn get(&self, path: &str) -> Option<Spanned<&Container>> {
+        let value = self.root.pointer(path)?;
+        Some(Spanned::new(value, self.values.get(path)?))
+    }
+
+    /// The key of the member at the JSON Pointer `path`, with the span of
+    /// the key as written, quotes included.
+    pub fn key(&self, path: &str) -> Option<Spanned<&str>> {
+        let span = self.keys.get(path)?;
+        let (parent, _) = path.rsplit_once('/')?;
+        let wanted = pointer::split(path)?.pop()?;
+        let Container::Object(map) = self.root.pointer(parent)? else {
+            return None;
+        };
+        let (key, _) = map.iter().find(|(key, _)| *key == wanted)?;
+        Some(Spanned::new(key, span))
+    }
+
+    pub fn into_container(self) -> Container {
+        self.root
+    }
+}
+
+/// Builds the document and its spans from the events of the parser.
+#[derive(Default)]
+struct SpanScan {
+    path: Path,
+    /// Open containers, innermost last, with the span of their opening
+    /// bracket and the key of the member being read
+    open: Vec<(Container, Span, Option<String>)>,
+    root: Option<Container>,
+    values: HashMap<String, Span>,
+    keys: HashMap<String, Span>,
+}
+
+impl SpanScan {
+    fn push(&mut self, value: Container) {
+        match self.open.last_mut() {
+            Some((Container::Array(array), _, _)) => array.push(value),
+            Some((Container::Object(map), _, key)) => {
+                map.insert(key.take().unwrap_or_default(), value);
+            }
+            _ => self.root = Some(value),
+        }
+    }
+}
+
+impl Observer for SpanScan {
+    fn open(&mut self, object: bool, span: Span) {
+        let container = if object {
+            Container::Object(Map::new())
+        } else {
+            Container::Array(Vec::new())
+        };
+        self.open.push((container, span, None));
+        self.path.open(object);
+    }
+
+    fn close(&mut self, span: Span) {
+        let Some((container, open, _)) = self.open.pop() else {
+            return;
+        };
+        self.values.insert(
+            self.path.container_pointer(),
+            Span {
+                end: span.end,
+                ..open
+            },
+        );
+        self.path.close();
+        self.push(container);
+    }
+
+    fn key(&mut self, key: &str, span: Span) {
+        self.path.key(key);
+        self.keys.insert(self.path.pointer(), span);
+        if let Some((_, _, pending)) = self.open.last_mut() {
+            *pending = Some(key.to_owned());
+        }
+    }
+
+    fn scalar(&mut self, value: &Container, span: Span) {
+        self.values.insert(self.path.pointer(), span);
+        self.path.value_done();
+        self.push(value.clone());
+    }
+}
+
+/// Parses `input` as JSON, keeping the span of every value and key.
+///
+/// ```
+/// use json_parser::spanned::parse_spanned;
+///
+/// let input = "{\n  \"db\": {\n    \"port\": \"80\"\n  }\n}";
+/// let doc = parse_spanned(input).unwrap();
+/// let port = doc.get("/db/port").unwrap();
+/// assert!(port.value.get_uint().is_none());
+/// assert_eq!((port.line, port.col), (3, 13));
+/// assert_eq!(&input[port.byte_range], "\"80\"");
+/// assert_eq!(doc.key("/db/port").unwrap().value, "port");
+/// assert_eq!(doc.root().byte_range, 0..input.len());
+/// ```
+pub fn parse_spanned(
+    input: &str,
+) -> Result<SpannedDocument, Box<dyn core::error::Error>> {
+    let mut scan = SpanScan::default();
+    parser::observe(input, &Json, &mut scan)?;
+    Ok(SpannedDocument {
+        root: scan.root.unwrap_or(Container::Null),
+        values: scan.values,
+        keys: scan.keys,
+    })
+}
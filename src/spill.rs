@@ -0,0 +1,203 @@
+//! Arrays that move their elements to a temporary file past a memory
+//! ceiling, for bounded-memory jobs collecting values from inputs of any
+//! size.
+//!
+//! A [`SpillArray`] keeps the elements pushed last in memory. Once they
+//! take more than its ceiling, it appends them to its file as JSON Lines
+//! and starts over, so the file holds the first elements and memory the
+//! last ones. Iteration reads the file back, then memory, in push order.
+//!
+//! Elements read back are parsed from their JSON, so they are not always
+//! of the variant pushed: raw numbers come back as the numbers they
+//! write, and UUIDs as strings. They may be up to `u16::MAX` levels deep,
+//! whatever limit they were first parsed with.
+use super::container::{Container, DumpOptions};
+use super::parser::{parse_with_options, ParserOptions};
+use super::split::compact_len;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Lines, Write};
+use std::path::PathBuf;
+
+/// Files created by this process, to name the next one.
+static FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// Options reading back what was written with the default `DumpOptions`,
+/// non-finite numbers and deep values included.
+fn read_options() -> ParserOptions {
+    ParserOptions {
+        max_depth: u16::MAX,
+        non_finite_numbers: true,
+        ..Default::default()
+    }
+}
+
+/// The temporary file of a [`SpillArray`], removed with it.
+struct SpillFile {
+    path: PathBuf,
+    file: File,
+    /// Bytes of the elements written in full
+    len: u64,
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// An append-only array holding at most about `limit` bytes of elements
+/// in memory, the others in a temporary file.
+///
+/// Sizes are those of the compact JSON of the elements.
+///
+/// ```
+/// use json_parser::parser::parse_str;
+/// use json_parser::spill::SpillArray;
+///
+/// let mut rows = SpillArray::new(64);
+/// for n in 0..100u64 {
+///     rows.push(parse_str(&format!(r#"{{"n": {}}}"#, n)).unwrap()).unwrap();
+/// }
+/// assert_eq!(rows.len(), 100);
+/// assert!(rows.spilled() > 90);
+///
+/// let total: u64 = rows
+///     .iter()
+///     .unwrap()
+///     .map(|row| row.unwrap()["n"].get_uint().unwrap())
+///     .sum();
+/// assert_eq!(total, 4950);
+/// ```
+pub struct SpillArray {
+    memory: Vec<Container>,
+    memory_bytes: usize,
+    limit: usize,
+    file: Option<SpillFile>,
+    spilled: usize,
+}
+
+impl SpillArray {
+    /// An empty array keeping up to `limit` bytes of elements in memory.
+    pub fn new(limit: usize) -> Self {
+        Self {
+            memory: Vec::new(),
+            memory_bytes: 0,
+            limit,
+            file: None,
+            spilled: 0,
+        }
+    }
+
+    /// Appends `value`, moving the elements in memory to the file if they
+    /// take more than the limit.
+    pub fn push(
+        &mut self,
+        value: Container,
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        self.memory_bytes += compact_len(&value);
+        self.memory.push(value);
+        if self.memory_bytes > self.limit {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    /// Writes the elements in memory to the file, keeping them in memory
+    /// if it fails.
+    fn spill(&mut self) -> Result<(), Box<dyn core::error::Error>> {
+        let options = DumpOptions::new();
+        let mut lines = String::new();
+        for value in &self.memory {
+            // Compact JSON escapes line breaks, so each value is a line.
+            lines.push_str(&value.dump(&options)?);
+            lines.push('\n');
+        }
+        let file = match &mut self.file {
+            Some(file) => file,
+            None => {
+                let path = std::env::temp_dir().join(format!(
+                    "json_parser-spill-{}-{}.jsonl",
+                    std::process::id(),
+                    FILES.fetch_add(1, Ordering::Relaxed)
+                ));
+                let file = File::options()
+                    .append(true)
+                    .create_new(true)
+                    .open(&path)?;
+                self.file.insert(SpillFile { path, file, len: 0 })
+            }
+        };
+        if let Err(error) = file.file.write_all(lines.as_bytes()) {
+            // Drops what was written of the elements kept in memory.
+            let _ = file.file.set_len(file.len);
+            return Err(error.into());
+        }
+        file.len += lines.len() as u64;
+        self.spilled += self.memory.len();
+        self.memory.clear();
+        self.memory_bytes = 0;
+        Ok(())
+    }
+
+    /// Number of elements.
+    pub fn len(&self) -> usize {
+        self.spilled + self.memory.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of elements in the file.
+    pub fn spilled(&self) -> usize {
+        self.spilled
+    }
+
+    /// The elements in push order, those of the file parsed as they are
+    /// read.
+    pub fn iter(
+        &mut self,
+    ) -> Result<SpillIter<'_>, Box<dyn core::error::Error>> {
+        let lines = match &self.file {
+            Some(file) => Some(BufReader::new(File::open(&file.path)?).lines()),
+            None => None,
+        };
+        Ok(SpillIter {
+            lines,
+            memory: self.memory.iter(),
+        })
+    }
+
+    /// Builds the `Container` array of all the elements, in memory.
+    pub fn into_container(
+        mut self,
+    ) -> Result<Container, Box<dyn core::error::Error>> {
+        let elements = self.iter()?.collect::<Result<_, _>>()?;
+        Ok(Container::Array(elements))
+    }
+}
+
+/// Iterator over the elements of a [`SpillArray`], created by
+/// [`SpillArray::iter`].
+pub struct SpillIter<'a> {
+    lines: Option<Lines<BufReader<File>>>,
+    memory: core::slice::Iter<'a, Container>,
+}
+
+impl Iterator for SpillIter<'_> {
+    type Item = Result<Container, Box<dyn core::error::Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(lines) = &mut self.lines {
+            match lines.next() {
+                Some(Ok(line)) => {
+                    return Some(parse_with_options(&line, &read_options()))
+                }
+                Some(Err(error)) => return Some(Err(error.into())),
+                None => self.lines = None,
+            }
+        }
+        self.memory.next().cloned().map(Ok)
+    }
+}
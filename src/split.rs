@@ -0,0 +1,135 @@
+//! Splitting large documents into several smaller containers, e.g. to
+//! stay under the request size limits of downstream APIs.
+use super::container::Container;
+use super::dump;
+
+/// Number of bytes `value` takes once serialized as compact JSON.
+pub(crate) fn compact_len(value: &Container) -> usize {
+    match value {
+        Container::Null => 4,
+        Container::Boolean(true) => 4,
+        Container::Boolean(false) => 5,
+        Container::Number(v) => v.to_string().len(),
+        Container::Unsigned(v) => v.to_string().len(),
+        Container::Number128(v) => v.to_string().len(),
+        Container::Unsigned128(v) => v.to_string().len(),
+        Container::Decimal(v) if v.is_finite() => {
+            dump::format_decimal(*v).len()
+        }
+        Container::Decimal(v) if v.is_nan() => 3,
+        Container::Decimal(v) => 8 + usize::from(*v < 0.0),
+        Container::RawNumber(v) | Container::BigNumber(v)
+            if dump::is_number(v) =>
+        {
+            v.len()
+        }
+        Container::RawNumber(v) | Container::BigNumber(v) => string_len(v),
+        Container::String(v) => string_len(v),
+        #[cfg(feature = "uuid")]
+        Container::Uuid(_) => 38,
+        Container::Array(values) => {
+            2 + values.iter().map(compact_len).sum::<usize>()
+                + values.len().saturating_sub(1)
+        }
+        Container::Object(map) => {
+            2 + map.iter().map(|(k, v)| member_len(k, v)).sum::<usize>()
+                + map.len().saturating_sub(1)
+        }
+    }
+}
+
+/// Bytes taken by `"key":value` inside an object.
+fn member_len(key: &str, value: &Container) -> usize {
+    string_len(key) + 1 + compact_len(value)
+}
+
+/// Bytes taken by a quoted and escaped string.
+pub(crate) fn string_len(value: &str) -> usize {
+    2 + value
+        .chars()
+        .map(|chr| match chr {
+            '"' | '\\' | '\n' | '\r' | '\t' | '\u{8}' | '\u{c}' => 2,
+            '\0'..='\u{1f}' => 6,
+            _ => chr.len_utf8(),
+        })
+        .sum::<usize>()
+}
+
+/// Splits an object into objects holding at most `n` members each
+/// (`n == 0` is treated as 1), taking keys in sorted order.
+///
+/// Anything other than a non-empty object is returned as a single part.
+///
+/// ```
+/// use json_parser::parser::parse_str;
+/// use json_parser::split;
+///
+/// let doc = parse_str(r#"{"a": 1, "b": 2, "c": 3}"#).unwrap();
+/// let parts = split::by_keys(&doc, 2);
+/// assert_eq!(parts[0], parse_str(r#"{"a": 1, "b": 2}"#).unwrap());
+/// assert_eq!(parts[1], parse_str(r#"{"c": 3}"#).unwrap());
+/// ```
+pub fn by_keys(document: &Container, n: usize) -> Vec<Container> {
+    let Container::Object(map) = document else {
+        return vec![document.clone()];
+    };
+    if map.is_empty() {
+        return vec![document.clone()];
+    }
+
+    let mut keys: Vec<&str> = map.keys().collect();
+    keys.sort_unstable();
+
+    keys.chunks(n.max(1))
+        .map(|chunk| {
+            let mut part = Container::new_object();
+            for key in chunk {
+                part.insert_str(key, map[*key].clone());
+            }
+            part
+        })
+        .collect()
+}
+
+/// Splits an array into arrays whose compact JSON serialization fits in
+/// `max_bytes`, preserving element order.
+///
+/// An element that alone exceeds the budget is placed in a chunk of its
+/// own, so every element ends up in exactly one chunk. Anything other
+/// than a non-empty array is returned as a single part.
+///
+/// ```
+/// use json_parser::parser::parse_str;
+/// use json_parser::split;
+///
+/// let doc = parse_str(r#"[1, 22, 333, 4444]"#).unwrap();
+/// let chunks = split::array_chunks(&doc, 8);
+/// assert_eq!(chunks, vec![
+///     parse_str("[1,22]").unwrap(),
+///     parse_str("[333]").unwrap(),
+///     parse_str("[4444]").unwrap(),
+/// ]);
+/// ```
+pub fn array_chunks(document: &Container, max_bytes: usize) -> Vec<Container> {
+    let Container::Array(values) = document else {
+        return vec![document.clone()];
+    };
+    if values.is_empty() {
+        return vec![document.clone()];
+    }
+
+    let mut chunks = Vec::new();
+    let (mut current, mut current_len) = (Vec::new(), 2);
+
+    for value in values {
+        let len = compact_len(value);
+        if !current.is_empty() && current_len + 1 + len > max_bytes {
+            chunks.push(Container::Array(core::mem::take(&mut current)));
+            current_len = 2;
+        }
+        current_len += if current.is_empty() { len } else { len + 1 };
+        current.push(value.clone());
+    }
+    chunks.push(Container::Array(current));
+    chunks
+}
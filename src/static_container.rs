@@ -0,0 +1,205 @@
+use super::container::Container;
+
+/// A borrowed, allocation-free counterpart of `Container` that can be
+/// declared in `const` and `static` items, e.g. for defaults embedded in
+/// a binary.
+///
+/// Arrays and objects are `'static` slices, so nothing is allocated until
+/// the value is turned into an owned `Container`.
+///
+/// ```
+/// use json_parser::parser::parse_str;
+/// use json_parser::static_container::StaticContainer;
+///
+/// static DEFAULTS: StaticContainer = StaticContainer::Object(&[
+///     ("port", StaticContainer::Unsigned(8080)),
+///     ("hosts", StaticContainer::Array(&[StaticContainer::String("::1")])),
+/// ]);
+///
+/// const PORT: Option<u64> = DEFAULTS.get("port").get_uint();
+/// assert_eq!(PORT, Some(8080));
+/// assert_eq!(
+///     DEFAULTS.to_container(),
+///     parse_str(r#"{"port": 8080, "hosts": ["::1"]}"#).unwrap()
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StaticContainer {
+    /// Representing an object of null type
+    Null,
+    /// A signed integer
+    Number(i64),
+    /// An unsigned integer
+    Unsigned(u64),
+    /// A real number
+    Decimal(f64),
+    /// boolean value
+    Boolean(bool),
+    /// Borrowed string
+    String(&'static str),
+    /// Borrowed list of values
+    Array(&'static [StaticContainer]),
+    /// Borrowed key value pairs, in declaration order
+    Object(&'static [(&'static str, StaticContainer)]),
+}
+
+impl StaticContainer {
+    /// Returns the value associated with `key`, or `Null` if self is not
+    /// an object or the key is missing.
+    pub const fn get(&self, key: &str) -> &StaticContainer {
+        if let Self::Object(pairs) = self {
+            let mut idx = 0;
+            while idx < pairs.len() {
+                if str_eq(pairs[idx].0, key) {
+                    return &pairs[idx].1;
+                }
+                idx += 1;
+            }
+        }
+        &Self::Null
+    }
+
+    /// Returns the element at `idx`, or `Null` if self is not an array or
+    /// the index is out of bounds.
+    pub const fn get_index(&self, idx: usize) -> &StaticContainer {
+        match self {
+            Self::Array(values) if idx < values.len() => &values[idx],
+            _ => &Self::Null,
+        }
+    }
+
+    pub const fn get_str(&self) -> Option<&'static str> {
+        match self {
+            Self::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub const fn get_uint(&self) -> Option<u64> {
+        match self {
+            Self::Unsigned(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub const fn get_int(&self) -> Option<i64> {
+        match self {
+            Self::Number(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub const fn get_real(&self) -> Option<f64> {
+        match self {
+            Self::Decimal(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub const fn get_bool(&self) -> Option<bool> {
+        match self {
+            Self::Boolean(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub const fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+
+    /// Returns the length of an array, object or string, else 1.
+    pub const fn len(&self) -> usize {
+        match self {
+            Self::Array(values) => values.len(),
+            Self::Object(pairs) => pairs.len(),
+            Self::String(value) => value.len(),
+            _ => 1,
+        }
+    }
+
+    /// Returns `true` if an array, object or string holds no elements
+    pub const fn is_empty(&self) -> bool {
+        match self {
+            Self::Array(values) => values.is_empty(),
+            Self::Object(pairs) => pairs.is_empty(),
+            Self::String(value) => value.is_empty(),
+            _ => false,
+        }
+    }
+
+    /// Builds an owned `Container` out of self.
+    pub fn to_container(&self) -> Container {
+        match self {
+            Self::Null => Container::Null,
+            Self::Number(value) => Container::Number(*value),
+            Self::Unsigned(value) => Container::Unsigned(*value),
+            Self::Decimal(value) => Container::Decimal(*value),
+            Self::Boolean(value) => Container::Boolean(*value),
+            Self::String(value) => Container::String((*value).into()),
+            Self::Array(values) => Container::Array(
+                values.iter().map(Self::to_container).collect(),
+            ),
+            Self::Object(pairs) => Container::Object(
+                pairs
+                    .iter()
+                    .map(|(key, value)| {
+                        ((*key).to_owned(), value.to_container())
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// `str` equality usable in const context.
+const fn str_eq(lhs: &str, rhs: &str) -> bool {
+    let (lhs, rhs) = (lhs.as_bytes(), rhs.as_bytes());
+    if lhs.len() != rhs.len() {
+        return false;
+    }
+    let mut idx = 0;
+    while idx < lhs.len() {
+        if lhs[idx] != rhs[idx] {
+            return false;
+        }
+        idx += 1;
+    }
+    true
+}
+
+impl From<&StaticContainer> for Container {
+    fn from(value: &StaticContainer) -> Self {
+        value.to_container()
+    }
+}
+
+impl From<StaticContainer> for Container {
+    fn from(value: StaticContainer) -> Self {
+        value.to_container()
+    }
+}
+
+impl PartialEq<Container> for StaticContainer {
+    /// Compares without allocating an owned copy of self.
+    fn eq(&self, other: &Container) -> bool {
+        match (self, other) {
+            (Self::Null, Container::Null) => true,
+            (Self::Number(this), Container::Number(other)) => this == other,
+            (Self::Unsigned(this), Container::Unsigned(other)) => this == other,
+            (Self::Decimal(this), Container::Decimal(other)) => this == other,
+            (Self::Boolean(this), Container::Boolean(other)) => this == other,
+            (Self::String(this), Container::String(other)) => this == other,
+            (Self::Array(this), Container::Array(other)) => {
+                this.len() == other.len()
+                    && this.iter().zip(other).all(|(a, b)| a == b)
+            }
+            (Self::Object(this), Container::Object(other)) => {
+                this.len() == other.len()
+                    && this.iter().all(|(k, v)| {
+                        other.get(*k).is_some_and(|found| v == found)
+                    })
+            }
+            _ => false,
+        }
+    }
+}
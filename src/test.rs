@@ -1,6 +1,10 @@
 use crate::container::Container;
-use crate::parser::parse_str;
+use crate::parser::{
+    parse_documents, parse_str, parse_with_options, ParserOptions,
+};
 
+// The deprecated positional dumps keep their coverage.
+#[allow(deprecated)]
 mod tests {
     use super::*;
 
@@ -27,8 +31,22 @@ mod tests {
             ("-7.5e-5", true, Container::Decimal(-7.5e-5)),
             ("-.5e-5", false, Container::Null),
             ("-1-e5", false, Container::Null),
+            ("-", false, Container::Null),
+            ("[-]", false, Container::Null),
+            ("1-2", false, Container::Null),
+            ("12-3", false, Container::Null),
+            ("1+2", false, Container::Null),
+            ("0-1", false, Container::Null),
+            ("1--2", false, Container::Null),
+            ("[1.5-2]", false, Container::Null),
+            ("18-446744073709551615", false, Container::Null),
+            ("1e5-2", false, Container::Null),
             ("-12132e5", true, Container::Decimal(-12132e5)),
-            ("321321321342132412341234231412", false, Container::Null),
+            (
+                "321321321342132412341234231412",
+                true,
+                Container::Unsigned128(321321321342132412341234231412),
+            ),
         ]
         .iter()
         .for_each(|(string, good, compare)| {
@@ -45,6 +63,15 @@ mod tests {
             }
         });
 
+        // A sign inside a number is rejected in every mode.
+        let strict = ParserOptions {
+            strict: true,
+            ..Default::default()
+        };
+        for string in ["1-2", "1+2", "0-1", "18-446744073709551615"] {
+            assert!(parse_with_options(string, &strict).is_err(), "{string}");
+        }
+
         Ok(())
     }
 
@@ -59,13 +86,14 @@ mod tests {
         assert!(parse_str("1.4e-8")
             .is_ok_and(|c| c.get_real().is_some_and(|d| d == 1.4e-8)));
         assert!(parse_str("null").is_ok_and(|c| c.is_null()));
-        assert!(parse_str("true")
-            .is_ok_and(|c| c.get_bool().is_some_and(|d| d == true)));
-        assert!(parse_str("false")
-            .is_ok_and(|c| c.get_bool().is_some_and(|d| d == false)));
-        assert!(parse_str("\"false\"").is_ok_and(|c| c
-            .get_string()
-            .is_some_and(|d| d == "false".to_owned())));
+        assert!(
+            parse_str("true").is_ok_and(|c| c.get_bool().is_some_and(|d| d))
+        );
+        assert!(
+            parse_str("false").is_ok_and(|c| c.get_bool().is_some_and(|d| !d))
+        );
+        assert!(parse_str("\"false\"")
+            .is_ok_and(|c| c.get_string().is_some_and(|d| d == "false")));
 
         Ok(())
     }
@@ -93,6 +121,56 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_unicode_escapes() -> Result<(), Box<dyn core::error::Error>> {
+        let value = parse_str(r#""\u0041\u00e9-\u4e2d-\ud83d\ude00\uFFFF""#)?;
+        assert_eq!(
+            value.get_string().unwrap(),
+            "A\u{e9}-\u{4e2d}-\u{1f600}\u{ffff}"
+        );
+        assert_eq!(parse_str(r#"{"\u006b": 1}"#)?["k"].get_uint(), Some(1));
+
+        for input in [
+            r#""\u12""#,
+            r#""\u12g4""#,
+            r#""\ud83d""#,
+            r#""\ud83dx""#,
+            r#""\ud83d\u0041""#,
+            r#""\ude00\ud83d""#,
+            r#""\u"#,
+        ] {
+            assert!(parse_str(input).is_err(), "{input}");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_escapes() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::error::{Error, ParseError};
+
+        let value = parse_str(r#""a\"b\\c\/d\be\ff\ng\rh\ti""#)?;
+        assert_eq!(
+            value.get_string().unwrap(),
+            "a\"b\\c/d\u{8}e\u{c}f\ng\rh\ti"
+        );
+        assert_eq!(parse_str(r#""\\""#)?.get_string().unwrap(), "\\");
+
+        let error = parse_str("\"ok\n \\é\"").unwrap_err();
+        match error.downcast_ref::<Error>() {
+            Some(Error::Parsing(ParseError::InvalidEscape(chr, span))) => {
+                assert_eq!((*chr, span.line, span.col), ('é', 2, 3));
+                assert_eq!((span.start, span.end), (6, 8));
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+        for input in [r#""\a""#, r#""\'""#, r#""\x41""#, r#""\"#] {
+            assert!(parse_str(input).is_err(), "{input}");
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn test_array() -> Result<(), Box<dyn core::error::Error>> {
         assert!(
@@ -131,6 +209,36 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_deep_nesting_with_options(
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        // Deep enough to exhaust the native stack of a recursive parser.
+        let depth = 10_000;
+        let options = ParserOptions {
+            max_depth: depth,
+            ..Default::default()
+        };
+        let string = "[".repeat(depth as usize) + &"]".repeat(depth as usize);
+        let mut value = &parse_with_options(&string, &options)?;
+        for _ in 0..depth - 1 {
+            value = &value[0];
+        }
+        assert!(value.is_empty());
+
+        let string = "[".repeat(depth as usize + 1);
+        assert!(parse_with_options(&string, &options).is_err());
+        assert!(parse_with_options(
+            "[[",
+            &ParserOptions {
+                max_depth: 1,
+                ..Default::default()
+            }
+        )
+        .is_err());
+
+        Ok(())
+    }
+
     #[test]
     fn test_object() -> Result<(), Box<dyn core::error::Error>> {
         let a = parse_str(
@@ -165,16 +273,5032 @@ mod tests {
 
         assert_eq!(a["i'll"]["you"][1]["parser"].get_string(), None);
 
-        assert_eq!(a["i'll"]["you"][2].get_bool().unwrap(), true);
+        assert!(a["i'll"]["you"][2].get_bool().unwrap());
         Ok(())
     }
 
     #[test]
     fn test_empty() -> Result<(), Box<dyn core::error::Error>> {
-        assert!(parse_str("{}").is_ok_and(|c| c.is_object() && c.len() == 0));
-        assert!(parse_str("[]").is_ok_and(|c| c.is_array() && c.len() == 0));
+        assert!(parse_str("{}").is_ok_and(|c| c.is_object() && c.is_empty()));
+        assert!(parse_str("[]").is_ok_and(|c| c.is_array() && c.is_empty()));
         assert!(parse_str("[[]").is_err());
 
         Ok(())
     }
+
+    #[test]
+    fn test_apply_patch() -> Result<(), Box<dyn core::error::Error>> {
+        let mut doc = parse_str(
+            r#"{"a": {"b": [1, 2, 3]}, "c": "d", "e/f": true, "g~h": null}"#,
+        )?;
+        let patch = parse_str(
+            r#"[
+                {"op": "add", "path": "/a/b/1", "value": 9},
+                {"op": "add", "path": "/a/b/-", "value": {"x": [true]}},
+                {"op": "remove", "path": "/a/b/0"},
+                {"op": "replace", "path": "/c", "value": [1]},
+                {"op": "move", "from": "/e~1f", "path": "/moved"},
+                {"op": "copy", "from": "/a/b", "path": "/copied"},
+                {"op": "test", "path": "/g~0h", "value": null},
+                {"op": "test", "path": "/copied/3/x/0", "value": true}
+            ]"#,
+        )?;
+        doc.apply_patch(&patch)?;
+
+        let expected = parse_str(
+            r#"{
+                "a": {"b": [9, 2, 3, {"x": [true]}]},
+                "c": [1],
+                "moved": true,
+                "g~h": null,
+                "copied": [9, 2, 3, {"x": [true]}]
+            }"#,
+        )?;
+        assert_eq!(doc, expected);
+
+        let mut root = parse_str("[1]")?;
+        root.apply_patch(&parse_str(
+            r#"[{"op": "replace", "path": "", "value": {"k": 1}}]"#,
+        )?)?;
+        assert_eq!(root.pointer("/k"), Some(&Container::Unsigned(1)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_patch_failures() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::error::{Error, PatchError};
+
+        let original = parse_str(r#"{"a": [1, 2], "b": "c"}"#)?;
+        let failing = |patch: &str| -> PatchError {
+            let mut doc = original.clone();
+            let err = doc.apply_patch(&parse_str(patch).unwrap()).unwrap_err();
+            assert_eq!(doc, original, "{patch} modified the document");
+            match err.downcast_ref::<Error>() {
+                Some(Error::Patch(patch_error)) => patch_error.clone(),
+                _ => panic!("{patch}: expected a patch error"),
+            }
+        };
+
+        assert!(matches!(
+            failing(
+                r#"[{"op": "remove", "path": "/b"},
+                    {"op": "test", "path": "/a/0", "value": 2}]"#
+            ),
+            PatchError::TestFailed { index: 1, .. }
+        ));
+        assert!(matches!(
+            failing(r#"[{"op": "remove", "path": "/a/2"}]"#),
+            PatchError::PathNotFound { index: 0, .. }
+        ));
+        assert!(matches!(
+            failing(r#"[{"op": "add", "path": "/a/01", "value": 1}]"#),
+            PatchError::PathNotFound { .. }
+        ));
+        assert!(matches!(
+            failing(r#"[{"op": "add", "path": "/x/y", "value": 1}]"#),
+            PatchError::PathNotFound { .. }
+        ));
+        assert!(matches!(
+            failing(r#"[{"op": "replace", "path": "a", "value": 1}]"#),
+            PatchError::InvalidPointer { .. }
+        ));
+        assert!(matches!(
+            failing(r#"[{"op": "move", "from": "/a", "path": "/a/0"}]"#),
+            PatchError::MalformedOperation { .. }
+        ));
+        assert!(matches!(
+            failing(r#"[{"op": "frobnicate", "path": "/a"}]"#),
+            PatchError::MalformedOperation { .. }
+        ));
+        assert!(matches!(
+            failing(r#"[{"op": "add", "path": "/a"}]"#),
+            PatchError::MalformedOperation { .. }
+        ));
+        assert!(matches!(failing(r#"{}"#), PatchError::InvalidPatch));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_macro() -> Result<(), Box<dyn core::error::Error>> {
+        let inner = crate::json!(["b"]);
+        assert_eq!(crate::json!({"a": ["b"]}), parse_str(r#"{"a": ["b"]}"#)?);
+        assert_eq!(crate::json!({"a": inner.clone()})["a"], inner);
+
+        let limit: u64 = 1 << 40;
+        let value = crate::json!([
+            null,
+            true,
+            -1,
+            limit,
+            1.5e3,
+            [[], {}],
+            {"deep": {"deeper": [1 + 1, "two", None::<bool>]}},
+        ]);
+        let expected = parse_str(
+            r#"[null, true, -1, 1099511627776, 1.5e3, [[], {}],
+                {"deep": {"deeper": [2, "two", null]}}]"#,
+        )?;
+        assert_eq!(value, expected);
+        assert_eq!(crate::json!(null), Container::Null);
+        assert_eq!(crate::json!("text"), Container::String("text".into()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_static_container() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::static_container::StaticContainer;
+
+        const LEVELS: StaticContainer = StaticContainer::Array(&[
+            StaticContainer::String("debug"),
+            StaticContainer::String("info"),
+        ]);
+        static DEFAULTS: StaticContainer = StaticContainer::Object(&[
+            ("levels", LEVELS),
+            ("retries", StaticContainer::Number(-1)),
+            ("ratio", StaticContainer::Decimal(0.5)),
+            ("verbose", StaticContainer::Boolean(false)),
+            ("proxy", StaticContainer::Null),
+        ]);
+
+        const INFO: Option<&str> = LEVELS.get_index(1).get_str();
+        assert_eq!(INFO, Some("info"));
+        assert_eq!(DEFAULTS.get("retries").get_int(), Some(-1));
+        assert!(DEFAULTS.get("missing").is_null());
+        assert_eq!(DEFAULTS.len(), 5);
+
+        let parsed = parse_str(
+            r#"{"levels": ["debug", "info"], "retries": -1, "ratio": 0.5,
+                "verbose": false, "proxy": null}"#,
+        )?;
+        assert_eq!(DEFAULTS, parsed);
+        assert_eq!(Container::from(&DEFAULTS), parsed);
+        assert_ne!(LEVELS, parsed["levels"][0]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_diff() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::diff::diff;
+
+        let pairs = [
+            (r#"{"a": 1}"#, r#"{"a": 1}"#),
+            (r#"{"a": 1, "b/c": [1, 2, 3]}"#, r#"{"b/c": [1], "d~": {}}"#),
+            (
+                r#"[1, {"x": [true]}]"#,
+                r#"[1, {"x": [false, null]}, 3, 4]"#,
+            ),
+            (r#"{"nested": {"keep": 1, "drop": 2}}"#, r#"{"nested": []}"#),
+            (r#"[1, 2]"#, r#""root replaced""#),
+        ];
+        for (source, target) in pairs {
+            let (mut source, target) = (parse_str(source)?, parse_str(target)?);
+            source.apply_patch(&diff(&source, &target))?;
+            assert_eq!(source, target);
+        }
+
+        let same = parse_str(r#"{"a": [1, 2]}"#)?;
+        assert!(diff(&same, &same).is_empty());
+
+        let patch = diff(&parse_str("[1, 2, 3]")?, &parse_str("[1]")?);
+        assert_eq!(
+            patch,
+            parse_str(
+                r#"[{"op": "remove", "path": "/2"},
+                    {"op": "remove", "path": "/1"}]"#
+            )?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_binary_search_by_path() -> Result<(), Box<dyn core::error::Error>> {
+        let records = parse_str(
+            r#"[
+                {"meta": {"ts": -5}},
+                {"meta": {"ts": 0}},
+                {"meta": {"ts": 2.5}},
+                {"meta": {"ts": 7}},
+                {"meta": {"ts": 1e3}}
+            ]"#,
+        )?;
+        let search =
+            |key: Container| records.binary_search_by_path("/meta/ts", &key);
+
+        assert_eq!(search(Container::Number(-5)), Ok(0));
+        assert_eq!(search(Container::Unsigned(0)), Ok(1));
+        assert_eq!(search(Container::Decimal(7.0)), Ok(3));
+        assert_eq!(search(Container::Unsigned(1000)), Ok(4));
+        assert_eq!(search(Container::Number(-10)), Err(0));
+        assert_eq!(search(Container::Unsigned(3)), Err(3));
+        assert_eq!(search(Container::Decimal(1e9)), Err(5));
+
+        let names = parse_str(r#"["alpha", "beta", "gamma"]"#)?;
+        assert_eq!(names.binary_search_by_path("", &"beta".into()), Ok(1));
+        assert_eq!(names.binary_search_by_path("", &"delta".into()), Err(2));
+        assert_eq!(
+            Container::Null.binary_search_by_path("", &Container::Null),
+            Err(0)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_index_by() -> Result<(), Box<dyn core::error::Error>> {
+        let records = parse_str(
+            r#"[
+                {"id": 1, "v": "first"},
+                {"id": "1", "v": "duplicate"},
+                {"id": 2.5, "v": "real"},
+                {"id": null, "v": "skipped"},
+                {"v": "no id"},
+                "not an object"
+            ]"#,
+        )?;
+
+        let index = records.index_by("id");
+        assert_eq!(index.len(), 2);
+        assert_eq!(index["1"]["v"].get_string().unwrap(), "first");
+        assert_eq!(index["2.5"]["v"].get_string().unwrap(), "real");
+
+        let owned = records.clone().into_index_by("id");
+        assert_eq!(owned.len(), 2);
+        assert_eq!(&owned["1"], index["1"]);
+        assert!(Container::Null.index_by("id").is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_detach() -> Result<(), Box<dyn core::error::Error>> {
+        let mut doc = parse_str(
+            r#"{"meta": {"id": 7}, "items": [10, 20, 30], "body": "text"}"#,
+        )?;
+
+        assert_eq!(doc.detach("/items/1"), Some(Container::Unsigned(20)));
+        assert_eq!(doc.detach("/items/5"), None);
+        assert_eq!(doc.detach("items"), None);
+
+        let extracted =
+            doc.extract_paths(&["/meta/id", "/items/0", "/items/0", "/x"]);
+        assert_eq!(
+            extracted,
+            vec![
+                Some(Container::Unsigned(7)),
+                Some(Container::Unsigned(10)),
+                Some(Container::Unsigned(30)),
+                None
+            ]
+        );
+        assert_eq!(
+            doc,
+            parse_str(r#"{"meta": {}, "items": [], "body": "text"}"#)?
+        );
+
+        let whole = doc.detach("").unwrap();
+        assert!(doc.is_null());
+        assert!(whole.is_object());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_documents() -> Result<(), Box<dyn core::error::Error>> {
+        let docs =
+            parse_documents("{\"a\":1}{\"b\":2}\n[3] 4 \"five\" true null")
+                .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            docs,
+            vec![
+                parse_str(r#"{"a": 1}"#)?,
+                parse_str(r#"{"b": 2}"#)?,
+                parse_str("[3]")?,
+                Container::Unsigned(4),
+                Container::String("five".into()),
+                Container::Boolean(true),
+                Container::Null,
+            ]
+        );
+
+        let mut broken = parse_documents("[1] ] [2]");
+        assert!(broken.next().is_some_and(|doc| doc.is_ok()));
+        assert!(broken.next().is_some_and(|doc| doc.is_err()));
+        assert!(broken.next().is_none());
+
+        assert_eq!(parse_documents("  \n ").count(), 0);
+        assert!(parse_str(" [1] ").is_ok());
+        assert!(parse_str("[1] [2]").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_split() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::split::{self, compact_len};
+
+        let doc = parse_str(
+            r#"{"k\"ey": ["a\nb", -1, 2.5, true, null, {}], "e": {"x": []}}"#,
+        )?;
+        let compact = r#"{"k\"ey":["a\nb",-1,2.5,true,null,{}],"e":{"x":[]}}"#;
+        assert_eq!(compact_len(&doc), compact.len());
+
+        let parts = split::by_keys(&doc, 1);
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0], parse_str(r#"{"e": {"x": []}}"#)?);
+        assert_eq!(split::by_keys(&Container::Unsigned(1), 3).len(), 1);
+
+        let array =
+            parse_str(r#"["aaaaaaaaaa", 1, 2, 3, "bbbbbbbbbbbbbbbbbbbb"]"#)?;
+        let chunks = split::array_chunks(&array, 16);
+        assert!(chunks
+            .iter()
+            .all(|chunk| { compact_len(chunk) <= 16 || chunk.len() == 1 }));
+        let rejoined: Vec<Container> = chunks
+            .into_iter()
+            .flat_map(|chunk| match chunk {
+                Container::Array(values) => values,
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(Container::Array(rejoined), array);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_files() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::error::{Error, FileError};
+        use crate::merge::{merge_files, MergeStrategy};
+
+        let dir = std::env::temp_dir()
+            .join(format!("json_parser_merge_{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let files = [
+            (
+                r#"[1, 2]"#,
+                r#"{"db": {"host": "a", "port": 1}, "tags": [1]}"#,
+            ),
+            (
+                r#"[3]"#,
+                r#"{"db": {"port": 2}, "tags": [2], "debug": true}"#,
+            ),
+            (r#""four""#, r#"{"db": {"user": "root"}}"#),
+        ];
+        let mut arrays = Vec::new();
+        let mut objects = Vec::new();
+        for (idx, (array, object)) in files.iter().enumerate() {
+            arrays.push(dir.join(format!("array_{idx}.json")));
+            objects.push(dir.join(format!("object_{idx}.json")));
+            std::fs::write(&arrays[idx], array)?;
+            std::fs::write(&objects[idx], object)?;
+        }
+
+        assert_eq!(
+            merge_files(&arrays, MergeStrategy::ConcatArrays)?,
+            parse_str(r#"[1, 2, 3, "four"]"#)?
+        );
+        assert_eq!(
+            merge_files(&objects, MergeStrategy::DeepMerge)?,
+            parse_str(
+                r#"{"db": {"host": "a", "port": 2, "user": "root"},
+                    "tags": [2], "debug": true}"#
+            )?
+        );
+        let missing = merge_files(
+            [objects[0].clone(), dir.join("missing.json")],
+            MergeStrategy::DeepMerge,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            missing.downcast_ref::<Error>(),
+            Some(Error::File(FileError::Read { path, .. }))
+                if path.ends_with("missing.json")
+        ));
+        let invalid = dir.join("invalid.json");
+        std::fs::write(&invalid, "{\"db\": }")?;
+        let invalid =
+            merge_files([&invalid], MergeStrategy::DeepMerge).unwrap_err();
+        assert!(matches!(
+            invalid.downcast_ref::<Error>(),
+            Some(Error::File(FileError::Parse { path, .. }))
+                if path.ends_with("invalid.json")
+        ));
+        assert!(merge_files(Vec::<&str>::new(), MergeStrategy::DeepMerge)?
+            .is_null());
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_generate_from_schema() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::generate::{from_schema, SplitMix64};
+
+        let schema = parse_str(
+            r#"{
+                "type": "object",
+                "required": ["id", "tags", "kind"],
+                "properties": {
+                    "id": {"type": "integer", "minimum": -5, "maximum": 5},
+                    "score": {"type": "number", "minimum": 0.5, "maximum": 1},
+                    "name": {"type": "string", "minLength": 2, "maxLength": 4},
+                    "tags": {
+                        "type": "array",
+                        "minItems": 1,
+                        "maxItems": 3,
+                        "items": {"enum": ["a", "b"]}
+                    },
+                    "kind": {"const": "user"},
+                    "extra": {"type": ["null", "boolean"]}
+                }
+            }"#,
+        )?;
+
+        let mut rng = SplitMix64::new(42);
+        for _ in 0..200 {
+            let doc = from_schema(&schema, &mut rng);
+            let id = doc["id"]
+                .get_int()
+                .or(doc["id"].get_uint().map(|v| v as i64))
+                .unwrap();
+            assert!((-5..=5).contains(&id), "{id}");
+            assert!(doc["tags"].is_array_and(|tags| {
+                (1..=3).contains(&tags.len())
+                    && tags
+                        .iter()
+                        .all(|t| t.is_string_and(|t| t == "a" || t == "b"))
+            }));
+            assert_eq!(doc["kind"].get_string().unwrap(), "user");
+            assert!(
+                doc["score"].is_null()
+                    || doc["score"]
+                        .is_decimal_and(|s| (0.5..=1.0).contains(&s))
+            );
+            assert!(
+                doc["name"].is_null()
+                    || doc["name"]
+                        .is_string_and(|n| (2..=4).contains(&n.len()))
+            );
+            assert!(doc["extra"].is_null() || doc["extra"].is_bool());
+        }
+
+        let first = from_schema(&schema, &mut SplitMix64::new(1));
+        assert_eq!(first, from_schema(&schema, &mut SplitMix64::new(1)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_generate_mutations() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::generate::{mutate, MutationKind};
+
+        let documents = [
+            r#"{"name": "esc\"aped", "list": [1, [], {}, "x"], "n": null}"#,
+            r#"[[["deep"]], {"k": {"k": "v"}}]"#,
+            r#""top-level string""#,
+        ];
+        let mut kinds = std::collections::HashSet::new();
+        for document in documents {
+            parse_str(document)?;
+            for mutation in mutate(document) {
+                assert!(
+                    parse_str(&mutation.text).is_err(),
+                    "{:?} at {} still parses: {}",
+                    mutation.kind,
+                    mutation.offset,
+                    mutation.text
+                );
+                kinds.insert(mutation.kind);
+            }
+        }
+        assert_eq!(kinds.len(), 6);
+        assert!(kinds.contains(&MutationKind::BadEscape));
+
+        let large = format!("[{}]", vec!["\"s\""; 1000].join(","));
+        let mutations = mutate(&large);
+        assert!(mutations.len() <= 6 * 16);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_serializer_trait() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::serializer::Serializer;
+
+        type Res = Result<(), Box<dyn core::error::Error>>;
+
+        /// Records a flat event log and fails on a forbidden string.
+        #[derive(Default)]
+        struct Events(Vec<String>);
+
+        impl Serializer for Events {
+            fn visit_null(&mut self) -> Res {
+                self.0.push("null".into());
+                Ok(())
+            }
+            fn visit_bool(&mut self, value: bool) -> Res {
+                self.0.push(format!("bool {value}"));
+                Ok(())
+            }
+            fn visit_number(&mut self, value: i64) -> Res {
+                self.0.push(format!("int {value}"));
+                Ok(())
+            }
+            fn visit_unsigned(&mut self, value: u64) -> Res {
+                self.0.push(format!("uint {value}"));
+                Ok(())
+            }
+            fn visit_decimal(&mut self, value: f64) -> Res {
+                self.0.push(format!("real {value}"));
+                Ok(())
+            }
+            fn visit_str(&mut self, value: &str) -> Res {
+                if value == "forbidden" {
+                    return Err("forbidden string".into());
+                }
+                self.0.push(format!("str {value}"));
+                Ok(())
+            }
+            fn begin_array(&mut self, len: usize) -> Res {
+                self.0.push(format!("[{len}"));
+                Ok(())
+            }
+            fn end_array(&mut self) -> Res {
+                self.0.push("]".into());
+                Ok(())
+            }
+            fn begin_object(&mut self, len: usize) -> Res {
+                self.0.push(format!("{{{len}"));
+                Ok(())
+            }
+            fn visit_key(&mut self, key: &str) -> Res {
+                self.0.push(format!("key {key}"));
+                Ok(())
+            }
+            fn end_object(&mut self) -> Res {
+                self.0.push("}".into());
+                Ok(())
+            }
+        }
+
+        let mut events = Events::default();
+        parse_str(r#"[-1, 2, 0.5, true, null, "s", {"k": []}]"#)?
+            .serialize(&mut events)?;
+        assert_eq!(
+            events.0,
+            [
+                "[7",
+                "int -1",
+                "uint 2",
+                "real 0.5",
+                "bool true",
+                "null",
+                "str s",
+                "{1",
+                "key k",
+                "[0",
+                "]",
+                "}",
+                "]"
+            ]
+        );
+
+        let mut failing = Events::default();
+        assert!(parse_str(r#"[1, "forbidden", 2]"#)?
+            .serialize(&mut failing)
+            .is_err());
+        assert_eq!(failing.0, ["[3", "uint 1"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_container_builder() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::builder::ContainerBuilder;
+        use crate::serializer::Serializer;
+
+        let original = parse_str(
+            r#"{"a": [1, -2, 3.5, "s", null, true], "b": {"c": {}, "d": []}}"#,
+        )?;
+        let mut builder = ContainerBuilder::new();
+        original.serialize(&mut builder)?;
+        assert_eq!(builder.finish()?, original);
+
+        let mut builder = ContainerBuilder::new();
+        assert!(builder.visit_key("k").is_err());
+        builder.begin_object(0)?;
+        assert!(builder.visit_null().is_err());
+        assert!(builder.end_array().is_err());
+
+        let mut builder = ContainerBuilder::new();
+        builder.begin_object(0)?;
+        builder.visit_key("k")?;
+        assert!(builder.visit_key("again").is_err());
+
+        let mut builder = ContainerBuilder::new();
+        builder.visit_bool(true)?;
+        assert!(builder.visit_bool(false).is_err());
+        assert!(ContainerBuilder::new().finish().is_err());
+
+        let mut builder = ContainerBuilder::new();
+        builder.begin_array(0)?;
+        assert!(builder.finish().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dialect() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::dialect::{Dialect, JSON_LITERALS};
+        use crate::parser::parse_with_dialect;
+        use crate::static_container::StaticContainer;
+
+        /// HJSON-flavoured test dialect.
+        struct Loose;
+
+        impl Dialect for Loose {
+            fn quotes(&self) -> &[u8] {
+                b"\"'"
+            }
+            fn literals(&self) -> &[(&str, StaticContainer)] {
+                &[
+                    ("yes", StaticContainer::Boolean(true)),
+                    ("no", StaticContainer::Boolean(false)),
+                    ("nothing", StaticContainer::Null),
+                ]
+            }
+            fn comment_starts(&self) -> &[u8] {
+                b"#/"
+            }
+            fn skip_comment(&self, rest: &[u8]) -> Option<usize> {
+                let line = |rest: &[u8]| {
+                    rest.iter().position(|b| *b == b'\n').unwrap_or(rest.len())
+                };
+                match rest {
+                    [b'#', ..] | [b'/', b'/', ..] => Some(line(rest)),
+                    [b'/', b'*', body @ ..] => body
+                        .windows(2)
+                        .position(|w| w == b"*/")
+                        .map(|end| end + 4),
+                    _ => None,
+                }
+            }
+            fn trailing_commas(&self) -> bool {
+                true
+            }
+            fn bare_keys(&self) -> bool {
+                true
+            }
+        }
+
+        let doc = parse_with_dialect(
+            r#"{
+                # comment
+                name: 'it\'s', // trailing comment
+                "quoted": [yes, no, nothing, 12/* inline */, -3,],
+                /* block
+                   comment */ $id_2: {},
+            }"#,
+            &Loose,
+        )?;
+        assert_eq!(
+            doc,
+            parse_str(
+                r#"{"name": "it's", "quoted": [true, false, null, 12, -3],
+                    "$id_2": {}}"#
+            )?
+        );
+
+        assert!(parse_with_dialect("[true]", &Loose).is_err());
+        assert!(parse_with_dialect("[1] / 2", &Loose).is_err());
+        assert!(parse_with_dialect("{1a: 1}", &Loose).is_err());
+        assert!(parse_with_dialect("[,]", &Loose).is_err());
+
+        // Standard JSON rejects every extension.
+        for input in ["{a: 1}", "['a']", "[1,]", "[1] # c", "[yes]"] {
+            assert!(parse_str(input).is_err(), "{input}");
+        }
+        assert_eq!(JSON_LITERALS.len(), 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_encrypt_fields() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::encrypt::Cipher;
+
+        /// Shifts every byte, failing on text it did not produce.
+        struct Shift;
+
+        impl Cipher for Shift {
+            fn encrypt(
+                &self,
+                text: &str,
+            ) -> Result<String, Box<dyn core::error::Error>> {
+                Ok(text.bytes().map(|b| format!("{:02x}", b ^ 0x5a)).collect())
+            }
+
+            fn decrypt(
+                &self,
+                text: &str,
+            ) -> Result<String, Box<dyn core::error::Error>> {
+                let bytes = (0..text.len())
+                    .step_by(2)
+                    .map(|idx| {
+                        u8::from_str_radix(&text[idx..idx + 2], 16)
+                            .map(|b| b ^ 0x5a)
+                    })
+                    .collect::<Result<Vec<u8>, _>>()?;
+                Ok(String::from_utf8(bytes)?)
+            }
+        }
+
+        let original = parse_str(
+            r#"{"user": "ada", "card": {"number": "4111", "cvv": 123},
+                "tokens": [[1, 2], {"a": null}]}"#,
+        )?;
+        let mut doc = original.clone();
+        // "/card" is listed twice, wrapping the first wrapper.
+        doc.encrypt_paths(&["/card", "/tokens/1", "/card"], &Shift)?;
+        assert!(doc["card"]["$enc"].is_str());
+        assert!(doc["tokens"][1]["$enc"].is_str());
+        assert_eq!(doc["tokens"][0], original["tokens"][0]);
+
+        // Missing paths and failing ciphers leave the document untouched.
+        let encrypted = doc.clone();
+        assert!(doc.encrypt_paths(&["/user", "/missing"], &Shift).is_err());
+        assert!(doc.decrypt_paths(&["/user"], &Shift).is_err());
+        assert!(doc.decrypt_paths(&["bad"], &Shift).is_err());
+        assert_eq!(doc, encrypted);
+
+        let mut partially = doc.clone();
+        partially.decrypt_paths(&["/tokens/1"], &Shift)?;
+        assert_eq!(partially["tokens"], original["tokens"]);
+
+        doc.decrypt_all(&Shift)?;
+        assert_eq!(doc, original);
+
+        let mut tampered = encrypted.clone();
+        tampered["tokens"][1]["$enc"] = Container::String("zz".into());
+        assert!(tampered.decrypt_all(&Shift).is_err());
+        assert_eq!(tampered["user"], original["user"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dump_deep() -> Result<(), Box<dyn core::error::Error>> {
+        let doc = parse_str(r#"{"a": [1, {"b": null}, [], {}]}"#)?;
+        assert_eq!(
+            doc.dump_object(true, 2, 1),
+            "{\n  \"a\": [\n    1,\n    {\n      \"b\": null\n    },\n    [],\n    {}\n  ]\n}"
+        );
+        assert_eq!(
+            doc.dump_object(false, 2, 1),
+            r#"{"a":[1,{"b":null},[],{}]}"#
+        );
+        assert_eq!(doc.to_string(), doc.dump_object(false, 0, 1));
+        assert_eq!(format!("{doc:#}"), doc.dump_object(true, 4, 1));
+        assert_eq!(format!("{doc:#2}"), doc.dump_object(true, 2, 1));
+        // The deprecated depth still indents the closing lines.
+        assert_eq!(parse_str("[1]")?.dump_object(true, 2, 2), "[\n    1\n  ]");
+
+        let depth = 10_000;
+        let mut deep = Container::Null;
+        for _ in 0..depth {
+            deep = Container::Array(vec![deep]);
+        }
+        let compact = deep.dump_object(false, 0, 1);
+        assert_eq!(compact, "[".repeat(depth) + "null" + &"]".repeat(depth));
+
+        let pretty = deep.dump_object(true, 1, 1);
+        assert_eq!(pretty.lines().count(), 2 * depth + 1);
+        assert_eq!(
+            pretty.lines().nth(depth),
+            Some(&*(" ".repeat(depth) + "null"))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_preview() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::split;
+
+        let small = parse_str(r#"{"a": [1, 2], "b": "text"}"#)?;
+        assert_eq!(small.preview(100), small);
+
+        let doc = parse_str(
+            r#"{"id": 12, "body": "a long body of text that will not fit",
+                "items": [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14],
+                "zeta": {"nested": [true, false, null]}}"#,
+        )?;
+        for budget in [0, 10, 40, 80, 120] {
+            let preview = doc.preview(budget);
+            // Always a valid, re-parseable document.
+            assert_eq!(parse_str(&preview.dump_object(true, 2, 1))?, preview);
+            assert!(preview.is_object());
+            if budget >= 40 {
+                assert!(split::compact_len(&preview) <= budget, "{budget}");
+            }
+        }
+
+        let preview = doc.preview(80);
+        assert_eq!(preview["body"], doc["body"]);
+        assert_eq!(preview["id"].get_uint(), Some(12));
+        assert_eq!(preview["…"].get_string().unwrap(), "… 2 more");
+        assert_eq!(
+            doc.preview(40),
+            parse_str(r#"{"body": "a lon…", "…": "… 3 more"}"#)?
+        );
+
+        let items = doc["items"].preview(30);
+        assert_eq!(items[items.len() - 1].get_string().unwrap(), "… 10 more");
+        assert_eq!(
+            Container::String("héllo wörld".into()).preview(9),
+            Container::String("hél…".into())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_locale_numbers() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::error::WarningKind;
+        use crate::parser::{parse_with_warnings, NumberLocale};
+
+        let point = ParserOptions {
+            locale_numbers: Some(NumberLocale::PointDecimal),
+            ..Default::default()
+        };
+        let (doc, warnings) = parse_with_warnings(
+            "[1,234.5, -1,000,000, 1_000, 2.5e1_0, 7, 1,2, 0.5]",
+            &point,
+        )?;
+        assert_eq!(
+            doc,
+            parse_str("[1234.5, -1000000.0, 1000.0, 2.5e10, 7, 1, 2, 0.5]")?
+        );
+        assert!(doc[4].is_unsigned() && doc[6].is_unsigned());
+        let originals: Vec<_> = warnings
+            .iter()
+            .map(|warning| match &warning.kind {
+                WarningKind::LocaleNumber(original) => original.as_str(),
+                other => panic!("unexpected warning {:?}", other),
+            })
+            .collect();
+        assert_eq!(originals, ["1,234.5", "-1,000,000", "1_000", "2.5e1_0"]);
+        assert_eq!((warnings[1].line, warnings[1].col), (1, 11));
+
+        let comma = ParserOptions {
+            locale_numbers: Some(NumberLocale::CommaDecimal),
+            ..Default::default()
+        };
+        let (doc, warnings) =
+            parse_with_warnings("[1.234,5, 12,75, 3, 2.5]", &comma)?;
+        assert_eq!(doc, parse_str("[1234.5, 12.75, 3, 2.5]")?);
+        assert_eq!(warnings.len(), 2);
+
+        for input in ["1,23", "1__0", "1_", "1,234x", "01_0"] {
+            assert!(parse_with_options(input, &point).is_err(), "{input}");
+        }
+        // Without the option, nothing changes.
+        assert!(parse_str("1_000").is_err());
+        assert_eq!(parse_with_warnings("[1.5]", &point)?.1, []);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_strict_mode() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::parser::NumberLocale;
+
+        let strict = ParserOptions {
+            strict: true,
+            locale_numbers: Some(NumberLocale::PointDecimal),
+            ..Default::default()
+        };
+        let valid = "{\"a\": [1, -0.5e3, \"tab\\t\"],\r\n\t\"b\": null}";
+        assert_eq!(parse_with_options(valid, &strict)?, parse_str(valid)?);
+
+        for input in [
+            "\"raw\ttab\"",
+            "\"new\nline\"",
+            "[1,\x0c2]",
+            "'single'",
+            "+1",
+            "01",
+            "-01.5",
+            "1,000",
+            "[1,]",
+        ] {
+            assert!(parse_with_options(input, &strict).is_err(), "{input:?}");
+        }
+        // The default mode keeps accepting raw control characters.
+        assert_eq!(
+            parse_str("\"raw\ttab\"")?.get_string().unwrap(),
+            "raw\ttab"
+        );
+        assert!(parse_str("[1,\x0c2]").is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_coerce_strings() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::error::WarningKind;
+        use crate::parser::parse_with_warnings;
+
+        let options = ParserOptions {
+            coerce_strings: true,
+            ..Default::default()
+        };
+        let (doc, warnings) = parse_with_warnings(
+            r#"{"42": "42", "neg": "-7", "pi": "3.5e0", "ok": "true",
+                "no": "false", "keep": ["abc", "", " 1", "1 ", "01", "1e",
+                "True", "null", 5]}"#,
+            &options,
+        )?;
+        assert_eq!(
+            doc,
+            parse_str(
+                r#"{"42": 42, "neg": -7, "pi": 3.5, "ok": true, "no": false,
+                    "keep": ["abc", "", " 1", "1 ", "01", "1e", "True",
+                             "null", 5]}"#
+            )?
+        );
+        let mut originals: Vec<_> = warnings
+            .iter()
+            .map(|warning| match &warning.kind {
+                WarningKind::CoercedString(original) => original.as_str(),
+                other => panic!("unexpected warning {:?}", other),
+            })
+            .collect();
+        originals.sort_unstable();
+        assert_eq!(originals, ["-7", "3.5e0", "42", "false", "true"]);
+        assert_eq!((warnings[0].line, warnings[0].col), (1, 8));
+
+        assert!(parse_str(r#""42""#)?.is_str());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_input_encodings() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::encoding::{decode, detect, Encoding};
+        use crate::parser::parse_bytes;
+
+        let text = r#"{"name": "naïve 😀", "n": [1, 2]}"#;
+        let expected = parse_str(text)?;
+        let utf16le: Vec<u8> =
+            text.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        let utf16be: Vec<u8> =
+            text.encode_utf16().flat_map(u16::to_be_bytes).collect();
+        let utf32le: Vec<u8> = text
+            .chars()
+            .flat_map(|c| (c as u32).to_le_bytes())
+            .collect();
+        let utf32be: Vec<u8> = text
+            .chars()
+            .flat_map(|c| (c as u32).to_be_bytes())
+            .collect();
+
+        assert_eq!(detect(&utf16le), (Encoding::Utf16Le, 0));
+        assert_eq!(detect(&utf16be), (Encoding::Utf16Be, 0));
+        assert_eq!(detect(&utf32le), (Encoding::Utf32Le, 0));
+        assert_eq!(detect(&utf32be), (Encoding::Utf32Be, 0));
+        assert_eq!(detect(text.as_bytes()), (Encoding::Utf8, 0));
+
+        for (bom, bytes) in [
+            (&b""[..], text.as_bytes()),
+            (b"\xEF\xBB\xBF", text.as_bytes()),
+            (b"", &utf16le),
+            (b"\xFF\xFE", &utf16le),
+            (b"", &utf16be),
+            (b"\xFE\xFF", &utf16be),
+            (b"", &utf32le),
+            (b"\xFF\xFE\x00\x00", &utf32le),
+            (b"", &utf32be),
+            (b"\x00\x00\xFE\xFF", &utf32be),
+        ] {
+            let input = [bom, bytes].concat();
+            assert_eq!(decode(&input)?, text);
+            assert_eq!(parse_bytes(&input)?, expected);
+        }
+
+        // A BOM is skipped when parsing text directly, too.
+        assert_eq!(parse_str(&format!("\u{feff}{text}"))?, expected);
+        assert!(parse_str("\u{feff}\u{feff}1").is_err());
+
+        assert!(decode(&utf16le[1..]).is_err());
+        assert!(decode(b"\xFF\xFE\x00\xD8").is_err());
+        assert!(decode(b"\xFF\xFE\x00\x00\x00\x00\x11\x00").is_err());
+        assert!(parse_bytes(b"[\xFF]").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_duplicate_keys() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::analyze::duplicate_keys;
+        use crate::parser::Span;
+
+        let input = "{\"x\": [[{\"k\": 1}], {\"a/b\": {\"k\": 1, \"k\": {}}}],\n \"y\": {\"\\u006b\": 0, \"k\": 1, \"k\": 2},\n \"x\": null}";
+        let duplicates = duplicate_keys(input)?;
+        assert_eq!(duplicates.len(), 3);
+
+        assert_eq!(duplicates[0].key, "x");
+        assert_eq!(duplicates[0].path, "");
+        assert_eq!(
+            duplicates[0].spans,
+            [
+                Span {
+                    start: 1,
+                    end: 4,
+                    line: 1,
+                    col: 2,
+                    path: None
+                },
+                Span {
+                    start: 86,
+                    end: 89,
+                    line: 3,
+                    col: 2,
+                    path: None
+                }
+            ]
+        );
+        assert_eq!(duplicates[1].path, "/x/1/a~1b");
+        assert_eq!(duplicates[1].spans.len(), 2);
+        assert_eq!(duplicates[2].path, "/y");
+        assert_eq!(duplicates[2].spans.len(), 3);
+        assert_eq!(
+            &input[duplicates[2].spans[0].start..duplicates[2].spans[0].end],
+            "\"\\u006b\""
+        );
+
+        assert!(duplicate_keys(r#"{"a": 1, "b": {"a": 2}}"#)?.is_empty());
+        assert!(duplicate_keys(r#"{"a": 1, "a": 2"#).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lint() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::lint::{lint, LintConfig, Rule};
+
+        let input = r#"{
+  "user_id": 1, "first_name": "a", "lastName": "b",
+  "tags": ["x", null, 1, "y", true],
+  "ids": [9007199254740991, 9007199254740992, -9007199254740993],
+  "deep": [[[{"id": 1}]]],
+  "user_id": 2
+}"#;
+        let config = LintConfig {
+            max_depth: 3,
+            ..LintConfig::default()
+        };
+        let findings = lint(input, &config)?;
+        let summary: Vec<(Rule, &str)> = findings
+            .iter()
+            .map(|finding| (finding.rule, finding.path.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (Rule::KeyCasing, "/lastName"),
+                (Rule::MixedArrayTypes, "/tags"),
+                (Rule::UnsafeInteger, "/ids/1"),
+                (Rule::UnsafeInteger, "/ids/2"),
+                (Rule::DeepNesting, "/deep/0/0"),
+                (Rule::DuplicateKey, "/user_id"),
+            ]
+        );
+
+        let tags = &findings[1];
+        assert_eq!(
+            &input[tags.span.start..tags.span.end],
+            "[\"x\", null, 1, \"y\", true]"
+        );
+        assert_eq!((tags.span.line, tags.span.col), (3, 11));
+        assert_eq!(findings[5].span.line, 6);
+        assert_eq!(findings[5].to_string(), "6:3: key \"user_id\" already appears at line 2, col 3 (duplicate-key)");
+
+        let config = LintConfig {
+            rules: vec![Rule::DuplicateKey],
+            ..LintConfig::default()
+        };
+        assert_eq!(lint(input, &config)?.len(), 1);
+        assert!(lint(
+            r#"{"a": [1, 2.5, -3], "b": [[], []]}"#,
+            &LintConfig::default()
+        )?
+        .is_empty());
+        assert!(lint("[1,", &LintConfig::default()).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lossy_utf8() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::error::WarningKind;
+        use crate::parser::{parse_bytes, parse_bytes_with_warnings};
+
+        let input =
+            b"\xEF\xBB\xBF{\"a\": \"\xFF\xFEx\",\n \"b\xC3\": [\"\xE2\x82\"]}";
+        assert!(parse_bytes(input).is_err());
+        let options = ParserOptions {
+            lossy_utf8: true,
+            ..Default::default()
+        };
+        let (doc, warnings) = parse_bytes_with_warnings(input, &options)?;
+        assert_eq!(doc["a"].get_string().unwrap(), "\u{FFFD}\u{FFFD}x");
+        assert_eq!(doc["b\u{FFFD}"][0].get_string().unwrap(), "\u{FFFD}");
+        let replaced: Vec<(usize, usize, WarningKind)> = warnings
+            .into_iter()
+            .map(|warning| (warning.line, warning.col, warning.kind))
+            .collect();
+        assert_eq!(
+            replaced,
+            [
+                (1, 8, WarningKind::InvalidUtf8(vec![0xFF])),
+                (1, 11, WarningKind::InvalidUtf8(vec![0xFE])),
+                (2, 4, WarningKind::InvalidUtf8(vec![0xC3])),
+                (2, 12, WarningKind::InvalidUtf8(vec![0xE2, 0x82])),
+            ]
+        );
+
+        // Outside strings the replacement is still a syntax error.
+        assert!(parse_bytes_with_warnings(b"[1, \xFF]", &options).is_err());
+        let (_, warnings) = parse_bytes_with_warnings(b"[1]", &options)?;
+        assert!(warnings.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lint_fixes() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::lint::{apply_fixes, lint, Edit, LintConfig, Rule};
+
+        let input = "{'it\\'s': 'say \"hi\"',\n \"tab\": \"a\tb\nc\",\n \"list\": [{},],\n}";
+        let findings = lint(input, &LintConfig::default())?;
+        let rules: Vec<(Rule, usize, usize)> = findings
+            .iter()
+            .map(|finding| (finding.rule, finding.span.line, finding.span.col))
+            .collect();
+        assert_eq!(
+            rules,
+            [
+                (Rule::SingleQuotes, 1, 2),
+                (Rule::SingleQuotes, 1, 11),
+                (Rule::ControlCharacter, 2, 11),
+                (Rule::ControlCharacter, 2, 13),
+                (Rule::TrailingComma, 4, 13),
+                (Rule::TrailingComma, 4, 15),
+            ]
+        );
+        assert_eq!(findings[2].path, "/tab");
+        assert_eq!(
+            findings[3].fix,
+            [Edit {
+                start: 34,
+                end: 35,
+                replacement: "\\n".to_owned()
+            }]
+        );
+
+        let fixed = apply_fixes(input, &findings);
+        assert_eq!(
+            fixed,
+            "{\"it's\": \"say \\\"hi\\\"\",\n \"tab\": \"a\\tb\\nc\",\n \"list\": [{}]\n}"
+        );
+        assert!(lint(&fixed, &LintConfig::default())?.is_empty());
+        let doc = parse_str(&fixed)?;
+        assert_eq!(doc["it's"].get_string().unwrap(), "say \"hi\"");
+        assert_eq!(doc["tab"].get_string().unwrap(), "a\tb\nc");
+
+        let config = LintConfig {
+            rules: vec![Rule::TrailingComma],
+            ..LintConfig::default()
+        };
+        assert_eq!(apply_fixes("['a',]", &lint("['a',]", &config)?), "['a']");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_raw_numbers() -> Result<(), Box<dyn core::error::Error>> {
+        let options = ParserOptions {
+            raw_numbers: true,
+            ..Default::default()
+        };
+        let input = "[0.1000000000000000000001,1234567890123456789012345678901234567890,-0,1E+400,{\"a\":-2.50e-3}]";
+        let doc = parse_with_options(input, &options)?;
+        assert!(doc[0].is_raw_number());
+        assert_eq!(
+            doc[1].get_raw_number().unwrap(),
+            "1234567890123456789012345678901234567890"
+        );
+        assert_eq!(doc.dump_object(false, 0, 1), input);
+
+        // Numbers are still validated.
+        assert!(parse_with_options("[01]", &options).is_err());
+        assert!(parse_with_options("[1.]", &options).is_err());
+
+        let doc = parse_with_options("[12, 12.0]", &options)?;
+        assert_ne!(doc[0], doc[1]);
+        assert_ne!(doc[0], Container::Unsigned(12));
+        assert_eq!(parse_str("[12]")?[0], Container::Unsigned(12));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_big_numbers() -> Result<(), Box<dyn core::error::Error>> {
+        let doc = parse_str(
+            "[18446744073709551615, 18446744073709551616, -9223372036854775808, -9223372036854775809,
+              340282366920938463463374607431768211455, 340282366920938463463374607431768211456,
+              -170141183460469231731687303715884105728, -170141183460469231731687303715884105729]",
+        )?;
+        assert_eq!(doc[0], Container::Unsigned(u64::MAX));
+        assert_eq!(doc[1].get_uint128(), Some(u64::MAX as u128 + 1));
+        assert_eq!(doc[2], Container::Number(i64::MIN));
+        assert_eq!(doc[3].get_int128(), Some(i64::MIN as i128 - 1));
+        assert_eq!(doc[4], Container::Unsigned128(u128::MAX));
+        assert_eq!(
+            doc[5].get_big_number().unwrap(),
+            "340282366920938463463374607431768211456"
+        );
+        assert_eq!(doc[6], Container::Number128(i128::MIN));
+        assert!(doc[7].is_big_number());
+        assert_eq!(
+            doc.dump_object(false, 0, 1),
+            "[18446744073709551615,18446744073709551616,-9223372036854775808,-9223372036854775809,340282366920938463463374607431768211455,340282366920938463463374607431768211456,-170141183460469231731687303715884105728,-170141183460469231731687303715884105729]"
+        );
+
+        assert_eq!(Container::from(u64::MAX as u128), doc[0]);
+        assert_eq!(Container::from(u64::MAX as u128 + 1), doc[1]);
+        assert_eq!(Container::from(i64::MIN as i128 - 1), doc[3]);
+        assert_eq!(Container::from(u128::MAX as i128), Container::Number(-1));
+        assert_eq!(Container::from(-5i128), Container::Number(-5));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_explain_error() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::error::Error;
+        use crate::explain::explain_error;
+
+        let explain = |input: &str| {
+            let error = parse_str(input).unwrap_err();
+            explain_error(input, error.downcast_ref::<Error>().unwrap())
+        };
+
+        assert_eq!(
+            explain("{\"a\": {\"b\" 1}}"),
+            "Found `1` where it is not allowed at line 1, column 12.\n\
+             Expected a `:`.\n\
+             Inside the object started at line 1, column 7 (at /a)."
+        );
+        assert_eq!(
+            explain("[1, 2,]"),
+            "Found `]` where it is not allowed at line 1, column 7.\n\
+             Expected a value.\n\
+             Inside the array started at line 1, column 1."
+        );
+        assert_eq!(
+            explain("{\"a\": [1}"),
+            "Found `}` closing an array, which ends with `]` at line 1, column 9.\n\
+             Expected a `,` or a `]`.\n\
+             Inside the array started at line 1, column 7 (at /a)."
+        );
+        assert_eq!(
+            explain("{\"a\": 1, \"b\": }"),
+            "The member \"b\" has no value at line 1, column 15.\n\
+             Expected a value.\n\
+             Inside the object started at line 1, column 1."
+        );
+        assert_eq!(
+            explain("[\"abc"),
+            "The input ends too early.\n\
+             Expected a `\"` closing the string.\n\
+             Inside the array started at line 1, column 1."
+        );
+        assert_eq!(
+            explain("[{}, {\"k\": \"\\x\"}]"),
+            "`\\x` is not an escape sequence; a backslash itself is written `\\\\` at line 1, column 14.\n\
+             Inside the object started at line 1, column 6 (at /1)."
+        );
+        assert_eq!(
+            explain("1 2"),
+            "Found `2` where it is not allowed at line 1, column 3.\n\
+             Expected the end of the input.\n\
+             At the top level of the document."
+        );
+        assert_eq!(
+            explain(""),
+            "The input ends too early.\n\
+             Expected a JSON value.\n\
+             At the top level of the document."
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dom() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::dom::{Dom, Node};
+
+        let input = r#"{"name": "caf\u00e9 \"x\"", "ids": [1, -2, 3.5e1, 98765432109876543210, 1234567890123456789012345678901234567890],
+            "nested": {"a": [], "b": {}, "c": [[null]]}, "ok": true, "name": "last"}"#;
+        let mut dom = Dom::with_capacity(32, 64, 4);
+        dom.parse(input)?;
+        assert_eq!(dom.to_container(), parse_str(input)?);
+
+        let root = dom.root().unwrap();
+        assert_eq!(root.len(), 5);
+        assert_eq!(root.get("name").unwrap().as_str(), Some("last"));
+        let ids = root.get("ids").unwrap();
+        assert_eq!(ids.at(1).unwrap().as_i64(), Some(-2));
+        assert_eq!(ids.at(2).unwrap().as_f64(), Some(35.0));
+        assert_eq!(
+            ids.at(3).unwrap().node(),
+            Node::Unsigned128(98765432109876543210)
+        );
+        assert!(matches!(ids.at(4).unwrap().node(), Node::BigNumber { .. }));
+        assert!(ids.at(5).is_none());
+        let nested = root.get("nested").unwrap();
+        let keys: Vec<&str> = nested.members().map(|(key, _)| key).collect();
+        assert_eq!(keys, ["a", "b", "c"]);
+        assert!(nested
+            .get("c")
+            .unwrap()
+            .at(0)
+            .unwrap()
+            .at(0)
+            .unwrap()
+            .is_null());
+        assert_eq!(
+            dom.nodes()[nested.get("b").unwrap().id()],
+            Node::Object { len: 0, end: 16 }
+        );
+
+        // Parsing again within the capacity reuses the buffers.
+        let capacity = (dom.nodes().as_ptr(), dom.nodes().len());
+        dom.parse(input)?;
+        assert_eq!((dom.nodes().as_ptr(), dom.nodes().len()), capacity);
+
+        for bad in [
+            "",
+            "[1,]",
+            "{\"a\" 1}",
+            "[1}",
+            "{\"a\":1]",
+            "[01]",
+            "[1.]",
+            "\"\\x\"",
+            "[\"\\ud800\"]",
+            "[1] 2",
+            "[tru]",
+        ] {
+            assert!(dom.parse(bad).is_err(), "{bad} parsed");
+            assert!(parse_str(bad).is_err(), "{bad} parsed");
+            assert!(dom.root().is_none());
+        }
+        dom.parse("  -0 ")?;
+        assert_eq!(dom.to_container(), Container::Number(0));
+        let deep = "[".repeat(501) + &"]".repeat(501);
+        assert!(dom.parse(&deep).is_err());
+        dom.parse(&deep[1..deep.len() - 1])?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_object_order() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::parser::ObjectOrder;
+
+        let input = r#"{"z": 1, "a": {"y": [], "b": null}, "m": 2, "z": 3}"#;
+        let doc = parse_str(input)?;
+        // A repeated key keeps its first position and its last value.
+        assert_eq!(
+            doc.dump_object(false, 0, 1),
+            r#"{"z":3,"a":{"y":[],"b":null},"m":2}"#
+        );
+        for _ in 0..8 {
+            assert_eq!(parse_str(input)?.to_string(), doc.to_string());
+        }
+
+        let options = ParserOptions {
+            object_order: ObjectOrder::Sorted,
+            ..Default::default()
+        };
+        let sorted = parse_with_options(input, &options)?;
+        assert_eq!(
+            sorted.dump_object(false, 0, 1),
+            r#"{"a":{"b":null,"y":[]},"m":2,"z":3}"#
+        );
+        // Order does not take part in equality.
+        assert_eq!(sorted, doc);
+
+        let mut object = Container::new_object();
+        for key in ["c", "a", "b"] {
+            object.insert_str(key, Container::Null);
+        }
+        object.insert_str("a", Container::Boolean(true));
+        if let Container::Object(map) = &mut object {
+            assert_eq!(map.remove("c"), Some(Container::Null));
+            map.insert("c".to_owned(), Container::Null);
+            let keys: Vec<&str> = map.keys().collect();
+            assert_eq!(keys, ["a", "b", "c"]);
+            assert_eq!(map["b"], Container::Null);
+        }
+        assert_eq!(
+            object.dump_object(false, 0, 1),
+            r#"{"a":true,"b":null,"c":null}"#
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_number_overflow() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::error::{Error, ParseError};
+        use crate::parser::NumberOverflow;
+
+        let big = "123456789012345678901234567890123456789012";
+        let input = format!(
+            "[{big}, -1e400, 1e308, 170141183460469231731687303715884105727]"
+        );
+        let with = |number_overflow| ParserOptions {
+            number_overflow,
+            ..Default::default()
+        };
+
+        let doc = parse_str(&input)?;
+        assert_eq!(doc[0], Container::BigNumber(big.to_owned()));
+        assert_eq!(doc[1], Container::RawNumber("-1e400".to_owned()));
+        assert_eq!(doc[2], Container::Decimal(1e308));
+        assert!(doc[3].is_unsigned128());
+        assert!(doc
+            .dump_object(false, 0, 1)
+            .starts_with(&format!("[{big},-1e400,")));
+
+        let doc = parse_with_options(&input, &with(NumberOverflow::Decimal))?;
+        assert_eq!(doc[0], Container::Decimal(1.2345678901234568e41));
+        assert_eq!(doc[1], Container::Decimal(f64::NEG_INFINITY));
+        assert!(doc[3].is_unsigned128());
+
+        let options = with(NumberOverflow::Error);
+        let error = parse_with_options(&input, &options).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<Error>(),
+            Some(Error::Parsing(ParseError::NumberOutOfRange(text, span)))
+                if text == big && (span.line, span.col) == (1, 2)
+                    && span.end - span.start == big.len()
+        ));
+        let error =
+            parse_with_options("{\n \"a\": 2e999}", &options).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<Error>(),
+            Some(Error::Parsing(ParseError::NumberOutOfRange(text, span)))
+                if text == "2e999" && (span.line, span.col) == (2, 7)
+        ));
+        assert!(parse_with_options("-1e400", &options).is_err());
+        assert!(parse_with_options(
+            "[1e308, -170141183460469231731687303715884105728]",
+            &options
+        )
+        .is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_extension_registry() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::error::{Error, ExtensionError};
+        use crate::extension::{tagged, Registry};
+
+        #[derive(Debug, PartialEq)]
+        struct Money {
+            cents: i64,
+            currency: String,
+        }
+
+        let mut registry = Registry::new();
+        registry
+            .register(
+                "money",
+                |money: &Money| {
+                    let mut payload = Container::new_object();
+                    payload.insert_str("cents", money.cents.into());
+                    payload
+                        .insert_str("currency", money.currency.clone().into());
+                    payload
+                },
+                |payload| {
+                    let cents = payload["cents"]
+                        .get_int()
+                        .or(payload["cents"].get_uint().map(|c| c as i64));
+                    match (cents, payload["currency"].get_string()) {
+                        (Some(cents), Some(currency)) => {
+                            Ok(Money { cents, currency })
+                        }
+                        _ => Err("malformed money".into()),
+                    }
+                },
+            )
+            .register(
+                "flag",
+                |flag: &bool| Container::Boolean(*flag),
+                |payload| payload.get_bool().ok_or("not a flag".into()),
+            );
+
+        let price = Money {
+            cents: -1250,
+            currency: "EUR".to_owned(),
+        };
+        let mut doc = Container::new_array();
+        doc.push(registry.encode(&price)?);
+        doc.push(registry.encode(&true)?);
+        let text = doc.dump_object(false, 0, 1);
+        assert_eq!(
+            text,
+            r#"[{"$type":"money","$value":{"cents":-1250,"currency":"EUR"}},{"$type":"flag","$value":true}]"#
+        );
+
+        let doc = parse_str(&text)?;
+        assert_eq!(registry.decode::<Money>(&doc[0])?, price);
+        let (tag, flag) = registry.decode_any(&doc[1])?;
+        assert_eq!((tag, flag.downcast_ref::<bool>()), ("flag", Some(&true)));
+        assert_eq!(tagged(&doc[1]), Some(("flag", &Container::Boolean(true))));
+        assert_eq!(tagged(&doc[0]["$value"]), None);
+
+        let kind =
+            |result: Result<Money, Box<dyn core::error::Error>>| match result
+                .unwrap_err()
+                .downcast_ref::<Error>()
+            {
+                Some(Error::Extension(error)) => Some(error.clone()),
+                _ => None,
+            };
+        assert!(matches!(
+            kind(registry.decode(&doc[1])),
+            Some(ExtensionError::TypeMismatch { tag, .. }) if tag == "flag"
+        ));
+        assert!(matches!(
+            kind(registry.decode(&doc)),
+            Some(ExtensionError::NotTagged)
+        ));
+        let unknown = parse_str(r#"{"$type": "uuid", "$value": ""}"#)?;
+        assert!(matches!(
+            kind(registry.decode(&unknown)),
+            Some(ExtensionError::UnknownTag(_))
+        ));
+        let malformed = parse_str(r#"{"$type": "money", "$value": {}}"#)?;
+        assert_eq!(
+            registry
+                .decode::<Money>(&malformed)
+                .unwrap_err()
+                .to_string(),
+            "malformed money"
+        );
+        assert!(registry.encode(&1.5f64).is_err());
+
+        // Registering a tag again replaces its type.
+        registry.register(
+            "flag",
+            |n: &u8| Container::from(*n as u64),
+            |_| Ok(0u8),
+        );
+        assert_eq!(registry.tag_of::<bool>(), None);
+        assert_eq!(registry.tag_of::<u8>(), Some("flag"));
+        assert!(registry.encode(&true).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_non_finite_numbers() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::container::NonFinite;
+        use crate::error::{DumpError, Error};
+
+        let input = "[NaN, Infinity, -Infinity, -1.5, {\"x\": NaN}]";
+        assert!(parse_str(input).is_err());
+        let options = ParserOptions {
+            non_finite_numbers: true,
+            ..Default::default()
+        };
+        let doc = parse_with_options(input, &options)?;
+        assert!(doc[0].get_real().is_some_and(f64::is_nan));
+        assert_eq!(doc[1], Container::Decimal(f64::INFINITY));
+        assert_eq!(doc[2], Container::Decimal(f64::NEG_INFINITY));
+        assert_eq!(doc[3], Container::Decimal(-1.5));
+        assert!(parse_with_options("[NaNa]", &options).is_err());
+        assert!(parse_with_options("[-Inf]", &options).is_err());
+        let strict = ParserOptions {
+            strict: true,
+            ..options.clone()
+        };
+        assert!(parse_with_options(input, &strict).is_err());
+
+        // Literals are written back as they are read.
+        let text = doc.dump_object(false, 0, 1);
+        assert_eq!(text, r#"[NaN,Infinity,-Infinity,-1.5,{"x":NaN}]"#);
+        assert_eq!(
+            parse_with_options(&text, &options)?.dump_object(false, 0, 1),
+            text
+        );
+        assert_eq!(
+            doc.dump_object_with(false, 0, 1, NonFinite::Null)?,
+            r#"[null,null,null,-1.5,{"x":null}]"#
+        );
+        let error = doc
+            .dump_object_with(true, 2, 1, NonFinite::Error)
+            .unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<Error>(),
+            Some(Error::Dump(DumpError::NonFiniteNumber))
+        ));
+        assert!(doc[3]
+            .dump_object_with(false, 0, 1, NonFinite::Error)
+            .is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_extended_numbers() -> Result<(), Box<dyn core::error::Error>> {
+        let options = ParserOptions {
+            extended_numbers: true,
+            ..Default::default()
+        };
+        let parse = |input: &str| parse_with_options(input, &options);
+
+        let doc = parse(
+            "[0xFF, -0x1f, 0XdEaD_bEeF, 1_000_000, -2_147_483_649, 0.000_1, \
+             1_0e1_0, 12, 3.5, 0x1_0000_0000_0000_0000_0000_0000_0000_0000]",
+        )?;
+        assert_eq!(doc[0], Container::Unsigned(255));
+        assert_eq!(doc[1], Container::Number(-31));
+        assert_eq!(doc[2], Container::Unsigned(0xdead_beef));
+        assert_eq!(doc[3], Container::Unsigned(1_000_000));
+        assert_eq!(doc[4], Container::Number(-2_147_483_649));
+        assert_eq!(doc[5], Container::Decimal(0.0001));
+        assert_eq!(doc[6], Container::Decimal(1e11));
+        assert_eq!(doc[7], Container::Unsigned(12));
+        assert_eq!(doc[8], Container::Decimal(3.5));
+        assert_eq!(
+            doc[9],
+            Container::BigNumber(
+                "340282366920938463463374607431768211456".to_owned()
+            )
+        );
+
+        for bad in [
+            "0x", "0xG", "0x_1", "1__0", "1_", "_1", "01_0", "1_.5", "1._5",
+            "0x1.5", "1_0x", "-_1",
+        ] {
+            assert!(parse(bad).is_err(), "{bad} parsed");
+        }
+        assert!(parse_str("0xFF").is_err());
+        assert!(parse_str("1_000").is_err());
+        let strict = ParserOptions {
+            strict: true,
+            ..options.clone()
+        };
+        assert!(parse_with_options("0xFF", &strict).is_err());
+
+        let raw = ParserOptions {
+            raw_numbers: true,
+            ..options.clone()
+        };
+        let doc = parse_with_options("{\"mask\": 0xff, \"n\": 1_0}", &raw)?;
+        assert_eq!(doc.dump_object(false, 0, 1), r#"{"mask":255,"n":10}"#);
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "uuid")]
+    fn test_uuids() -> Result<(), Box<dyn core::error::Error>> {
+        let input = r#"{"67e55044-10b1-426f-9247-bb680e5fe0c8": ["67E55044-10B1-426F-9247-BB680E5FE0C8", "67e55044-10b1-426f-9247-bb680e5fe0c", "67e55044x10b1-426f-9247-bb680e5fe0c8", "g7e55044-10b1-426f-9247-bb680e5fe0c8"]}"#;
+        let bytes = [
+            0x67, 0xe5, 0x50, 0x44, 0x10, 0xb1, 0x42, 0x6f, 0x92, 0x47, 0xbb,
+            0x68, 0x0e, 0x5f, 0xe0, 0xc8,
+        ];
+
+        let doc = parse_str(input)?;
+        let ids = &doc["67e55044-10b1-426f-9247-bb680e5fe0c8"];
+        assert!(ids[0].is_str());
+        assert_eq!(ids[0].get_uuid(), Some(bytes));
+
+        let options = ParserOptions {
+            uuids: true,
+            ..Default::default()
+        };
+        let doc = parse_with_options(input, &options)?;
+        let ids = &doc["67e55044-10b1-426f-9247-bb680e5fe0c8"];
+        assert_eq!(ids[0], Container::Uuid(bytes));
+        assert_eq!(ids[0].get_uuid(), Some(bytes));
+        for id in 1..4 {
+            assert!(ids[id].is_str());
+            assert_eq!(ids[id].get_uuid(), None);
+        }
+        assert_eq!(
+            ids[0].dump_object(false, 0, 1),
+            "\"67e55044-10b1-426f-9247-bb680e5fe0c8\""
+        );
+        assert_eq!(parse_with_options(&doc.to_string(), &options)?, doc);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_network_addresses() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::net::Cidr;
+        use std::net::IpAddr;
+
+        let doc = parse_str(
+            r#"["192.168.1.77/24", "fe80::1%eth0", "fe80::1/10", "0.0.0.0/0",
+                "10.0.0.1/33", "10.0.0.1/08", "10.0.0.1/+8", "10.0.0.1/",
+                "::ffff:10.0.0.1", 42]"#,
+        )?;
+        let ip = |text: &str| text.parse::<IpAddr>().unwrap();
+
+        let lan = doc[0].get_cidr().unwrap();
+        assert_eq!(
+            lan,
+            Cidr {
+                addr: ip("192.168.1.77"),
+                prefix: 24
+            }
+        );
+        assert_eq!(lan.network(), ip("192.168.1.0"));
+        assert_eq!(lan.to_string(), "192.168.1.77/24");
+        assert!(lan.contains(&ip("192.168.1.255")));
+        assert!(!lan.contains(&ip("192.168.2.1")));
+        assert!(!lan.contains(&ip("::ffff:192.168.1.1")));
+        assert_eq!(doc[0].get_ipaddr(), None);
+
+        assert_eq!(doc[1].get_ipaddr(), None);
+        let link_local = doc[2].get_cidr().unwrap();
+        assert_eq!(link_local.network(), ip("fe80::"));
+        assert!(link_local.contains(&ip("febf::1")));
+        assert!(doc[3].get_cidr().unwrap().contains(&ip("255.1.2.3")));
+        for idx in 4..8 {
+            assert_eq!(doc[idx].get_cidr(), None, "{}", doc[idx]);
+        }
+        assert_eq!(doc[8].get_ipaddr(), Some(ip("::ffff:10.0.0.1")));
+        assert_eq!(doc[8].get_cidr(), None);
+        assert_eq!(doc[9].get_ipaddr(), None);
+
+        // Cached results are the same as fresh ones.
+        for _ in 0..2 {
+            assert_eq!(doc[0].get_cidr(), Some(lan));
+            assert_eq!(doc[8].get_ipaddr(), Some(ip("::ffff:10.0.0.1")));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decimal_formatting() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::generate::{Rng, SplitMix64};
+        use crate::split::compact_len;
+
+        for (value, text) in [
+            (0.1, "0.1"),
+            (1.0, "1.0"),
+            (-0.0, "-0.0"),
+            (100.0, "1e2"),
+            (123.0, "123.0"),
+            (1e21, "1e21"),
+            (1e308, "1e308"),
+            (f64::MAX, "1.7976931348623157e308"),
+            (5e-324, "5e-324"),
+            (0.001, "1e-3"),
+            (0.025, "0.025"),
+            (1.5e-7, "1.5e-7"),
+            (-2.5e-4, "-2.5e-4"),
+            (0.1 + 0.2, "0.30000000000000004"),
+            (123456.789, "123456.789"),
+        ] {
+            let value = Container::Decimal(value);
+            assert_eq!(value.dump_object(false, 0, 1), text);
+            assert_eq!(compact_len(&value), text.len());
+        }
+
+        // Every finite value is read back exactly, as a Decimal.
+        let mut rng = SplitMix64::new(42);
+        for _ in 0..10_000 {
+            let value = f64::from_bits(rng.next_u64());
+            if !value.is_finite() {
+                continue;
+            }
+            let text = Container::Decimal(value).dump_object(false, 0, 1);
+            let parsed = parse_str(&text)?;
+            assert_eq!(
+                parsed.get_real().map(f64::to_bits),
+                Some(value.to_bits()),
+                "{text}"
+            );
+            assert!(text.len() <= format!("{:e}", value).len() + 2, "{text}");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_units() -> Result<(), Box<dyn core::error::Error>> {
+        use std::time::Duration;
+
+        let duration = |text: &str| Container::from(text).get_duration();
+        assert_eq!(duration("1d2h3m4s"), Some(Duration::from_secs(93_784)));
+        assert_eq!(duration("1.5h"), Some(Duration::from_secs(5400)));
+        assert_eq!(duration("2m500ms"), Some(Duration::from_millis(120_500)));
+        assert_eq!(duration("10us"), Some(Duration::from_micros(10)));
+        assert_eq!(duration("10µs7ns"), Some(Duration::from_nanos(10_007)));
+        assert_eq!(duration("0s"), Some(Duration::ZERO));
+        for bad in [
+            "", "h", "1", "1x", "1 h", "-1s", "1.h", ".5s", "1..5s", "1hh",
+        ] {
+            assert_eq!(duration(bad), None, "{bad}");
+        }
+        assert_eq!(duration("999999999999999999999999d"), None);
+        assert_eq!(Container::from(5u64).get_duration(), None);
+
+        let size = |text: &str| Container::from(text).get_bytesize();
+        assert_eq!(size("0B"), Some(0));
+        assert_eq!(size("1kb"), Some(1000));
+        assert_eq!(size("1KiB"), Some(1024));
+        assert_eq!(size("2 GiB"), Some(2 << 30));
+        assert_eq!(size("0.5MiB"), Some(512 << 10));
+        assert_eq!(size("1.0001 B"), Some(1));
+        assert_eq!(size("16384PiB"), None);
+        for bad in ["", "MiB", "10", "10  MiB", "10 M", "-1B", "1e3B"] {
+            assert_eq!(size(bad), None, "{bad}");
+        }
+        assert_eq!(Container::from(-5i64).get_bytesize(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_semver() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::semver::{Identifier, Version, VersionReq};
+
+        let version = |text: &str| Version::parse(text).unwrap();
+        let parsed = version("1.0.0-alpha.1+build.5");
+        assert_eq!(
+            parsed.pre,
+            [
+                Identifier::Alphanumeric("alpha".to_owned()),
+                Identifier::Numeric(1)
+            ]
+        );
+        assert_eq!(parsed.build, "build.5");
+        assert_eq!(parsed.to_string(), "1.0.0-alpha.1+build.5");
+        for bad in [
+            "1.0",
+            "1.0.0.0",
+            "01.0.0",
+            "1.0.0-",
+            "1.0.0-01",
+            "1.0.0+",
+            "1.0.0-a..b",
+            "1.0.0+a+b",
+            "v1.0.0",
+            " 1.0.0",
+            "1.0.x",
+        ] {
+            assert_eq!(Version::parse(bad), None, "{bad}");
+        }
+
+        // Precedence example from the specification.
+        let ordered = [
+            "1.0.0-alpha",
+            "1.0.0-alpha.1",
+            "1.0.0-alpha.beta",
+            "1.0.0-beta",
+            "1.0.0-beta.2",
+            "1.0.0-beta.11",
+            "1.0.0-rc.1",
+            "1.0.0",
+            "1.0.1",
+            "1.1.0",
+            "2.0.0",
+        ];
+        for pair in ordered.windows(2) {
+            assert!(version(pair[0]) < version(pair[1]), "{pair:?}");
+        }
+        assert!(version("1.0.0+a")
+            .cmp_precedence(&version("1.0.0+b"))
+            .is_eq());
+
+        let doc = parse_str(r#"{"version": "2.3.4", "name": "pkg"}"#)?;
+        assert_eq!(doc["version"].get_semver(), Some(Version::new(2, 3, 4)));
+        assert_eq!(doc["name"].get_semver(), None);
+
+        let matches = |req: &str, text: &str| {
+            VersionReq::parse(req).unwrap().matches(&version(text))
+        };
+        for (req, yes, no) in [
+            ("^1.2.3", &["1.2.3", "1.9.0"][..], &["1.2.2", "2.0.0"][..]),
+            ("^0.2.3", &["0.2.3", "0.2.9"], &["0.3.0"]),
+            ("^0.0.3", &["0.0.3"], &["0.0.4"]),
+            ("^0.0", &["0.0.7"], &["0.1.0"]),
+            ("1.2", &["1.2.0", "1.7.1"], &["2.0.0"]),
+            ("~1.2.3", &["1.2.3", "1.2.9"], &["1.3.0"]),
+            ("~1", &["1.9.9"], &["2.0.0"]),
+            ("=1.2", &["1.2.0", "1.2.5"], &["1.3.0"]),
+            ("=1.2.3", &["1.2.3+meta"], &["1.2.4"]),
+            ("1.2.*", &["1.2.8"], &["1.3.0"]),
+            (">1.2", &["1.3.0"], &["1.2.9"]),
+            (">=1.2, <1.5", &["1.2.0", "1.4.9"], &["1.5.0"]),
+            (">= 1.2 <= 1.5", &["1.5.9"], &["1.6.0"]),
+            ("<=1.5.0", &["1.5.0"], &["1.5.1"]),
+            ("*", &["0.0.1", "9.9.9"], &["1.0.0-rc.1"]),
+            (
+                ">=1.0.0-beta",
+                &["1.0.0-rc.1", "1.0.0", "2.0.0"],
+                &["1.0.0-alpha", "2.0.0-rc.1"],
+            ),
+        ] {
+            for text in yes {
+                assert!(matches(req, text), "{req} should match {text}");
+            }
+            for text in no {
+                assert!(!matches(req, text), "{req} should not match {text}");
+            }
+        }
+        for bad in ["", "^", ">=x", "1.*.3", "~1.*", "1.2-rc", "^1.2.3.4", "01"]
+        {
+            assert_eq!(VersionReq::parse(bad), None, "{bad}");
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn test_str_matching() {
+        let value = Container::from("key-2024_b");
+        let matching = |pattern| value.get_str_matching(pattern).is_some();
+        assert!(matching(r"^key-\d{4}_[a-c]$"));
+        assert!(matching(r"\d+"));
+        assert!(matching(r"^(lock|key)-"));
+        assert!(matching(r"^k.*b$"));
+        assert!(matching(r"^[^\s]+$"));
+        assert!(matching(r"^([a-z]+|\d+)(?:[-_]([a-z]+|\d+))*$"));
+        assert!(!matching(r"^\d"));
+        assert!(!matching(r"^key-\d{5}"));
+        assert!(!matching(r"^(lock|door)"));
+        assert!(!matching(r"\s"));
+        // Invalid patterns match nothing.
+        assert!(!matching(r"^(key"));
+        assert!(!matching(r"[z-a]"));
+        assert!(!matching(r"a**"));
+        assert_eq!(Container::from(12u64).get_str_matching(r"\d"), None);
+        assert_eq!(Container::from("").get_str_matching(r"^$"), Some(""));
+    }
+
+    #[test]
+    fn test_dump_to_writer() {
+        use crate::container::DumpOptions;
+
+        /// Accepts a given number of bytes.
+        struct Limited(usize);
+        impl std::io::Write for Limited {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                if buf.len() > self.0 {
+                    return Err(std::io::ErrorKind::WriteZero.into());
+                }
+                self.0 -= buf.len();
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let value =
+            parse_str(r#"{"name": "disk", "sizes": [1, 2.5, null]}"#).unwrap();
+        let options = DumpOptions::new().indent(2);
+        let mut out = Vec::new();
+        value.dump_to_writer(&mut out, &options).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            value.dump_object(true, 2, 1)
+        );
+
+        let error = value
+            .dump_to_writer(&mut Limited(10), &options)
+            .unwrap_err();
+        let error = error.downcast::<std::io::Error>().unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::WriteZero);
+        assert!(value.dump_to_writer(&mut Limited(1024), &options).is_ok());
+    }
+
+    #[test]
+    fn test_extractor() {
+        use crate::error::{Error, FieldError};
+        use crate::extract::Extractor;
+
+        let doc = parse_str(
+            r#"{"id": 12, "ratio": 3, "temp": -4, "live": true,
+                "owner": {"name": "ops"}, "tags": ["a/b"], "a/b": null}"#,
+        )
+        .unwrap();
+        let mut ex = Extractor::new(&doc);
+        assert_eq!(ex.u64("id"), 12);
+        assert_eq!(ex.f64("ratio"), 3.0);
+        assert_eq!(ex.i64("temp"), -4);
+        assert!(ex.bool("live"));
+        assert_eq!(ex.str("/owner/name"), "ops");
+        assert_eq!(ex.str("/tags/0"), "a/b");
+        assert!(ex.errors().is_empty());
+
+        assert_eq!(ex.u64("temp"), 0);
+        assert_eq!(ex.str("a/b"), "");
+        assert!(!ex.bool("/owner/enabled"));
+        assert_eq!(ex.str("/tags/1"), "");
+        match ex.errors() {
+            [FieldError::Mismatch {
+                path,
+                expected: "an unsigned integer",
+                found: "number",
+            }, FieldError::Mismatch {
+                path: escaped,
+                found: "null",
+                ..
+            }, FieldError::Missing(nested), FieldError::Missing(index)] => {
+                assert_eq!(path, "/temp");
+                assert_eq!(escaped, "/a~1b");
+                assert_eq!(nested, "/owner/enabled");
+                assert_eq!(index, "/tags/1");
+            }
+            errors => panic!("unexpected errors {:?}", errors),
+        }
+
+        let error = ex.finish().unwrap_err();
+        let error = error.downcast_ref::<Error>().unwrap();
+        assert!(matches!(error, Error::Extract(errors) if errors.len() == 4));
+        assert_eq!(error.to_string().lines().count(), 5);
+        assert!(Extractor::new(&doc).finish().is_ok());
+    }
+
+    #[test]
+    fn test_extractor_policies() {
+        use crate::error::FieldError;
+        use crate::extract::Extractor;
+
+        let doc =
+            parse_str(r#"{"page": 3, "size": null, "sort": 1, "tag": "x"}"#)
+                .unwrap();
+        let mut ex = Extractor::new(&doc);
+        assert_eq!(ex.field("page").or(1u64).u64(), 3);
+        assert_eq!(ex.field("size").or(20u64).u64(), 20);
+        assert_eq!(ex.field("offset").or(-1i64).i64(), -1);
+        assert_eq!(ex.field("/filters/0").or("any").str(), "any");
+        assert_eq!(ex.field("tag").optional().str(), Some("x".into()));
+        assert_eq!(ex.field("size").optional().u64(), None);
+        assert_eq!(ex.field("cursor").optional().str(), None);
+        assert_eq!(
+            ex.field("limit")
+                .optional()
+                .get("a duration", |v| v.get_duration()),
+            None
+        );
+        assert!(ex.errors().is_empty());
+
+        // Values of the wrong type are reported whatever the policy.
+        assert_eq!(ex.field("sort").optional().str(), None);
+        assert_eq!(ex.field("tag").or(0.5).f64(), 0.0);
+        assert!(!ex.field("missing").or("yes").bool());
+        assert!(!ex.field("size").bool());
+        let found: Vec<_> = ex
+            .errors()
+            .iter()
+            .map(|error| match error {
+                FieldError::Mismatch { path, found, .. } => {
+                    (path.as_str(), *found)
+                }
+                FieldError::Missing(path) => (path.as_str(), "nothing"),
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("/sort", "number"),
+                ("/tag", "string"),
+                ("/missing", "string"),
+                ("/size", "null")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dump_options() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::container::{DumpOptions, Newline};
+
+        let doc =
+            parse_str(r#"{"z": {"y": 1, "x": [2]}, "a": "é😀\u0001\b"}"#)?;
+        assert_eq!(
+            doc.dump(&DumpOptions::new().sort_keys(true).ascii(true))?,
+            r#"{"a":"\u00e9\ud83d\ude00\u0001\b","z":{"x":[2],"y":1}}"#
+        );
+        assert_eq!(
+            doc.dump(&DumpOptions::new())?,
+            "{\"z\":{\"y\":1,\"x\":[2]},\"a\":\"\u{e9}\u{1f600}\\u0001\\b\"}"
+        );
+        assert_eq!(
+            doc["z"].dump(&DumpOptions::pretty().newline(Newline::CrLf))?,
+            "{\r\n    \"y\": 1,\r\n    \"x\": [\r\n        2\r\n    ]\r\n}"
+        );
+        assert_eq!(
+            doc["z"].dump(&DumpOptions::new().indent(2).indent_char('.'))?,
+            "{\n..\"y\": 1,\n..\"x\": [\n....2\n..]\n}"
+        );
+        for options in [
+            DumpOptions::new().ascii(true),
+            DumpOptions::pretty().sort_keys(true),
+        ] {
+            assert_eq!(parse_str(&doc.dump(&options)?)?, doc);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_json_string() -> Result<(), Box<dyn core::error::Error>> {
+        let text = |value: &Container| value.to_json_string();
+        let round_trip = |value: &Container| parse_str(&value.to_json_string());
+
+        for value in [
+            Container::Null,
+            Container::Boolean(true),
+            Container::Boolean(false),
+            Container::Number(i64::MIN),
+            Container::Unsigned(u64::MAX),
+            Container::Number128(i128::MIN),
+            Container::Unsigned128(u128::MAX),
+            Container::Decimal(-2.5e-7),
+            Container::Decimal(1.0),
+            Container::RawNumber("1e400".into()),
+            Container::BigNumber("-1".to_owned() + &"9".repeat(50)),
+            Container::String(
+                "\"\\/\u{0}\u{8}\u{c}\n\r\t\u{1f}\u{7f}é😀".into(),
+            ),
+            Container::Array(vec![]),
+            Container::Array(vec![Container::Null, vec![1u64.into()].into()]),
+            Container::new_object(),
+        ] {
+            assert_eq!(round_trip(&value)?, value, "{}", text(&value));
+        }
+
+        let mut object = Container::new_object();
+        object.insert_str("", Container::Array(vec![]));
+        object.insert_str("a\"b\\c\u{1}", Container::from("x"));
+        object.insert_str("é", Container::new_object());
+        assert_eq!(round_trip(&object)?, object);
+        assert_eq!(text(&object), r#"{"":[],"a\"b\\c\u0001":"x","é":{}}"#);
+
+        // Values JSON cannot represent still give valid JSON.
+        for value in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert_eq!(
+                round_trip(&Container::Decimal(value))?,
+                Container::Null
+            );
+        }
+        let words = Container::RawNumber("not a number".into());
+        assert_eq!(text(&words), r#""not a number""#);
+        assert_eq!(round_trip(&words)?, Container::from("not a number"));
+        #[cfg(feature = "uuid")]
+        assert_eq!(
+            round_trip(&Container::Uuid([0xab; 16]))?,
+            Container::from("abababab-abab-abab-abab-abababababab")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_extractor_deprecations() -> Result<(), Box<dyn core::error::Error>>
+    {
+        use crate::error::WarningKind;
+        use crate::extract::Extractor;
+
+        let doc = parse_str(r#"{"host": "db", "opts": {"tls": true}}"#)?;
+        let mut ex = Extractor::new(&doc);
+        ex.deprecate("host", Some("/server/host"))
+            .deprecate("/opts/tls", None)
+            .deprecate("port", None);
+        assert_eq!(ex.field("/host").optional().str().as_deref(), Some("db"));
+        assert_eq!(ex.str("host"), "db");
+        assert!(ex
+            .field("opts")
+            .optional()
+            .get("an object", |v| v.is_object().then_some(()))
+            .is_some());
+        assert!(ex.field("/opts/tls").optional().str().is_none());
+        assert_eq!(ex.field("port").or(5432u64).u64(), 5432);
+
+        // Reported once each, even when the read fails.
+        let kinds: Vec<_> =
+            ex.warnings().iter().map(|warning| &warning.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                &WarningKind::DeprecatedField {
+                    path: "/host".into(),
+                    replacement: Some("/server/host".into())
+                },
+                &WarningKind::DeprecatedField {
+                    path: "/opts/tls".into(),
+                    replacement: None
+                }
+            ]
+        );
+        assert_eq!(
+            ex.warnings()[0].to_string(),
+            r#"Deprecated field "/host" is provided, use "/server/host" instead"#
+        );
+        assert!(ex.finish_with_warnings().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_dump_colored() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::container::DumpOptions;
+
+        let doc =
+            parse_str(r#"{"id": 1, "tags": ["a", null, true], "none": {}}"#)?;
+        assert_eq!(
+            doc.dump(&DumpOptions::new().color(true))?,
+            "{\x1b[1;34m\"id\"\x1b[0m:\x1b[33m1\x1b[0m,\
+             \x1b[1;34m\"tags\"\x1b[0m:[\x1b[32m\"a\"\x1b[0m,\
+             \x1b[35mnull\x1b[0m,\x1b[35mtrue\x1b[0m],\
+             \x1b[1;34m\"none\"\x1b[0m:{}}"
+        );
+        // Output piped elsewhere than a terminal is left plain.
+        if !std::io::IsTerminal::is_terminal(&std::io::stdout()) {
+            assert_eq!(doc.dump_colored(), doc.dump(&DumpOptions::pretty())?);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_mapping() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::error::{Error, MappingError};
+        use crate::mapping::Mapping;
+
+        let mut mapping = Mapping::new();
+        mapping
+            .field("/id", "/meta/ids/-")
+            .field("/alias", "/meta/ids/-")
+            .field("/tags", "/labels")
+            .field_with(
+                "/enabled",
+                "/status",
+                |on| {
+                    Ok(Container::from(if on.get_bool() == Some(true) {
+                        "on"
+                    } else {
+                        "off"
+                    }))
+                },
+                |status| {
+                    Ok(Container::from(status.is_string_and(|s| s == "on")))
+                },
+            );
+        let source = parse_str(
+            r#"{"id": 7, "tags": ["x"], "enabled": true, "extra": 1}"#,
+        )?;
+        let target = mapping.forward(&source)?;
+        assert_eq!(
+            target,
+            parse_str(
+                r#"{"meta": {"ids": [7]}, "labels": ["x"], "status": "on"}"#
+            )?
+        );
+        // Appending has no inverse: `/meta/ids/-` reads nothing back.
+        assert_eq!(
+            mapping.backward(&target)?,
+            parse_str(r#"{"tags": ["x"], "enabled": true}"#)?
+        );
+
+        let failing = |mapping: &Mapping, doc: &Container| {
+            let error = mapping.forward(doc).unwrap_err();
+            match error.downcast_ref::<Error>() {
+                Some(Error::Mapping(error)) => error.clone(),
+                _ => panic!("unexpected error {error}"),
+            }
+        };
+        let mut conflict = Mapping::new();
+        conflict.field("/a", "/out").field("/b", "/out/inner");
+        let doc = parse_str(r#"{"a": 1, "b": 2}"#)?;
+        assert!(
+            matches!(failing(&conflict, &doc), MappingError::Conflict(path) if path == "/out/inner")
+        );
+        let mut invalid = Mapping::new();
+        invalid.field("a", "/a");
+        assert!(matches!(
+            failing(&invalid, &doc),
+            MappingError::InvalidPointer(_)
+        ));
+
+        let mut transform = Mapping::new();
+        transform.field_with(
+            "/a",
+            "/a",
+            |_| Err("rejected".into()),
+            |v| Ok(v.clone()),
+        );
+        assert_eq!(
+            transform.forward(&doc).unwrap_err().to_string(),
+            "rejected"
+        );
+        assert_eq!(transform.backward(&doc)?, parse_str(r#"{"a": 1}"#)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_script() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::error::{Error, ScriptError};
+        use crate::script::Script;
+
+        let run = |source: &str, doc: &str| {
+            Script::parse(source)?.apply(parse_str(doc)?)
+        };
+        let failing = |source: &str, doc: &str| match run(source, doc) {
+            Err(error) => match error.downcast_ref::<Error>() {
+                Some(Error::Script(error)) => error.clone(),
+                _ => panic!("unexpected error {error}"),
+            },
+            Ok(value) => panic!("{source} gave {value:?}"),
+        };
+
+        let doc = r#"{"a": 7, "b": 2.5, "s": "Hi", "tags": ["x", "y"], "user-id": 3}"#;
+        let value =
+            |expr: &str| -> Result<Container, Box<dyn core::error::Error>> {
+                let script = format!("set /out = {expr}");
+                Ok(run(&script, doc)?.unwrap()["out"].clone())
+            };
+        assert_eq!(value("/a + 1 * 2")?, Container::from(9u64));
+        assert_eq!(value("(/a + 1) * 2")?, Container::from(16u64));
+        assert_eq!(value("/a / 2")?, Container::from(3.5));
+        assert_eq!(value("/a % 4 - 5")?, Container::from(-2i64));
+        assert_eq!(value("-/b")?, Container::from(-2.5));
+        assert_eq!(value("/user-id * -1")?, Container::from(-3i64));
+        assert_eq!(value(r#"lower(/s) + "!""#)?, Container::from("hi!"));
+        assert_eq!(
+            value("len(/tags) == 2 && len(/s) < 3")?,
+            Container::from(true)
+        );
+        assert_eq!(value("/missing == null || !/a")?, Container::from(true));
+        assert_eq!(
+            value("/a == 7.0 && /a != 8 && /s >= \"Ha\"")?,
+            Container::from(true)
+        );
+        assert_eq!(value("/tags/1")?, Container::from("y"));
+
+        let kept = run("set /meta/seen = true; del /tags; del /nothing", doc)?
+            .unwrap();
+        assert_eq!(kept["meta"]["seen"], Container::from(true));
+        assert!(kept.pointer("/tags").is_none());
+        assert_eq!(
+            run("filter /a > 5 && /s == \"Hi\"", doc)?,
+            Some(parse_str(doc)?)
+        );
+        assert_eq!(run("filter /missing", doc)?, None);
+        assert_eq!(run("", doc)?, Some(parse_str(doc)?));
+
+        assert!(matches!(
+            failing("set /x = /s - 1", doc),
+            ScriptError::TypeMismatch {
+                op: "`-`",
+                found: "string"
+            }
+        ));
+        assert!(matches!(
+            failing("set /x = len(/a)", doc),
+            ScriptError::TypeMismatch { op: "`len`", .. }
+        ));
+        assert!(matches!(
+            failing("filter /s < 3", doc),
+            ScriptError::TypeMismatch { .. }
+        ));
+        assert!(matches!(
+            failing("set /x = /a / 0", doc),
+            ScriptError::DivisionByZero
+        ));
+        assert!(
+            matches!(failing("set /s/x = 1", doc), ScriptError::InvalidTarget(path) if path == "/s/x")
+        );
+        assert!(matches!(
+            failing("filter 1 < 2 < 3", doc),
+            ScriptError::Syntax { pos: 13, .. }
+        ));
+        assert!(matches!(
+            failing("print /a", doc),
+            ScriptError::Syntax { pos: 0, .. }
+        ));
+        assert!(matches!(
+            failing("set a = 1", doc),
+            ScriptError::Syntax { pos: 4, .. }
+        ));
+        assert!(matches!(
+            failing("filter (/a", doc),
+            ScriptError::Syntax { .. }
+        ));
+        assert!(matches!(
+            failing(r#"filter "open"#, doc),
+            ScriptError::Syntax { .. }
+        ));
+
+        let nested = format!("filter {}1{}", "(".repeat(100), ")".repeat(100));
+        assert!(matches!(
+            failing(&nested, doc),
+            ScriptError::Syntax {
+                expected: "less nesting",
+                ..
+            }
+        ));
+        assert!(run(
+            &format!("filter {}1{}", "(".repeat(60), ")".repeat(60)),
+            doc
+        )?
+        .is_some());
+
+        // Runs are bounded, each evaluated expression being a step.
+        let long = vec!["/a"; 200].join(" + ");
+        let script = Script::parse(&format!("set /sum = {long}"))?;
+        assert_eq!(
+            script.apply(parse_str(doc)?)?.unwrap()["sum"],
+            Container::from(1400u64)
+        );
+        let error = script.max_steps(100).apply(parse_str(doc)?).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<Error>(),
+            Some(Error::Script(ScriptError::StepLimit(100)))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_minify() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::dialect::Dialect;
+        use crate::parser::{minify, minify_with_dialect};
+
+        let text =
+            " {\r\n\t\"a b\" : [ 1 , -2.5e3, \"x\\\" y\" , {} , [ ] ],\n \
+                    \"c\": { \"d\" : null , \"e\":true } } ";
+        let minified = minify(text)?;
+        assert_eq!(
+            minified,
+            r#"{"a b":[1,-2.5e3,"x\" y",{},[]],"c":{"d":null,"e":true}}"#
+        );
+        assert_eq!(parse_str(&minified)?, parse_str(text)?);
+        assert_eq!(minify(" 12 ")?, "12");
+
+        for invalid in [
+            "",
+            "[1,]",
+            "[1 2]",
+            "{\"a\" 1}",
+            "{\"a\":1,}",
+            "[1}",
+            "[\"\\x\"]",
+            "[01]",
+            "[tru]",
+            "1 2",
+            "{1: 2}",
+            "[\"\\ud800\"]",
+        ] {
+            assert!(minify(invalid).is_err(), "{invalid}");
+        }
+        assert!(minify(&"[".repeat(501)).is_err());
+        let deep = format!("{}{}", "[".repeat(500), "]".repeat(500));
+        assert_eq!(minify(&deep)?, deep);
+
+        struct Commented;
+
+        impl Dialect for Commented {
+            fn comment_starts(&self) -> &[u8] {
+                b"/"
+            }
+            fn skip_comment(&self, rest: &[u8]) -> Option<usize> {
+                rest.starts_with(b"//").then(|| {
+                    rest.iter().position(|b| *b == b'\n').unwrap_or(rest.len())
+                })
+            }
+            fn trailing_commas(&self) -> bool {
+                true
+            }
+        }
+
+        let text = "// settings\n{\"a\": [1, 2,], // two\n \"b\": \"//\",}";
+        assert_eq!(
+            minify_with_dialect(text, &Commented)?,
+            r#"{"a":[1,2],"b":"//"}"#
+        );
+        assert!(minify(text).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_subtree_cache() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::cache::SubtreeCache;
+
+        let same = [
+            (
+                r#"{"a": 1, "b": [true, null]}"#,
+                r#"{"b": [true, null], "a": 1}"#,
+            ),
+            ("[0.0]", "[-0.0]"),
+        ];
+        for (a, b) in same {
+            assert_eq!(
+                parse_str(a)?.structural_hash(),
+                parse_str(b)?.structural_hash()
+            );
+        }
+        let different = [
+            ("[1, 2]", "[2, 1]"),
+            ("[[1], 2]", "[1, [2]]"),
+            (r#"{"a": 1}"#, r#"{"a": "1"}"#),
+            ("[1]", "[-1]"),
+            ("[null]", "[[]]"),
+        ];
+        for (a, b) in different {
+            assert_ne!(
+                parse_str(a)?.structural_hash(),
+                parse_str(b)?.structural_hash()
+            );
+        }
+
+        let mut cache = SubtreeCache::with_limit(2);
+        let mut doc = parse_str(r#"{"a": {"x": 1}, "b": {"x": 1}}"#)?;
+        let runs = core::cell::Cell::new(0);
+        let mut valid = |value: &Container| {
+            runs.set(runs.get() + 1);
+            value["x"].get_uint() == Some(1)
+        };
+        assert!(*cache.get_or_insert_with(&doc["a"], &mut valid));
+        assert!(*cache.get_or_insert_with(&doc["b"], &mut valid));
+        assert_eq!(cache.len(), 1);
+
+        // A modified subtree is looked up by its new content.
+        doc["b"]["x"] = Container::from(2u64);
+        assert_eq!(cache.get(&doc["b"]), None);
+        assert!(!*cache.get_or_insert_with(&doc["b"], &mut valid));
+        assert_eq!(runs.get(), 2);
+        assert_eq!(cache.get(&doc["a"]), Some(&true));
+
+        // The cache is emptied once full.
+        cache.get_or_insert_with(&doc, &mut valid);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.get(&doc["a"]), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_reformat() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::container::{DumpOptions, Newline};
+        use crate::parser::reformat;
+
+        // Layout matches that of `dump` for the same document.
+        let text = r#" {"a": [1, {"b": null, "c": [[], {}]}], "d": "é\n",
+            "e": {"f": true}} "#;
+        let doc = parse_str(text)?;
+        for options in [
+            DumpOptions::new(),
+            DumpOptions::pretty(),
+            DumpOptions::new().indent(1).indent_char('\t'),
+            DumpOptions::pretty().newline(Newline::CrLf).ascii(true),
+            DumpOptions::pretty().color(true),
+        ] {
+            assert_eq!(reformat(text, &options)?, doc.dump(&options)?);
+        }
+
+        // Numbers and strings keep their text, members their order.
+        assert_eq!(
+            reformat(
+                r#"{"z": 1.50, "y": "A", "x": 1E2}"#,
+                &DumpOptions::new()
+            )?,
+            r#"{"z":1.50,"y":"A","x":1E2}"#
+        );
+        assert_eq!(
+            reformat("\"ü\"", &DumpOptions::new().ascii(true))?,
+            r#""\u00fc""#
+        );
+        assert!(reformat("[1,]", &DumpOptions::pretty()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::error::{Error, ParseError};
+        use crate::parser::validate;
+
+        // Validation agrees with parsing.
+        for text in [
+            "0",
+            " -1.5e+3 ",
+            "\"\"",
+            "\"\\u00e9\\ud83d\\ude00\"",
+            "[]",
+            "{}",
+            r#"{"a": [1, {"b": null}], "c": "\\/"}"#,
+            "[true,false,null]",
+            "",
+            " ",
+            "[",
+            "]",
+            "[1,]",
+            "[,1]",
+            "{\"a\"}",
+            "{\"a\":}",
+            "[1 2]",
+            "01",
+            "1.",
+            "1e",
+            "[nul]",
+            "[truer]",
+            "\"\\q\"",
+            "\"\\u12\"",
+            "\"\\udc00\"",
+            "\"open",
+            "{\"a\":1]",
+            "[1}",
+            "1 2",
+            "{'a': 1}",
+        ] {
+            assert_eq!(
+                validate(text).is_ok(),
+                parse_str(text).is_ok(),
+                "{text}"
+            );
+        }
+
+        let error = validate("[1,\n 2,,]").unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<Error>(),
+            Some(Error::Parsing(ParseError::UnexpectedToken(',', span)))
+                if (span.line, span.col, span.start) == (2, 4, 7)
+        ));
+        let deep = "[".repeat(501);
+        assert!(matches!(
+            validate(&deep).unwrap_err().downcast_ref::<Error>(),
+            Some(Error::Parsing(ParseError::NestedDepthExceeded(501, _)))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_equals_json_str() -> Result<(), Box<dyn core::error::Error>> {
+        let doc = parse_str(
+            r#"{"a": [1, -2, 3.5, "x\ny", true, null], "b": {"c": {}},
+                "dé": "😀", "e": 18446744073709551616}"#,
+        )?;
+        // Comparison agrees with parsing the text.
+        for text in [
+            r#"{"a": [1, -2, 3.5, "x\ny", true, null], "b": {"c": {}},
+                "dé": "😀", "e": 18446744073709551616}"#,
+            r#"{"e":18446744073709551616,"dé":"😀",
+                "b":{"c":{}},"a":[1,-2,3.5e0,"x\u000ay",true,null]}"#,
+            r#"{"a": [1, -2, 3.5, "x\ny", true], "b": {"c": {}},
+                "dé": "😀", "e": 18446744073709551616}"#,
+            r#"{"a": [1, -2, 3.5, "x\ny", true, null], "b": {"c": []},
+                "dé": "😀", "e": 18446744073709551616}"#,
+            r#"{"a": [1, 2, 3.5, "x\ny", true, null], "b": {"c": {}},
+                "dé": "😀", "e": 18446744073709551616}"#,
+            r#"{"a": [1, -2, 3.5, "x\ny", true, null], "b": {"c": {}},
+                "de": "😀", "e": 18446744073709551616}"#,
+            r#"{"a": [1, -2, 3.5, "x\ny", true, null], "b": {"c": {}},
+                "dé": "😀", "e": 1, "f": 2}"#,
+            r#"[1, -2]"#,
+            "null",
+        ] {
+            assert_eq!(doc.equals_json_str(text)?, doc == parse_str(text)?);
+        }
+        assert!(parse_str("[1.0]")?.equals_json_str("[1.0]")?);
+        assert!(!parse_str("[1.0]")?.equals_json_str("[1]")?);
+        assert!(Container::from("é").equals_json_str(r#""é""#)?);
+
+        // Invalid text fails even after a difference.
+        assert!(doc.equals_json_str("[2, ]").is_err());
+        assert!(doc.equals_json_str("{} {}").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_skip_value() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::dialect::Json;
+        use crate::error::{Error, ParseError};
+        use crate::parser::{observe, Observer, Span};
+
+        /// Reads the scalars of a document, skipping the value of
+        /// every `skip` member.
+        #[derive(Default)]
+        struct Projection {
+            skip_next: bool,
+            scalars: Vec<String>,
+            skipped: Vec<Span>,
+        }
+
+        impl Observer for Projection {
+            fn key(&mut self, key: &str, _span: Span) {
+                self.skip_next = key == "skip";
+            }
+            fn scalar(&mut self, value: &Container, _span: Span) {
+                self.scalars.push(value.to_string());
+            }
+            fn skip(&mut self) -> bool {
+                core::mem::take(&mut self.skip_next)
+            }
+            fn skipped(&mut self, span: Span) {
+                self.skipped.push(span);
+            }
+        }
+
+        let text = "{\"a\": 1, \"skip\": {\"b\": [2, \"x\\\"]\"]},\n \
+                    \"c\": [3, {\"skip\": 4}], \"skip\": \"5\"}";
+        let mut projection = Projection::default();
+        observe(text, &Json, &mut projection)?;
+        assert_eq!(projection.scalars, ["1", "3"]);
+        let skipped: Vec<_> = projection
+            .skipped
+            .iter()
+            .map(|span| &text[span.start..span.end])
+            .collect();
+        assert_eq!(skipped, [r#"{"b": [2, "x\"]"]}"#, "4", r#""5""#]);
+        assert_eq!(
+            (projection.skipped[1].line, projection.skipped[1].col),
+            (2, 20)
+        );
+
+        // Skipped values are still checked, with positions in the input.
+        let error = observe(
+            "[0,\n {\"skip\": [1,\n 2,]}]",
+            &Json,
+            &mut Projection::default(),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<Error>(),
+            Some(Error::Parsing(ParseError::UnexpectedToken(']', span)))
+                if (span.line, span.col) == (3, 4)
+        ));
+        let deep = format!("{{\"skip\": {}}}", "[".repeat(500));
+        let error =
+            observe(&deep, &Json, &mut Projection::default()).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<Error>(),
+            Some(Error::Parsing(ParseError::NestedDepthExceeded(501, _)))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_watched() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::watch::Watched;
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        type Log = Rc<RefCell<Vec<String>>>;
+        fn logger(
+            log: &Log,
+            name: &'static str,
+        ) -> impl FnMut(&crate::watch::Change) {
+            let log = log.clone();
+            move |change| {
+                let show = |value: Option<&Container>| {
+                    value.map_or("-".to_owned(), |value| value.to_string())
+                };
+                log.borrow_mut().push(format!(
+                    "{name} {} {} {}",
+                    change.path,
+                    show(change.old),
+                    show(change.new)
+                ));
+            }
+        }
+
+        let log = Log::default();
+        let mut doc = Watched::new(parse_str(
+            r#"{"servers": [{"port": 1}, {"port": 2}], "name": "x"}"#,
+        )?);
+        doc.watch("/servers/*/port", logger(&log, "ports"))?;
+        doc.watch("/name", logger(&log, "name"))?;
+        doc.watch("", logger(&log, "root"))?;
+        assert!(doc.watch("name", logger(&log, "bad")).is_err());
+
+        doc.set("/servers/1/port", Container::from(3u64))?;
+        doc.set("/name", Container::from("x"))?;
+        doc.remove("/servers/0")?;
+        doc.apply_patch(&parse_str(
+            r#"[{"op": "add", "path": "/servers/-", "value": {"port": 4}},
+                {"op": "move", "from": "/name", "path": "/title"}]"#,
+        )?)?;
+        doc.update(|document| document["title"] = Container::from("y"));
+        assert!(doc.remove("/missing").is_err());
+
+        let log = log.borrow();
+        let names: Vec<_> = log
+            .iter()
+            .filter(|line| !line.starts_with("root"))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "ports /servers/1/port 2 3",
+                "ports /servers/0/port 1 3",
+                "ports /servers/1/port 3 -",
+                "ports /servers/1/port - 4",
+                "name /name \"x\" -",
+            ]
+        );
+        // The whole document is told of every change but the no-op.
+        assert_eq!(log.len() - names.len(), 4);
+        assert_eq!(doc.document()["title"], Container::from("y"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge3() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::merge::merge3;
+
+        let base = parse_str(
+            r#"{"name": "app", "port": 80, "tags": ["a"], "old": 1,
+                "db": {"host": "h", "pool": 5}}"#,
+        )?;
+        let ours = parse_str(
+            r#"{"name": "app", "port": 8080, "tags": ["a", "b"],
+                "db": {"host": "h2", "pool": 5}, "debug": true}"#,
+        )?;
+        let theirs = parse_str(
+            r#"{"name": "svc", "port": 8081, "tags": ["a", "c"], "old": 1,
+                "db": {"host": "h", "pool": 10}, "debug": true}"#,
+        )?;
+        let (merged, conflicts) = merge3(&base, &ours, &theirs);
+        assert_eq!(
+            merged,
+            parse_str(
+                r#"{"name": "svc", "port": 8080, "tags": ["a", "b"],
+                    "db": {"host": "h2", "pool": 10}, "debug": true}"#
+            )?
+        );
+        let paths: Vec<_> = conflicts.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(paths, ["/port", "/tags"]);
+        assert_eq!(conflicts[0].base, Some(Container::from(80u64)));
+        assert_eq!(conflicts[0].theirs, Some(Container::from(8081u64)));
+
+        // A removal against a change conflicts, and so do two additions.
+        let base = parse_str(r#"{"a": 1}"#)?;
+        let ours = parse_str(r#"{"b": {"x": 1, "y": 2}}"#)?;
+        let theirs = parse_str(r#"{"a": 2, "b": {"x": 1, "y": 3}}"#)?;
+        let (merged, conflicts) = merge3(&base, &ours, &theirs);
+        assert_eq!(merged, ours);
+        assert_eq!(conflicts[0].path, "/b/y");
+        assert_eq!(conflicts[1].path, "/a");
+        assert_eq!(conflicts[1].ours, None);
+
+        let (merged, conflicts) = merge3(&base, &base, &Container::Null);
+        assert_eq!((merged, conflicts.len()), (Container::Null, 0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_lazy_document() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::lazy::LazyDocument;
+
+        let text = r#" {"a~b": [10, {"c": "x"}, []], "d\/e": null,
+            "big": [[1, 2, 3], {"f": -1.5e3}], "s": "é"} "#;
+        let mut doc = LazyDocument::new(text)?;
+        assert_eq!(doc.raw(""), Some(text.trim()));
+        assert_eq!(doc.raw("/a~0b/1"), Some(r#"{"c": "x"}"#));
+        assert_eq!(doc.raw("/a~0b/2"), Some("[]"));
+        assert_eq!(doc.raw("/d~1e"), Some("null"));
+        assert_eq!(doc.raw("/big/1/f"), Some("-1.5e3"));
+        for missing in ["/a~0b/3", "/a~0b/01", "/d~1e/x", "/z", "big"] {
+            assert_eq!(doc.span(missing), None, "{missing}");
+        }
+
+        assert_eq!(doc.get("/s"), Some(&Container::from("é")));
+        assert_eq!(doc.get("/big/0/2").unwrap().get_uint(), Some(3));
+        let span = doc.span("/big/0").unwrap();
+        assert_eq!(&text[span], "[1, 2, 3]");
+        assert_eq!(doc.to_container(), parse_str(text)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_diff_array_alignment() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::diff::{diff, diff_with, DiffOptions};
+
+        let inserted =
+            diff(&parse_str("[1, 2, 3, 4]")?, &parse_str("[0, 1, 2, 3, 4]")?);
+        assert_eq!(
+            inserted,
+            parse_str(r#"[{"op": "add", "path": "/0", "value": 0}]"#)?
+        );
+        let moved =
+            diff(&parse_str("[1, 2, 3, 4]")?, &parse_str("[4, 1, 2, 3]")?);
+        assert_eq!(
+            moved,
+            parse_str(r#"[{"op": "move", "path": "/0", "from": "/3"}]"#)?
+        );
+
+        let by_id = DiffOptions::new().id_field("/meta/id");
+        let source = parse_str(
+            r#"[{"meta": {"id": "a"}, "n": 1}, {"meta": {"id": "b"}}, 5]"#,
+        )?;
+        let target = parse_str(
+            r#"[{"meta": {"id": "c"}}, {"meta": {"id": "a"}, "n": 2}, 6]"#,
+        )?;
+        assert_eq!(
+            diff_with(&source, &target, &by_id),
+            parse_str(
+                r#"[{"op": "remove", "path": "/1"},
+                    {"op": "add", "path": "/0", "value": {"meta": {"id": "c"}}},
+                    {"op": "replace", "path": "/1/n", "value": 2},
+                    {"op": "replace", "path": "/2", "value": 6}]"#
+            )?
+        );
+
+        // Round trips over random arrays with repeated elements and ids.
+        let mut seed = 7u64;
+        let mut next = move |bound: u64| {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+            (seed >> 33) % bound
+        };
+        for _ in 0..200 {
+            let mut array = |len: u64| {
+                let elements: Vec<String> = (0..next(len))
+                    .map(|_| match next(3) {
+                        0 => format!(
+                            r#"{{"id": {}, "v": {}}}"#,
+                            next(4),
+                            next(2)
+                        ),
+                        _ => next(6).to_string(),
+                    })
+                    .collect();
+                parse_str(&format!("[{}]", elements.join(",")))
+            };
+            let (mut source, target) = (array(12)?, array(12)?);
+            for options in
+                [DiffOptions::new(), DiffOptions::new().id_field("id")]
+            {
+                let mut copy = source.clone();
+                copy.apply_patch(&diff_with(&source, &target, &options))?;
+                assert_eq!(copy, target);
+            }
+            source.apply_patch(&diff(&source, &target))?;
+            assert_eq!(source, target);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dom_parse_indexed() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::dom::Dom;
+        use crate::index::StructuralIndex;
+
+        // Backslash runs and quotes on both sides of 64 byte blocks.
+        let long = format!(
+            r#"{{"{}\\": ["{}\"\\\\", "{}"], "n": [1, 2.5e3, true, null]}}"#,
+            "k".repeat(59),
+            "\\\\".repeat(40),
+            "{[,:]}".repeat(30),
+        );
+        let mut valid = vec![
+            r#"{"a": [{"b": null}, "cé\"", -1, 1e2], "d": {}}"#.to_owned(),
+            " [ ] ".to_owned(),
+            "true".to_owned(),
+            long,
+        ];
+        for pad in 0..70 {
+            valid.push(format!(r#"{}["{}\"", 1]"#, " ".repeat(pad), "x\\\\"));
+        }
+        let invalid = [
+            "",
+            "[1 2]",
+            "[truex]",
+            "nul",
+            "{\"a\" 1}",
+            "[\"open",
+            "[1,]",
+            "\"a\"b",
+            "{\"a\": 1} x",
+            "[1]]",
+            "[01]",
+            "\\\"a\"",
+        ];
+
+        let (mut dom, mut indexed) = (Dom::default(), Dom::default());
+        let mut index = StructuralIndex::new();
+        for text in &valid {
+            dom.parse(text)?;
+            indexed.parse_indexed(text, &mut index)?;
+            assert_eq!(indexed.nodes(), dom.nodes());
+            assert_eq!(indexed.to_container(), parse_str(text)?);
+        }
+        for text in invalid {
+            let expected = dom.parse(text).map_err(|error| error.to_string());
+            let found = indexed
+                .parse_indexed(text, &mut index)
+                .map_err(|error| error.to_string());
+            assert!(found.is_err(), "{}", text);
+            if !text.starts_with('\\') {
+                assert_eq!(found, expected, "{}", text);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_diff_options() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::diff::{diff, diff_with, DiffOptions};
+
+        let source = parse_str(
+            r#"{"meta": {"ts": 1, "id": 4}, "rows": [{"ts": 5, "v": 1.0}, {"ts": 6, "v": 2}], "gone": 1}"#,
+        )?;
+        let target = parse_str(
+            r#"{"meta": {"ts": 2, "id": 4}, "rows": [{"ts": 7, "v": 1.001}, {"ts": 8, "v": 3}], "new": 1}"#,
+        )?;
+        let options = DiffOptions::new()
+            .ignore_path("/meta/ts")
+            .ignore_path("/rows/*/ts")
+            .ignore_path("/new")
+            .ignore_path("not a pointer")
+            .tolerance(0.01);
+        assert_eq!(
+            diff_with(&source, &target, &options),
+            parse_str(
+                r#"[{"op": "remove", "path": "/gone"},
+                    {"op": "replace", "path": "/rows/1/v", "value": 3}]"#
+            )?
+        );
+        let strict = DiffOptions::new().tolerance(0.0001);
+        assert_eq!(diff_with(&source, &target, &strict).len(), 7);
+
+        let unordered = DiffOptions::new().ignore_array_order(true);
+        let source = parse_str(r#"[3, [1, 2], {"a": 1}, 3, "x"]"#)?;
+        let target = parse_str(r#"[{"a": 2}, [2, 1], 3, "y", 3, 4]"#)?;
+        let patch = diff_with(&source, &target, &unordered);
+        assert_eq!(
+            patch,
+            parse_str(
+                r#"[{"op": "replace", "path": "/2/a", "value": 2},
+                    {"op": "replace", "path": "/4", "value": "y"},
+                    {"op": "add", "path": "/5", "value": 4}]"#
+            )?
+        );
+        let mut patched = source.clone();
+        patched.apply_patch(&patch)?;
+        assert!(diff_with(&patched, &target, &unordered).is_empty());
+        assert!(!diff(&patched, &target).is_empty());
+
+        let timestamps = DiffOptions::new()
+            .ignore_array_order(true)
+            .ignore_path("/*/ts");
+        let source = parse_str(r#"[{"id": 1, "ts": 1}, {"id": 2, "ts": 2}]"#)?;
+        let target = parse_str(r#"[{"id": 2, "ts": 3}, {"id": 1, "ts": 4}]"#)?;
+        assert!(diff_with(&source, &target, &timestamps).is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_patch_invert_compose() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::patch::{compose, invert};
+
+        let original = parse_str(
+            r#"{"a": {"b": 1, "c": [1, 2, 3]}, "d": "x", "e": null}"#,
+        )?;
+        let patches = [
+            r#"[{"op": "add", "path": "/a/b", "value": 2},
+                {"op": "add", "path": "/a/c/1", "value": 9},
+                {"op": "add", "path": "/a/c/-", "value": 8},
+                {"op": "remove", "path": "/d"},
+                {"op": "test", "path": "/e", "value": null}]"#,
+            r#"[{"op": "move", "from": "/a/c/0", "path": "/a/c/-"},
+                {"op": "move", "from": "/a/b", "path": "/e"},
+                {"op": "copy", "from": "/a", "path": "/d"},
+                {"op": "copy", "from": "/d", "path": "/f"}]"#,
+            r#"[{"op": "replace", "path": "/a/c", "value": []},
+                {"op": "move", "from": "/d", "path": "/d"},
+                {"op": "move", "from": "/a", "path": ""}]"#,
+            r#"[{"op": "add", "path": "", "value": [1]},
+                {"op": "add", "path": "/0", "value": 0}]"#,
+        ];
+        for patch in patches {
+            let patch = parse_str(patch)?;
+            let mut document = original.clone();
+            document.apply_patch(&patch)?;
+            assert_ne!(document, original);
+            document.apply_patch(&invert(&patch, &original)?)?;
+            assert_eq!(document, original);
+        }
+        let failing = parse_str(r#"[{"op": "remove", "path": "/nope"}]"#)?;
+        assert!(invert(&failing, &original).is_err());
+
+        // Composing any two patches does what applying both does.
+        for first in patches {
+            let first = parse_str(first)?;
+            let mut middle = original.clone();
+            middle.apply_patch(&first)?;
+            let second = crate::diff::diff(&middle, &parse_str(patches[0])?);
+            let mut expected = middle.clone();
+            expected.apply_patch(&second)?;
+            let mut document = original.clone();
+            document.apply_patch(&compose(&first, &second)?)?;
+            assert_eq!(document, expected);
+        }
+        let squashed = compose(
+            &parse_str(
+                r#"[{"op": "add", "path": "/l/0", "value": 1},
+                    {"op": "replace", "path": "/l/0", "value": 2}]"#,
+            )?,
+            &parse_str(
+                r#"[{"op": "replace", "path": "/m", "value": 1},
+                    {"op": "remove", "path": "/m"},
+                    {"op": "add", "path": "/l/-", "value": 1},
+                    {"op": "replace", "path": "/l/-", "value": 2}]"#,
+            )?,
+        )?;
+        assert_eq!(
+            squashed,
+            parse_str(
+                r#"[{"op": "add", "path": "/l/0", "value": 2},
+                    {"op": "remove", "path": "/m"},
+                    {"op": "add", "path": "/l/-", "value": 1},
+                    {"op": "replace", "path": "/l/-", "value": 2}]"#,
+            )?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_lines() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::error::{Error, ErrorCode};
+        use crate::parser::{parse_lines, parse_lines_parallel};
+
+        let mut input = String::new();
+        for n in 0..100_000 {
+            match n % 1000 {
+                0 => input.push_str("  \n"),
+                1 => input.push_str("{\"broken\": \n"),
+                _ => input.push_str(&format!(
+                    "{{\"n\": {}, \"s\": [\"{}\"]}}\r\n",
+                    n, n
+                )),
+            }
+        }
+        let sequential: Vec<_> = parse_lines(&input).collect();
+        let parallel: Vec<_> = parse_lines_parallel(&input).collect();
+        assert_eq!(sequential.len(), 99_900);
+        assert_eq!(parallel.len(), sequential.len());
+        for (parallel, sequential) in parallel.iter().zip(&sequential) {
+            match (parallel, sequential) {
+                (Ok(parallel), Ok(sequential)) => {
+                    assert_eq!(parallel, sequential)
+                }
+                (Err(parallel), Err(sequential)) => {
+                    assert_eq!(parallel.to_string(), sequential.to_string());
+                    let parallel = parallel.downcast_ref::<Error>().unwrap();
+                    assert_eq!(
+                        parallel.parse_error().map(|error| error.code()),
+                        Some(ErrorCode::EndOfBuffer)
+                    );
+                }
+                _ => panic!("results differ"),
+            }
+        }
+        assert_eq!(parallel.iter().filter(|line| line.is_err()).count(), 100);
+        assert_eq!(
+            parallel[99_899].as_ref().unwrap()["n"].get_uint(),
+            Some(99_999)
+        );
+        assert!(parse_lines_parallel("").next().is_none());
+        // Dropped midway, the iterator stops its threads.
+        let mut partial = parse_lines_parallel(&input);
+        assert!(partial.nth(50_000).is_some());
+        drop(partial);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_patch() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::error::PatchError;
+
+        let doc = parse_str(r#"{"a": [1, 2], "b": {"c": true}}"#)?;
+        let good = parse_str(
+            r#"[{"op": "test", "path": "/b/c", "value": true},
+                {"op": "remove", "path": "/a/0"},
+                {"op": "replace", "path": "/a/0", "value": 3},
+                {"op": "move", "from": "/b", "path": "/d"}]"#,
+        )?;
+        assert!(doc.check_patch(&good).is_empty());
+
+        let bad = parse_str(
+            r#"[{"op": "remove", "path": "/a/0"},
+                {"op": "remove", "path": "/a/1"},
+                {"op": "frobnicate", "path": "/a"},
+                {"op": "replace", "path": "a", "value": 1},
+                {"op": "test", "path": "/b", "value": {}}]"#,
+        )?;
+        let issues = doc.check_patch(&bad);
+        assert!(matches!(
+            issues.as_slice(),
+            [
+                PatchError::PathNotFound { index: 1, .. },
+                PatchError::MalformedOperation { index: 2, .. },
+                PatchError::InvalidPointer { index: 3, .. },
+                PatchError::TestFailed { index: 4, .. },
+            ]
+        ));
+        assert!(matches!(
+            doc.check_patch(&parse_str("{}")?).as_slice(),
+            [PatchError::InvalidPatch]
+        ));
+        assert_eq!(doc, parse_str(r#"{"a": [1, 2], "b": {"c": true}}"#)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_spill_array() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::spill::SpillArray;
+
+        let values = [
+            parse_str(r#"{"text": "two\nlines", "list": [1, [2, {}]]}"#)?,
+            Container::Decimal(f64::INFINITY),
+            parse_str("123456789012345678901234567890123456789")?,
+            parse_str(r#""é ""#)?,
+            Container::Null,
+        ];
+        let mut array = SpillArray::new(40);
+        let mut expected = Vec::new();
+        for round in 0..50 {
+            for value in &values {
+                array.push(value.clone())?;
+                expected.push(value.clone());
+            }
+            if round % 10 == 0 {
+                let read: Vec<_> = array.iter()?.collect::<Result<_, _>>()?;
+                assert_eq!(read, expected);
+            }
+        }
+        assert_eq!(array.len(), 250);
+        assert!(array.spilled() > 0 && array.spilled() < 250);
+        assert_eq!(array.into_container()?, Container::Array(expected));
+
+        let mut small = SpillArray::new(1 << 20);
+        small.push(Container::from(true))?;
+        assert_eq!(small.spilled(), 0);
+        assert_eq!(small.into_container()?, parse_str("[true]")?);
+        assert!(SpillArray::new(0).is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pipeline() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::parser::parse_lines;
+        use crate::pipeline::{run, Order};
+
+        let mut input = String::new();
+        for n in 0..20_000u64 {
+            match n % 500 {
+                7 => input.push_str("[1,\n"),
+                8 => input.push('\n'),
+                _ => input.push_str(&format!("{{\"n\": {}}}\n", n)),
+            }
+        }
+        let double = |mut doc: Container| {
+            let n = doc["n"].get_uint()?;
+            if n % 3 == 0 {
+                return None;
+            }
+            doc.insert_str("n", (n * 2).into());
+            Some(doc)
+        };
+        let options = ParserOptions::default();
+
+        let mut ordered = Vec::new();
+        let summary = run(
+            input.as_bytes(),
+            &options,
+            double,
+            &mut ordered,
+            8,
+            Order::Input,
+        )?;
+        assert_eq!(summary.read, 19_960);
+        assert_eq!(summary.errors.len(), 40);
+        assert_eq!(summary.errors[0].0, 8);
+        assert_eq!(summary.written + summary.dropped + 40, summary.read);
+        let expected: String = parse_lines(&input)
+            .filter_map(|doc| double(doc.ok()?))
+            .map(|doc| format!("{}\n", doc))
+            .collect();
+        assert_eq!(String::from_utf8(ordered)?, expected);
+
+        let mut unordered = Vec::new();
+        let summary = run(
+            input.as_bytes(),
+            &options,
+            double,
+            &mut unordered,
+            3,
+            Order::Completion,
+        )?;
+        let mut lines: Vec<_> =
+            std::str::from_utf8(&unordered)?.lines().collect();
+        let mut expected: Vec<_> = expected.lines().collect();
+        lines.sort_unstable();
+        expected.sort_unstable();
+        assert_eq!(lines, expected);
+        assert!(summary.errors.windows(2).all(|pair| pair[0].0 < pair[1].0));
+
+        // A failing sink stops the run instead of blocking the reader.
+        struct Full;
+        impl std::io::Write for Full {
+            fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::ErrorKind::WriteZero.into())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+        assert!(run(
+            input.as_bytes(),
+            &options,
+            Some,
+            &mut Full,
+            2,
+            Order::Input
+        )
+        .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_file() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::error::{Error, FileError, ParseError};
+        use crate::explain::explain_error;
+        use crate::parser::parse_file;
+
+        let dir = std::env::temp_dir();
+        let path = dir.join(format!("parse_file_{}.json", std::process::id()));
+        std::fs::write(&path, b"\xFF\xFE[\x001\x00]\x00")?;
+        assert_eq!(parse_file(&path)?, parse_str("[1]")?);
+
+        let text = "{\n  \"a\": [1 2]\n}";
+        std::fs::write(&path, text)?;
+        let error = parse_file(path.to_str().unwrap()).unwrap_err();
+        let error = error.downcast_ref::<Error>().unwrap();
+        assert_eq!(error.json_path(), Some("$.a[1]"));
+        let Error::File(FileError::Parse {
+            path: name,
+            error: parse,
+        }) = error
+        else {
+            panic!("not a parse error: {:?}", error);
+        };
+        assert_eq!(name, &path.display().to_string());
+        assert!(matches!(
+            parse,
+            ParseError::UnexpectedToken('2', span) if (span.line, span.col) == (2, 11)
+        ));
+        let explained = explain_error(text, error);
+        assert!(explained.starts_with(&format!("In {:?}:\nFound `2`", name)));
+        std::fs::remove_file(&path)?;
+
+        let error = parse_file(&path).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<Error>(),
+            Some(Error::File(FileError::Read { .. }))
+        ));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_parse_async() {
+        use crate::asynchronous::{parse_async, parse_lines_async, PollRead};
+        use core::pin::Pin;
+        use core::task::{Context, Poll};
+
+        /// Gives out a few bytes at a time, pending before each read.
+        struct Trickle<'a> {
+            bytes: &'a [u8],
+            ready: bool,
+        }
+
+        impl PollRead for Trickle<'_> {
+            fn poll_read(
+                mut self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                buf: &mut [u8],
+            ) -> Poll<std::io::Result<usize>> {
+                if !core::mem::replace(&mut self.ready, false) {
+                    self.ready = true;
+                    cx.waker().wake_by_ref();
+                    return Poll::Pending;
+                }
+                let len = buf.len().min(self.bytes.len()).min(3);
+                buf[..len].copy_from_slice(&self.bytes[..len]);
+                self.bytes = &self.bytes[len..];
+                Poll::Ready(Ok(len))
+            }
+        }
+
+        fn block_on<F: core::future::Future>(future: F) -> F::Output {
+            let mut future = core::pin::pin!(future);
+            let mut cx = Context::from_waker(core::task::Waker::noop());
+            let mut polls = 0;
+            loop {
+                polls += 1;
+                assert!(polls < 10_000);
+                if let Poll::Ready(value) = future.as_mut().poll(&mut cx) {
+                    return value;
+                }
+            }
+        }
+
+        let trickle = |bytes| Trickle {
+            bytes,
+            ready: false,
+        };
+        // Chunks of 3 bytes split the 2 bytes of "é".
+        let text = r#"{"a": [1, "é"], "b": {}}"#;
+        let doc = block_on(parse_async(trickle(text.as_bytes()))).unwrap();
+        assert_eq!(doc, parse_str(text).unwrap());
+        assert!(block_on(parse_async(trickle(b"[1, 2"))).is_err());
+        assert!(block_on(parse_async(trickle(b""))).is_err());
+
+        let input = b"{\"n\": 1}\r\n\n  \n[2, 3]\n{oops}\n\"last\"";
+        let mut lines = parse_lines_async(trickle(input));
+        let mut seen = Vec::new();
+        while let Some(doc) = block_on(lines.next()) {
+            seen.push(doc.ok());
+        }
+        assert_eq!(
+            seen,
+            [
+                Some(parse_str(r#"{"n": 1}"#).unwrap()),
+                Some(parse_str("[2, 3]").unwrap()),
+                None,
+                Some(Container::from("last")),
+            ]
+        );
+        assert!(block_on(lines.next()).is_none());
+    }
+
+    #[cfg(feature = "fixtures")]
+    #[test]
+    fn test_fixtures() {
+        use crate::fixtures;
+
+        let all = fixtures::load_all();
+        for (name, doc) in &all {
+            let again = parse_str(&doc.to_string()).unwrap();
+            assert_eq!(&again, doc, "{} does not round trip", name);
+        }
+        let numbers = fixtures::get("numbers").unwrap().load();
+        assert_eq!(numbers["u64_max"], Container::Unsigned(u64::MAX));
+        assert_eq!(numbers["u128_max"], Container::Unsigned128(u128::MAX));
+        assert!(matches!(numbers["beyond_u128"], Container::BigNumber(_)));
+        let unicode = fixtures::get("unicode").unwrap().load();
+        assert_eq!(unicode["escaped_emoji"].get_string().unwrap(), "🚀 👍🏽");
+        assert_eq!(unicode["keys"]["🔑"].get_uint(), Some(3));
+        let escapes = fixtures::get("escapes").unwrap().load();
+        assert_eq!(escapes["slashes"].get_string().unwrap(), "a/b/c");
+        assert!(escapes["key \"with\" escapes\n"].get_bool().unwrap());
+
+        let names: Vec<_> = all.iter().map(|(name, _)| *name).collect();
+        let deep =
+            &all[names.iter().position(|n| *n == "deep_nesting").unwrap()];
+        let mut node = &deep.1;
+        let mut depth = 0;
+        while let Container::Array(array) = node {
+            node = &array[0];
+            depth += 1;
+        }
+        assert_eq!(depth, 500);
+        assert!(parse_str(&fixtures::deep_nesting(501)).is_err());
+        assert_eq!(fixtures::big_array(0), "[]");
+        assert_eq!(fixtures::big_array(50), fixtures::big_array(50));
+    }
+
+    #[test]
+    fn test_incremental_parser() {
+        use crate::incremental::{Feed, IncrementalParser};
+
+        let input = "\u{feff}{\"a\": \"]}\\\"[\", \"b\": [1, {\"c\": null}]}\n\
+            [true,false]\"x\\\\\"-1.5e3 null{}  \"é\"[[]] 42";
+        let expected: Vec<_> = parse_documents(&input[3..])
+            .collect::<Result<_, _>>()
+            .unwrap();
+        for size in [1, 2, 7, input.len()] {
+            let mut parser = IncrementalParser::new();
+            let mut values = Vec::new();
+            for piece in input.as_bytes().chunks(size) {
+                if let Feed::Values(done) = parser.feed(piece).unwrap() {
+                    values.extend(done);
+                }
+            }
+            assert_eq!(values.len(), expected.len() - 1);
+            assert_eq!(parser.buffered(), 2);
+            if let Feed::Values(done) = parser.finish().unwrap() {
+                values.extend(done);
+            }
+            assert_eq!(values, expected, "pieces of {} bytes", size);
+            assert_eq!(parser.buffered(), 0);
+        }
+
+        // A failing value is raised after the values before it, then
+        // left out.
+        let mut parser = IncrementalParser::new();
+        let Feed::Values(values) = parser.feed(b"[1] [2,] {\"k\"").unwrap()
+        else {
+            panic!("[1] is complete");
+        };
+        assert_eq!(values, [parse_str("[1]").unwrap()]);
+        assert!(parser.feed(b": 3}").is_err());
+        let Feed::Values(values) = parser.feed(b"").unwrap() else {
+            panic!("the object is complete");
+        };
+        assert_eq!(values[0]["k"].get_uint(), Some(3));
+        assert_eq!(parser.feed(b" [\"open").unwrap(), Feed::NeedMoreData);
+        assert!(parser.finish().is_err());
+        assert!(parser.feed(b"}").is_err());
+        let seven = parser.feed(b"7 ").unwrap();
+        assert_eq!(seven, Feed::Values(vec![Container::Unsigned(7)]));
+        assert_eq!(parser.finish().unwrap(), Feed::NeedMoreData);
+    }
+
+    #[test]
+    fn test_parser_reset() {
+        use crate::parser::Parser;
+
+        let inputs = [
+            r#"{"a": "\"quoted\" é", "b": [1, [2, {"c": "x\\y"}]]}"#,
+            r#"[1, [2, [3"#,
+            "\u{feff}\"plain\"",
+            r#"{"key\nline": "🚀"}"#,
+            "[[[[[]]]]] 1",
+            r#""tab\tend""#,
+        ];
+        let mut parser = Parser::new("");
+        assert!(parser.parse_str().is_err());
+        for input in inputs {
+            parser.reset(input);
+            let reused = parser.parse_str();
+            match parse_str(input) {
+                Ok(expected) => assert_eq!(reused.unwrap(), expected),
+                Err(expected) => {
+                    assert_eq!(
+                        reused.unwrap_err().to_string(),
+                        expected.to_string()
+                    )
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "arena")]
+    #[test]
+    fn test_parse_in_arena() {
+        use crate::arena::{parse_in, ArenaValue, Bump};
+
+        let long = "x".repeat(10_000);
+        let inputs = [
+            r#"{"a": [1, -2, 2.5, true, null], "b": {"c": "é\n\ud83d\ude80"}}"#
+                .to_owned(),
+            format!(r#"[18446744073709551616, -1e400, 0, "{}"]"#, long),
+            format!("[{}]", vec!["[[{\"k\": []}]]"; 500].join(",")),
+            "  \"plain\"  ".to_owned(),
+        ];
+        let mut bump = Bump::with_capacity(64);
+        for input in &inputs {
+            let doc = parse_in(input, &bump).unwrap();
+            assert_eq!(doc.to_container(), parse_str(input).unwrap());
+            bump.reset();
+        }
+        assert!(bump.allocated() >= 10_000);
+
+        let doc = parse_in(r#"{"k": 1, "k": [2, 3]}"#, &bump).unwrap();
+        assert_eq!(doc.len(), 2);
+        assert_eq!(doc.get("k"), &ArenaValue::Unsigned(1));
+        assert_eq!(doc.get("missing"), &ArenaValue::Null);
+        assert_eq!(doc.to_container()["k"][1].get_uint(), Some(3));
+        for input in ["", "[1,]", "{\"a\" 1}", "[1] 2", "[\"\\x\"]", "[[]"] {
+            assert!(parse_in(input, &bump).is_err(), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_shared_keys() {
+        use crate::parser::Parser;
+
+        let keys = |doc: &Container| -> Vec<*const u8> {
+            let Container::Object(map) = doc else {
+                panic!("an object");
+            };
+            map.keys().map(str::as_ptr).collect()
+        };
+        let doc = parse_str(
+            r#"[{"id": 1, "name": "a"}, {"name": "b", "id": 2},
+                {"name": "c", "other": {"id": 3}}]"#,
+        )
+        .unwrap();
+        let (first, second, third) =
+            (keys(&doc[0]), keys(&doc[1]), keys(&doc[2]));
+        assert_eq!((first[0], first[1]), (second[1], second[0]));
+        assert_eq!(third[0], first[1]);
+        assert_eq!(keys(&doc[2]["other"])[0], first[0]);
+        assert_eq!(doc[2]["name"].get_string().unwrap(), "c");
+
+        // A parser shares keys across the documents it reads.
+        let (one, two) = (r#"{"seq": 1}"#, r#"{"seq": 2}"#);
+        let mut parser = Parser::new(one);
+        let one = parser.parse_str().unwrap();
+        parser.reset(two);
+        let two = parser.parse_str().unwrap();
+        assert_eq!(keys(&one), keys(&two));
+        assert_ne!(keys(&parse_str(r#"{"seq": 1}"#).unwrap()), keys(&one));
+
+        // Copies share the keys of the original.
+        assert_eq!(keys(&one.clone()), keys(&one));
+    }
+
+    #[test]
+    fn test_round_trip_key_order() {
+        use crate::container::DumpOptions;
+
+        let text = r#"{"zeta": 1, "alpha": {"y": [], "b": null}, "mid": "x"}"#;
+        let mut config = parse_str(text).unwrap();
+        config["alpha"].insert_str("a", Container::from(true));
+        config["zeta"] = Container::from(2u64);
+        config.insert_str("first", Container::Null);
+        assert_eq!(
+            config.dump(&DumpOptions::new()).unwrap(),
+            r#"{"zeta":2,"alpha":{"y":[],"b":null,"a":true},"mid":"x","first":null}"#
+        );
+    }
+
+    #[test]
+    fn test_map_small_and_indexed() {
+        use crate::generate::{Rng, SplitMix64};
+        use crate::map::Map;
+
+        // Checked against a list kept in order, across the size where
+        // maps start using an index.
+        let mut rng = SplitMix64::new(62);
+        let mut map = Map::new();
+        let mut model: Vec<(String, u64)> = Vec::new();
+        for step in 0..4000 {
+            let key = format!("k{}", rng.below(24));
+            match rng.below(3) {
+                0 => {
+                    let removed = map.remove(key.as_str());
+                    let at = model.iter().position(|(other, _)| *other == key);
+                    let expected = at.map(|at| model.remove(at).1);
+                    assert_eq!(removed.and_then(|v| v.get_uint()), expected);
+                }
+                _ => {
+                    map.insert(key.clone(), Container::from(step as u64));
+                    match model.iter_mut().find(|(other, _)| *other == key) {
+                        Some(entry) => entry.1 = step as u64,
+                        None => model.push((key, step as u64)),
+                    }
+                }
+            }
+            if step % 500 == 0 {
+                map.sort_keys();
+                model.sort();
+            }
+            let members: Vec<_> = map
+                .iter()
+                .map(|(key, value)| (key.to_owned(), value.get_uint().unwrap()))
+                .collect();
+            assert_eq!(members, model);
+            for idx in 0..24 {
+                let key = format!("k{}", idx);
+                let expected = model.iter().find(|(other, _)| *other == key);
+                assert_eq!(
+                    map.get(&key).and_then(Container::get_uint),
+                    expected.map(|(_, value)| *value)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_inline_strings() {
+        use crate::small_string::{SmallString, INLINE_LEN};
+
+        let short = "é".repeat(INLINE_LEN / 2);
+        let long = format!("{}x", short);
+        let doc = parse_str(&format!(
+            r#"["{}", "{}", "tab\tand é", ""]"#,
+            short, long
+        ))
+        .unwrap();
+        let strings: Vec<&SmallString> = (0..4)
+            .map(|idx| match &doc[idx] {
+                Container::String(text) => text,
+                _ => panic!("a string"),
+            })
+            .collect();
+        assert!(strings[0].is_inline() && *strings[0] == short);
+        assert!(!strings[1].is_inline() && *strings[1] == long);
+        assert!(strings[2].is_inline() && *strings[2] == "tab\tand é");
+        assert!(strings[3].is_inline() && strings[3].is_empty());
+        assert_eq!(parse_str(&doc.to_string()).unwrap(), doc);
+        assert_eq!(doc[1].get_string(), Some(long.clone()));
+        assert_eq!(
+            Container::from(long.as_str()),
+            Container::from(long.clone())
+        );
+        let mut sorted = [SmallString::from("b"), "a".into(), long.into()];
+        sorted.sort();
+        assert_eq!(sorted[0], "a");
+    }
+
+    #[test]
+    fn test_arc_container() {
+        use crate::arc_container::ArcContainer;
+
+        let text = r#"{"a": [1, -2, 3.5, "x", null, true, 18446744073709551616],
+            "b": {"c": {"d": "deep"}}, "k0": 0, "k1": 1, "k2": 2, "k3": 3,
+            "k4": 4, "k5": 5, "k6": 6, "k7": 7}"#;
+        let doc = parse_str(text).unwrap();
+        let shared = ArcContainer::from(&doc);
+        assert_eq!(shared.to_container(), doc);
+        assert_eq!(ArcContainer::from(doc.clone()), shared);
+        assert_eq!(shared.len(), 10);
+        assert_eq!(shared.get("k7").get_uint(), Some(7));
+        assert!(shared.get("missing").is_null());
+        assert_eq!(shared.pointer("/a/3").unwrap().get_str(), Some("x"));
+        assert_eq!(shared.pointer("/b/c/d").unwrap().get_str(), Some("deep"));
+        assert!(shared.pointer("/a/9").is_none());
+        assert!(shared.pointer("a").is_none());
+
+        // Clones share their arrays, objects and strings.
+        let ArcContainer::Object(map) = &shared else {
+            panic!("an object");
+        };
+        let copy = shared.clone();
+        let ArcContainer::Object(copied) = &copy else {
+            panic!("an object");
+        };
+        assert!(std::sync::Arc::ptr_eq(map, copied));
+        let threads: Vec<_> = (0..4)
+            .map(|idx| {
+                let subtree = shared.get("a").clone();
+                std::thread::spawn(move || {
+                    subtree.get_index(idx).to_container()
+                })
+            })
+            .collect();
+        let elements: Vec<_> =
+            threads.into_iter().map(|t| t.join().unwrap()).collect();
+        assert_eq!(
+            Container::Array(elements),
+            parse_str(r#"[1, -2, 3.5, "x"]"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_shared_document() {
+        use crate::arc_container::SharedDocument;
+
+        let doc =
+            SharedDocument::new(parse_str(r#"{"a": 0, "b": 0}"#).unwrap());
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for _ in 0..25 {
+                        doc.update(|doc| {
+                            let next = doc["a"].get_uint().unwrap() + 1;
+                            doc["a"] = Container::from(next);
+                            doc["b"] = Container::from(next);
+                        });
+                    }
+                });
+            }
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for _ in 0..200 {
+                        // Changes are seen whole.
+                        let snapshot = doc.snapshot();
+                        assert_eq!(snapshot.get("a"), snapshot.get("b"));
+                    }
+                });
+            }
+        });
+        assert_eq!(doc.snapshot().get("b").get_uint(), Some(100));
+        doc.replace(Container::Null);
+        assert!(doc.snapshot().is_null());
+    }
+
+    #[test]
+    fn test_parse_spanned() {
+        use crate::spanned::parse_spanned;
+
+        let input = "[\n  {\"id\": 1, \"tags\": [\"a\", \"b\"]},\n  true\n]";
+        let doc = parse_spanned(input).unwrap();
+        assert_eq!(doc.root().value, &parse_str(input).unwrap());
+        assert_eq!(doc.root().byte_range, 0..input.len());
+
+        let tag = doc.get("/0/tags/1").unwrap();
+        assert_eq!(tag.value, &Container::String("b".into()));
+        assert_eq!((tag.line, tag.col), (2, 27));
+        assert_eq!(&input[tag.byte_range], "\"b\"");
+        let object = doc.get("/0").unwrap();
+        assert_eq!(
+            &input[object.byte_range],
+            r#"{"id": 1, "tags": ["a", "b"]}"#
+        );
+        assert_eq!(doc.get("/1").unwrap().line, 3);
+        let key = doc.key("/0/tags").unwrap();
+        assert_eq!((key.value, key.col), ("tags", 13));
+        assert!(doc.get("/2").is_none());
+        assert!(doc.key("/1").is_none());
+
+        // The overwritten value of a key written twice has no span left.
+        let doc = parse_spanned(r#"{"a": {"x": 1}, "a": 2}"#).unwrap();
+        assert!(doc.get("/a/x").is_none());
+        assert_eq!(doc.get("/a").unwrap().byte_range, 21..22);
+        assert_eq!(doc.key("/a").unwrap().byte_range, 16..19);
+        assert_eq!(parse_spanned("7").unwrap().root().byte_range, 0..1);
+        assert!(parse_spanned("[1,").is_err());
+    }
+
+    #[test]
+    fn test_error_spans() {
+        use crate::dom::Dom;
+        use crate::error::{Error, ParseError};
+        use crate::parser::validate;
+
+        fn span(
+            result: Result<(), Box<dyn core::error::Error>>,
+        ) -> Option<(usize, usize)> {
+            let error = result.unwrap_err();
+            let Some(Error::Parsing(error)) = error.downcast_ref::<Error>()
+            else {
+                panic!("not a parse error: {:?}", error);
+            };
+            error.span().map(|span| (span.start, span.end))
+        }
+
+        for (input, expected) in [
+            ("[1, 2}", Some((5, 6))),
+            ("[1 2]", Some((3, 4))),
+            ("[01]", Some((2, 3))),
+            (r#"{"a": "\uD800x"}"#, Some((7, 13))),
+            (r#"["\q"]"#, Some((3, 4))),
+            ("[1,", Some((3, 3))),
+            ("[\"ab", Some((4, 4))),
+            ("", Some((0, 0))),
+        ] {
+            let parsed = span(parse_str(input).map(|_| ()));
+            assert_eq!(parsed, expected, "{input}");
+            assert_eq!(span(validate(input)), expected, "{input}");
+            assert_eq!(span(Dom::default().parse(input)), expected, "{input}");
+        }
+
+        let error = parse_str("{\"a\":\n  }").unwrap_err();
+        let error = error.downcast_ref::<Error>().unwrap();
+        assert!(matches!(
+            error,
+            Error::Parsing(ParseError::InvalidKeyValueFormat { span, .. })
+                if (span.start, span.line, span.col) == (8, 2, 3)
+        ));
+
+        // The end of the input is past its last character.
+        for input in ["[1,\n  ", "{\"a\":\n"] {
+            let error = parse_str(input).unwrap_err();
+            assert!(matches!(
+                error.downcast_ref::<Error>(),
+                Some(Error::Parsing(ParseError::EndOfBuffer(span)))
+                    if (span.start, span.end) == (input.len(), input.len())
+                        && (span.line, span.col)
+                            == (2, input.len() - input.find('\n').unwrap())
+            ));
+        }
+    }
+
+    #[test]
+    fn test_render_error() {
+        use crate::error::Error;
+        use crate::explain::render_error;
+
+        let render = |input: &str| {
+            let error = parse_str(input).unwrap_err();
+            render_error(input, error.downcast_ref::<Error>().unwrap())
+        };
+
+        let input = format!("[{}\"\\uD800\"]", "1,\n".repeat(9));
+        let rendered = render(&input);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines[1], "  --> line 10, column 2");
+        assert_eq!(lines[3..6], [" 8 | 1,", " 9 | 1,", "10 | \"\\uD800\"]"]);
+        assert!(lines[6].starts_with("   |  ^^^^^^ The escape `\\uD800`"));
+
+        let rendered = render("{\"a\": [1,\n\t2,\n");
+        assert!(rendered
+            .ends_with("  | \t  ^ The input ends too early\n  = at $.a[2]\n"));
+        assert_eq!(
+            render("[1, 2}").lines().nth(4),
+            Some("  |      ^ Found `}` closing an array, which ends with `]`")
+        );
+
+        let dir = std::env::temp_dir().join("json_parser_render_error.json");
+        std::fs::write(&dir, "[1 2]").unwrap();
+        let error =
+            crate::parser::parse_file(dir.to_str().unwrap()).unwrap_err();
+        let rendered =
+            render_error("[1 2]", error.downcast_ref::<Error>().unwrap());
+        assert!(rendered.contains(&format!(" --> {}:1:4\n", dir.display())));
+        std::fs::remove_file(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_literal_suggestions() {
+        use crate::error::{Error, ParseError};
+        use crate::parser::validate;
+
+        let suggest = |error: Box<dyn core::error::Error>| match error
+            .downcast_ref::<Error>()
+        {
+            Some(Error::Parsing(ParseError::UnknownLiteral {
+                found,
+                suggestion,
+                span,
+            })) => (found.clone(), suggestion.clone(), span.start),
+            other => panic!("unexpected error {:?}", other),
+        };
+        for (input, found, suggestion) in [
+            ("[tru]", "tru", Some("true")),
+            ("[flase]", "flase", Some("false")),
+            ("[nil]", "nil", Some("null")),
+            ("[True]", "True", Some("true")),
+            ("[yes]", "yes", None),
+            ("[a]", "a", None),
+        ] {
+            let expected = (found.to_owned(), suggestion.map(str::to_owned), 1);
+            assert_eq!(suggest(parse_str(input).unwrap_err()), expected);
+            assert_eq!(suggest(validate(input).unwrap_err()), expected);
+        }
+
+        let error = parse_str("{\"on\": nulll}").unwrap_err();
+        assert_eq!(
+            error.to_string().lines().nth(1),
+            Some(
+                "Unknown literal nulll at line 1, col: 8, did you mean `null`?"
+            )
+        );
+        let options = ParserOptions {
+            non_finite_numbers: true,
+            ..ParserOptions::default()
+        };
+        let error = parse_with_options("[Infinty]", &options).unwrap_err();
+        assert!(error
+            .to_string()
+            .ends_with("did you mean `Infinity`?\nAt $[0]"));
+    }
+
+    #[test]
+    fn test_parse_str_repair() {
+        use crate::repair::{parse_str_repair, FixKind};
+
+        let repair = |input: &str| {
+            let (value, fixes) = parse_str_repair(input).unwrap();
+            let kinds: Vec<FixKind> =
+                fixes.iter().map(|fix| fix.kind).collect();
+            (value, kinds)
+        };
+
+        // Valid JSON goes through untouched.
+        let input = r#"{"a": [1, "x'y", {"b": null}]}"#;
+        assert_eq!(repair(input), (parse_str(input).unwrap(), Vec::new()));
+
+        let (value, kinds) = repair(r#"['it\'s "ok"', {a: 1 b: 2,}]"#);
+        assert_eq!(
+            value,
+            parse_str(r#"["it's \"ok\"", {"a": 1, "b": 2}]"#).unwrap()
+        );
+        assert_eq!(
+            kinds,
+            [
+                FixKind::SingleQuotes,
+                FixKind::UnquotedKey,
+                FixKind::MissingComma,
+                FixKind::UnquotedKey,
+                FixKind::TrailingComma,
+            ]
+        );
+
+        for (input, expected) in [
+            ("[1, 2,", "[1, 2]"),
+            (r#"{"a": {"b": "#, r#"{"a": {"b": null}}"#),
+            (r#"{"a": 1, "b"#, r#"{"a": 1, "b": null}"#),
+            (r#"["abc"#, r#"["abc"]"#),
+        ] {
+            let (value, kinds) = repair(input);
+            assert_eq!(value, parse_str(expected).unwrap(), "{input}");
+            assert_eq!(kinds.last(), Some(&FixKind::Truncated), "{input}");
+            assert_eq!(
+                kinds
+                    .iter()
+                    .filter(|kind| **kind == FixKind::Truncated)
+                    .count(),
+                1
+            );
+        }
+
+        let (_, fixes) = parse_str_repair("[1,\n  2,\n]").unwrap();
+        assert_eq!(fixes[0].span.start, 7);
+        assert_eq!((fixes[0].span.line, fixes[0].span.col), (2, 4));
+        assert!(parse_str_repair("[1, ]]").is_err());
+        assert!(parse_str_repair("[tru]").is_err());
+    }
+
+    #[test]
+    fn test_strictness_presets() {
+        use crate::parser::{parse_with_strictness, Strictness};
+
+        let presets = [
+            Strictness::Strict,
+            Strictness::Standard,
+            Strictness::Lenient,
+            Strictness::Json5,
+        ];
+        // Which presets accept each input, in the order above.
+        for (input, accepted) in [
+            (r#"{"a": [1, 2.5, "x"]}"#, [true, true, true, true]),
+            ("[\"tab\there\"]", [false, true, true, true]),
+            ("[1, 2,]", [false, false, true, true]),
+            ("['single']", [false, false, true, true]),
+            ("[1, // one\n 2 /* two */]", [false, false, true, true]),
+            ("[NaN, -Infinity]", [false, false, true, true]),
+            ("{bare: 1}", [false, false, false, true]),
+            ("[0xFF]", [false, false, false, true]),
+            ("[1 /* open", [false, false, false, false]),
+        ] {
+            for (strictness, accepted) in presets.into_iter().zip(accepted) {
+                assert_eq!(
+                    parse_with_strictness(input, strictness).is_ok(),
+                    accepted,
+                    "{input} with {strictness:?}"
+                );
+            }
+        }
+
+        let doc = parse_with_strictness(
+            "{name: 'ada', 'tags': [Infinity,], n: 0x10}",
+            Strictness::Json5,
+        )
+        .unwrap();
+        assert_eq!(doc["name"].get_string().unwrap(), "ada");
+        assert_eq!(doc["tags"][0], Container::Decimal(f64::INFINITY));
+        assert_eq!(doc["n"].get_uint(), Some(16));
+        assert_eq!(Strictness::default(), Strictness::Standard);
+        assert!(Strictness::Strict.options().strict);
+    }
+
+    #[test]
+    fn test_error_codes() {
+        use crate::error::{Error, ErrorCode};
+        use crate::parser::parse_bytes;
+
+        let code = |error: Box<dyn core::error::Error>| {
+            let Some(Error::Parsing(error)) = error.downcast_ref::<Error>()
+            else {
+                panic!("not a parse error: {:?}", error);
+            };
+            let category =
+                (error.is_syntax(), error.is_limit(), error.is_encoding());
+            (error.code(), category)
+        };
+        let syntax = (true, false, false);
+        for (input, expected, category) in [
+            ("", ErrorCode::EndOfBuffer, syntax),
+            ("[1}", ErrorCode::BracketMismatch, syntax),
+            ("{\"a\":}", ErrorCode::MissingValue, syntax),
+            ("[01]", ErrorCode::InvalidNumber, syntax),
+            (r#"["\x"]"#, ErrorCode::InvalidEscape, syntax),
+            (r#"["\u12"]"#, ErrorCode::InvalidUnicodeEscape, syntax),
+            (r#"["\uDC00"]"#, ErrorCode::LoneSurrogate, syntax),
+            ("[nul]", ErrorCode::UnknownLiteral, syntax),
+            (
+                &"[".repeat(600),
+                ErrorCode::NestedDepthExceeded,
+                (false, true, false),
+            ),
+        ] {
+            assert_eq!(
+                code(parse_str(input).unwrap_err()),
+                (expected, category)
+            );
+        }
+        let error = parse_bytes(b"[\"\xff\"]").unwrap_err();
+        assert_eq!(code(error).1, (false, false, true));
+
+        assert_eq!(ErrorCode::UnexpectedToken.code(), "E0001");
+        assert_eq!(ErrorCode::UnknownLiteral.number(), 13);
+        assert_eq!(
+            ErrorCode::NumberOutOfRange.to_string(),
+            "E0012 NumberOutOfRange"
+        );
+    }
+
+    #[test]
+    fn test_error_json_path() {
+        use crate::error::{Error, ErrorCode, FileError};
+
+        let path = |input: &str| {
+            let error = parse_str(input).unwrap_err();
+            let Some(Error::Parsing(error)) = error.downcast_ref::<Error>()
+            else {
+                panic!("not a parse error: {:?}", error);
+            };
+            error.json_path().map(str::to_owned)
+        };
+        let users = r#"{"users": [{}, {}, {}, {"address": {"zip": 01}}]}"#;
+        assert_eq!(path(users).as_deref(), Some("$.users[3].address.zip"));
+        assert_eq!(path("[[1], [2 3]]").as_deref(), Some("$[1][1]"));
+        assert_eq!(path(r#"{"a b": [tru]}"#).as_deref(), Some("$[\"a b\"][0]"));
+        assert_eq!(
+            path(r#"[{"a": 1, "b": [1,]}]"#).as_deref(),
+            Some("$[0].b[1]")
+        );
+        assert_eq!(path("tru"), None);
+        assert_eq!(path("[1] 2"), None);
+        // Errors around keys are in the member of the key, if read.
+        assert_eq!(path(r#"{"a": 1, "b" 2}"#).as_deref(), Some("$.b"));
+        assert_eq!(path(r#"{"b" 2}"#).as_deref(), Some("$.b"));
+        assert_eq!(path(r#"{"a": {"b": 1}, "c"}"#).as_deref(), Some("$.c"));
+        assert_eq!(path(r#"{"a": 1,}"#).as_deref(), Some("$"));
+        assert_eq!(path(r#"{"a": 1 "b": 2}"#).as_deref(), Some("$"));
+        assert_eq!(path(r#"[{"a": [1], x}]"#).as_deref(), Some("$[0]"));
+
+        let file = std::env::temp_dir().join("json_parser_json_path.json");
+        std::fs::write(&file, users).unwrap();
+        let error =
+            crate::parser::parse_file(file.to_str().unwrap()).unwrap_err();
+        let error = error.downcast_ref::<Error>().unwrap();
+        assert!(matches!(error, Error::File(FileError::Parse { .. })));
+        assert_eq!(error.json_path(), Some("$.users[3].address.zip"));
+        assert_eq!(
+            error.parse_error().map(|error| error.code()),
+            Some(ErrorCode::InvalidNumber)
+        );
+        std::fs::remove_file(&file).unwrap();
+    }
+
+    #[test]
+    fn test_resource_limits() {
+        use crate::error::{Error, ParseError};
+        use crate::parser::parse_with_options;
+
+        let fail = |input: &str, options: &ParserOptions| {
+            let error = parse_with_options(input, options).unwrap_err();
+            let error = error.downcast_ref::<Error>().unwrap();
+            let error = error.parse_error().unwrap().clone();
+            assert!(error.is_limit(), "{input}");
+            error
+        };
+
+        let strings = ParserOptions {
+            max_string_length: Some(3),
+            ..ParserOptions::default()
+        };
+        assert!(parse_with_options(r#"{"abc": "a\tb"}"#, &strings).is_ok());
+        assert!(matches!(
+            fail(r#"["abc", "abcd"]"#, &strings),
+            ParseError::StringTooLong(3, span) if (span.start, span.end) == (8, 14)
+        ));
+        assert!(matches!(
+            fail(r#"{"long": 1}"#, &strings),
+            ParseError::StringTooLong(3, span) if span.col == 2
+        ));
+        assert!(matches!(
+            fail(r#"["abcd"]"#, &strings),
+            ParseError::StringTooLong(..)
+        ));
+
+        let elements = ParserOptions {
+            max_elements: Some(2),
+            ..ParserOptions::default()
+        };
+        assert!(parse_with_options("[[1, 2], {\"a\": [3]}]", &elements).is_ok());
+        assert!(matches!(
+            fail("[[1, 2, 3]]", &elements),
+            ParseError::TooManyElements(2, span) if span.start == 8
+        ));
+        assert!(matches!(
+            fail(r#"{"a": 1, "b": 2, "c": 3}"#, &elements),
+            ParseError::TooManyElements(2, span) if span.start == 22
+        ));
+        let empty = ParserOptions {
+            max_elements: Some(0),
+            ..ParserOptions::default()
+        };
+        assert!(parse_with_options("[{}, []]", &empty).is_err());
+        assert!(parse_with_options("{}", &empty).is_ok());
+
+        let nodes = ParserOptions {
+            max_nodes: Some(5),
+            ..ParserOptions::default()
+        };
+        assert!(parse_with_options("[1, [2, 3]]", &nodes).is_ok());
+        assert!(matches!(
+            fail("[1, [2, 3], 4]", &nodes),
+            ParseError::TooManyNodes(5, span) if span.start == 12
+        ));
+    }
+
+    #[test]
+    fn test_parse_deadline() {
+        use crate::dialect::Json;
+        use crate::error::{Error, ErrorCode, ParseError};
+        use crate::parser::{parse_with_options, Parser};
+        use std::time::{Duration, Instant};
+
+        let input =
+            format!("[{}0]", "[1, \"a\", {\"b\": null}], ".repeat(5000));
+        let options = ParserOptions {
+            deadline: Some(Instant::now() + Duration::from_secs(3600)),
+            ..ParserOptions::default()
+        };
+        assert!(parse_with_options(&input, &options).is_ok());
+
+        let options = ParserOptions {
+            deadline: Some(Instant::now()),
+            ..ParserOptions::default()
+        };
+        let mut parser = Parser::with_options(&input, &Json, &options);
+        for _ in 0..2 {
+            let error = parser.parse_str().unwrap_err();
+            let error = error.downcast_ref::<Error>().unwrap();
+            let error = error.parse_error().unwrap();
+            assert!(
+                matches!(error, ParseError::Timeout(span) if span.start == 0)
+            );
+            assert_eq!(error.code(), ErrorCode::Timeout);
+            assert!(error.is_limit());
+            parser.reset(&input);
+        }
+    }
+
+    #[test]
+    fn test_cancel_token() {
+        use crate::error::{Error, ErrorCode};
+        use crate::parser::{parse_with_options, CancelToken};
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::Arc;
+
+        let flag = Arc::new(AtomicBool::new(false));
+        let options = ParserOptions {
+            cancel: Some(CancelToken::from(flag.clone())),
+            ..ParserOptions::default()
+        };
+        let input = format!("[{}0]", "{\"a\": [1, 2]}, ".repeat(10000));
+        assert!(parse_with_options(&input, &options).is_ok());
+
+        let token = options.cancel.clone().unwrap();
+        std::thread::spawn(move || token.cancel()).join().unwrap();
+        assert!(flag.load(Ordering::Relaxed));
+        let error = parse_with_options(&input, &options).unwrap_err();
+        let error = error.downcast_ref::<Error>().unwrap();
+        let error = error.parse_error().unwrap();
+        assert_eq!(error.code(), ErrorCode::Cancelled);
+        assert!(!error.is_syntax() && !error.is_limit());
+        assert_eq!(error.offset(), Some(0));
+    }
+
+    #[test]
+    fn test_memory_usage() {
+        use crate::map::Map;
+
+        let mut array = Vec::with_capacity(4);
+        array.push(Container::from(1));
+        array.push(Container::from("x".repeat(40)));
+        let value = Container::Array(array);
+        let elements = 4 * core::mem::size_of::<Container>();
+        assert!(value.memory_usage() >= elements + 40);
+        assert!(value.memory_usage() < elements + 80);
+        assert_eq!(Container::Array(Vec::new()).memory_usage(), 0);
+        assert_eq!(
+            Container::RawNumber("1.50".to_owned()).memory_usage(),
+            "1.50".to_owned().capacity()
+        );
+
+        // Keys shared by the parser are counted once.
+        let parsed = parse_str(r#"[{"name": 1}, {"name": 2}]"#).unwrap();
+        let mut built = Vec::new();
+        for idx in 1..=2 {
+            let mut map = Map::with_capacity(1);
+            map.insert("name".to_owned(), Container::from(idx));
+            built.push(Container::Object(map));
+        }
+        built.shrink_to_fit();
+        let built = Container::Array(built);
+        assert_eq!(parsed, built);
+        let mut shared = parsed.clone();
+        if let Container::Array(array) = &mut shared {
+            array.shrink_to_fit();
+        }
+        assert!(shared.memory_usage() < built.memory_usage());
+
+        let mut large = Map::new();
+        for idx in 0..100 {
+            large.insert(format!("{idx:03}"), Container::Null);
+        }
+        let large = Container::Object(large);
+        assert!(large.memory_usage() > 100 * core::mem::size_of::<Container>());
+    }
+
+    #[test]
+    fn test_option_accessors() {
+        let doc = parse_str(r#"{"a": null, "b": [null, 1], "c": {"d": null}}"#)
+            .unwrap();
+        assert_eq!(doc.get("a"), Some(&Container::Null));
+        assert_eq!(doc.get("z"), None);
+        assert_eq!(doc["b"].get_index(0), Some(&Container::Null));
+        assert_eq!(doc["b"].get_index(2), None);
+        assert_eq!(
+            doc.get("c").and_then(|c| c.get("d")),
+            Some(&Container::Null)
+        );
+        assert_eq!(doc.get_index(0), None);
+        assert_eq!(doc["b"].get("a"), None);
+        assert_eq!(Container::Null.get("a"), None);
+    }
+
+    #[test]
+    fn test_mutable_accessors() {
+        let mut doc = parse_str(r#"{"a": [1, {"b": null}], "c": 2}"#).unwrap();
+        let before = doc.clone();
+        assert!(doc.get_mut("z").is_none());
+        assert!(doc["a"].get_index_mut(2).is_none());
+        assert!(doc.get_index_mut(0).is_none());
+        assert!(doc.pointer_mut("/a/5").is_none());
+        assert!(doc.pointer_mut("/a/1/x/y").is_none());
+        assert!(doc.pointer_mut("a").is_none());
+        assert_eq!(doc, before);
+
+        *doc.get_mut("c").unwrap() = Container::Null;
+        if let Some(first) = doc.get_mut("a").and_then(|a| a.get_index_mut(0)) {
+            *first = Container::from("one");
+        }
+        *doc.pointer_mut("/a/1/b").unwrap() = Container::from(true);
+        assert_eq!(
+            doc,
+            parse_str(r#"{"a": ["one", {"b": true}], "c": null}"#).unwrap()
+        );
+        *doc.pointer_mut("").unwrap() = Container::from(0);
+        assert_eq!(doc, Container::from(0));
+    }
+
+    #[test]
+    fn test_map_entry() {
+        use crate::map::{Entry, Map};
+
+        let mut map = Map::new();
+        for idx in 0..20u64 {
+            let key = format!("k{}", idx % 12);
+            let value = map.entry(key.as_str()).or_insert(0u64.into());
+            *value = (value.get_uint().unwrap() + idx).into();
+        }
+        assert_eq!(map.len(), 12);
+        assert_eq!(map["k0"].get_uint(), Some(12));
+        assert_eq!(map["k11"].get_uint(), Some(11));
+        assert_eq!(map.keys().next(), Some("k0"));
+
+        match map.entry("k3") {
+            Entry::Occupied(mut entry) => {
+                assert_eq!(entry.key(), "k3");
+                assert_eq!(entry.insert(Container::Null).get_uint(), Some(18));
+                assert_eq!(entry.remove(), Container::Null);
+            }
+            Entry::Vacant(_) => panic!("k3 is present"),
+        }
+        assert!(!map.contains_key("k3"));
+        assert_eq!(map["k4"].get_uint(), Some(4 + 16));
+        assert_eq!(map.keys().nth(3), Some("k4"));
+
+        let entry = map.entry("new");
+        assert_eq!(entry.key(), "new");
+        assert!(matches!(entry, Entry::Vacant(_)));
+        assert!(map.entry("new").or_default().is_null());
+        assert_eq!(map.keys().last(), Some("new"));
+        let mut calls = 0;
+        map.entry("new").or_insert_with(|| {
+            calls += 1;
+            Container::Null
+        });
+        map.entry("absent").and_modify(|_| calls += 1);
+        assert_eq!(calls, 0);
+        assert!(!map.contains_key("absent"));
+
+        let mut doc = parse_str(r#"{"a": {"b": 1}}"#).unwrap();
+        doc["a"].entry("c").unwrap().or_insert(Container::from(2));
+        assert_eq!(doc, parse_str(r#"{"a": {"b": 1, "c": 2}}"#).unwrap());
+        assert!(doc["a"]["b"].entry("x").is_none());
+    }
+
+    #[test]
+    fn test_remove_pop_take() {
+        let mut doc =
+            parse_str(r#"{"a": [1, 2, 3], "b": "x", "c": null}"#).unwrap();
+        assert_eq!(doc["a"].remove_index(1), Some(Container::from(2)));
+        assert_eq!(doc["a"].remove_index(2), None);
+        assert_eq!(doc["a"].pop(), Some(Container::from(3)));
+        assert_eq!(doc["a"].pop(), Some(Container::from(1)));
+        assert_eq!(doc["a"].pop(), None);
+        assert_eq!(doc["b"].pop(), None);
+        assert_eq!(doc["b"].remove("x"), None);
+        assert_eq!(doc.remove_index(0), None);
+
+        assert_eq!(doc.remove("c"), Some(Container::Null));
+        assert_eq!(doc.remove("c"), None);
+        assert_eq!(doc.get("b").map(Container::len), Some(1));
+        assert_eq!(doc["b"].take(), Container::from("x"));
+        assert_eq!(doc, parse_str(r#"{"a": [], "b": null}"#).unwrap());
+
+        let all = doc.take();
+        assert!(doc.is_null());
+        assert_eq!(all.len(), 2);
+    }
+
+    #[test]
+    fn test_script_long_chains() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::script::Script;
+
+        // Long chains are kept flat: compiling, running and dropping them
+        // does not recurse once per operator.
+        let terms = 200_000;
+        let source = format!("set /sum = {}", vec!["1"; terms].join("+"));
+        let script = Script::parse(&source)?.max_steps(usize::MAX);
+        let doc = script.apply(parse_str("{}")?)?.unwrap();
+        assert_eq!(doc["sum"], Container::from(terms as u64));
+        drop(script);
+
+        let source =
+            format!("filter {} || 1", vec!["null"; terms].join(" || "));
+        assert!(Script::parse(&source)?
+            .max_steps(usize::MAX)
+            .apply(Container::Null)?
+            .is_some());
+        let source =
+            format!("filter {} && 1", vec!["true"; terms].join(" && "));
+        assert!(Script::parse(&source)?
+            .max_steps(usize::MAX)
+            .apply(Container::Null)?
+            .is_some());
+
+        let value =
+            |source: &str| -> Result<Container, Box<dyn core::error::Error>> {
+                Ok(Script::parse(source)?.apply(parse_str("{}")?)?.unwrap()
+                    ["v"]
+                    .clone())
+            };
+        assert_eq!(value("set /v = 10 - 2 - 3")?, Container::from(5u64));
+        assert_eq!(value("set /v = 2 * 3 % 4 / 2")?, Container::from(1u64));
+        assert_eq!(value("set /v = 1 || 1 / 0")?, Container::from(true));
+        assert_eq!(
+            value("set /v = 0 && null && 1 / 0")?,
+            Container::from(false)
+        );
+        assert!(Script::parse("filter 1 < 2 < 3").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_pipeline_window() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::error::{Error, ErrorCode};
+        use crate::pipeline::{run, Order};
+        use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+        let mut input: String =
+            (0..5_000).map(|n| format!("{{\"n\": {}}}\n", n)).collect();
+        input.push_str("{\"n\": }\n");
+        // While the first line is held up, the others stay within the
+        // window of 2 * 64 lines past it.
+        let (first_done, furthest) =
+            (AtomicBool::new(false), AtomicU64::new(0));
+        let slow_first = |doc: Container| {
+            let n = doc["n"].get_uint()?;
+            if n == 0 {
+                std::thread::sleep(std::time::Duration::from_millis(200));
+                first_done.store(true, Ordering::SeqCst);
+            } else if !first_done.load(Ordering::SeqCst) {
+                furthest.fetch_max(n, Ordering::SeqCst);
+            }
+            Some(doc)
+        };
+        let mut output = Vec::new();
+        let summary = run(
+            input.as_bytes(),
+            &ParserOptions::default(),
+            slow_first,
+            &mut output,
+            2,
+            Order::Input,
+        )?;
+        assert_eq!(summary.written, 5_000);
+        assert!(furthest.load(Ordering::SeqCst) < 128);
+        assert!(std::str::from_utf8(&output)?.starts_with("{\"n\":0}\n"));
+
+        // Errors come from the workers as they were raised.
+        let (line, error) = &summary.errors[0];
+        assert_eq!(*line, 5_001);
+        let error = error.downcast_ref::<Error>().unwrap();
+        assert_eq!(
+            error.parse_error().map(|error| error.code()),
+            Some(ErrorCode::MissingValue)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_binary_search_128_bit() -> Result<(), Box<dyn core::error::Error>> {
+        // Neighbours past 2^64 collapse to one f64, so they must compare
+        // exactly.
+        let big = u64::MAX as u128;
+        let unsigned = Container::Array(
+            (1..=3).map(|n| Container::Unsigned128(big + n)).collect(),
+        );
+        for n in 1..=3 {
+            assert_eq!(
+                unsigned.binary_search_by_path(
+                    "",
+                    &Container::Unsigned128(big + n)
+                ),
+                Ok(n as usize - 1)
+            );
+        }
+        assert_eq!(
+            unsigned
+                .binary_search_by_path("", &Container::Unsigned128(big + 4)),
+            Err(3)
+        );
+
+        let small = i64::MIN as i128;
+        let signed = Container::Array(
+            (1..=3)
+                .rev()
+                .map(|n| Container::Number128(small - n))
+                .collect(),
+        );
+        assert_eq!(
+            signed.binary_search_by_path("", &Container::Number128(small - 3)),
+            Ok(0)
+        );
+        assert_eq!(
+            signed.binary_search_by_path("", &Container::Number128(small - 1)),
+            Ok(2)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_drop_deep_nesting() -> Result<(), Box<dyn core::error::Error>> {
+        // Dropped recursively, either would exhaust the native stack.
+        let options = ParserOptions {
+            max_depth: u16::MAX,
+            ..Default::default()
+        };
+        let arrays = "[".repeat(60_000) + &"]".repeat(60_000);
+        parse_with_options(&arrays, &options)?.drop_deep();
+        let objects = "{\"a\": [".repeat(30_000) + &"]}".repeat(30_000);
+        parse_with_options(&objects, &options)?.drop_deep();
+
+        // So are the values a failed parse read so far.
+        let failed = format!("[{}, x]", arrays);
+        assert!(parse_with_options(&failed, &options).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_map_index_is_boxed() {
+        use crate::map::Map;
+
+        // The index of large maps lives behind a pointer, so small
+        // objects and every other value stay narrow.
+        assert_eq!(
+            core::mem::size_of::<Map>(),
+            core::mem::size_of::<Vec<Container>>()
+                + core::mem::size_of::<usize>()
+        );
+        assert!(core::mem::size_of::<Container>() <= 48);
+
+        let mut map = Map::with_capacity(32);
+        for idx in 0..20 {
+            map.insert(format!("k{idx}"), Container::from(idx));
+        }
+        for idx in (0..20).step_by(2) {
+            assert_eq!(
+                map.remove(&format!("k{idx}")),
+                Some(Container::from(idx))
+            );
+        }
+        map.sort_keys();
+        assert_eq!(map.len(), 10);
+        assert_eq!(map["k7"], Container::from(7));
+        assert!(map.get("k8").is_none());
+        for idx in (1..20).step_by(4) {
+            map.remove(&format!("k{idx}"));
+        }
+        assert_eq!(
+            map.keys().collect::<Vec<_>>(),
+            ["k11", "k15", "k19", "k3", "k7"]
+        );
+        map.insert("k0", Container::Null);
+        assert_eq!(map["k3"], Container::from(3));
+        map.clear();
+        assert!(map.get("k3").is_none());
+    }
+
+    #[test]
+    fn test_bracket_mismatch_names_opening_bracket() {
+        use crate::error::{Error, ParseError};
+
+        for (input, opening, closing) in [
+            ("[1}", '[', '}'),
+            ("[[1, 2}]", '[', '}'),
+            ("{\"a\": 1]", '{', ']'),
+            ("{\"a\": {}]", '{', ']'),
+        ] {
+            let error = parse_str(input).unwrap_err();
+            match error.downcast_ref::<Error>() {
+                Some(Error::Parsing(
+                    ParseError::ContainerParanthesisMismatch {
+                        opening_container,
+                        closing_container,
+                        ..
+                    },
+                )) => {
+                    assert_eq!(
+                        (*opening_container, *closing_container),
+                        (opening, closing),
+                        "{}",
+                        input
+                    )
+                }
+                other => panic!("{}: {:?}", input, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_tokens_match_parser() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::dom::Dom;
+        use crate::error::{Error, ParseError};
+        use crate::lazy::LazyDocument;
+        use crate::parser::{minify, validate};
+
+        // A leading byte order mark is skipped wherever a document is read.
+        let text = "\u{feff}{\"a\": [1, 2]}";
+        assert!(parse_str(text).is_ok());
+        validate(text)?;
+        assert_eq!(minify(text)?, "{\"a\":[1,2]}");
+        let mut lazy = LazyDocument::new(text)?;
+        assert_eq!(lazy.raw("/a"), Some("[1, 2]"));
+        assert_eq!(lazy.get("/a/1").and_then(Container::get_uint), Some(2));
+        let mut dom = Dom::default();
+        dom.parse(text)?;
+        assert_eq!(dom.root().unwrap().get("a").unwrap().len(), 2);
+
+        // Mismatched brackets are named alike.
+        let mismatch = |error: Box<dyn core::error::Error>| match error
+            .downcast_ref::<Error>()
+        {
+            Some(Error::Parsing(
+                ParseError::ContainerParanthesisMismatch {
+                    opening_container,
+                    closing_container,
+                    ..
+                },
+            )) => Some((*opening_container, *closing_container)),
+            _ => None,
+        };
+        for input in ["{\"a\": 1]", "[1, {}}"] {
+            let expected = mismatch(parse_str(input).unwrap_err());
+            assert!(expected.is_some());
+            assert_eq!(mismatch(validate(input).unwrap_err()), expected);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dom_matches_parse_str() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::dom::Dom;
+        use crate::error::Error;
+        use crate::index::StructuralIndex;
+
+        let (mut dom, mut index) = (Dom::default(), StructuralIndex::new());
+        for input in [
+            "\u{c}1",
+            "[1,\u{c}2]\u{c}",
+            "{\"a\":\u{c}true}",
+            "1.5e999",
+            "[-1e400, 1e-400, 2.5]",
+            "\u{feff}[1]",
+        ] {
+            let expected = parse_str(input)?;
+            dom.parse(input)?;
+            assert_eq!(dom.to_container(), expected, "{:?}", input);
+            dom.parse_indexed(input, &mut index)?;
+            assert_eq!(dom.to_container(), expected, "{:?}", input);
+        }
+
+        let code = |error: Box<dyn core::error::Error>| {
+            let error = error.downcast::<Error>().unwrap();
+            let error = error.parse_error().unwrap();
+            // The DOM does not report JSONPaths.
+            let span = error.span().unwrap();
+            let span = (span.start, span.end, span.line, span.col);
+            (error.code(), error.to_string(), span)
+        };
+        for input in ["[1}", "{\"a\": [1]]", "[1 2]", "\u{b}1"] {
+            let expected = code(parse_str(input).unwrap_err());
+            assert_eq!(code(dom.parse(input).unwrap_err()), expected);
+            assert_eq!(
+                code(dom.parse_indexed(input, &mut index).unwrap_err()),
+                expected
+            );
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn test_str_matching_long() {
+        let long = Container::from("a".repeat(100_000));
+        assert!(long.get_str_matching(r"^a*$").is_some());
+        assert!(long.get_str_matching(r"^(a|aa)+$").is_some());
+        // Without backtracking this fails as fast as it matches.
+        let started = std::time::Instant::now();
+        let value = Container::from(format!("{}b", "a".repeat(10_000)));
+        assert!(value.get_str_matching(r"^(a|a)*$").is_none());
+        assert!(value.get_str_matching(r"^(a*)*c").is_none());
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+        let value = Container::from("ab");
+        assert!(value.get_str_matching(r"^(a|ab)(c|b)?$").is_some());
+        assert!(value.get_str_matching(r"^a{2,}").is_none());
+        assert!(value.get_str_matching(r"^a{1001}").is_none());
+        assert!(value.get_str_matching(r"^(b|a){1,2}b$").is_some());
+    }
+
+    #[test]
+    fn test_script_string_limit() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::error::{Error, ScriptError};
+        use crate::script::Script;
+
+        // Doubling a string a few dozen times would take gigabytes.
+        let source = vec!["set /a = /a + /a"; 26].join("; ");
+        let error = Script::parse(&source)?
+            .apply(parse_str(r#"{"a": "0123456789abcdef"}"#)?)
+            .unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<Error>(),
+            Some(Error::Script(ScriptError::StringLimit(1_048_576)))
+        ));
+
+        let script =
+            Script::parse("set /a = upper(/a) + /a")?.max_string_len(8);
+        let fits = script.apply(parse_str(r#"{"a": "abcd"}"#)?)?.unwrap();
+        assert_eq!(fits["a"], Container::from("ABCDabcd"));
+        let error = script.apply(parse_str(r#"{"a": "abcde"}"#)?).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<Error>(),
+            Some(Error::Script(ScriptError::StringLimit(8)))
+        ));
+        // Strings read from the document are not limited.
+        let script = Script::parse("filter len(/a) > 4")?.max_string_len(4);
+        assert!(script.apply(parse_str(r#"{"a": "abcde"}"#)?)?.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_error_non_ascii() {
+        use crate::error::Error;
+        use crate::explain::render_error;
+
+        for input in ["é日本\"", "[\"日本\" é]", "{\"é\": 日}", "[1, \"é", "日"]
+        {
+            let error = parse_str(input).unwrap_err();
+            let rendered =
+                render_error(input, error.downcast_ref::<Error>().unwrap());
+            assert!(rendered.starts_with("error: "), "{}", rendered);
+        }
+        let input = "[\"日本\" é]";
+        let error = parse_str(input).unwrap_err();
+        let rendered =
+            render_error(input, error.downcast_ref::<Error>().unwrap());
+        // The caret is under the `é` the error points into.
+        assert!(rendered.lines().nth(4).unwrap().starts_with("  |       ^ "));
+    }
+
+    #[test]
+    fn test_binary_search_wide_integers() {
+        // Through f64 both would be 2^64.
+        let values = Container::Array(vec![
+            Container::Unsigned(u64::MAX),
+            Container::Unsigned128(u64::MAX as u128 + 1),
+        ]);
+        let search = |key: Container| values.binary_search_by_path("", &key);
+        assert_eq!(search(Container::Unsigned(u64::MAX)), Ok(0));
+        assert_eq!(search(Container::Unsigned128(u64::MAX as u128)), Ok(0));
+        assert_eq!(search(Container::Number128(u64::MAX as i128 + 1)), Ok(1));
+        assert_eq!(search(Container::Unsigned(u64::MAX - 1)), Err(0));
+
+        let values = Container::Array(vec![
+            Container::Number128(i64::MIN as i128 - 1),
+            Container::Number(i64::MIN),
+            Container::Number(-1),
+            Container::Unsigned(0),
+            Container::Unsigned128(u128::MAX),
+        ]);
+        let search = |key: Container| values.binary_search_by_path("", &key);
+        assert_eq!(search(Container::Number(i64::MIN)), Ok(1));
+        assert_eq!(search(Container::Number128(-1)), Ok(2));
+        assert_eq!(search(Container::Number(0)), Ok(3));
+        assert_eq!(search(Container::Unsigned128(u128::MAX - 1)), Err(4));
+    }
+
+    #[test]
+    fn test_container_builder_variants(
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        use crate::builder::ContainerBuilder;
+        use crate::map::Map;
+
+        #[allow(unused_mut)]
+        let mut values = vec![
+            Container::Null,
+            Container::Boolean(false),
+            Container::Number(-3),
+            Container::Unsigned(u64::MAX),
+            Container::Number128(i128::MIN),
+            Container::Unsigned128(u128::MAX),
+            Container::Decimal(0.5),
+            Container::RawNumber("1.50".to_owned()),
+            Container::BigNumber("1".repeat(50)),
+            Container::String("s".into()),
+            Container::Array(vec![Container::Null]),
+            Container::Object(Map::new()),
+        ];
+        #[cfg(feature = "uuid")]
+        values.push(Container::Uuid([7; 16]));
+        let original = Container::Array(values);
+        let mut builder = ContainerBuilder::new();
+        original.serialize(&mut builder)?;
+        let built = builder.finish()?;
+        assert_eq!(format!("{:?}", built), format!("{:?}", original));
+        Ok(())
+    }
+
+    #[test]
+    fn test_digit_after_decimal_point() {
+        let strict = ParserOptions {
+            strict: true,
+            ..ParserOptions::default()
+        };
+        for input in ["0.", "1.e1", "[0.]", "0.e1", "-0.", "0e", "[0E]", "1.E5"]
+        {
+            assert!(parse_str(input).is_err(), "{input} parsed");
+            assert!(parse_with_options(input, &strict).is_err(), "{input}");
+            assert!(crate::parser::validate(input).is_err(), "{input}");
+        }
+        for (input, value) in [("0.5", 0.5), ("0e1", 0.0), ("1.5e1", 15.0)] {
+            assert_eq!(parse_str(input).unwrap(), Container::Decimal(value));
+        }
+    }
+
+    #[test]
+    fn test_spill_array_read_back() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::spill::SpillArray;
+
+        // Elements in the file are read back from their JSON.
+        let mut array = SpillArray::new(0);
+        array.push(Container::RawNumber("1.50".to_owned()))?;
+        array.push(Container::RawNumber("2".to_owned()))?;
+        assert_eq!(array.spilled(), 2);
+        let read: Vec<_> = array.iter()?.collect::<Result<_, _>>()?;
+        assert_eq!(format!("{:?}", read), "[Decimal(1.5), Unsigned(2)]");
+        Ok(())
+    }
+
+    #[test]
+    fn test_lazy_document_duplicate_keys(
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        use crate::lazy::LazyDocument;
+
+        let text = r#"{"a": {"b": 1}, "c": [0], "a": {"b": [2, 3]}, "d": {"e": 4, "e": 5}}"#;
+        let mut doc = LazyDocument::new(text)?;
+        assert_eq!(doc.raw("/a"), Some(r#"{"b": [2, 3]}"#));
+        assert_eq!(doc.raw("/a/b/1"), Some("3"));
+        assert_eq!(doc.get("/d/e").and_then(Container::get_uint), Some(5));
+        let parsed = parse_str(text)?;
+        for path in ["/a", "/a/b", "/a/b/0", "/c/0", "/d", "/d/e"] {
+            assert_eq!(doc.get(path), parsed.pointer(path), "{path}");
+        }
+        let shadowed = LazyDocument::new(r#"{"a": {"b": 1}, "a": 2}"#)?;
+        assert_eq!(shadowed.raw("/a/b"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_equals_json_str_duplicate_keys(
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        let doc = parse_str(r#"{"a": 2, "b": {"c": 1}}"#)?;
+        // A key written twice keeps its last value, as when parsing.
+        for text in [
+            r#"{"a": 1, "b": {"c": 1}, "a": 2}"#,
+            r#"{"a": 2, "b": {"c": 1}, "a": 1}"#,
+            r#"{"a": 2, "b": {"c": 0, "c": 1}}"#,
+            r#"{"a": 2, "a": 2, "b": {"c": 1}}"#,
+            r#"{"a": 2, "a": 2}"#,
+            r#"{"a": 2, "b": {"c": 1}, "d": 3, "d": 3}"#,
+        ] {
+            assert_eq!(
+                doc.equals_json_str(text)?,
+                doc == parse_str(text)?,
+                "{text}"
+            );
+        }
+        assert!(doc.equals_json_str(r#"{"a": 1, "b": {"c": 1}, "a": 2}"#)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_pipeline_non_finite() -> Result<(), Box<dyn core::error::Error>> {
+        use crate::parser::{parse_str, Strictness};
+        use crate::pipeline::{run, Order};
+
+        let options = ParserOptions {
+            non_finite_numbers: true,
+            ..Strictness::Json5.options()
+        };
+        let input = "[NaN, Infinity, -Infinity, 0x10]\n";
+        let mut output = Vec::new();
+        let summary = run(
+            input.as_bytes(),
+            &options,
+            Some,
+            &mut output,
+            2,
+            Order::Input,
+        )?;
+        assert_eq!(summary.written, 1);
+        let output = String::from_utf8(output)?;
+        assert_eq!(output, "[null,null,null,16]\n");
+        assert!(parse_str(output.trim_end()).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_lenient_extended_numbers() -> Result<(), Box<dyn core::error::Error>>
+    {
+        use crate::dialect::Lenient;
+        use crate::parser::{Parser, Strictness};
+
+        assert!(!Strictness::Lenient.options().extended_numbers);
+        let options = ParserOptions {
+            extended_numbers: true,
+            ..Strictness::Lenient.options()
+        };
+        let doc =
+            Parser::with_options("[0xFF, 1_000, // n\n]", &Lenient, &options)
+                .parse_str()?;
+        assert_eq!(doc, parse_str("[255, 1000]")?);
+        Ok(())
+    }
 }
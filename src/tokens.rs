@@ -0,0 +1,612 @@
+//! A tokenizer checking the syntax of a document as it is read, without
+//! building it, for tools that only transform or validate the text.
+//!
+//! Tokens borrow their text from the input and the nesting of open
+//! containers is kept in a fixed bit set, so reading a document does not
+//! allocate. Only failures do, to report their error.
+use super::container::Container;
+use super::dialect::{Dialect, Json};
+use super::dom::{number_len, Reader};
+use super::encoding::bom_len;
+use super::error::{Error, ParseError};
+use super::parser::{self, is_identifier, NEST_LIMIT};
+use core::str::Chars;
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// A token of a document. Strings, keys, numbers and literals hold their
+/// text as written, with quotes and escapes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Token<'a> {
+    BeginArray,
+    EndArray,
+    BeginObject,
+    EndObject,
+    /// The key of an object member; a bare identifier if the dialect
+    /// allows them
+    Key(&'a str),
+    String(&'a str),
+    Number(&'a str),
+    Literal(&'a str),
+}
+
+/// What the tokenizer accepts next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// A value, after `[`: or the closing bracket
+    ArrayStart,
+    /// A key, after `{`: or the closing brace
+    ObjectStart,
+    /// A value, after `,` in an array, or `:`
+    Value,
+    /// A key, after `,` in an object
+    Key,
+    /// A separator or a closing bracket, after a value
+    After,
+    /// Nothing but whitespace: the document is complete
+    Done,
+}
+
+/// Bits of the nesting stack, enough for any `ParserOptions::max_depth`.
+const STACK_WORDS: usize = u16::MAX as usize / 64 + 1;
+
+/// Reads the tokens of one document, checking its syntax.
+pub(crate) struct Tokens<'a> {
+    reader: Reader<'a>,
+    dialect: &'a dyn Dialect,
+    /// One bit per open container, set for objects
+    stack: [u64; STACK_WORDS],
+    depth: usize,
+    /// Containers already open around the value read, and the deepest
+    /// nesting allowed in all
+    outer_depth: usize,
+    max_depth: usize,
+    state: State,
+}
+
+impl<'a> Tokens<'a> {
+    pub(crate) fn new(input: &'a str, dialect: &'a dyn Dialect) -> Self {
+        Self::nested(input, bom_len(input), dialect, 0, NEST_LIMIT)
+    }
+
+    /// Tokens of the value at byte `start` of `input`, nested in
+    /// `outer_depth` containers, where at most `max_depth` may be open.
+    pub(crate) fn nested(
+        input: &'a str,
+        start: usize,
+        dialect: &'a dyn Dialect,
+        outer_depth: usize,
+        max_depth: u16,
+    ) -> Self {
+        Self {
+            reader: Reader::new(input.as_bytes(), start),
+            dialect,
+            stack: [0; STACK_WORDS],
+            depth: 0,
+            outer_depth,
+            max_depth: max_depth as usize,
+            state: State::Value,
+        }
+    }
+
+    /// Whether the innermost open container is an object.
+    fn in_object(&self) -> bool {
+        let idx = self.depth - 1;
+        self.stack[idx / 64] & (1 << (idx % 64)) != 0
+    }
+
+    fn push(
+        &mut self,
+        object: bool,
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        let depth = self.outer_depth + self.depth + 1;
+        if depth > self.max_depth {
+            return Err(Error::Parsing(ParseError::NestedDepthExceeded(
+                depth.min(u16::MAX as usize) as u16,
+                self.reader.span(self.reader.pos, self.reader.pos + 1),
+            ))
+            .into());
+        }
+        let (word, bit) = (self.depth / 64, 1 << (self.depth % 64));
+        if object {
+            self.stack[word] |= bit;
+        } else {
+            self.stack[word] &= !bit;
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Skips whitespace and the comments of the dialect, returning the
+    /// first byte after them without reading it.
+    fn peek(&mut self) -> Option<u8> {
+        loop {
+            let byte = *self.reader.bytes.get(self.reader.pos)?;
+            if matches!(byte, b' ' | b'\t' | b'\n' | b'\r' | 0x0c) {
+                self.reader.pos += 1;
+                continue;
+            }
+            if self.dialect.comment_starts().contains(&byte) {
+                let rest = &self.reader.bytes[self.reader.pos..];
+                if let Some(len) = self.dialect.skip_comment(rest) {
+                    self.reader.pos += len.clamp(1, rest.len());
+                    continue;
+                }
+            }
+            return Some(byte);
+        }
+    }
+
+    /// Text of the input from `start` to the current position.
+    fn text(&self, start: usize) -> &'a str {
+        // Tokens start and end next to ASCII bytes of a `str`.
+        unsafe {
+            core::str::from_utf8_unchecked(
+                &self.reader.bytes[start..self.reader.pos],
+            )
+        }
+    }
+
+    /// Reads the next token, or `None` at the end of a complete document.
+    pub(crate) fn next_token(
+        &mut self,
+    ) -> Result<Option<Token<'a>>, Box<dyn core::error::Error>> {
+        let Some(byte) = self.peek() else {
+            return match self.state {
+                State::Done => Ok(None),
+                _ => Err(self.reader.end_of_buffer()),
+            };
+        };
+
+        match self.state {
+            State::Done => {
+                Err(self.reader.unexpected_at(byte, self.reader.pos))
+            }
+            State::ArrayStart if byte == b']' => self.close(byte).map(Some),
+            State::ObjectStart if byte == b'}' => self.close(byte).map(Some),
+            State::Key if byte == b'}' && self.dialect.trailing_commas() => {
+                self.close(byte).map(Some)
+            }
+            State::ObjectStart | State::Key => self.read_key(byte).map(Some),
+            State::ArrayStart | State::Value => self.read_value(byte).map(Some),
+            State::After => {
+                self.reader.pos += 1;
+                let object = self.in_object();
+                match byte {
+                    b',' => {
+                        self.state =
+                            if object { State::Key } else { State::Value };
+                        if !object
+                            && self.dialect.trailing_commas()
+                            && self.peek() == Some(b']')
+                        {
+                            self.state = State::ArrayStart;
+                        }
+                        self.next_token()
+                    }
+                    b']' | b'}' => {
+                        self.reader.pos -= 1;
+                        if (byte == b'}') != object {
+                            return Err(Error::Parsing(
+                                ParseError::ContainerParanthesisMismatch {
+                                    opening_container: if object {
+                                        '{'
+                                    } else {
+                                        '['
+                                    },
+                                    closing_container: byte as char,
+                                    span: self.reader.span(
+                                        self.reader.pos,
+                                        self.reader.pos + 1,
+                                    ),
+                                },
+                            )
+                            .into());
+                        }
+                        self.close(byte).map(Some)
+                    }
+                    _ => Err(self.reader.unexpected(byte)),
+                }
+            }
+        }
+    }
+
+    /// Reads one value, returning the position of its end.
+    pub(crate) fn skip_value(
+        &mut self,
+    ) -> Result<usize, Box<dyn core::error::Error>> {
+        while self.state != State::Done {
+            self.next_token()?;
+        }
+        Ok(self.reader.pos)
+    }
+
+    /// Reads the rest of the value starting with `token`, which was just
+    /// read, returning the position of its end.
+    pub(crate) fn finish_value(
+        &mut self,
+        token: Token,
+    ) -> Result<usize, Box<dyn core::error::Error>> {
+        if matches!(token, Token::BeginArray | Token::BeginObject) {
+            let depth = self.depth - 1;
+            while self.depth > depth {
+                inner_token(self)?;
+            }
+        }
+        Ok(self.reader.pos)
+    }
+
+    /// Position of the value token that was just read.
+    pub(crate) fn value_start(&self, token: Token) -> usize {
+        match token {
+            Token::String(text)
+            | Token::Number(text)
+            | Token::Literal(text) => self.reader.pos - text.len(),
+            _ => self.reader.pos - 1,
+        }
+    }
+
+    /// Reads the bracket closing the innermost container.
+    fn close(
+        &mut self,
+        byte: u8,
+    ) -> Result<Token<'a>, Box<dyn core::error::Error>> {
+        self.reader.pos += 1;
+        self.depth -= 1;
+        self.state = if self.depth == 0 {
+            State::Done
+        } else {
+            State::After
+        };
+        Ok(if byte == b'}' {
+            Token::EndObject
+        } else {
+            Token::EndArray
+        })
+    }
+
+    /// Reads a member key starting with `byte`, and the `:` after it.
+    fn read_key(
+        &mut self,
+        byte: u8,
+    ) -> Result<Token<'a>, Box<dyn core::error::Error>> {
+        let start = self.reader.pos;
+        if self.dialect.quotes().contains(&byte) {
+            self.reader.pos += 1;
+            self.skip_string(byte)?;
+        } else if self.dialect.bare_keys() && is_identifier(byte, true) {
+            self.reader.pos += 1;
+            while self
+                .reader
+                .bytes
+                .get(self.reader.pos)
+                .is_some_and(|byte| is_identifier(*byte, false))
+            {
+                self.reader.pos += 1;
+            }
+        } else {
+            return Err(self.reader.unexpected_at(byte, start));
+        }
+        let key = self.text(start);
+
+        match self.peek() {
+            Some(b':') => {
+                self.reader.pos += 1;
+                self.state = State::Value;
+                Ok(Token::Key(key))
+            }
+            Some(byte) => Err(self.reader.unexpected_at(byte, self.reader.pos)),
+            None => Err(self.reader.end_of_buffer()),
+        }
+    }
+
+    /// Reads a value starting with `byte`, or the bracket opening one.
+    fn read_value(
+        &mut self,
+        byte: u8,
+    ) -> Result<Token<'a>, Box<dyn core::error::Error>> {
+        let start = self.reader.pos;
+        self.state = State::After;
+        let token = match byte {
+            b'[' | b'{' => {
+                let object = byte == b'{';
+                self.push(object)?;
+                self.reader.pos += 1;
+                self.state = if object {
+                    State::ObjectStart
+                } else {
+                    State::ArrayStart
+                };
+                return Ok(if object {
+                    Token::BeginObject
+                } else {
+                    Token::BeginArray
+                });
+            }
+            _ if self.dialect.quotes().contains(&byte) => {
+                self.reader.pos += 1;
+                self.skip_string(byte)?;
+                Token::String(self.text(start))
+            }
+            _ => self.read_scalar()?,
+        };
+        if self.depth == 0 {
+            self.state = State::Done;
+        }
+        Ok(token)
+    }
+
+    /// Reads a literal keyword or a number.
+    fn read_scalar(
+        &mut self,
+    ) -> Result<Token<'a>, Box<dyn core::error::Error>> {
+        let start = self.reader.pos;
+        let rest = &self.reader.bytes[start..];
+        let literal = self
+            .dialect
+            .literals()
+            .iter()
+            .filter(|(word, _)| {
+                !word.is_empty() && rest.starts_with(word.as_bytes())
+            })
+            .max_by_key(|(word, _)| word.len());
+        if let Some((word, _)) = literal {
+            self.reader.pos += word.len();
+            let longer = rest.first().is_some_and(u8::is_ascii_alphabetic)
+                && rest.get(word.len()).is_some_and(u8::is_ascii_alphanumeric);
+            if longer {
+                return Err(self.unknown_literal(rest, start));
+            }
+            return Ok(Token::Literal(self.text(start)));
+        }
+
+        let (_, len) = number_len(rest).ok_or_else(|| match rest.first() {
+            Some(b'-' | b'0'..=b'9') => {
+                Error::Parsing(ParseError::InvalidNumberParse(
+                    rest.get(1).map_or('\0', |byte| *byte as char),
+                    self.reader
+                        .span(start, (start + 2).min(self.reader.bytes.len())),
+                ))
+                .into()
+            }
+            Some(byte) if byte.is_ascii_alphabetic() => {
+                self.unknown_literal(rest, start)
+            }
+            Some(byte) => self.reader.unexpected_at(*byte, start),
+            None => self.reader.end_of_buffer(),
+        })?;
+        self.reader.pos += len;
+        let delimited =
+            self.reader.bytes.get(self.reader.pos).is_none_or(|byte| {
+                matches!(
+                    byte,
+                    b' ' | b'\t' | b'\n' | b'\r' | 0x0c | b',' | b']' | b'}'
+                ) || self.dialect.comment_starts().contains(byte)
+            });
+        if !delimited {
+            return Err(Error::Parsing(ParseError::InvalidNumberParse(
+                self.reader.bytes[self.reader.pos] as char,
+                self.reader.span(self.reader.pos, self.reader.pos + 1),
+            ))
+            .into());
+        }
+        Ok(Token::Number(self.text(start)))
+    }
+
+    /// Error for the word starting at byte `start`, which is no literal.
+    fn unknown_literal(
+        &self,
+        rest: &[u8],
+        start: usize,
+    ) -> Box<dyn core::error::Error> {
+        let literals: Vec<&str> = self
+            .dialect
+            .literals()
+            .iter()
+            .map(|(word, _)| *word)
+            .collect();
+        let (line, col) = self.reader.position(start);
+        parser::unknown_literal(rest, &literals, start, line, col)
+    }
+
+    /// Checks the escapes of a string whose opening `quote` was read, and
+    /// reads it up to its closing quote.
+    fn skip_string(
+        &mut self,
+        quote: u8,
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        loop {
+            let rest = &self.reader.bytes[self.reader.pos..];
+            let run = rest
+                .iter()
+                .position(|byte| *byte == quote || *byte == b'\\')
+                .ok_or_else(|| self.reader.end_of_buffer())?;
+            self.reader.pos += run + 1;
+            if rest[run] == quote {
+                return Ok(());
+            }
+
+            let escape_at = self.reader.pos;
+            match self.reader.next_byte()? {
+                b'"' | b'\\' | b'/' | b'b' | b'f' | b'n' | b'r' | b't' => {}
+                byte if byte == quote => {}
+                b'u' => {
+                    self.reader.read_unicode()?;
+                }
+                _ => {
+                    let chr =
+                        core::str::from_utf8(&self.reader.bytes[escape_at..])
+                            .ok()
+                            .and_then(|text| text.chars().next())
+                            .unwrap_or(char::REPLACEMENT_CHARACTER);
+                    let end = escape_at + chr.len_utf8();
+                    return Err(Error::Parsing(ParseError::InvalidEscape(
+                        chr,
+                        self.reader.span(escape_at, end),
+                    ))
+                    .into());
+                }
+            }
+        }
+    }
+}
+
+/// Reads the four hexadecimal digits of a `\\u` escape.
+fn hex_unit(chars: &mut Chars) -> Option<u32> {
+    let rest = chars.as_str();
+    let unit = u32::from_str_radix(rest.get(..4)?, 16).ok()?;
+    *chars = rest[4..].chars();
+    Some(unit)
+}
+
+/// The characters of a string read by [`Tokens`], given without its
+/// quotes, with escapes decoded.
+pub(crate) fn unescape(raw: &str) -> impl Iterator<Item = char> + '_ {
+    let mut chars = raw.chars();
+    core::iter::from_fn(move || {
+        let chr = chars.next()?;
+        if chr != '\\' {
+            return Some(chr);
+        }
+        Some(match chars.next()? {
+            'b' => '\u{8}',
+            'f' => '\u{c}',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'u' => {
+                let mut code = hex_unit(&mut chars)?;
+                if (0xD800..=0xDBFF).contains(&code) {
+                    // The tokenizer checked that the low half follows.
+                    chars.nth(1);
+                    let low = hex_unit(&mut chars)?;
+                    code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                }
+                char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER)
+            }
+            quote => quote,
+        })
+    })
+}
+
+/// Reads a token inside a document, which cannot end there.
+pub(crate) fn inner_token<'a>(
+    tokens: &mut Tokens<'a>,
+) -> Result<Token<'a>, Box<dyn core::error::Error>> {
+    match tokens.next_token()? {
+        Some(token) => Ok(token),
+        None => Err(tokens.reader.end_of_buffer()),
+    }
+}
+
+/// Reads the value starting with `token`, returning whether it equals
+/// `value`. Without `value`, the document already differs and the value
+/// is only checked.
+fn matches_value<'a>(
+    value: Option<&Container>,
+    token: Token<'a>,
+    tokens: &mut Tokens<'a>,
+) -> Result<bool, Box<dyn core::error::Error>> {
+    Ok(match token {
+        Token::BeginArray => {
+            let elements = match value {
+                Some(Container::Array(elements)) => Some(elements),
+                _ => None,
+            };
+            let (mut equal, mut len) = (elements.is_some(), 0);
+            loop {
+                let token = inner_token(tokens)?;
+                if token == Token::EndArray {
+                    break;
+                }
+                let element =
+                    elements.filter(|_| equal).and_then(|e| e.get(len));
+                equal &= matches_value(element, token, tokens)?;
+                len += 1;
+            }
+            equal && elements.is_some_and(|elements| elements.len() == len)
+        }
+        Token::BeginObject => {
+            let map = match value {
+                Some(Container::Object(map)) => Some(map),
+                _ => None,
+            };
+            // A key written twice keeps its last value, as when parsing:
+            // members are compared at their last occurrence.
+            let mut members: HashMap<Cow<'a, str>, bool> = HashMap::new();
+            let mut equal = map.is_some();
+            loop {
+                let token = inner_token(tokens)?;
+                let Token::Key(key) = token else {
+                    break;
+                };
+                let key = &key[1..key.len() - 1];
+                // Only keys with escapes need decoding.
+                let key = if key.contains('\\') {
+                    Cow::Owned(unescape(key).collect())
+                } else {
+                    Cow::Borrowed(key)
+                };
+                let member =
+                    map.filter(|_| equal).and_then(|map| map.get(key.as_ref()));
+                let token = inner_token(tokens)?;
+                let same = matches_value(member, token, tokens)?;
+                match member {
+                    Some(_) => {
+                        members.insert(key, same);
+                    }
+                    None => equal = false,
+                }
+            }
+            equal
+                && map.is_some_and(|map| map.len() == members.len())
+                && members.values().all(|same| *same)
+        }
+        Token::String(raw) => match value {
+            Some(Container::String(value)) => {
+                unescape(&raw[1..raw.len() - 1]).eq(value.chars())
+            }
+            _ => false,
+        },
+        Token::Number(text) => match value {
+            Some(value) => parser::parse_str(text)? == *value,
+            None => false,
+        },
+        Token::Literal(text) => match value {
+            Some(Container::Boolean(value)) => text == value.to_string(),
+            Some(Container::Null) => text == "null",
+            _ => false,
+        },
+        Token::EndArray | Token::EndObject | Token::Key(_) => false,
+    })
+}
+
+impl Container {
+    /// Whether the JSON document `text` equals this value, as if parsed
+    /// with `parse_str`, compared as it is read instead of being built.
+    ///
+    /// The document is read to its end even when it differs, so that
+    /// invalid text always fails. A key written twice keeps its last
+    /// value, as when parsing.
+    ///
+    /// ```
+    /// use json_parser::parser::parse_str;
+    ///
+    /// let stored = parse_str(r#"{"id": 7, "tags": ["a", "b"]}"#).unwrap();
+    /// assert!(stored
+    ///     .equals_json_str(r#"{"tags": ["a", "\u0062"], "id": 7}"#)
+    ///     .unwrap());
+    /// assert!(!stored.equals_json_str(r#"{"id": 7, "tags": ["a"]}"#).unwrap());
+    /// assert!(stored.equals_json_str(r#"{"id": 7,"#).is_err());
+    /// ```
+    pub fn equals_json_str(
+        &self,
+        text: &str,
+    ) -> Result<bool, Box<dyn core::error::Error>> {
+        let mut tokens = Tokens::new(text, &Json);
+        let token = inner_token(&mut tokens)?;
+        let equal = matches_value(Some(self), token, &mut tokens)?;
+        while tokens.next_token()?.is_some() {}
+        Ok(equal)
+    }
+}
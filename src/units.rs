@@ -0,0 +1,129 @@
+//! Durations and byte sizes written with units in string values, as found
+//! in configuration files: `"1h30m"`, `"250ms"`, `"10MiB"`.
+use super::container::Container;
+use std::time::Duration;
+
+/// Nanoseconds in each duration unit.
+const DURATION_UNITS: &[(&str, u128)] = &[
+    ("ns", 1),
+    ("us", 1_000),
+    ("µs", 1_000),
+    ("ms", 1_000_000),
+    ("s", 1_000_000_000),
+    ("m", 60_000_000_000),
+    ("h", 3_600_000_000_000),
+    ("d", 86_400_000_000_000),
+];
+
+/// Bytes in each size unit, compared without case.
+const SIZE_UNITS: &[(&str, u128)] = &[
+    ("b", 1),
+    ("kb", 1_000),
+    ("mb", 1_000_000),
+    ("gb", 1_000_000_000),
+    ("tb", 1_000_000_000_000),
+    ("pb", 1_000_000_000_000_000),
+    ("kib", 1 << 10),
+    ("mib", 1 << 20),
+    ("gib", 1 << 30),
+    ("tib", 1 << 40),
+    ("pib", 1 << 50),
+];
+
+/// Splits the leading decimal number (`12`, `1.5`) off `text`.
+fn split_number(text: &str) -> Option<(&str, &str)> {
+    let end = text
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(text.len());
+    let number = &text[..end];
+    let valid = match number.split_once('.') {
+        Some((integer, fraction)) => {
+            !integer.is_empty()
+                && !fraction.is_empty()
+                && !fraction.contains('.')
+        }
+        None => !number.is_empty(),
+    };
+    valid.then_some((number, &text[end..]))
+}
+
+/// `number` times `unit`, truncated, if it fits.
+fn scale(number: &str, unit: u128) -> Option<u128> {
+    match number.split_once('.') {
+        None => number.parse::<u128>().ok()?.checked_mul(unit),
+        Some(_) => {
+            let value = number.parse::<f64>().ok()? * unit as f64;
+            (value < u128::MAX as f64).then_some(value as u128)
+        }
+    }
+}
+
+fn parse_duration(mut text: &str) -> Option<Duration> {
+    let mut nanos: u128 = 0;
+    while !text.is_empty() {
+        let (number, rest) = split_number(text)?;
+        // The longest unit matching wins, so that `ms` is not read as `m`.
+        let (unit, factor) = DURATION_UNITS
+            .iter()
+            .filter(|(unit, _)| rest.starts_with(unit))
+            .max_by_key(|(unit, _)| unit.len())?;
+        nanos = nanos.checked_add(scale(number, *factor)?)?;
+        text = &rest[unit.len()..];
+    }
+
+    let secs = u64::try_from(nanos / 1_000_000_000).ok()?;
+    Some(Duration::new(secs, (nanos % 1_000_000_000) as u32))
+}
+
+fn parse_bytesize(text: &str) -> Option<u64> {
+    let (number, unit) = split_number(text)?;
+    let unit = unit.strip_prefix(' ').unwrap_or(unit).to_ascii_lowercase();
+    let (_, factor) = SIZE_UNITS.iter().find(|(name, _)| *name == unit)?;
+    u64::try_from(scale(number, *factor)?).ok()
+}
+
+impl Container {
+    /// Returns the duration held by a string value: a sequence of
+    /// numbers, each followed by a unit among `d`, `h`, `m`, `s`, `ms`,
+    /// `us` (or `µs`) and `ns`. Numbers may have a fraction, e.g. `1.5h`.
+    ///
+    /// ```
+    /// use json_parser::container::Container;
+    /// use std::time::Duration;
+    ///
+    /// let timeout = Container::from("1h30m");
+    /// assert_eq!(timeout.get_duration(), Some(Duration::from_secs(5400)));
+    /// let delay = Container::from("250ms");
+    /// assert_eq!(delay.get_duration(), Some(Duration::from_millis(250)));
+    /// assert_eq!(Container::from("250").get_duration(), None);
+    /// ```
+    pub fn get_duration(&self) -> Option<Duration> {
+        match self {
+            Self::String(text) if !text.is_empty() => parse_duration(text),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of bytes held by a string value: a number and a
+    /// unit, optionally separated by a space. Units are `B`, the decimal
+    /// `kB`, `MB`, `GB`, `TB`, `PB` and the binary `KiB`, `MiB`, `GiB`,
+    /// `TiB`, `PiB`, in any case. A fraction of a byte is dropped.
+    ///
+    /// Plain unsigned integers are taken as a number of bytes.
+    ///
+    /// ```
+    /// use json_parser::container::Container;
+    ///
+    /// assert_eq!(Container::from("10MiB").get_bytesize(), Some(10 << 20));
+    /// assert_eq!(Container::from("1.5 kB").get_bytesize(), Some(1500));
+    /// assert_eq!(Container::from(512u64).get_bytesize(), Some(512));
+    /// assert_eq!(Container::from("10 parsecs").get_bytesize(), None);
+    /// ```
+    pub fn get_bytesize(&self) -> Option<u64> {
+        match self {
+            Self::String(text) => parse_bytesize(text),
+            Self::Unsigned(bytes) => Some(*bytes),
+            _ => None,
+        }
+    }
+}
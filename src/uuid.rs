@@ -0,0 +1,39 @@
+//! The canonical text form of UUIDs, `xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx`.
+
+/// Bytes of the UUID spelled by `text` in canonical form, in either case.
+pub(crate) fn parse(text: &str) -> Option<[u8; 16]> {
+    let text = text.as_bytes();
+    if text.len() != 36 {
+        return None;
+    }
+
+    let mut bytes = [0; 16];
+    let mut digits = text
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| !matches!(idx, 8 | 13 | 18 | 23));
+    for byte in bytes.iter_mut() {
+        let mut value = 0;
+        for _ in 0..2 {
+            let (_, digit) = digits.next()?;
+            value = value * 16 + (*digit as char).to_digit(16)? as u8;
+        }
+        *byte = value;
+    }
+    [8, 13, 18, 23]
+        .iter()
+        .all(|idx| text[*idx] == b'-')
+        .then_some(bytes)
+}
+
+/// Canonical lowercase form of `bytes`.
+pub(crate) fn format(bytes: &[u8; 16]) -> String {
+    let mut text = String::with_capacity(36);
+    for (idx, byte) in bytes.iter().enumerate() {
+        if matches!(idx, 4 | 6 | 8 | 10) {
+            text.push('-');
+        }
+        text.push_str(&format!("{:02x}", byte));
+    }
+    text
+}
@@ -0,0 +1,302 @@
+//! Documents that tell watchers when a change reaches the paths they
+//! watch, for configuration systems reacting to updates.
+//!
+//! A [`Watched`] document is changed through its methods only. Each
+//! change compares the values at the watched paths it may reach before
+//! and after, and calls the watchers of those that differ.
+use super::container::Container;
+use super::error::{Error, WatchError};
+use super::pointer;
+use core::convert::Infallible;
+
+/// A value that changed at a watched path.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Change<'a> {
+    /// JSON Pointer to the value
+    pub path: &'a str,
+    /// The value before the change, `None` if there was none
+    pub old: Option<&'a Container>,
+    /// The value after the change, `None` if it was removed
+    pub new: Option<&'a Container>,
+}
+
+type Callback = Box<dyn FnMut(&Change)>;
+
+struct Watcher {
+    pattern: Vec<String>,
+    callback: Callback,
+}
+
+/// The values matched by a pattern, by path, in document order.
+type Matches = Vec<(String, Container)>;
+
+/// Collects the values at `pattern` below `node`, `*` standing for every
+/// member or element.
+fn collect(
+    node: &Container,
+    pattern: &[String],
+    path: String,
+    out: &mut Matches,
+) {
+    let Some((token, rest)) = pattern.split_first() else {
+        out.push((path, node.clone()));
+        return;
+    };
+    let mut visit = |key: &str, child: &Container| {
+        let path = format!("{}/{}", path, pointer::escape(key));
+        collect(child, rest, path, out);
+    };
+    match node {
+        Container::Object(map) if token == "*" => {
+            map.iter().for_each(|(key, child)| visit(key, child))
+        }
+        Container::Array(array) if token == "*" => array
+            .iter()
+            .enumerate()
+            .for_each(|(idx, child)| visit(&idx.to_string(), child)),
+        _ => {
+            if let Some(child) =
+                pointer::resolve(node, core::slice::from_ref(token))
+            {
+                visit(token, child);
+            }
+        }
+    }
+}
+
+/// Whether a change at `path` may reach a value matched by `pattern`.
+///
+/// A change inside an array is taken to reach the whole array, since
+/// inserting or removing an element moves the elements after it.
+fn overlaps(pattern: &[String], path: &[String], document: &Container) -> bool {
+    let mut node = Some(document);
+    for (token, watched) in path.iter().zip(pattern) {
+        if let Some(Container::Array(_)) = node {
+            return true;
+        }
+        if watched != "*" && watched != token {
+            return false;
+        }
+        node = node.and_then(|node| {
+            pointer::resolve(node, core::slice::from_ref(token))
+        });
+    }
+    true
+}
+
+/// A document whose changes are reported to watchers.
+///
+/// ```
+/// use json_parser::container::Container;
+/// use json_parser::parser::parse_str;
+/// use json_parser::watch::Watched;
+/// use std::cell::RefCell;
+/// use std::rc::Rc;
+///
+/// let config = parse_str(r#"{"db": {"host": "a", "port": 1}}"#).unwrap();
+/// let mut config = Watched::new(config);
+/// let seen = Rc::new(RefCell::new(Vec::new()));
+/// let log = seen.clone();
+/// config
+///     .watch("/db/*", move |change| {
+///         log.borrow_mut().push((change.path.to_owned(), change.new.cloned()))
+///     })
+///     .unwrap();
+///
+/// config.set("/db/port", Container::from(2u64)).unwrap();
+/// config.set("/db/port", Container::from(2u64)).unwrap();
+/// config.set("/cache", Container::from(true)).unwrap();
+/// assert_eq!(
+///     *seen.borrow(),
+///     [("/db/port".to_owned(), Some(Container::from(2u64)))]
+/// );
+/// ```
+pub struct Watched {
+    document: Container,
+    watchers: Vec<Watcher>,
+}
+
+impl Watched {
+    pub fn new(document: Container) -> Self {
+        Self {
+            document,
+            watchers: Vec::new(),
+        }
+    }
+
+    pub fn document(&self) -> &Container {
+        &self.document
+    }
+
+    pub fn into_inner(self) -> Container {
+        self.document
+    }
+
+    /// Calls `callback` for every change of a value at `pattern`: a JSON
+    /// Pointer where a `*` token stands for any member or element.
+    pub fn watch(
+        &mut self,
+        pattern: &str,
+        callback: impl FnMut(&Change) + 'static,
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        let pattern = pointer::split(pattern).ok_or_else(|| {
+            Error::Watch(WatchError::InvalidPointer(pattern.to_owned()))
+        })?;
+        self.watchers.push(Watcher {
+            pattern,
+            callback: Box::new(callback),
+        });
+        Ok(())
+    }
+
+    /// Writes `value` at `path`, creating the objects and arrays on the
+    /// way as [`Mapping`](super::mapping::Mapping) does.
+    pub fn set(
+        &mut self,
+        path: &str,
+        value: Container,
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        let tokens = split(path)?;
+        self.change(core::slice::from_ref(&tokens), |document| {
+            pointer::insert(document, &tokens, value)
+                .ok_or_else(|| not_found(path))
+        })
+    }
+
+    /// Removes and returns the value at `path`.
+    pub fn remove(
+        &mut self,
+        path: &str,
+    ) -> Result<Container, Box<dyn core::error::Error>> {
+        let tokens = split(path)?;
+        self.change(core::slice::from_ref(&tokens), |document| {
+            pointer::remove(document, &tokens).ok_or_else(|| not_found(path))
+        })
+    }
+
+    /// Applies a JSON Patch, reporting the changes once it has succeeded.
+    pub fn apply_patch(
+        &mut self,
+        patch: &Container,
+    ) -> Result<(), Box<dyn core::error::Error>> {
+        let mut paths = Vec::new();
+        if let Container::Array(operations) = patch {
+            for operation in operations {
+                for member in ["/path", "/from"] {
+                    let path = operation
+                        .pointer(member)
+                        .and_then(|path| pointer::split(&path.get_string()?));
+                    paths.extend(path);
+                }
+            }
+        }
+        self.change(&paths, |document| document.apply_patch(patch))
+    }
+
+    /// Changes the document with `update`, which may reach any path.
+    pub fn update<T>(&mut self, update: impl FnOnce(&mut Container) -> T) -> T {
+        let result = self.change(&[Vec::new()], |document| {
+            Ok::<_, Infallible>(update(document))
+        });
+        match result {
+            Ok(value) => value,
+            Err(never) => match never {},
+        }
+    }
+
+    /// Runs `change`, which may reach the values at `paths`, then calls
+    /// the watchers of the values it changed.
+    fn change<T, E>(
+        &mut self,
+        paths: &[Vec<String>],
+        change: impl FnOnce(&mut Container) -> Result<T, E>,
+    ) -> Result<T, E> {
+        let reached: Vec<_> = self
+            .watchers
+            .iter()
+            .map(|watcher| {
+                paths.iter().any(|path| {
+                    overlaps(&watcher.pattern, path, &self.document)
+                })
+            })
+            .collect();
+        let before: Vec<Matches> = self
+            .watchers
+            .iter()
+            .zip(&reached)
+            .map(|(watcher, reached)| {
+                let mut matches = Vec::new();
+                if *reached {
+                    collect(
+                        &self.document,
+                        &watcher.pattern,
+                        String::new(),
+                        &mut matches,
+                    );
+                }
+                matches
+            })
+            .collect();
+
+        let result = change(&mut self.document)?;
+
+        for ((watcher, reached), before) in
+            self.watchers.iter_mut().zip(reached).zip(before)
+        {
+            if !reached {
+                continue;
+            }
+            let mut after = Vec::new();
+            collect(
+                &self.document,
+                &watcher.pattern,
+                String::new(),
+                &mut after,
+            );
+            notify(&mut watcher.callback, &before, &after);
+        }
+        Ok(result)
+    }
+}
+
+/// The value matched at `path`.
+fn find<'a>(matches: &'a Matches, path: &str) -> Option<&'a Container> {
+    matches
+        .iter()
+        .find(|(other, _)| other == path)
+        .map(|(_, value)| value)
+}
+
+/// Calls `callback` for every path whose value differs between `before`
+/// and `after`.
+fn notify(callback: &mut Callback, before: &Matches, after: &Matches) {
+    for (path, old) in before {
+        let new = find(after, path);
+        if new != Some(old) {
+            callback(&Change {
+                path,
+                old: Some(old),
+                new,
+            });
+        }
+    }
+    for (path, new) in after {
+        if find(before, path).is_none() {
+            callback(&Change {
+                path,
+                old: None,
+                new: Some(new),
+            });
+        }
+    }
+}
+
+fn split(path: &str) -> Result<Vec<String>, Box<dyn core::error::Error>> {
+    pointer::split(path).ok_or_else(|| {
+        Error::Watch(WatchError::InvalidPointer(path.to_owned())).into()
+    })
+}
+
+fn not_found(path: &str) -> Box<dyn core::error::Error> {
+    Error::Watch(WatchError::PathNotFound(path.to_owned())).into()
+}